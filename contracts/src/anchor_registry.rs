@@ -1,334 +1,8418 @@
-/// Anchor Registry – Core contract for deterministic hash registration.
-///
-/// Stores SHA-256 hashes of Merkle roots, claim scores, and equation proofs
-/// on-chain for immutable integrity verification.
-///
-/// Properties:
-///   - Deterministic storage
-///   - No randomness
-///   - No token logic
-///   - Content-hash based
-///   - Event emission via attributes
-///   - Extendable for Substrate or EVM wrappers
+//! Anchor Registry – Core contract for deterministic hash registration.
+//!
+//! Stores SHA-256 hashes of Merkle roots, claim scores, and equation proofs
+//! on-chain for immutable integrity verification.
+//!
+//! Properties:
+//!   - Deterministic storage
+//!   - No randomness
+//!   - No token logic
+//!   - Content-hash based
+//!   - Event emission via attributes
+//!   - Extendable for Substrate or EVM wrappers
 
 #[cfg(feature = "cosmwasm")]
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult,
+    entry_point, to_json_binary, to_json_vec, Addr, Api, Binary, CosmosMsg, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, OverflowError, OverflowOperation, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    SubMsgResult, WasmMsg,
 };
 
 #[cfg(feature = "cosmwasm")]
-use cw_storage_plus::Map;
+use cw_storage_plus::{Bound, Item, Map, PrimaryKey};
 
+#[cfg(feature = "schema")]
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::claim_score_anchor::ClaimScorePayload;
+use crate::equation_proof_anchor::EquationProofPayload;
+#[cfg(feature = "cosmwasm")]
+use crate::error::ContractError;
+use crate::merkle_anchor::{
+    verify_inclusion, verify_proof, MerkleProof, MerkleRootPayload, ProofNode, Side, MAX_PROOF_DEPTH,
+};
+use crate::limits::{
+    is_equation_name_char, is_namespace_char, validate_charset, validate_fields, validate_tags, validate_text, Limits, TextError,
+    MAX_EQUATION_NAME_LEN, MAX_MEMO_LEN, MAX_NAMESPACE_LEN, MAX_NAME_LEN,
+};
+
+/// Contract name recorded with cw2
+pub const CONTRACT_NAME: &str = "crates.io:gravity-anchor-contracts";
+
+/// Contract version recorded with cw2
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Event emitted once per registered anchor (`wasm-anchor_registered` on chain)
+pub const EVENT_ANCHOR_REGISTERED: &str = "anchor_registered";
+
+/// Event emitted once per anchor removed by `PruneExpired`
+pub const EVENT_ANCHOR_PRUNED: &str = "anchor_pruned";
+
+/// Event emitted by `instantiate` (`wasm-registry_instantiated` on chain)
+pub const EVENT_REGISTRY_INSTANTIATED: &str = "registry_instantiated";
+
 // ── Storage Maps ────────────────────────────────────────────────────────────
 
-/// Registered Merkle root hashes
+/// Registered anchors of every built-in type: (anchor_type, hash) → entry.
+/// Accessed through `may_load_anchor`, `save_anchor` and `range_anchors`.
+#[cfg(feature = "cosmwasm")]
+pub const ANCHORS: Map<(&str, &[u8]), AnchorEntry> = Map::new("anchors");
+
+/// Merkle roots stored before `ANCHORS`; read until moved by
+/// `MigrateMsg::MoveAnchors`
 #[cfg(feature = "cosmwasm")]
 pub const ROOTS: Map<&[u8], AnchorEntry> = Map::new("roots");
 
-/// Registered claim score hashes
+/// Claim scores stored before `ANCHORS`; read until moved by
+/// `MigrateMsg::MoveAnchors`
 #[cfg(feature = "cosmwasm")]
 pub const CLAIM_SCORES: Map<&[u8], AnchorEntry> = Map::new("claim_scores");
 
-/// Registered equation proof hashes
+/// Equation proofs stored before `ANCHORS`; read until moved by
+/// `MigrateMsg::MoveAnchors`
 #[cfg(feature = "cosmwasm")]
 pub const EQUATION_PROOFS: Map<&[u8], AnchorEntry> = Map::new("equation_proofs");
 
+/// Co-signatures on existing anchors: (anchor_type, hash) → attestations,
+/// oldest first
+#[cfg(feature = "cosmwasm")]
+pub const ATTESTATIONS: Map<(&str, &[u8]), Vec<Attestation>> = Map::new("attestations");
+
+/// Anchors of caller-defined categories: (namespace, hash) → entry
+#[cfg(feature = "cosmwasm")]
+pub const NAMESPACED: Map<(&str, &[u8]), NamespacedEntry> = Map::new("namespaced");
+
+/// Namespace → number of anchors registered in it
+#[cfg(feature = "cosmwasm")]
+pub const NAMESPACES: Map<&str, u64> = Map::new("namespaces");
+
+/// Presence map: (anchor_type, hash) → compact entry, written alongside every
+/// anchor entry so existence checks need not load the full metadata
+#[cfg(feature = "cosmwasm")]
+pub const PRESENCE: Map<(&str, &[u8]), CompactEntry> = Map::new("presence");
+
+/// Claim index: (claim_id, registered_at, hash) → (), for claim scores
+/// registered through `RegisterClaimScoreIndexed`
+#[cfg(feature = "cosmwasm")]
+pub const CLAIM_INDEX: Map<(u64, u64, &[u8]), ()> = Map::new("claim_index");
+
+/// Claim score payloads registered through `RegisterClaimScorePayload`:
+/// (claim_id, sequence) → payload, with sequences from 1 per claim
+#[cfg(feature = "cosmwasm")]
+pub const CLAIM_PAYLOADS: Map<(u64, u64), ClaimScorePayload> = Map::new("claim_payloads");
+
+/// Equation proof payloads registered through `RegisterEquationProofPayload`:
+/// (equation_name, payload hash) → payload
+#[cfg(feature = "cosmwasm")]
+pub const PROOFS_BY_EQUATION: Map<(&str, &[u8]), EquationProofPayload> = Map::new("proofs_by_equation");
+
+/// Full payloads of roots registered through `RegisterRootWithMetadata`,
+/// keyed by root bytes
+#[cfg(feature = "cosmwasm")]
+pub const ROOT_METADATA: Map<&[u8], MerkleRootPayload> = Map::new("root_metadata");
+
+/// Dependency index: (root hash hex, proof hash) → (), for equation proofs
+/// registered through `RegisterEquationProofLinked`
+#[cfg(feature = "cosmwasm")]
+pub const DEPENDENTS: Map<(&str, &[u8]), ()> = Map::new("dependents");
+
+/// Tag index: (tag, anchor_type, hash) → ()
+#[cfg(feature = "cosmwasm")]
+pub const TAG_INDEX: Map<(&str, &str, &[u8]), ()> = Map::new("tag_index");
+
+/// Registrant index: (registrant, anchor_type, hash) → ()
+#[cfg(feature = "cosmwasm")]
+pub const REGISTRANT_INDEX: Map<(&str, &str, &[u8]), ()> = Map::new("registrant_index");
+
+/// Height index: (registered_at, hash, anchor_type) → ()
+#[cfg(feature = "cosmwasm")]
+pub const HEIGHT_INDEX: Map<(u64, &[u8], &str), ()> = Map::new("height_index");
+
+/// Per-type height index: (anchor_type, registered_at, hash) → (), so one
+/// type's registrations can be walked in order without the others
+#[cfg(feature = "cosmwasm")]
+pub const TYPE_HEIGHT_INDEX: Map<(&str, u64, &[u8]), ()> = Map::new("type_height_index");
+
+/// Time index: (registered_at_time, hash, anchor_type) → ()
+///
+/// Entries that predate time recording (`registered_at_time == 0`) are not indexed.
+#[cfg(feature = "cosmwasm")]
+pub const TIME_INDEX: Map<(u64, &[u8], &str), ()> = Map::new("time_index");
+
+/// Modification index: (last_modified_height, hash, anchor_type) → ()
+#[cfg(feature = "cosmwasm")]
+pub const MODIFIED_INDEX: Map<(u64, &[u8], &str), ()> = Map::new("modified_index");
+
+/// Expiry index: (expires_at, hash, anchor_type) → (), for anchors
+/// registered with a TTL
+#[cfg(feature = "cosmwasm")]
+pub const EXPIRY_INDEX: Map<(u64, &[u8], &str), ()> = Map::new("expiry_index");
+
+/// Change log: (hash, anchor_type, height) → change kinds recorded at that height
+#[cfg(feature = "cosmwasm")]
+pub const CHANGE_LOG: Map<(&[u8], &str, u64), Vec<String>> = Map::new("change_log");
+
+/// Prune tombstones: (anchor_type, hash) → height the anchor was pruned at.
+/// Its `MODIFIED_INDEX` key stays behind so `ListModifiedSince` reports the
+/// deletion, until the hash is registered again.
+#[cfg(feature = "cosmwasm")]
+pub const PRUNE_TOMBSTONES: Map<(&str, &[u8]), u64> = Map::new("prune_tombstones");
+
+/// Hashes that may not be registered under any anchor type
+#[cfg(feature = "cosmwasm")]
+pub const BLOCKLIST: Map<&[u8], BlockReason> = Map::new("blocklist");
+
+/// Subscriber contract → execute variant that receives `AnchorNotification`s
+#[cfg(feature = "cosmwasm")]
+pub const SUBSCRIBERS: Map<&Addr, String> = Map::new("subscribers");
+
+/// Last subscriber notification id handed out; ids double as reply ids
+#[cfg(feature = "cosmwasm")]
+pub const DISPATCH_SEQUENCE: Item<u64> = Item::new("dispatch_sequence");
+
+/// Notifications sent in the current transaction and not yet replied to
+#[cfg(feature = "cosmwasm")]
+pub const PENDING_DISPATCHES: Map<u64, PendingDispatch> = Map::new("pending_dispatches");
+
+/// Notifications whose subscriber call failed, by notification id
+#[cfg(feature = "cosmwasm")]
+pub const FAILED_DISPATCHES: Map<u64, FailedDispatch> = Map::new("failed_dispatches");
+
+/// Addresses allowed to register anchors when `Config.restricted` is set
+#[cfg(feature = "cosmwasm")]
+pub const REGISTRANTS: Map<&Addr, ()> = Map::new("registrants");
+
 /// Contract configuration
 #[cfg(feature = "cosmwasm")]
 pub const CONFIG: cw_storage_plus::Item<Config> = cw_storage_plus::Item::new("config");
 
+/// Total anchors registered, kept apart from `CONFIG` so that registering does
+/// not rewrite the whole config
+#[cfg(feature = "cosmwasm")]
+pub const TOTAL_ANCHORS: Item<u64> = Item::new("total_anchors");
+
+/// Registered anchors per type; backfilled by `MigrateMsg::BackfillCounters`
+#[cfg(feature = "cosmwasm")]
+pub const TYPE_COUNTS: Map<&str, u64> = Map::new("type_counts");
+
+/// Last presence key (anchor_type, hash) counted by an unfinished
+/// `MigrateMsg::BackfillCounters` run; absent when no backfill is in progress
+#[cfg(feature = "cosmwasm")]
+pub const COUNTER_BACKFILL: Item<(String, Binary)> = Item::new("counter_backfill");
+
+/// Last anchor key (anchor_type, hash) given a presence entry by an
+/// unfinished `MigrateMsg::BackfillPresence` run
+#[cfg(feature = "cosmwasm")]
+pub const PRESENCE_BACKFILL: Item<(String, Binary)> = Item::new("presence_backfill");
+
+/// Set once every anchor has a presence entry: at instantiation, or when
+/// `MigrateMsg::BackfillPresence` completes. Until then existence checks fall
+/// back to loading the entry when the presence map misses.
+#[cfg(feature = "cosmwasm")]
+pub const PRESENCE_COMPLETE: Item<bool> = Item::new("presence_complete");
+
+/// Last anchor key (anchor_type, hash) indexed by an unfinished
+/// `MigrateMsg::BackfillTypeIndex` run
+#[cfg(feature = "cosmwasm")]
+pub const TYPE_INDEX_BACKFILL: Item<(String, Binary)> = Item::new("type_index_backfill");
+
+/// Set once `TYPE_HEIGHT_INDEX` covers every anchor: at instantiation, or
+/// when `MigrateMsg::BackfillTypeIndex` completes
+#[cfg(feature = "cosmwasm")]
+pub const TYPE_INDEX_COMPLETE: Item<bool> = Item::new("type_index_complete");
+
+/// Registrant → (block height, anchors registered in that block). Only the
+/// latest block is kept, so the entry resets itself on the next block.
+#[cfg(feature = "cosmwasm")]
+pub const BLOCK_COUNTS: Map<&Addr, (u64, u32)> = Map::new("block_counts");
+
+/// Registrant → anchors registered by it; never decremented
+#[cfg(feature = "cosmwasm")]
+pub const REGISTRANT_STATS: Map<&Addr, RegistrantStats> = Map::new("registrant_stats");
+
+/// Registrant → lifetime cap on its `REGISTRANT_STATS` total
+#[cfg(feature = "cosmwasm")]
+pub const QUOTAS: Map<&Addr, u64> = Map::new("quotas");
+
+/// Last registrant index key counted by an in-progress
+/// `MigrateMsg::BackfillRegistrantStats` run
+#[cfg(feature = "cosmwasm")]
+pub const STATS_BACKFILL: Item<(String, String, Binary)> = Item::new("stats_backfill");
+
+/// Last registration sequence number handed out; absent before the first
+#[cfg(feature = "cosmwasm")]
+pub const ANCHOR_SEQUENCE: Item<u64> = Item::new("anchor_sequence");
+
+/// Registration sequence number → (anchor_type, hash)
+#[cfg(feature = "cosmwasm")]
+pub const ANCHOR_BY_INDEX: Map<u64, (String, Binary)> = Map::new("anchor_by_index");
+
+/// Newest registered root; absent until the first root registration
+#[cfg(feature = "cosmwasm")]
+pub const LATEST_ROOT: Item<LatestRoot> = Item::new("latest_root");
+
+// ── Limits ──────────────────────────────────────────────────────────────────
+
+/// Maximum number of subscriber contracts notified on registration
+pub const MAX_SUBSCRIBERS: usize = 10;
+
+/// Default gas each subscriber notification may use
+pub const SUBSCRIBER_GAS_LIMIT: u64 = 500_000;
+
+/// Ceiling on `Config.subscriber_gas_limit`
+pub const HARD_MAX_SUBSCRIBER_GAS_LIMIT: u64 = 5_000_000;
+
+/// Canonical payload format version produced by the payload builders
+pub const PAYLOAD_FORMAT_VERSION: u16 = 1;
+
+/// Payload format versions a registration may claim
+pub const SUPPORTED_PAYLOAD_VERSIONS: [u16; 1] = [PAYLOAD_FORMAT_VERSION];
+
+/// Format version of `AnchorReceipt` and its `receipt_hash`
+pub const RECEIPT_FORMAT_VERSION: u16 = 1;
+
+/// Maximum number of sub-queries in a `QueryMsg::Multi`
+pub const MAX_MULTI_QUERIES: usize = 20;
+
+/// Maximum number of proofs in a `QueryMsg::VerifyInclusionBatch`
+pub const MAX_INCLUSION_BATCH: usize = 50;
+
+/// Default maximum number of anchors in an `ExecuteMsg::RegisterBatch`
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// Ceiling on `Config.max_batch_size`
+pub const HARD_MAX_BATCH_SIZE: u32 = 100;
+
+/// Digest lengths a deployment may accept: 20 (Keccak-160 style), 32
+/// (SHA-256) and 64 (SHA-512) bytes
+pub const SUPPORTED_HASH_LENGTHS: [u32; 3] = [20, 32, 64];
+
+/// Maximum number of attestations kept per anchor
+pub const MAX_ATTESTATIONS: usize = 32;
+
+/// Maximum serialized size, in bytes, of a payload stored by
+/// `RegisterRootWithMetadata`
+pub const MAX_ROOT_METADATA_BYTES: usize = 4_096;
+
+/// Maximum number of anchors imported by one instantiate or `ImportAnchors`
+pub const MAX_IMPORT_BATCH: usize = 100;
+
+/// Maximum number of expired anchors removed by one `PruneExpired`
+pub const MAX_PRUNE_BATCH: u32 = 100;
+
+/// Maximum number of buckets in an activity histogram
+pub const MAX_HISTOGRAM_BUCKETS: u32 = 100;
+
+/// Maximum block span (`bucket_blocks * buckets`) of an activity histogram
+pub const MAX_HISTOGRAM_SPAN: u64 = 100_000;
+
+/// Maximum number of claim score entries scanned by a stability histogram
+pub const MAX_STABILITY_SCAN: usize = 1_000;
+
+/// Maximum number of one type's registrations scanned by `GetOldest`
+pub const MAX_OLDEST_SCAN: usize = 1_000;
+
+/// Version of the `StateExport` chunk format
+pub const EXPORT_FORMAT_VERSION: u16 = 1;
+
+/// Default page size for listing queries
+pub const DEFAULT_LIMIT: u32 = 10;
+
+/// Maximum page size for listing queries
+pub const MAX_LIMIT: u32 = 100;
+
+/// Default and maximum anchors counted per `BackfillCounters` migration
+pub const MAX_BACKFILL_BATCH: u32 = 1_000;
+
+/// Sentinel `previous_root` starting a new root chain
+pub const GENESIS_ROOT: [u8; 32] = [0; 32];
+
 // ── Data Structures ─────────────────────────────────────────────────────────
 
 /// Configuration for the anchor registry contract.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Config {
-    /// Contract administrator address
-    pub admin: String,
-    /// Total anchors registered
-    pub total_anchors: u64,
+    /// Registry name; `registry_id` is derived from it
+    #[serde(default)]
+    pub name: String,
+    /// Text limits for persisted strings
+    #[serde(default)]
+    pub limits: Limits,
+    /// Admin opt-in allowing a registration to replace an existing entry;
+    /// first writer wins by default
+    #[serde(default)]
+    pub allow_overwrite: bool,
+    /// Blocks after a revocation before the admin or original registrant may
+    /// re-register the hash; `None` keeps revoked hashes blocked for good
+    #[serde(default)]
+    pub reregistration_lockout_blocks: Option<u64>,
+    /// Only the admin and allowlisted registrants may register anchors
+    #[serde(default)]
+    pub restricted: bool,
+    /// Registrations are rejected while set; queries keep working
+    #[serde(default)]
+    pub paused: bool,
+    /// Most anchors one registrant may register per block; `None` disables
+    /// the limit
+    #[serde(default)]
+    pub max_anchors_per_block_per_registrant: Option<u32>,
+    /// Addresses that may manage the registrant allowlist and pause the
+    /// registry, but not change configuration or revoke anchors
+    #[serde(default)]
+    pub operators: Vec<String>,
+    /// Most anchors accepted by one `RegisterBatch`; 0 disables batching
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: u32,
+    /// Digest lengths accepted on registration, from `SUPPORTED_HASH_LENGTHS`
+    #[serde(default = "default_accepted_hash_lengths")]
+    pub accepted_hash_lengths: Vec<u32>,
+    /// Gas each subscriber notification may use; a subscriber that runs out
+    /// fails on its own instead of aborting the registration
+    #[serde(default = "default_subscriber_gas_limit")]
+    pub subscriber_gas_limit: u64,
+}
+
+fn default_max_batch_size() -> u32 {
+    MAX_BATCH_SIZE as u32
+}
+
+fn default_subscriber_gas_limit() -> u64 {
+    SUBSCRIBER_GAS_LIMIT
+}
+
+fn default_digest_len() -> u32 {
+    32
+}
+
+fn default_payload_version() -> u16 {
+    1
+}
+
+fn default_accepted_hash_lengths() -> Vec<u32> {
+    vec![32]
+}
+
+/// Counters as stored in `Config` before they moved to `TOTAL_ANCHORS` and
+/// `TYPE_COUNTS`.
+#[cfg(feature = "cosmwasm")]
+#[derive(Deserialize)]
+struct LegacyCounters {
+    #[serde(default)]
+    total_anchors: u64,
+    #[serde(default)]
+    total_roots: u64,
+    #[serde(default)]
+    total_claim_scores: u64,
+    #[serde(default)]
+    total_equation_proofs: u64,
+}
+
+/// The admin as stored in `Config` before it moved to the cw-ownable
+/// ownership item.
+#[cfg(feature = "cosmwasm")]
+#[derive(Deserialize)]
+struct LegacyAdmin {
+    admin: Option<String>,
+}
+
+/// Kind of anchored hash. Serialized as `"root"`, `"claim_score"` and
+/// `"equation_proof"`, the strings that also key every index, so stored
+/// entries and existing clients are unaffected; `"merkle_root"` is accepted
+/// as an alias.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorType {
+    #[serde(rename = "root", alias = "merkle_root")]
+    MerkleRoot,
+    ClaimScore,
+    EquationProof,
+}
+
+impl AnchorType {
+    /// Every anchor type, in the order of its storage map.
+    pub const ALL: [AnchorType; 3] = [AnchorType::MerkleRoot, AnchorType::ClaimScore, AnchorType::EquationProof];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnchorType::MerkleRoot => "root",
+            AnchorType::ClaimScore => "claim_score",
+            AnchorType::EquationProof => "equation_proof",
+        }
+    }
+
+    /// The pre-`ANCHORS` map that held this anchor type.
+    #[cfg(feature = "cosmwasm")]
+    fn legacy_store<'a>(self) -> &'static Map<'static, &'a [u8], AnchorEntry> {
+        match self {
+            AnchorType::MerkleRoot => &ROOTS,
+            AnchorType::ClaimScore => &CLAIM_SCORES,
+            AnchorType::EquationProof => &EQUATION_PROOFS,
+        }
+    }
+}
+
+impl std::fmt::Display for AnchorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for AnchorType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "root" | "merkle_root" => Ok(AnchorType::MerkleRoot),
+            "claim_score" => Ok(AnchorType::ClaimScore),
+            "equation_proof" => Ok(AnchorType::EquationProof),
+            _ => Err(format!("Unknown anchor type: {}", value)),
+        }
+    }
 }
 
 /// An anchored hash entry with metadata.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct AnchorEntry {
-    /// The 32-byte SHA-256 hash (hex-encoded)
+    /// The anchored digest (hex-encoded)
     pub hash_hex: String,
-    /// Anchor type: "root", "claim_score", or "equation_proof"
-    pub anchor_type: String,
+    /// Byte length of the digest; 32 for entries that predate other lengths
+    #[serde(default = "default_digest_len")]
+    pub digest_len: u32,
+    /// Canonical payload format the hash was computed under; 1 for entries
+    /// that predate versioning
+    #[serde(default = "default_payload_version")]
+    pub payload_version: u16,
+    pub anchor_type: AnchorType,
     /// Block height at registration
     pub registered_at: u64,
+    /// Block time at registration, in seconds; 0 for entries that predate it
+    #[serde(default)]
+    pub registered_at_time: u64,
+    /// Height at which a historical anchor was imported; `registered_at`
+    /// and `registered_at_time` then hold the original values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_at: Option<u64>,
     /// Registrant address
     pub registrant: String,
+    /// Tags attached at registration (immutable)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Height of the latest change recorded by `touch_anchor`
+    #[serde(default)]
+    pub last_modified_height: u64,
+    /// Stability class, recorded only by payload-based claim score registration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stability_class: Option<String>,
+    /// Short human-readable note from the registrant; not part of any hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// Height at which the anchor was revoked; the entry is kept as a tombstone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<u64>,
+    /// Reason given on revocation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_reason: Option<String>,
+    /// Root this root was chained onto (hex), set only by
+    /// `RegisterRootChained`; all zeros for the first root of a chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_root: Option<String>,
+    /// Root that replaced this one (hex), set by `SupersedeRoot`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<String>,
+    /// Chain the entry was registered on; for `ImportAnchors`, the origin
+    /// chain when given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    /// Index of the registering transaction within its block; unset for imports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_index: Option<u32>,
+    /// Claim the score belongs to, recorded only by `RegisterClaimScoreIndexed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim_id: Option<u64>,
+    /// Root the equation proof was computed from (hex), recorded only by
+    /// `RegisterEquationProofLinked`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depends_on_root: Option<String>,
+    /// Local IBC channel an anchor relayed from another registry arrived on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_channel: Option<String>,
+    /// Registrant on the origin chain, for relayed anchors; `registrant` is
+    /// then this registry itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_registrant: Option<String>,
+    /// Block height of the registration on the origin chain, for relayed anchors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_height: Option<u64>,
+    /// Registration sequence number shared by all anchor types, from 1; kept
+    /// on overwrite and absent for anchors that predate numbering
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u64>,
+    /// Registration height of the revoked entry this one replaced, for
+    /// hashes re-registered after `Config.reregistration_lockout_blocks`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_registration: Option<u64>,
+    /// Height from which the entry is expired and may be pruned, for
+    /// anchors registered with `ttl_blocks`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl AnchorEntry {
+    /// Whether the entry's TTL has run out at `height`. Expired entries
+    /// remain until `PruneExpired` removes them.
+    pub fn is_expired(&self, height: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| height >= expires_at)
+    }
+}
+
+/// An address vouching for an anchor it did not register.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Attestation {
+    pub attester: String,
+    /// Block height of the attestation
+    pub height: u64,
+}
+
+/// Head of the root chain as tracked by `LATEST_ROOT`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct LatestRoot {
+    pub hash_hex: String,
+    /// Block height at registration
+    pub height: u64,
+    pub registrant: String,
+}
+
+/// An anchor registered under a caller-defined namespace. Namespaced anchors
+/// are first-writer-wins and kept out of the per-type indexes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct NamespacedEntry {
+    pub namespace: String,
+    /// The anchored digest (hex-encoded)
+    pub hash_hex: String,
+    /// Block height at registration
+    pub registered_at: u64,
+    /// Block time at registration, in seconds
+    pub registered_at_time: u64,
+    pub registrant: String,
+    pub chain_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_index: Option<u32>,
+}
+
+/// Anchors registered by one address, per kind.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RegistrantStats {
+    pub roots: u64,
+    pub claim_scores: u64,
+    pub equation_proofs: u64,
+    /// Anchors registered through `RegisterNamespaced`
+    pub namespaced: u64,
+}
+
+impl RegistrantStats {
+    /// Sum across all kinds; errors instead of wrapping on overflow.
+    #[cfg(feature = "cosmwasm")]
+    pub fn total(&self) -> StdResult<u64> {
+        [self.claim_scores, self.equation_proofs, self.namespaced].into_iter().try_fold(self.roots, |sum, count| {
+            sum.checked_add(count).ok_or_else(|| OverflowError::new(OverflowOperation::Add, sum, count).into())
+        })
+    }
+
+    fn type_counter(&mut self, anchor_type: AnchorType) -> &mut u64 {
+        match anchor_type {
+            AnchorType::MerkleRoot => &mut self.roots,
+            AnchorType::ClaimScore => &mut self.claim_scores,
+            AnchorType::EquationProof => &mut self.equation_proofs,
+        }
+    }
+}
+
+/// Sent to every subscriber contract when an anchor is registered, wrapped
+/// in the subscriber's execute variant: `{"<msg_template>": {...}}`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorNotification {
+    pub anchor_type: String,
+    pub hash_hex: String,
+    /// Block height at registration
+    pub height: u64,
+    pub registrant: String,
+}
+
+/// Minimal per-anchor record kept in the presence map.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct CompactEntry {
+    pub registered_at: u64,
+}
+
+/// Why and when a hash was blocklisted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct BlockReason {
+    pub reason: String,
+    /// Block height at which the hash was blocked
+    pub blocked_at: u64,
+    /// Admin address that blocked the hash
+    pub blocked_by: String,
+}
+
+/// Composite key identifying an anchor across all types.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorKey {
+    pub anchor_type: String,
+    pub hash: Binary,
+}
+
+/// Direction of a listing query, serialized as `"ascending"` or
+/// `"descending"`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+#[cfg(feature = "cosmwasm")]
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
+    }
+}
+
+/// One payload in a claim's on-chain history.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ClaimPayloadRecord {
+    /// Position in the claim's history, from 1; the `start_after` cursor
+    pub sequence: u64,
+    pub payload: ClaimScorePayload,
+}
+
+/// Response for `GetClaimScorePayloads`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ClaimPayloadsResponse {
+    pub payloads: Vec<ClaimPayloadRecord>,
+}
+
+/// Response for `GetProofsByEquation`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct EquationProofsResponse {
+    pub proofs: Vec<EquationProofPayload>,
+}
+
+/// Pagination cursor for `GetClaimScoreHistory`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ClaimCursor {
+    pub registered_at: u64,
+    pub hash: Binary,
+}
+
+/// Pagination cursor for time-range listings, in time index key order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct TimeCursor {
+    pub registered_at_time: u64,
+    pub hash: Binary,
+    pub anchor_type: String,
+}
+
+/// Pagination cursor for `QueryMsg::ExportState`: the last exported anchor.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ExportCursor {
+    pub hash: Binary,
+    pub anchor_type: AnchorType,
+}
+
+/// Pagination cursor for `QueryMsg::ListModifiedSince`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ModifiedCursor {
+    pub last_modified_height: u64,
+    pub hash: Binary,
+    pub anchor_type: String,
+}
+
+/// One anchor within `ExecuteMsg::RegisterBatch`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct BatchEntry {
+    pub anchor_type: String,
+    pub hash: Binary,
+    pub tags: Option<Vec<String>>,
+    pub memo: Option<String>,
+}
+
+/// One anchor within `ExecuteMsg::RegisterMixedBatch`: a `BatchEntry` with
+/// a typed anchor type and no tags.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MixedEntry {
+    pub anchor_type: AnchorType,
+    pub hash: Binary,
+    pub memo: Option<String>,
+}
+
+impl From<MixedEntry> for BatchEntry {
+    fn from(entry: MixedEntry) -> Self {
+        BatchEntry { anchor_type: entry.anchor_type.to_string(), hash: entry.hash, tags: None, memo: entry.memo }
+    }
+}
+
+/// An anchor replayed from history by `ExecuteMsg::RegisterHistorical`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct HistoricalAnchor {
+    pub hash: Binary,
+    pub anchor_type: String,
+    /// Block height of the original registration
+    pub original_height: u64,
+    /// Block time of the original registration, in seconds
+    pub original_time: u64,
+    pub tags: Option<Vec<String>>,
+    pub memo: Option<String>,
+}
+
+/// An anchor carried over from another registry, stored verbatim.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ImportEntry {
+    pub anchor_type: String,
+    pub hash: Binary,
+    /// Registrant on the original registry
+    pub registrant: String,
+    /// Block height of the original registration
+    pub original_height: u64,
+    /// Chain of the original registration; unset when unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
 }
 
 // ── Messages ────────────────────────────────────────────────────────────────
 
 /// Instantiation message – sets the admin address.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct InstantiateMsg {
     pub admin: Option<String>,
+    /// Registry name (defaults to the contract address)
+    pub name: Option<String>,
+    /// Override the default text limits
+    pub limits: Option<Limits>,
+    /// Only accept registrations from admin-managed registrants
+    #[serde(default)]
+    pub restricted: bool,
+    /// Whether senders outside the allowlist may register; defaults to true.
+    /// `Some(false)` is the same as `restricted` and cannot be combined with
+    /// `restricted: true` under `Some(true)`.
+    #[serde(default)]
+    pub public_registration: Option<bool>,
+    /// Anchors to seed the registry with, at most `MAX_IMPORT_BATCH`; larger
+    /// sets continue through `ExecuteMsg::ImportAnchors`
+    #[serde(default)]
+    pub initial_anchors: Option<Vec<ImportEntry>>,
+    /// Override the default `MAX_BATCH_SIZE`; 0 disables batching
+    #[serde(default)]
+    pub max_batch_size: Option<u32>,
+    /// Digest lengths to accept, from `SUPPORTED_HASH_LENGTHS`; defaults to
+    /// 32-byte hashes only
+    #[serde(default)]
+    pub accepted_hash_lengths: Option<Vec<u32>>,
+}
+
+/// Migration messages, one variant per state migration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MigrateMsg {
+    /// Code upgrade with no state changes
+    V1 {},
+    /// Recount the per-type counters from the presence map, `limit` anchors
+    /// per call; repeat until the response reports `backfill_complete=true`.
+    /// Requires `BackfillPresence` or `MoveAnchors` to have completed.
+    BackfillCounters { limit: Option<u32> },
+    /// Recount per-registrant stats from the registrant index, `limit`
+    /// anchors per call; repeat until `backfill_complete=true`
+    BackfillRegistrantStats { limit: Option<u32> },
+    /// Move up to `limit` anchors from the legacy per-type maps into
+    /// `ANCHORS`; repeat until `move_complete=true`. Unmoved anchors stay
+    /// readable in the meantime.
+    MoveAnchors { limit: Option<u32> },
+    /// Write presence entries for up to `limit` anchors stored before the
+    /// presence map; repeat until `backfill_complete=true`
+    BackfillPresence { limit: Option<u32> },
+    /// Index up to `limit` anchors registered before `TYPE_HEIGHT_INDEX` by
+    /// type and height; repeat until `backfill_complete=true`. `GetOldest`
+    /// fails until then.
+    BackfillTypeIndex { limit: Option<u32> },
+}
+
+/// Messages only chain governance can send. They touch operational
+/// parameters alone; anchors cannot be registered or removed this way.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    /// Replace the admin, e.g. after the admin key is lost
+    SetAdmin { address: String },
+    /// Reject all registrations until `Unpause`
+    Pause {},
+    /// Accept registrations again
+    Unpause {},
 }
 
 /// Execute messages for hash registration.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     /// Register a Merkle root hash (32 bytes)
-    RegisterRoot { hash: Binary },
+    RegisterRoot {
+        hash: Binary,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+        /// Blocks until the entry expires and may be pruned; `None` keeps
+        /// it permanently
+        #[serde(default)]
+        ttl_blocks: Option<u64>,
+        /// Canonical payload format the hash was computed under; 1 when
+        /// omitted
+        #[serde(default)]
+        payload_version: Option<u16>,
+    },
+    /// Register a Merkle root linked to an already registered root, or to
+    /// `GENESIS_ROOT` to start a chain
+    RegisterRootChained {
+        hash: Binary,
+        previous_root: Binary,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+    },
     /// Register a claim score hash (32 bytes)
-    RegisterClaimScore { hash: Binary },
+    RegisterClaimScore {
+        hash: Binary,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+        /// Blocks until the entry expires and may be pruned; `None` keeps
+        /// it permanently
+        #[serde(default)]
+        ttl_blocks: Option<u64>,
+        /// Canonical payload format the hash was computed under; 1 when
+        /// omitted
+        #[serde(default)]
+        payload_version: Option<u16>,
+    },
+    /// Register a claim score hash and index it under `claim_id`. Use
+    /// `RegisterClaimScore` to keep the claim id off-chain.
+    RegisterClaimScoreIndexed {
+        hash: Binary,
+        claim_id: u64,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+    },
+    /// Register a claim score from its full payload, recording its stability
+    /// class and appending the payload to its claim's on-chain history
+    RegisterClaimScorePayload {
+        payload: ClaimScorePayload,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+    },
     /// Register an equation proof hash (32 bytes)
-    RegisterEquationProof { hash: Binary },
+    RegisterEquationProof {
+        hash: Binary,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+        /// Blocks until the entry expires and may be pruned; `None` keeps
+        /// it permanently
+        #[serde(default)]
+        ttl_blocks: Option<u64>,
+        /// Canonical payload format the hash was computed under; 1 when
+        /// omitted
+        #[serde(default)]
+        payload_version: Option<u16>,
+    },
+    /// Register an equation proof computed from `depends_on_root`, which must
+    /// be a registered, unrevoked root
+    RegisterEquationProofLinked {
+        hash: Binary,
+        depends_on_root: Binary,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+    },
+    /// Register an equation proof from its full payload
+    RegisterEquationProofPayload {
+        payload: EquationProofPayload,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+    },
+    /// Register a Merkle root from its full payload and keep the payload
+    /// on-chain for `GetRootMetadata`
+    RegisterRootWithMetadata {
+        payload: MerkleRootPayload,
+        tags: Option<Vec<String>>,
+        memo: Option<String>,
+    },
+    /// Register a hash under a caller-defined namespace such as `"dataset"`;
+    /// the built-in anchor type names are reserved
+    RegisterNamespaced { namespace: String, hash: Binary },
+    /// Register up to `Config.max_batch_size` anchors of any type atomically
+    RegisterBatch { anchors: Vec<BatchEntry> },
+    /// `RegisterBatch` with typed anchor types, for jobs landing roots,
+    /// claim scores and equation proofs together
+    RegisterMixedBatch { entries: Vec<MixedEntry> },
+    /// Admin: import an anchor keeping its original height and time
+    RegisterHistorical(HistoricalAnchor),
+    /// Admin: import up to `MAX_IMPORT_BATCH` anchors from another registry,
+    /// continuing `InstantiateMsg.initial_anchors`
+    ImportAnchors { anchors: Vec<ImportEntry> },
+    /// Owner: transfer, accept or renounce ownership. Renouncing is final:
+    /// the registry opens to every sender and nothing can be paused,
+    /// allowlisted or revoked again
+    UpdateOwnership(cw_ownable::Action),
+    /// Anyone: remove up to `limit` (at most `MAX_PRUNE_BATCH`) anchors whose
+    /// TTL has run out, oldest expiry first
+    PruneExpired { limit: u32 },
+    /// Admin: forbid registration of a hash under any anchor type
+    BlockHash { hash: Binary, reason: String },
+    /// Admin: lift a blocklist entry
+    UnblockHash { hash: Binary },
+    /// Admin: replace the text limits for persisted strings
+    UpdateLimits { limits: Limits },
+    /// Admin: allow or forbid registrations replacing existing entries
+    SetAllowOverwrite { allow: bool },
+    /// Admin: let revoked hashes be re-registered this many blocks after
+    /// revocation; `None` keeps them blocked permanently
+    SetReregistrationLockout { blocks: Option<u64> },
+    /// Admin: cap anchors per registrant per block; `None` removes the cap
+    SetRateLimit { max_anchors_per_block_per_registrant: Option<u32> },
+    /// Admin: cap the anchors `registrant` may ever register; `None` removes
+    /// the quota
+    SetQuota { registrant: String, max_anchors: Option<u64> },
+    /// Admin: cap anchors per `RegisterBatch`, at most `HARD_MAX_BATCH_SIZE`;
+    /// 0 disables batching
+    SetMaxBatchSize { max_batch_size: u32 },
+    /// Admin: notify `contract` of every registration through its
+    /// `msg_template` execute variant
+    AddSubscriber { contract: String, msg_template: String },
+    /// Admin: stop notifying `contract`
+    RemoveSubscriber { contract: String },
+    /// Admin: cap the gas of each subscriber notification, from 1 to
+    /// `HARD_MAX_SUBSCRIBER_GAS_LIMIT`
+    SetSubscriberGasLimit { gas_limit: u64 },
+    /// Admin: grant the operator role
+    AddOperator { address: String },
+    /// Admin: withdraw the operator role
+    RemoveOperator { address: String },
+    /// Admin or operator: allow an address to register when the registry is restricted
+    AddRegistrant { address: String },
+    /// Admin or operator: revoke an address's permission to register
+    RemoveRegistrant { address: String },
+    /// Admin or original registrant: mark an anchor as invalid, keeping it
+    /// as a tombstone that blocks re-registration until any
+    /// `Config.reregistration_lockout_blocks` have elapsed
+    RevokeAnchor {
+        hash: Binary,
+        anchor_type: String,
+        reason: String,
+    },
+    /// Admin or registrant of `old_root`: point it at the registered root
+    /// `new_root` that replaces it. A root is superseded at most once.
+    SupersedeRoot { old_root: Binary, new_root: Binary },
+    /// Co-sign an existing, unrevoked anchor registered by someone else;
+    /// at most `MAX_ATTESTATIONS` per anchor
+    AttestAnchor { hash: Binary, anchor_type: String },
+    /// Admin or operator: reject all registrations until `Unpause`
+    Pause {},
+    /// Admin or operator: accept registrations again
+    Unpause {},
+    /// Send a registered anchor to the registry at the other end of an open
+    /// IBC channel
+    #[cfg(feature = "cosmwasm-ibc")]
+    RelayAnchor {
+        channel_id: String,
+        anchor_type: String,
+        hash: Binary,
+    },
+    /// Admin: allow IBC channels to `port_id` over `connection_id`
+    #[cfg(feature = "cosmwasm-ibc")]
+    AllowIbcCounterparty { connection_id: String, port_id: String },
+    /// Admin: refuse new channels to `port_id` over `connection_id` and stop
+    /// relaying over those already open
+    #[cfg(feature = "cosmwasm-ibc")]
+    DisallowIbcCounterparty { connection_id: String, port_id: String },
+}
+
+impl ExecuteMsg {
+    /// Whether the message registers anchors, and so is rejected while paused.
+    pub fn is_registration(&self) -> bool {
+        matches!(
+            self,
+            ExecuteMsg::RegisterRoot { .. }
+                | ExecuteMsg::RegisterRootChained { .. }
+                | ExecuteMsg::RegisterClaimScore { .. }
+                | ExecuteMsg::RegisterClaimScoreIndexed { .. }
+                | ExecuteMsg::RegisterClaimScorePayload { .. }
+                | ExecuteMsg::RegisterEquationProof { .. }
+                | ExecuteMsg::RegisterEquationProofLinked { .. }
+                | ExecuteMsg::RegisterEquationProofPayload { .. }
+                | ExecuteMsg::RegisterRootWithMetadata { .. }
+                | ExecuteMsg::RegisterNamespaced { .. }
+                | ExecuteMsg::RegisterBatch { .. }
+                | ExecuteMsg::RegisterMixedBatch { .. }
+                | ExecuteMsg::RegisterHistorical(_)
+                | ExecuteMsg::ImportAnchors { .. }
+        )
+    }
+
+    /// `RegisterRoot` for `hash` with no tags, memo or expiry.
+    #[cfg(feature = "cosmwasm")]
+    pub fn register_root(hash: Binary) -> Self {
+        ExecuteMsg::RegisterRoot { hash, tags: None, memo: None, ttl_blocks: None, payload_version: None }
+    }
+
+    /// `RegisterClaimScore` for `hash` with no tags, memo or expiry.
+    #[cfg(feature = "cosmwasm")]
+    pub fn register_claim_score(hash: Binary) -> Self {
+        ExecuteMsg::RegisterClaimScore { hash, tags: None, memo: None, ttl_blocks: None, payload_version: None }
+    }
+
+    /// `RegisterEquationProof` for `hash` with no tags, memo or expiry.
+    #[cfg(feature = "cosmwasm")]
+    pub fn register_equation_proof(hash: Binary) -> Self {
+        ExecuteMsg::RegisterEquationProof { hash, tags: None, memo: None, ttl_blocks: None, payload_version: None }
+    }
+
+    /// `RegisterBatch` for `anchors`.
+    #[cfg(feature = "cosmwasm")]
+    pub fn register_batch(anchors: Vec<BatchEntry>) -> Self {
+        ExecuteMsg::RegisterBatch { anchors }
+    }
+
+    /// Wrap the message in a `WasmMsg::Execute` against `registry`, for
+    /// contracts that anchor hashes themselves.
+    #[cfg(feature = "cosmwasm")]
+    pub fn into_cosmos_msg(self, registry: impl Into<String>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute { contract_addr: registry.into(), msg: to_json_binary(&self)?, funds: vec![] }.into())
+    }
 }
 
 /// Query messages for hash verification.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    /// Verify whether a root hash is registered
-    VerifyRoot { hash: Binary },
-    /// Verify whether a claim score hash is registered
-    VerifyClaimScore { hash: Binary },
-    /// Verify whether an equation proof hash is registered
-    VerifyEquationProof { hash: Binary },
+    /// Verify whether a root hash is registered, given as exactly one of
+    /// `hash` or `hash_hex` (lowercase hex of a supported digest length)
+    VerifyRoot { hash: Option<Binary>, hash_hex: Option<String> },
+    /// Verify whether a claim score hash is registered; hash as in `VerifyRoot`
+    VerifyClaimScore { hash: Option<Binary>, hash_hex: Option<String> },
+    /// Verify whether an equation proof hash is registered; hash as in `VerifyRoot`
+    VerifyEquationProof { hash: Option<Binary>, hash_hex: Option<String> },
+    /// Verify a claim score payload in-contract and look up its recomputed hash
+    VerifyClaimScorePayload { payload: ClaimScorePayload },
+    /// Verify an equation proof payload in-contract and look up its recomputed hash
+    VerifyEquationProofPayload { payload: EquationProofPayload },
+    /// Verify a Merkle root payload in-contract and look up its root hash
+    VerifyMerklePayload { payload: MerkleRootPayload },
     /// Get contract configuration
     GetConfig {},
-    /// Get anchor entry details
-    GetAnchor { hash: Binary, anchor_type: String },
+    /// Current owner, pending transfer and its expiry
+    Ownership {},
+    /// Anchor totals per type, read from the maintained counters
+    GetTotals {},
+    /// Code name and version, for auditing which build is deployed
+    GetContractInfo {},
+    /// Get anchor entry details; hash as in `VerifyRoot`
+    GetAnchor { hash: Option<Binary>, hash_hex: Option<String>, anchor_type: AnchorType },
+    /// Every anchor registered under `hash`, whatever its type
+    FindHash { hash: Binary },
+    /// Whether `hash` is registered under `anchor_type`. Cheaper than the
+    /// `Verify*` queries: only key presence is checked, so no entry is
+    /// loaded or deserialized. Revoked anchors still exist.
+    AnchorExists { anchor_type: String, hash: Binary },
+    /// Get the earliest-registered anchor of a type that is neither revoked
+    /// nor expired. Fails with `OldestScanTruncated` rather than answer
+    /// `None` when its first `MAX_OLDEST_SCAN` registrations are all dead.
+    GetOldest { anchor_type: String },
+    /// Get the anchor given a registration sequence number
+    GetAnchorByIndex { index: u64 },
+    /// Addresses that attested to an anchor, oldest first
+    GetAttestations { hash: Binary, anchor_type: String },
+    /// Check whether a hash is blocklisted
+    IsBlocked { hash: Binary },
+    /// List blocklisted hashes ordered by hash bytes
+    ListBlocked {
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+    },
+    /// Verify several `(leaf, siblings, directions)` inclusion proofs against
+    /// one registered root (see `merkle_anchor::verify_inclusion`)
+    VerifyInclusionBatch {
+        root: Binary,
+        items: Vec<(Binary, Vec<Binary>, Vec<bool>)>,
+    },
+    /// Verify one leaf's inclusion proof against `root`, reporting whether
+    /// the root is registered, unrevoked and unexpired separately from
+    /// whether the proof holds (see
+    /// `merkle_anchor::verify_proof`). At most `MAX_PROOF_DEPTH` steps.
+    VerifyLeaf {
+        root: Binary,
+        leaf: Binary,
+        proof: Vec<ProofStep>,
+    },
+    /// Registrations per `bucket_blocks`-wide window over the last
+    /// `buckets` windows, ending at the current height (oldest first)
+    ActivityHistogram {
+        anchor_type: Option<String>,
+        bucket_blocks: u64,
+        buckets: u32,
+    },
+    /// Claim score counts per stability class, scanning at most
+    /// `MAX_STABILITY_SCAN` entries
+    StabilityHistogram {},
+    /// Dry-run a registration: report whether it would succeed and why not
+    SimulateRegister {
+        anchor_type: String,
+        hash: Binary,
+        sender: String,
+        tags: Option<Vec<String>>,
+    },
+    /// List anchors registered between block times `start` and `end`
+    /// (seconds, inclusive), ordered by time; untimed entries are excluded
+    ListByTimeRange {
+        start: u64,
+        end: u64,
+        anchor_type: Option<String>,
+        start_after: Option<TimeCursor>,
+        limit: Option<u32>,
+    },
+    /// List anchors changed after `height`, ordered by last modification
+    ListModifiedSince {
+        height: u64,
+        start_after: Option<ModifiedCursor>,
+        limit: Option<u32>,
+    },
+    /// Export config and all anchors in deterministic chunks, ordered by
+    /// (hash, anchor type); query every chunk at the same height
+    ExportState {
+        start_after: Option<ExportCursor>,
+        limit: Option<u32>,
+    },
+    /// List anchors registered at or after `height`, ordered by
+    /// (registration height, hash), for incremental mirroring
+    GetAnchorsSince {
+        height: u64,
+        start_after: Option<(u64, Binary)>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
+    /// List anchors registered exactly at `height`, ordered by (hash, type);
+    /// a block without registrations yields an empty list
+    GetAnchorsAtHeight {
+        height: u64,
+        start_after: Option<AnchorKey>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
+    /// Run up to `MAX_MULTI_QUERIES` non-nested queries in one call
+    Multi { queries: Vec<QueryMsg> },
+    /// List anchors of any type carrying a tag, in index key order
+    ListByTag {
+        tag: String,
+        start_after: Option<AnchorKey>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
+    /// List anchors carrying a tag, optionally of one type, in index key order
+    GetAnchorsByTag {
+        tag: String,
+        anchor_type: Option<String>,
+        start_after: Option<AnchorKey>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
+    /// List anchors registered by an address, optionally of one type,
+    /// in index key order
+    GetAnchorsByRegistrant {
+        registrant: String,
+        anchor_type: Option<String>,
+        start_after: Option<AnchorKey>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
+    /// List allowlisted registrants ordered by address
+    ListRegistrants {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Verify whether a hash is registered under `namespace`
+    VerifyNamespaced { namespace: String, hash: Binary },
+    /// List namespaces in use with their anchor counts
+    ListNamespaces {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Claim scores indexed under `claim_id`, in registration order
+    GetClaimScoreHistory {
+        claim_id: u64,
+        start_after: Option<ClaimCursor>,
+        limit: Option<u32>,
+    },
+    /// Payloads registered for `claim_id` through `RegisterClaimScorePayload`,
+    /// oldest first
+    GetClaimScorePayloads {
+        claim_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Payloads registered for `equation_name` through
+    /// `RegisterEquationProofPayload`, ordered by payload hash
+    GetProofsByEquation {
+        equation_name: String,
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+    },
+    /// Equation proofs registered against `root_hash`, ordered by proof hash
+    GetDependents {
+        root_hash: Binary,
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+    },
+    /// Contracts notified on registration
+    ListSubscribers {},
+    /// Failed subscriber notifications, oldest first
+    ListFailedDispatches { start_after: Option<u64>, limit: Option<u32> },
+    /// Anchors registered by `registrant`, in total and per kind
+    GetRegistrantStats { registrant: String },
+    /// The newest registered root
+    GetLatestRoot {},
+    /// Walk a root chain backwards from `head`, newest first
+    GetRootChain {
+        head: Binary,
+        limit: Option<u32>,
+    },
+    /// Full payload of a root registered through `RegisterRootWithMetadata`
+    GetRootMetadata { hash: Binary },
+    /// List registered roots ordered by hash bytes
+    ListRoots {
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
+    /// List registered claim scores ordered by hash bytes
+    ListClaimScores {
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
+    /// List registered equation proofs ordered by hash bytes
+    ListEquationProofs {
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+        /// Traversal direction; ascending when omitted
+        order: Option<SortOrder>,
+    },
 }
 
 /// Response for verification queries.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct VerifyResponse {
     pub exists: bool,
     pub hash_hex: String,
+    /// Registration sequence number of the entry, when it has one
+    pub index: Option<u64>,
+    /// Number of addresses that attested to the entry
+    pub attestations: u32,
     pub entry: Option<AnchorEntry>,
+    /// Whether the hash is currently blocklisted
+    pub blocked: bool,
+    /// Whether the hash was anchored and later revoked
+    pub revoked: bool,
+    /// Successor root (hex) when the entry was superseded
+    pub superseded_by: Option<String>,
+    /// Height from which the entry is expired, for anchors with a TTL
+    pub expires_at: Option<u64>,
+    /// Whether the entry has expired; it still `exists` until pruned
+    pub expired: bool,
+    /// Canonical payload format of the entry's hash
+    pub payload_version: Option<u16>,
 }
 
-/// Response for config query.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct ConfigResponse {
-    pub admin: String,
-    pub total_anchors: u64,
+/// Response for full-payload verification queries. An invalid payload is
+/// reported through `payload_valid`, never as a query error.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PayloadVerifyResponse {
+    /// Whether `payload_hash` matches the payload contents
+    pub payload_valid: bool,
+    /// Whether the anchored hash is registered
+    pub registered: bool,
+    pub entry: Option<AnchorEntry>,
 }
 
-// ── Contract Entry Points ───────────────────────────────────────────────────
+/// Response for the blocklist lookup query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct BlockedResponse {
+    pub blocked: bool,
+    pub reason: Option<BlockReason>,
+}
+
+/// A single blocklist entry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct BlockedHash {
+    pub hash_hex: String,
+    pub reason: BlockReason,
+}
+
+/// Response for the blocklist listing query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct BlocklistResponse {
+    pub entries: Vec<BlockedHash>,
+}
+
+/// Response for the registrant allowlist query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RegistrantsResponse {
+    pub registrants: Vec<String>,
+}
+
+/// Receipt returned in the `Response.data` of a registration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorReceipt {
+    /// Hex SHA-256 of the registry name, for client-side namespacing
+    pub registry_id: String,
+    pub anchor_type: String,
+    pub hash_hex: String,
+    pub block_height: u64,
+    /// Block time in seconds
+    pub block_time: u64,
+    pub registrant: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_index: Option<u32>,
+    /// Registration sequence number of the anchor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u64>,
+    /// `RECEIPT_FORMAT_VERSION`; 0 for receipts that predate it
+    #[serde(default)]
+    pub version: u16,
+    /// Hex SHA-256 of `anchor_receipt:{anchor_type}:{hash_hex}:{block_height}:{registrant}`
+    #[serde(default)]
+    pub receipt_hash: String,
+}
+
+impl AnchorReceipt {
+    /// Hash of the fields that identify the registration.
+    pub fn compute_receipt_hash(&self) -> String {
+        let preimage = format!(
+            "anchor_receipt:{}:{}:{}:{}",
+            self.anchor_type, self.hash_hex, self.block_height, self.registrant
+        );
+        hex::encode(compute_sha256(preimage.as_bytes()))
+    }
+}
+
+/// Response for single-anchor lookups that may find nothing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorResponse {
+    pub entry: Option<AnchorEntry>,
+}
+
+/// Response for `GetRootMetadata`; `payload` is `None` for roots registered
+/// by hash alone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RootMetadataResponse {
+    pub payload: Option<MerkleRootPayload>,
+}
+
+/// Response for listing queries.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorListResponse {
+    pub anchors: Vec<AnchorEntry>,
+}
+
+/// Response for per-type listing queries.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ListResponse {
+    pub anchors: Vec<AnchorEntry>,
+    /// Base64 hash to pass as `start_after` for the next page; `None` on the last page
+    pub next_start_after: Option<String>,
+}
+
+/// Response for `VerifyNamespaced`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct NamespacedVerifyResponse {
+    pub exists: bool,
+    pub hash_hex: String,
+    pub entry: Option<NamespacedEntry>,
+    /// Whether the hash is currently blocklisted
+    pub blocked: bool,
+}
+
+/// A namespace and the number of anchors registered in it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct NamespaceCount {
+    pub namespace: String,
+    pub count: u64,
+}
+
+/// Response for `ListNamespaces`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct NamespacesResponse {
+    pub namespaces: Vec<NamespaceCount>,
+}
+
+/// A contract notified on registration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Subscriber {
+    pub contract: String,
+    pub msg_template: String,
+}
+
+/// A subscriber notification awaiting its reply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct PendingDispatch {
+    pub contract: String,
+    pub anchor_type: String,
+    pub hash_hex: String,
+}
+
+/// A subscriber notification that failed; the anchor itself was kept.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FailedDispatch {
+    pub id: u64,
+    pub contract: String,
+    pub anchor_type: String,
+    pub hash_hex: String,
+    /// Block height at which the notification failed
+    pub height: u64,
+    pub error: String,
+}
+
+/// Response for `GetAttestations`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AttestationsResponse {
+    pub attestations: Vec<Attestation>,
+}
+
+/// Response for `ListFailedDispatches`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FailedDispatchesResponse {
+    pub dispatches: Vec<FailedDispatch>,
+}
+
+/// Response for `ListSubscribers`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SubscribersResponse {
+    pub subscribers: Vec<Subscriber>,
+}
+
+/// Response for `AnchorExists`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorExistsResponse {
+    pub exists: bool,
+}
+
+/// Response for `GetTotals`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct TotalsResponse {
+    pub roots: u64,
+    pub claim_scores: u64,
+    pub equation_proofs: u64,
+    /// All anchors, including namespaced ones
+    pub total: u64,
+}
+
+/// Response for `GetRegistrantStats`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RegistrantStatsResponse {
+    pub registrant: String,
+    pub total: u64,
+    pub by_type: RegistrantStats,
+    /// Lifetime cap on `total` set through `SetQuota`, if any
+    pub quota: Option<u64>,
+}
+
+/// Response for `GetContractInfo`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ContractInfoResponse {
+    /// `CONTRACT_NAME` of the running code
+    pub contract_name: String,
+    /// Crate version of the running code
+    pub version: String,
+    /// `PAYLOAD_FORMAT_VERSION` the running code expects
+    pub payload_format_version: u16,
+    /// Payload format versions registrations may claim
+    pub supported_payload_versions: Vec<u16>,
+    /// Version recorded by cw2 at instantiate or the last migration, if any
+    pub stored_version: Option<String>,
+}
+
+/// Response for `GetLatestRoot`; `None` before the first root registration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct LatestRootResponse {
+    pub latest: Option<LatestRoot>,
+}
+
+/// Response for `GetRootChain`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RootChainResponse {
+    /// Roots from `head` backwards; revoked roots are included as tombstones
+    pub roots: Vec<AnchorEntry>,
+    /// Why the walk stopped
+    pub end: ChainEnd,
+}
+
+/// How a root chain walk ended.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ChainEnd {
+    /// The last root links to `GENESIS_ROOT`
+    Genesis,
+    /// The last root was registered without a link
+    Unlinked,
+    /// The last root links to a hash that is not a registered root
+    Broken { missing_hex: String },
+    /// `limit` roots were returned; pass `next_head` (base64) as `head` to continue
+    Limit { next_head: String },
+}
+
+/// Response for batch inclusion verification.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct InclusionBatchResponse {
+    /// Whether the root is registered and neither revoked nor expired
+    pub root_registered: bool,
+    /// One verdict per item, in order; all `false` when the root is unregistered
+    pub results: Vec<bool>,
+}
+
+/// One level of a `VerifyLeaf` proof: the sibling hash and the side it sits
+/// on, `"left"` or `"right"`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ProofStep {
+    pub hash: Binary,
+    pub side: String,
+}
+
+/// Response for `VerifyLeaf`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct LeafVerifyResponse {
+    /// Whether `root` is registered and neither revoked nor expired
+    pub root_registered: bool,
+    /// Whether the proof recomputes to `root`, whether or not it is registered
+    pub proof_valid: bool,
+}
+
+/// One window of an activity histogram; heights are inclusive.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct HistogramBucket {
+    pub start_height: u64,
+    pub end_height: u64,
+    pub count: u64,
+}
+
+/// Response for the activity histogram query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct HistogramResponse {
+    pub buckets: Vec<HistogramBucket>,
+}
+
+/// Number of claim scores recorded under one stability class.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StabilityCount {
+    pub stability_class: String,
+    pub count: u64,
+}
+
+/// Response for the stability histogram query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StabilityHistogramResponse {
+    /// Counts per class, sorted by class name
+    pub classes: Vec<StabilityCount>,
+    /// Claim scores registered by bare hash, without a stability class
+    pub unclassified: u64,
+    /// True when the scan stopped at `MAX_STABILITY_SCAN` entries
+    pub truncated: bool,
+}
+
+/// An anchor together with the kinds of change it saw since the queried height.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ModifiedAnchor {
+    pub anchor_type: AnchorType,
+    pub hash_hex: String,
+    /// Height of the latest change, for `ModifiedCursor`
+    pub last_modified_height: u64,
+    /// The current entry; `None` once the anchor has been pruned
+    pub entry: Option<AnchorEntry>,
+    /// Distinct change kinds (e.g. "register", "block"), oldest first
+    pub change_kinds: Vec<String>,
+}
+
+/// Response for the modified-since query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ModifiedSinceResponse {
+    pub anchors: Vec<ModifiedAnchor>,
+}
+
+/// Response for `GetAnchorsSince`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorsSinceResponse {
+    pub anchors: Vec<AnchorEntry>,
+    /// (height, hash) to pass as `start_after` for the next page; `None` on the last page
+    pub next_start_after: Option<(u64, Binary)>,
+}
+
+/// One chunk of a state export. Only the first chunk carries the config.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StateExport {
+    /// `EXPORT_FORMAT_VERSION` at export time
+    pub version: u16,
+    pub start_after: Option<ExportCursor>,
+    pub config: Option<Config>,
+    pub anchors: Vec<AnchorEntry>,
+    /// Cursor for the next chunk; `None` on the last chunk
+    pub next_start_after: Option<ExportCursor>,
+    /// Hex SHA-256 of the chunk's JSON encoding with this field empty
+    pub chunk_hash: String,
+}
+
+#[cfg(feature = "cosmwasm")]
+impl StateExport {
+    /// Hash of the canonical chunk encoding. Field order is fixed by the
+    /// struct definitions, so equal chunks always hash equally.
+    pub fn compute_chunk_hash(&self) -> StdResult<String> {
+        let unhashed = StateExport { chunk_hash: String::new(), ..self.clone() };
+        Ok(hex::encode(compute_sha256(&to_json_vec(&unhashed)?)))
+    }
+}
+
+/// Response for the registration dry-run query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SimulateResponse {
+    pub would_succeed: bool,
+    /// The error the registration would fail with
+    pub failure_reason: Option<String>,
+}
+
+/// Outcome of a single sub-query within `QueryMsg::Multi`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MultiResult {
+    /// The sub-query's JSON response
+    Ok(Binary),
+    /// The sub-query's error message
+    Err(String),
+}
+
+/// Response for `QueryMsg::Multi`, in the order the queries were given.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MultiResponse {
+    pub results: Vec<MultiResult>,
+}
+
+/// Response for config query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ConfigResponse {
+    /// The owner; empty once ownership is renounced
+    pub admin: String,
+    pub name: String,
+    pub registry_id: String,
+    pub total_anchors: u64,
+    pub total_roots: u64,
+    pub total_claim_scores: u64,
+    pub total_equation_proofs: u64,
+    pub limits: Limits,
+    pub allow_overwrite: bool,
+    pub reregistration_lockout_blocks: Option<u64>,
+    pub restricted: bool,
+    /// Whether any sender may register; the inverse of `restricted`
+    pub public_registration: bool,
+    pub paused: bool,
+    pub max_anchors_per_block_per_registrant: Option<u32>,
+    pub operators: Vec<String>,
+    pub max_batch_size: u32,
+    pub accepted_hash_lengths: Vec<u32>,
+    pub subscriber_gas_limit: u64,
+}
+
+// ── Contract Entry Points ───────────────────────────────────────────────────
 
 #[cfg(feature = "cosmwasm")]
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let admin = msg.admin.unwrap_or_else(|| info.sender.to_string());
+    let name = msg.name.unwrap_or_else(|| env.contract.address.to_string());
+    validate_text("name", &name, MAX_NAME_LEN)?;
+    let limits = msg.limits.unwrap_or_default();
+    limits.validate()?;
+    let max_batch_size = msg.max_batch_size.unwrap_or_else(default_max_batch_size);
+    check_max_batch_size(max_batch_size)?;
+    let accepted_hash_lengths = msg.accepted_hash_lengths.unwrap_or_else(default_accepted_hash_lengths);
+    check_accepted_hash_lengths(&accepted_hash_lengths)?;
+    let restricted = match msg.public_registration {
+        Some(true) if msg.restricted => return Err(ContractError::ConflictingRegistrationMode {}),
+        Some(public) => !public,
+        None => msg.restricted,
+    };
     let config = Config {
-        admin,
-        total_anchors: 0,
+        name,
+        limits,
+        allow_overwrite: false,
+        reregistration_lockout_blocks: None,
+        restricted,
+        paused: false,
+        max_anchors_per_block_per_registrant: None,
+        operators: vec![],
+        max_batch_size,
+        accepted_hash_lengths,
+        subscriber_gas_limit: SUBSCRIBER_GAS_LIMIT,
     };
+    cw_ownable::initialize_owner(deps.storage, deps.api, Some(&admin))?;
     CONFIG.save(deps.storage, &config)?;
+    TOTAL_ANCHORS.save(deps.storage, &0)?;
+    PRESENCE_COMPLETE.save(deps.storage, &true)?;
+    TYPE_INDEX_COMPLETE.save(deps.storage, &true)?;
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let imported = import_anchors(deps.storage, &env, msg.initial_anchors.unwrap_or_default())?;
 
-    Ok(Response::new()
+    let registry_id = registry_id(&config, &env);
+    // Legacy flat attributes are kept for one release alongside the event
+    let mut response = Response::new()
+        .add_event(
+            Event::new(EVENT_REGISTRY_INSTANTIATED)
+                .add_attribute("admin", &admin)
+                .add_attribute("registry_id", &registry_id),
+        )
         .add_attribute("action", "instantiate")
-        .add_attribute("admin", &config.admin))
+        .add_attribute("admin", admin)
+        .add_attribute("registry_id", registry_id);
+    if imported > 0 {
+        response = response.add_attribute("imported", imported.to_string());
+    }
+    Ok(response)
 }
 
+/// Upgrade the code, refusing other contracts and version downgrades.
 #[cfg(feature = "cosmwasm")]
 #[entry_point]
-pub fn execute(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = cw2::get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongContract { contract: stored.contract });
+    }
+    if version_parts(&stored.version) > version_parts(CONTRACT_VERSION) {
+        return Err(ContractError::Downgrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+    // Both moves read the legacy config before either rewrites it
+    let counters_moved = move_counters_out_of_config(deps.storage)?;
+    let admin_moved = move_admin_to_ownership(deps.storage, deps.api)?;
+    if counters_moved || admin_moved {
+        // Rewrite the config without the moved fields
+        let config = CONFIG.load(deps.storage)?;
+        CONFIG.save(deps.storage, &config)?;
+    }
     match msg {
-        ExecuteMsg::RegisterRoot { hash } => {
-            register_hash(deps, env, info, hash, "root", &ROOTS)
+        MigrateMsg::V1 {} => {}
+        MigrateMsg::BackfillCounters { limit } => {
+            let (counted, complete) = backfill_counters(deps.storage, limit)?;
+            response = response
+                .add_attribute("counted", counted.to_string())
+                .add_attribute("backfill_complete", complete.to_string());
+        }
+        MigrateMsg::BackfillRegistrantStats { limit } => {
+            let (counted, complete) = backfill_registrant_stats(deps.storage, limit)?;
+            response = response
+                .add_attribute("counted", counted.to_string())
+                .add_attribute("backfill_complete", complete.to_string());
+        }
+        MigrateMsg::MoveAnchors { limit } => {
+            let (moved, complete) = move_legacy_anchors(deps.storage, limit)?;
+            response = response
+                .add_attribute("moved", moved.to_string())
+                .add_attribute("move_complete", complete.to_string());
         }
-        ExecuteMsg::RegisterClaimScore { hash } => {
-            register_hash(deps, env, info, hash, "claim_score", &CLAIM_SCORES)
+        MigrateMsg::BackfillPresence { limit } => {
+            let (written, complete) = backfill_presence(deps.storage, limit)?;
+            response = response
+                .add_attribute("written", written.to_string())
+                .add_attribute("backfill_complete", complete.to_string());
         }
-        ExecuteMsg::RegisterEquationProof { hash } => {
-            register_hash(deps, env, info, hash, "equation_proof", &EQUATION_PROOFS)
+        MigrateMsg::BackfillTypeIndex { limit } => {
+            let (indexed, complete) = backfill_type_index(deps.storage, limit)?;
+            response = response
+                .add_attribute("indexed", indexed.to_string())
+                .add_attribute("backfill_complete", complete.to_string());
         }
     }
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(response)
 }
 
+/// Move counters still held in `Config` into their own items, returning
+/// whether any were moved. Runs on every migration and does nothing once
+/// `TOTAL_ANCHORS` exists.
 #[cfg(feature = "cosmwasm")]
-fn register_hash(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    hash: Binary,
-    anchor_type: &str,
-    store: &Map<&[u8], AnchorEntry>,
-) -> StdResult<Response> {
-    // Validate: must be exactly 32 bytes (SHA-256)
-    if hash.len() != 32 {
-        return Err(StdError::generic_err(
-            "Hash must be exactly 32 bytes (SHA-256)",
-        ));
+fn move_counters_out_of_config(storage: &mut dyn Storage) -> Result<bool, ContractError> {
+    if TOTAL_ANCHORS.may_load(storage)?.is_some() {
+        return Ok(false);
     }
+    let raw = storage.get(CONFIG.as_slice()).ok_or_else(|| StdError::not_found("config"))?;
+    let legacy: LegacyCounters = cosmwasm_std::from_json(&raw)?;
+    TOTAL_ANCHORS.save(storage, &legacy.total_anchors)?;
+    for (anchor_type, count) in [
+        (AnchorType::MerkleRoot, legacy.total_roots),
+        (AnchorType::ClaimScore, legacy.total_claim_scores),
+        (AnchorType::EquationProof, legacy.total_equation_proofs),
+    ] {
+        TYPE_COUNTS.save(storage, anchor_type.as_str(), &count)?;
+    }
+    Ok(true)
+}
 
-    let hash_hex = hex::encode(hash.as_slice());
-
-    let entry = AnchorEntry {
-        hash_hex: hash_hex.clone(),
-        anchor_type: anchor_type.to_string(),
-        registered_at: env.block.height,
-        registrant: info.sender.to_string(),
-    };
-
-    store.save(deps.storage, hash.as_slice(), &entry)?;
-
-    // Increment total anchors
-    let mut config = CONFIG.load(deps.storage)?;
-    config.total_anchors += 1;
-    CONFIG.save(deps.storage, &config)?;
-
-    Ok(Response::new()
-        .add_attribute("action", format!("register_{}", anchor_type))
-        .add_attribute("hash", &hash_hex)
-        .add_attribute("registrant", info.sender.to_string())
-        .add_attribute("block_height", env.block.height.to_string()))
+/// Move the admin still held in `Config` into the cw-ownable ownership item,
+/// returning whether it was moved. Runs on every migration and does nothing
+/// once ownership is initialized.
+#[cfg(feature = "cosmwasm")]
+fn move_admin_to_ownership(storage: &mut dyn Storage, api: &dyn Api) -> Result<bool, ContractError> {
+    if cw_ownable::get_ownership(storage).is_ok() {
+        return Ok(false);
+    }
+    let raw = storage.get(CONFIG.as_slice()).ok_or_else(|| StdError::not_found("config"))?;
+    let legacy: LegacyAdmin = cosmwasm_std::from_json(&raw)?;
+    cw_ownable::initialize_owner(storage, api, legacy.admin.as_deref())?;
+    Ok(true)
 }
 
+/// Move the next `limit` legacy per-type entries into `ANCHORS`, giving each
+/// a presence entry. An entry already rewritten to `ANCHORS` shadows its
+/// legacy copy, which is dropped. Returns the number moved and whether the
+/// legacy maps are now empty, at which point every anchor has a presence
+/// entry.
 #[cfg(feature = "cosmwasm")]
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::VerifyRoot { hash } => {
-            to_json_binary(&verify_hash(deps, hash, &ROOTS)?)
+fn move_legacy_anchors(storage: &mut dyn Storage, limit: Option<u32>) -> Result<(usize, bool), ContractError> {
+    let limit = limit.unwrap_or(MAX_BACKFILL_BATCH).clamp(1, MAX_BACKFILL_BATCH) as usize;
+    let mut moved = 0;
+    for anchor_type in AnchorType::ALL {
+        let legacy = anchor_type.legacy_store();
+        // Moved entries leave the legacy map, so each page starts from the top
+        let page = legacy
+            .range(storage, None, None, Order::Ascending)
+            .take(limit + 1 - moved)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (hash, entry) in page {
+            if moved == limit {
+                return Ok((moved, false));
+            }
+            if !ANCHORS.has(storage, (anchor_type.as_str(), &hash)) {
+                ANCHORS.save(storage, (anchor_type.as_str(), &hash), &entry)?;
+                let compact = CompactEntry { registered_at: entry.registered_at };
+                PRESENCE.save(storage, (anchor_type.as_str(), &hash), &compact)?;
+            }
+            legacy.remove(storage, &hash);
+            moved += 1;
         }
-        QueryMsg::VerifyClaimScore { hash } => {
-            to_json_binary(&verify_hash(deps, hash, &CLAIM_SCORES)?)
-        }
-        QueryMsg::VerifyEquationProof { hash } => {
-            to_json_binary(&verify_hash(deps, hash, &EQUATION_PROOFS)?)
+    }
+    PRESENCE_BACKFILL.remove(storage);
+    PRESENCE_COMPLETE.save(storage, &true)?;
+    Ok((moved, true))
+}
+
+/// (hash, entry) pairs visited by one page of a migration
+#[cfg(feature = "cosmwasm")]
+type AnchorPage = Vec<(Vec<u8>, AnchorEntry)>;
+
+/// The next `limit` anchors of every type, in `ANCHORS` or the legacy
+/// per-type maps, after the (anchor_type, hash) position saved in `cursor`.
+/// Advances the cursor, or clears it and returns `true` once every anchor
+/// has been visited.
+#[cfg(feature = "cosmwasm")]
+fn next_anchor_page(
+    storage: &mut dyn Storage,
+    cursor: &Item<(String, Binary)>,
+    limit: Option<u32>,
+) -> Result<(AnchorPage, bool), ContractError> {
+    let limit = limit.unwrap_or(MAX_BACKFILL_BATCH).clamp(1, MAX_BACKFILL_BATCH) as usize;
+    let resume = match cursor.may_load(storage)? {
+        Some((anchor_type, hash)) => Some((parse_anchor_type(&anchor_type)?, hash)),
+        None => None,
+    };
+
+    let mut page = Vec::with_capacity(limit + 1);
+    for anchor_type in AnchorType::ALL {
+        let start = match &resume {
+            Some((cursor_type, _)) if anchor_type < *cursor_type => continue,
+            Some((cursor_type, hash)) if anchor_type == *cursor_type => Some(Bound::exclusive(hash.as_slice())),
+            _ => None,
+        };
+        for item in range_anchors(storage, anchor_type, start, Order::Ascending).take(limit + 1 - page.len()) {
+            page.push(item?);
         }
-        QueryMsg::GetConfig {} => {
-            let config = CONFIG.load(deps.storage)?;
-            to_json_binary(&ConfigResponse {
-                admin: config.admin,
-                total_anchors: config.total_anchors,
-            })
+        if page.len() > limit {
+            break;
         }
-        QueryMsg::GetAnchor { hash, anchor_type } => {
-            let store = match anchor_type.as_str() {
-                "root" => &ROOTS,
-                "claim_score" => &CLAIM_SCORES,
-                "equation_proof" => &EQUATION_PROOFS,
-                _ => return Err(StdError::generic_err("Unknown anchor type")),
-            };
-            let entry = store.may_load(deps.storage, hash.as_slice())?;
-            to_json_binary(&VerifyResponse {
-                exists: entry.is_some(),
-                hash_hex: hex::encode(hash.as_slice()),
-                entry,
-            })
+    }
+    let complete = page.len() <= limit;
+    page.truncate(limit);
+
+    match page.last() {
+        Some((hash, entry)) if !complete => {
+            cursor.save(storage, &(entry.anchor_type.as_str().to_string(), Binary::from(hash.as_slice())))?
         }
+        _ => cursor.remove(storage),
     }
+    Ok((page, complete))
 }
 
+/// Write presence entries for the next `limit` anchors stored before the
+/// presence map. Returns the number visited and whether every anchor now
+/// has a presence entry.
 #[cfg(feature = "cosmwasm")]
-fn verify_hash(
-    deps: Deps,
-    hash: Binary,
-    store: &Map<&[u8], AnchorEntry>,
-) -> StdResult<VerifyResponse> {
-    let entry = store.may_load(deps.storage, hash.as_slice())?;
-    Ok(VerifyResponse {
-        exists: entry.is_some(),
-        hash_hex: hex::encode(hash.as_slice()),
-        entry,
-    })
+fn backfill_presence(storage: &mut dyn Storage, limit: Option<u32>) -> Result<(usize, bool), ContractError> {
+    let (page, complete) = next_anchor_page(storage, &PRESENCE_BACKFILL, limit)?;
+    for (hash, entry) in &page {
+        let compact = CompactEntry { registered_at: entry.registered_at };
+        PRESENCE.save(storage, (entry.anchor_type.as_str(), hash), &compact)?;
+    }
+    if complete {
+        PRESENCE_COMPLETE.save(storage, &true)?;
+    }
+    Ok((page.len(), complete))
 }
 
-// ── Pure Functions (no chain dependency) ────────────────────────────────────
-
-/// Validate that a hash is exactly 32 bytes.
-pub fn validate_hash(hash: &[u8]) -> bool {
-    hash.len() == 32
+/// Add the next `limit` anchors to `TYPE_HEIGHT_INDEX`. Returns the number
+/// visited and whether the index now covers every anchor.
+#[cfg(feature = "cosmwasm")]
+fn backfill_type_index(storage: &mut dyn Storage, limit: Option<u32>) -> Result<(usize, bool), ContractError> {
+    let (page, complete) = next_anchor_page(storage, &TYPE_INDEX_BACKFILL, limit)?;
+    for (hash, entry) in &page {
+        TYPE_HEIGHT_INDEX.save(storage, (entry.anchor_type.as_str(), entry.registered_at, hash), &())?;
+    }
+    if complete {
+        TYPE_INDEX_COMPLETE.save(storage, &true)?;
+    }
+    Ok((page.len(), complete))
 }
 
-/// Compute SHA-256 of arbitrary data (deterministic).
-pub fn compute_sha256(data: &[u8]) -> [u8; 32] {
-    use sha2::{Sha256, Digest};
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    let result = hasher.finalize();
-    let mut output = [0u8; 32];
-    output.copy_from_slice(&result);
-    output
+/// The registered count for one anchor type.
+#[cfg(feature = "cosmwasm")]
+fn type_count(storage: &dyn Storage, anchor_type: AnchorType) -> StdResult<u64> {
+    Ok(TYPE_COUNTS.may_load(storage, anchor_type.as_str())?.unwrap_or_default())
 }
 
-/// Format a deterministic anchor payload for off-chain verification.
-pub fn format_anchor_payload(
-    hash: &[u8; 32],
-    anchor_type: &str,
-    timestamp: u64,
-) -> Vec<u8> {
-    let mut payload = Vec::new();
-    payload.extend_from_slice(anchor_type.as_bytes());
-    payload.push(b':');
-    payload.extend_from_slice(&hex::encode(hash).as_bytes());
-    payload.push(b':');
-    payload.extend_from_slice(&timestamp.to_be_bytes());
-    payload
+/// Count the next `limit` presence entries into the per-type counters,
+/// zeroing them when a new run starts. Returns the number counted and
+/// whether the run reached the end of the registry.
+///
+/// Refused until every anchor has a presence entry, since anchors missing
+/// from it would be dropped from the counts carried over from the config.
+#[cfg(feature = "cosmwasm")]
+fn backfill_counters(storage: &mut dyn Storage, limit: Option<u32>) -> Result<(usize, bool), ContractError> {
+    if !PRESENCE_COMPLETE.may_load(storage)?.unwrap_or(false) {
+        return Err(ContractError::PresenceBackfillPending {});
+    }
+    let limit = limit.unwrap_or(MAX_BACKFILL_BATCH).clamp(1, MAX_BACKFILL_BATCH) as usize;
+    let cursor = COUNTER_BACKFILL.may_load(storage)?;
+    if cursor.is_none() {
+        TYPE_COUNTS.clear(storage);
+    }
+
+    let start = cursor
+        .as_ref()
+        .map(|(anchor_type, hash)| Bound::exclusive((anchor_type.as_str(), hash.as_slice())));
+    let keys = PRESENCE
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let complete = keys.len() <= limit;
+    let counted = &keys[..keys.len().min(limit)];
+
+    let mut counts = std::collections::BTreeMap::new();
+    for (anchor_type, _) in counted {
+        *counts.entry(parse_anchor_type(anchor_type)?).or_insert(0) += 1;
+    }
+    for (anchor_type, added) in counts {
+        let count = type_count(storage, anchor_type)?;
+        TYPE_COUNTS.save(storage, anchor_type.as_str(), &checked_count("type_counts", count, added)?)?;
+    }
+    match counted.last() {
+        Some((anchor_type, hash)) if !complete => {
+            COUNTER_BACKFILL.save(storage, &(anchor_type.clone(), Binary::from(hash.as_slice())))?
+        }
+        _ => COUNTER_BACKFILL.remove(storage),
+    }
+    Ok((counted.len(), complete))
 }
 
-// ── Tests ───────────────────────────────────────────────────────────────────
+/// Count the next `limit` registrant index entries into `REGISTRANT_STATS`.
+/// A registrant's per-type counts are zeroed when the run reaches its first
+/// entry, so each page touches only the registrants it counts; namespaced
+/// counts are not indexed and are left alone.
+#[cfg(feature = "cosmwasm")]
+fn backfill_registrant_stats(storage: &mut dyn Storage, limit: Option<u32>) -> Result<(usize, bool), ContractError> {
+    let limit = limit.unwrap_or(MAX_BACKFILL_BATCH).clamp(1, MAX_BACKFILL_BATCH) as usize;
+    let cursor = STATS_BACKFILL.may_load(storage)?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    let start = cursor.as_ref().map(|(registrant, anchor_type, hash)| {
+        Bound::exclusive((registrant.as_str(), anchor_type.as_str(), hash.as_slice()))
+    });
+    let keys = REGISTRANT_INDEX
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let complete = keys.len() <= limit;
+    let counted = &keys[..keys.len().min(limit)];
 
-    #[test]
-    fn test_validate_hash_valid() {
-        let hash = [0u8; 32];
-        assert!(validate_hash(&hash));
+    let mut previous = cursor.map(|(registrant, _, _)| registrant);
+    for (registrant, anchor_type, _) in counted {
+        let addr = Addr::unchecked(registrant);
+        let mut stats = REGISTRANT_STATS.may_load(storage, &addr)?.unwrap_or_default();
+        if previous.as_ref() != Some(registrant) {
+            stats = RegistrantStats { namespaced: stats.namespaced, ..Default::default() };
+            previous = Some(registrant.clone());
+        }
+        let counter = stats.type_counter(parse_anchor_type(anchor_type)?);
+        *counter = checked_count("registrant_stats", *counter, 1)?;
+        REGISTRANT_STATS.save(storage, &addr, &stats)?;
     }
-
-    #[test]
-    fn test_validate_hash_invalid_length() {
-        let hash = [0u8; 16];
-        assert!(!validate_hash(&hash));
+    match counted.last() {
+        Some((registrant, anchor_type, hash)) if !complete => STATS_BACKFILL.save(
+            storage,
+            &(registrant.clone(), anchor_type.clone(), Binary::from(hash.as_slice())),
+        )?,
+        _ => STATS_BACKFILL.remove(storage),
     }
+    Ok((counted.len(), complete))
+}
 
-    #[test]
-    fn test_compute_sha256_deterministic() {
-        let data = b"Project Anchor - Gravity Event";
-        let h1 = compute_sha256(data);
-        let h2 = compute_sha256(data);
-        assert_eq!(h1, h2);
+/// Count a newly added anchor in the counters and its registrant's stats.
+/// While a backfill is in progress, anchors past its cursor are left for the
+/// backfill to count.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn count_added(storage: &mut dyn Storage, entry: &AnchorEntry, hash: &[u8]) -> Result<(), ContractError> {
+    increment_total(storage)?;
+    if has_local_registrant(entry) && stats_counted(storage, entry, hash)? {
+        let addr = Addr::unchecked(&entry.registrant);
+        let mut stats = REGISTRANT_STATS.may_load(storage, &addr)?.unwrap_or_default();
+        let counter = stats.type_counter(entry.anchor_type);
+        *counter = checked_count("registrant_stats", *counter, 1)?;
+        REGISTRANT_STATS.save(storage, &addr, &stats)?;
     }
-
-    #[test]
-    fn test_compute_sha256_different_inputs() {
-        let h1 = compute_sha256(b"input_a");
-        let h2 = compute_sha256(b"input_b");
-        assert_ne!(h1, h2);
+    if type_counted(storage, entry, hash)? {
+        let count = type_count(storage, entry.anchor_type)?;
+        TYPE_COUNTS.save(storage, entry.anchor_type.as_str(), &checked_count("type_counts", count, 1)?)?;
     }
+    Ok(())
+}
 
-    #[test]
-    fn test_format_anchor_payload_deterministic() {
-        let hash = compute_sha256(b"test_root");
-        let p1 = format_anchor_payload(&hash, "root", 12345);
-        let p2 = format_anchor_payload(&hash, "root", 12345);
-        assert_eq!(p1, p2);
+/// Undo `count_added` for a removed anchor, skipping the counters a running
+/// backfill has not reached yet.
+#[cfg(feature = "cosmwasm")]
+fn count_removed(storage: &mut dyn Storage, entry: &AnchorEntry, hash: &[u8]) -> Result<(), ContractError> {
+    let total = TOTAL_ANCHORS.may_load(storage)?.unwrap_or_default();
+    TOTAL_ANCHORS.save(storage, &total.saturating_sub(1))?;
+    if has_local_registrant(entry) && stats_counted(storage, entry, hash)? {
+        let addr = Addr::unchecked(&entry.registrant);
+        let mut stats = REGISTRANT_STATS.may_load(storage, &addr)?.unwrap_or_default();
+        let counter = stats.type_counter(entry.anchor_type);
+        *counter = counter.saturating_sub(1);
+        REGISTRANT_STATS.save(storage, &addr, &stats)?;
+    }
+    if type_counted(storage, entry, hash)? {
+        let count = type_count(storage, entry.anchor_type)?;
+        TYPE_COUNTS.save(storage, entry.anchor_type.as_str(), &count.saturating_sub(1))?;
+    }
+    Ok(())
+}
+
+/// Whether the anchor was registered here rather than relayed over IBC, so
+/// its registrant is indexed and counted in `REGISTRANT_STATS`.
+#[cfg(feature = "cosmwasm")]
+fn has_local_registrant(entry: &AnchorEntry) -> bool {
+    entry.source_channel.is_none()
+}
+
+/// Whether the anchor is at or before the `BackfillRegistrantStats` cursor,
+/// so its registrant's stats include it.
+#[cfg(feature = "cosmwasm")]
+fn stats_counted(storage: &dyn Storage, entry: &AnchorEntry, hash: &[u8]) -> StdResult<bool> {
+    Ok(match STATS_BACKFILL.may_load(storage)? {
+        Some((registrant, cursor_type, cursor_hash)) => {
+            *REGISTRANT_INDEX.key((entry.registrant.as_str(), entry.anchor_type.as_str(), hash))
+                <= *REGISTRANT_INDEX.key((registrant.as_str(), cursor_type.as_str(), cursor_hash.as_slice()))
+        }
+        None => true,
+    })
+}
+
+/// Whether the anchor is at or before the `BackfillCounters` cursor, so
+/// `TYPE_COUNTS` includes it.
+#[cfg(feature = "cosmwasm")]
+fn type_counted(storage: &dyn Storage, entry: &AnchorEntry, hash: &[u8]) -> StdResult<bool> {
+    Ok(match COUNTER_BACKFILL.may_load(storage)? {
+        Some((cursor_type, cursor_hash)) => {
+            *PRESENCE.key((entry.anchor_type.as_str(), hash)) <= *PRESENCE.key((cursor_type.as_str(), cursor_hash.as_slice()))
+        }
+        None => true,
+    })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn increment_total(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let total = TOTAL_ANCHORS.may_load(storage)?.unwrap_or_default();
+    TOTAL_ANCHORS.save(storage, &checked_count("total_anchors", total, 1)?)?;
+    Ok(())
+}
+
+/// `value + by` for the counter named `counter`, failing with
+/// `CounterOverflow` instead of panicking.
+#[cfg(feature = "cosmwasm")]
+fn checked_count(counter: &str, value: u64, by: u64) -> Result<u64, ContractError> {
+    value.checked_add(by).ok_or_else(|| ContractError::CounterOverflow { counter: counter.to_string() })
+}
+
+/// Numeric components of a `major.minor.patch` version; pre-release and
+/// build suffixes are ignored.
+#[cfg(feature = "cosmwasm")]
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Namespace identifier for this registry: hex SHA-256 of the configured
+/// name, falling back to the contract address for configs predating names.
+#[cfg(feature = "cosmwasm")]
+pub fn registry_id(config: &Config, env: &Env) -> String {
+    let name = if config.name.is_empty() {
+        env.contract.address.as_str()
+    } else {
+        config.name.as_str()
+    };
+    hex::encode(compute_sha256(name.as_bytes()))
+}
+
+#[cfg(feature = "cosmwasm")]
+#[entry_point]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // Funds sent here could never be withdrawn
+    if !info.funds.is_empty() {
+        return Err(ContractError::FundsNotAccepted {});
+    }
+    if msg.is_registration() {
+        ensure_not_paused(deps.as_ref())?;
+    }
+    match msg {
+        ExecuteMsg::RegisterRoot { hash, tags, memo, ttl_blocks, payload_version } => {
+            let meta = EntryMeta::new(tags, memo).expiring(ttl_blocks).payload_version(payload_version);
+            register_hash(deps, env, info, hash, "root", meta)
+        }
+        ExecuteMsg::RegisterRootChained { hash, previous_root, tags, memo } => {
+            register_root_chained(deps, env, info, hash, previous_root, EntryMeta::new(tags, memo))
+        }
+        ExecuteMsg::RegisterClaimScore { hash, tags, memo, ttl_blocks, payload_version } => {
+            let meta = EntryMeta::new(tags, memo).expiring(ttl_blocks).payload_version(payload_version);
+            register_hash(deps, env, info, hash, "claim_score", meta)
+        }
+        ExecuteMsg::RegisterClaimScoreIndexed { hash, claim_id, tags, memo } => {
+            let mut meta = EntryMeta::new(tags, memo);
+            meta.claim_id = Some(claim_id);
+            register_hash(deps, env, info, hash, "claim_score", meta)
+        }
+        ExecuteMsg::RegisterClaimScorePayload { payload, tags, memo } => {
+            register_claim_score_payload(deps, env, info, payload, EntryMeta::new(tags, memo))
+        }
+        ExecuteMsg::RegisterEquationProof { hash, tags, memo, ttl_blocks, payload_version } => {
+            let meta = EntryMeta::new(tags, memo).expiring(ttl_blocks).payload_version(payload_version);
+            register_hash(deps, env, info, hash, "equation_proof", meta)
+        }
+        ExecuteMsg::RegisterEquationProofLinked { hash, depends_on_root, tags, memo } => {
+            register_equation_proof_linked(deps, env, info, hash, depends_on_root, EntryMeta::new(tags, memo))
+        }
+        ExecuteMsg::RegisterEquationProofPayload { payload, tags, memo } => {
+            register_equation_proof_payload(deps, env, info, payload, EntryMeta::new(tags, memo))
+        }
+        ExecuteMsg::RegisterRootWithMetadata { payload, tags, memo } => {
+            register_root_with_metadata(deps, env, info, payload, EntryMeta::new(tags, memo))
+        }
+        ExecuteMsg::RegisterNamespaced { namespace, hash } => register_namespaced(deps, env, info, namespace, hash),
+        ExecuteMsg::RegisterBatch { anchors } => register_batch(deps, env, info, anchors),
+        ExecuteMsg::RegisterMixedBatch { entries } => {
+            register_batch(deps, env, info, entries.into_iter().map(BatchEntry::from).collect())
+        }
+        ExecuteMsg::RegisterHistorical(anchor) => register_historical(deps, env, info, anchor),
+        ExecuteMsg::ImportAnchors { anchors } => {
+            ensure_admin(deps.as_ref(), &info)?;
+            let count = import_anchors(deps.storage, &env, anchors)?;
+            Ok(Response::new()
+                .add_attribute("action", "import_anchors")
+                .add_attribute("count", count.to_string()))
+        }
+        ExecuteMsg::UpdateOwnership(action) => update_ownership(deps, env, info, action),
+        ExecuteMsg::PruneExpired { limit } => prune_expired(deps, env, limit),
+        ExecuteMsg::BlockHash { hash, reason } => block_hash(deps, env, info, hash, reason),
+        ExecuteMsg::UnblockHash { hash } => unblock_hash(deps, env, info, hash),
+        ExecuteMsg::UpdateLimits { limits } => update_limits(deps, info, limits),
+        ExecuteMsg::SetAllowOverwrite { allow } => set_allow_overwrite(deps, info, allow),
+        ExecuteMsg::SetReregistrationLockout { blocks } => set_reregistration_lockout(deps, info, blocks),
+        ExecuteMsg::SetMaxBatchSize { max_batch_size } => set_max_batch_size(deps, info, max_batch_size),
+        ExecuteMsg::SetSubscriberGasLimit { gas_limit } => set_subscriber_gas_limit(deps, info, gas_limit),
+        ExecuteMsg::SetRateLimit { max_anchors_per_block_per_registrant } => {
+            set_rate_limit(deps, info, max_anchors_per_block_per_registrant)
+        }
+        ExecuteMsg::SetQuota { registrant, max_anchors } => set_quota(deps, info, registrant, max_anchors),
+        ExecuteMsg::AddSubscriber { contract, msg_template } => add_subscriber(deps, info, contract, msg_template),
+        ExecuteMsg::RemoveSubscriber { contract } => remove_subscriber(deps, info, contract),
+        ExecuteMsg::AddOperator { address } => add_operator(deps, info, address),
+        ExecuteMsg::RemoveOperator { address } => remove_operator(deps, info, address),
+        ExecuteMsg::AddRegistrant { address } => add_registrant(deps, info, address),
+        ExecuteMsg::RemoveRegistrant { address } => remove_registrant(deps, info, address),
+        ExecuteMsg::RevokeAnchor { hash, anchor_type, reason } => {
+            revoke_anchor(deps, env, info, hash, &anchor_type, reason)
+        }
+        ExecuteMsg::SupersedeRoot { old_root, new_root } => supersede_root(deps, env, info, old_root, new_root),
+        ExecuteMsg::AttestAnchor { hash, anchor_type } => attest_anchor(deps, env, info, hash, &anchor_type),
+        ExecuteMsg::Pause {} => set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => set_paused(deps, info, false),
+        #[cfg(feature = "cosmwasm-ibc")]
+        ExecuteMsg::RelayAnchor { channel_id, anchor_type, hash } => {
+            crate::ibc::relay_anchor(deps, env, channel_id, &anchor_type, hash)
+        }
+        #[cfg(feature = "cosmwasm-ibc")]
+        ExecuteMsg::AllowIbcCounterparty { connection_id, port_id } => {
+            crate::ibc::set_counterparty_allowed(deps, info, connection_id, port_id, true)
+        }
+        #[cfg(feature = "cosmwasm-ibc")]
+        ExecuteMsg::DisallowIbcCounterparty { connection_id, port_id } => {
+            crate::ibc::set_counterparty_allowed(deps, info, connection_id, port_id, false)
+        }
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+#[entry_point]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    let response = match msg {
+        SudoMsg::SetAdmin { address } => {
+            // Replaces the owner outright, dropping any pending transfer
+            let ownership = cw_ownable::initialize_owner(deps.storage, deps.api, Some(&address))?;
+            Response::new().add_attribute("action", "set_admin").add_attributes(ownership.into_attributes())
+        }
+        SudoMsg::Pause {} => write_paused(deps.storage, true)?,
+        SudoMsg::Unpause {} => write_paused(deps.storage, false)?,
+    };
+    Ok(response.add_attribute("authority", "sudo"))
+}
+
+#[cfg(feature = "cosmwasm")]
+impl From<TextError> for StdError {
+    fn from(err: TextError) -> Self {
+        StdError::generic_err(err.to_string())
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+fn update_limits(deps: DepsMut, info: MessageInfo, limits: Limits) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    limits.validate()?;
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        config.limits = limits;
+        Ok(config)
+    })?;
+
+    Ok(Response::new().add_attribute("action", "update_limits"))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn set_allow_overwrite(deps: DepsMut, info: MessageInfo, allow: bool) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        config.allow_overwrite = allow;
+        Ok(config)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_allow_overwrite")
+        .add_attribute("allow_overwrite", allow.to_string()))
+}
+
+/// Apply a cw-ownable action. Renouncing also lifts the allowlist and any
+/// pause and drops the operators, since nobody could undo them afterwards.
+#[cfg(feature = "cosmwasm")]
+fn update_ownership(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: cw_ownable::Action,
+) -> Result<Response, ContractError> {
+    let renounce = action == cw_ownable::Action::RenounceOwnership;
+    let ownership = cw_ownable::update_ownership(deps.branch(), &env.block, &info.sender, action)?;
+    if renounce {
+        CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+            config.restricted = false;
+            config.paused = false;
+            config.operators.clear();
+            Ok(config)
+        })?;
+    }
+
+    Ok(Response::new().add_attribute("action", "update_ownership").add_attributes(ownership.into_attributes()))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn set_reregistration_lockout(deps: DepsMut, info: MessageInfo, blocks: Option<u64>) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        config.reregistration_lockout_blocks = blocks;
+        Ok(config)
+    })?;
+
+    let blocks = blocks.map_or_else(|| "none".to_string(), |blocks| blocks.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "set_reregistration_lockout")
+        .add_attribute("reregistration_lockout_blocks", blocks))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn set_rate_limit(deps: DepsMut, info: MessageInfo, max: Option<u32>) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        config.max_anchors_per_block_per_registrant = max;
+        Ok(config)
+    })?;
+
+    let max = max.map_or_else(|| "none".to_string(), |max| max.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "set_rate_limit")
+        .add_attribute("max_anchors_per_block_per_registrant", max))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn set_quota(
+    deps: DepsMut,
+    info: MessageInfo,
+    registrant: String,
+    max_anchors: Option<u64>,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let addr = deps.api.addr_validate(&registrant)?;
+    match max_anchors {
+        Some(max) => QUOTAS.save(deps.storage, &addr, &max)?,
+        None => QUOTAS.remove(deps.storage, &addr),
+    }
+
+    let max = max_anchors.map_or_else(|| "none".to_string(), |max| max.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "set_quota")
+        .add_attribute("registrant", addr)
+        .add_attribute("max_anchors", max))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn set_max_batch_size(deps: DepsMut, info: MessageInfo, max_batch_size: u32) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    check_max_batch_size(max_batch_size)?;
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        config.max_batch_size = max_batch_size;
+        Ok(config)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_max_batch_size")
+        .add_attribute("max_batch_size", max_batch_size.to_string()))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn set_subscriber_gas_limit(deps: DepsMut, info: MessageInfo, gas_limit: u64) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    if gas_limit == 0 || gas_limit > HARD_MAX_SUBSCRIBER_GAS_LIMIT {
+        return Err(ContractError::InvalidSubscriberGasLimit { value: gas_limit, max: HARD_MAX_SUBSCRIBER_GAS_LIMIT });
+    }
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        config.subscriber_gas_limit = gas_limit;
+        Ok(config)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_subscriber_gas_limit")
+        .add_attribute("gas_limit", gas_limit.to_string()))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn check_max_batch_size(max_batch_size: u32) -> Result<(), ContractError> {
+    if max_batch_size > HARD_MAX_BATCH_SIZE {
+        return Err(ContractError::MaxBatchSizeTooLarge { value: max_batch_size, max: HARD_MAX_BATCH_SIZE });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cosmwasm")]
+fn check_accepted_hash_lengths(lengths: &[u32]) -> Result<(), ContractError> {
+    let supported = lengths.iter().all(|len| SUPPORTED_HASH_LENGTHS.contains(len));
+    let unique = lengths.iter().enumerate().all(|(i, len)| !lengths[..i].contains(len));
+    if lengths.is_empty() || !supported || !unique {
+        return Err(ContractError::InvalidHashLengths { lengths: lengths.to_vec() });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cosmwasm")]
+fn add_subscriber(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    msg_template: String,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let contract = deps.api.addr_validate(&contract)?;
+    validate_charset("msg_template", &msg_template, MAX_NAME_LEN, is_namespace_char)?;
+    if !SUBSCRIBERS.has(deps.storage, &contract) {
+        let count = SUBSCRIBERS.keys(deps.storage, None, None, Order::Ascending).count();
+        if count >= MAX_SUBSCRIBERS {
+            return Err(ContractError::TooManyItems { what: "Subscribers", max: MAX_SUBSCRIBERS, got: count + 1 });
+        }
+    }
+    SUBSCRIBERS.save(deps.storage, &contract, &msg_template)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_subscriber")
+        .add_attribute("subscriber", contract)
+        .add_attribute("msg_template", msg_template))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn remove_subscriber(deps: DepsMut, info: MessageInfo, contract: String) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let contract = deps.api.addr_validate(&contract)?;
+    if !SUBSCRIBERS.has(deps.storage, &contract) {
+        return Err(ContractError::UnknownSubscriber { contract: contract.to_string() });
+    }
+    SUBSCRIBERS.remove(deps.storage, &contract);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_subscriber")
+        .add_attribute("subscriber", contract))
+}
+
+/// One notification submessage per subscriber per anchor. Each is sent with
+/// `reply_always` under its own id and capped at `Config.subscriber_gas_limit`,
+/// so a failing subscriber cannot revert the registration, not even by
+/// running out of gas, and `reply` knows which notification failed.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn notify_subscribers(storage: &mut dyn Storage, entries: &[AnchorEntry]) -> Result<Vec<SubMsg>, ContractError> {
+    let subscribers = SUBSCRIBERS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    if subscribers.is_empty() {
+        return Ok(vec![]);
+    }
+    let gas_limit = CONFIG.load(storage)?.subscriber_gas_limit;
+    let mut messages = Vec::with_capacity(subscribers.len() * entries.len());
+    for entry in entries {
+        let notification = AnchorNotification {
+            anchor_type: entry.anchor_type.to_string(),
+            hash_hex: entry.hash_hex.clone(),
+            height: entry.registered_at,
+            registrant: entry.registrant.clone(),
+        };
+        for (contract, msg_template) in &subscribers {
+            let msg = std::collections::BTreeMap::from([(msg_template, &notification)]);
+            let execute = WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg: to_json_binary(&msg)?,
+                funds: vec![],
+            };
+            let id = checked_count("dispatch_sequence", DISPATCH_SEQUENCE.may_load(storage)?.unwrap_or_default(), 1)?;
+            DISPATCH_SEQUENCE.save(storage, &id)?;
+            let pending = PendingDispatch {
+                contract: contract.to_string(),
+                anchor_type: notification.anchor_type.clone(),
+                hash_hex: notification.hash_hex.clone(),
+            };
+            PENDING_DISPATCHES.save(storage, id, &pending)?;
+            messages.push(SubMsg::reply_always(execute, id).with_gas_limit(gas_limit));
+        }
+    }
+    Ok(messages)
+}
+
+/// Settle a subscriber notification. A failure is swallowed and recorded in
+/// `FAILED_DISPATCHES` so that it cannot revert the registration.
+#[cfg(feature = "cosmwasm")]
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let pending = PENDING_DISPATCHES
+        .may_load(deps.storage, msg.id)?
+        .ok_or_else(|| StdError::generic_err(format!("Unexpected reply id {}", msg.id)))?;
+    PENDING_DISPATCHES.remove(deps.storage, msg.id);
+    match msg.result {
+        SubMsgResult::Ok(_) => Ok(Response::new()),
+        SubMsgResult::Err(error) => {
+            let failed = FailedDispatch {
+                id: msg.id,
+                contract: pending.contract,
+                anchor_type: pending.anchor_type,
+                hash_hex: pending.hash_hex,
+                height: env.block.height,
+                error,
+            };
+            FAILED_DISPATCHES.save(deps.storage, msg.id, &failed)?;
+            Ok(Response::new()
+                .add_attribute("action", "subscriber_failed")
+                .add_attribute("dispatch_id", msg.id.to_string())
+                .add_attribute("subscriber", failed.contract)
+                .add_attribute("error", failed.error))
+        }
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+fn add_operator(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let address = deps.api.addr_validate(&address)?;
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        if !config.operators.contains(&address.to_string()) {
+            config.operators.push(address.to_string());
+        }
+        Ok(config)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_operator")
+        .add_attribute("operator", address))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn remove_operator(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let address = deps.api.addr_validate(&address)?;
+    CONFIG.update(deps.storage, |mut config| -> Result<_, ContractError> {
+        let before = config.operators.len();
+        config.operators.retain(|operator| *operator != address.as_str());
+        if config.operators.len() == before {
+            return Err(ContractError::UnknownOperator { address: address.to_string() });
+        }
+        Ok(config)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_operator")
+        .add_attribute("operator", address))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn add_registrant(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, ContractError> {
+    ensure_operator(deps.as_ref(), &info)?;
+    let address = deps.api.addr_validate(&address)?;
+    REGISTRANTS.save(deps.storage, &address, &())?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_registrant")
+        .add_attribute("registrant", address))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn remove_registrant(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, ContractError> {
+    ensure_operator(deps.as_ref(), &info)?;
+    let address = deps.api.addr_validate(&address)?;
+    if !REGISTRANTS.has(deps.storage, &address) {
+        return Err(ContractError::UnknownRegistrant { address: address.to_string() });
+    }
+    REGISTRANTS.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_registrant")
+        .add_attribute("registrant", address))
+}
+
+/// Tombstone an anchor: the entry stays readable with `revoked_at` set.
+#[cfg(feature = "cosmwasm")]
+fn revoke_anchor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hash: Binary,
+    anchor_type: &str,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owned(deps.storage)?;
+    let mut entry = may_load_anchor(deps.storage, parse_anchor_type(anchor_type)?, hash.as_slice())?
+        .ok_or_else(|| ContractError::AnchorNotFound {
+            anchor_type: anchor_type.to_string(),
+            hash_hex: hex::encode(hash.as_slice()),
+        })?;
+    if !is_admin(deps.storage, info.sender.as_str())? && info.sender.as_str() != entry.registrant {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(height) = entry.revoked_at {
+        return Err(ContractError::AlreadyRevoked { height });
+    }
+    validate_text("reason", &reason, config.limits.max_reason_len as usize)?;
+
+    entry.revoked_at = Some(env.block.height);
+    entry.revocation_reason = Some(reason.clone());
+    touch_anchor(deps.storage, hash.as_slice(), &mut entry, env.block.height, "revoke")?;
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_anchor")
+        .add_attribute("anchor_type", anchor_type)
+        .add_attribute("hash", entry.hash_hex)
+        .add_attribute("reason", reason))
+}
+
+/// Remove up to `limit` expired anchors with their indexes, and uncount them.
+#[cfg(feature = "cosmwasm")]
+fn prune_expired(deps: DepsMut, env: Env, limit: u32) -> Result<Response, ContractError> {
+    let limit = limit.min(MAX_PRUNE_BATCH) as usize;
+    let expired = EXPIRY_INDEX
+        .keys(deps.storage, None, Some(Bound::exclusive((env.block.height + 1, &[][..], ""))), Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut response = Response::new().add_attribute("action", "prune_expired");
+    for (expires_at, hash, anchor_type) in &expired {
+        let entry = load_indexed(deps.storage, anchor_type, hash)?;
+        remove_anchor(deps.storage, &entry, hash, env.block.height)?;
+        response = response.add_event(
+            Event::new(EVENT_ANCHOR_PRUNED)
+                .add_attribute("anchor_type", anchor_type)
+                .add_attribute("hash", &entry.hash_hex)
+                .add_attribute("expires_at", expires_at.to_string()),
+        );
+    }
+    Ok(response.add_attribute("pruned", expired.len().to_string()))
+}
+
+/// Delete an anchor outright: its entry, presence, indexes, sequence number,
+/// attestations and dependents, then uncount it. A "prune" change at
+/// `height` is left as a tombstone for `ListModifiedSince`.
+#[cfg(feature = "cosmwasm")]
+fn remove_anchor(storage: &mut dyn Storage, entry: &AnchorEntry, hash: &[u8], height: u64) -> Result<(), ContractError> {
+    let anchor_type = entry.anchor_type.as_str();
+    // Unindexing the original entry drops its old modification key only
+    touch_anchor(storage, hash, &mut entry.clone(), height, "prune")?;
+    PRUNE_TOMBSTONES.save(storage, (anchor_type, hash), &height)?;
+    unindex_anchor(storage, entry, hash);
+    ANCHORS.remove(storage, (anchor_type, hash));
+    entry.anchor_type.legacy_store().remove(storage, hash);
+    PRESENCE.remove(storage, (anchor_type, hash));
+    ATTESTATIONS.remove(storage, (anchor_type, hash));
+    if entry.anchor_type == AnchorType::MerkleRoot {
+        ROOT_METADATA.remove(storage, hash);
+        let dependents = DEPENDENTS
+            .prefix(entry.hash_hex.as_str())
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for proof in dependents {
+            DEPENDENTS.remove(storage, (entry.hash_hex.as_str(), &proof));
+        }
+    }
+    if let Some(index) = entry.index {
+        ANCHOR_BY_INDEX.remove(storage, index);
+    }
+    if LATEST_ROOT.may_load(storage)?.is_some_and(|head| entry.anchor_type == AnchorType::MerkleRoot && head.hash_hex == entry.hash_hex) {
+        reset_latest_root(storage, height)?;
+    }
+    count_removed(storage, entry, hash)
+}
+
+/// Point `LATEST_ROOT` at the newest remaining root registered on this
+/// chain and live at `height`, or clear it when there is none.
+#[cfg(feature = "cosmwasm")]
+fn reset_latest_root(storage: &mut dyn Storage, height: u64) -> Result<(), ContractError> {
+    let mut latest = None;
+    for key in HEIGHT_INDEX.keys(storage, None, None, Order::Descending) {
+        let (_, hash, anchor_type) = key?;
+        if anchor_type != AnchorType::MerkleRoot.as_str() {
+            continue;
+        }
+        let entry = load_anchor(storage, AnchorType::MerkleRoot, &hash)?;
+        if entry.imported_at.is_none() && !entry.is_expired(height) {
+            latest = Some(LatestRoot { hash_hex: entry.hash_hex, height: entry.registered_at, registrant: entry.registrant });
+            break;
+        }
+    }
+    match latest {
+        Some(latest) => LATEST_ROOT.save(storage, &latest)?,
+        None => LATEST_ROOT.remove(storage),
+    }
+    Ok(())
+}
+
+/// Link `old_root` to its replacement. Both roots must be registered and the
+/// replacement must not itself be superseded, so supersession chains never
+/// fork or loop.
+#[cfg(feature = "cosmwasm")]
+fn supersede_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    old_root: Binary,
+    new_root: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    for root in [&old_root, &new_root] {
+        check_hash_len(&config, root)?;
+    }
+    let load_root = |hash: &Binary| {
+        may_load_anchor(deps.storage, AnchorType::MerkleRoot, hash.as_slice())?.ok_or_else(|| {
+            ContractError::AnchorNotFound { anchor_type: "root".to_string(), hash_hex: hex::encode(hash.as_slice()) }
+        })
+    };
+    let mut old = load_root(&old_root)?;
+    if !is_admin(deps.storage, info.sender.as_str())? && info.sender.as_str() != old.registrant {
+        return Err(ContractError::Unauthorized {});
+    }
+    if old_root == new_root {
+        return Err(ContractError::SelfSupersession {});
+    }
+    let new = load_root(&new_root)?;
+    for entry in [&old, &new] {
+        if let Some(successor) = &entry.superseded_by {
+            return Err(ContractError::AlreadySuperseded {
+                hash_hex: entry.hash_hex.clone(),
+                successor: successor.clone(),
+            });
+        }
+    }
+
+    old.superseded_by = Some(new.hash_hex.clone());
+    touch_anchor(deps.storage, old_root.as_slice(), &mut old, env.block.height, "supersede")?;
+
+    Ok(Response::new()
+        .add_attribute("action", "supersede_root")
+        .add_attribute("old_root", old.hash_hex)
+        .add_attribute("new_root", new.hash_hex))
+}
+
+/// Append the sender to an anchor's attestations. Attesting is a write, so
+/// it is refused while the registry is paused.
+#[cfg(feature = "cosmwasm")]
+fn attest_anchor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hash: Binary,
+    anchor_type: &str,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.as_ref())?;
+    let parsed = parse_anchor_type(anchor_type)?;
+    let mut entry = may_load_anchor(deps.storage, parsed, hash.as_slice())?.ok_or_else(|| ContractError::AnchorNotFound {
+        anchor_type: anchor_type.to_string(),
+        hash_hex: hex::encode(hash.as_slice()),
+    })?;
+    if let Some(height) = entry.revoked_at {
+        return Err(ContractError::Revoked { height });
+    }
+    if info.sender.as_str() == entry.registrant {
+        return Err(ContractError::SelfAttestation {});
+    }
+
+    let key = (parsed.as_str(), hash.as_slice());
+    let mut attestations = ATTESTATIONS.may_load(deps.storage, key)?.unwrap_or_default();
+    if attestations.iter().any(|a| a.attester == info.sender.as_str()) {
+        return Err(ContractError::AlreadyAttested { attester: info.sender.to_string() });
+    }
+    if attestations.len() >= MAX_ATTESTATIONS {
+        return Err(ContractError::TooManyItems {
+            what: "attestations",
+            max: MAX_ATTESTATIONS,
+            got: attestations.len() + 1,
+        });
+    }
+    attestations.push(Attestation { attester: info.sender.to_string(), height: env.block.height });
+    ATTESTATIONS.save(deps.storage, key, &attestations)?;
+    touch_anchor(deps.storage, hash.as_slice(), &mut entry, env.block.height, "attest")?;
+
+    Ok(Response::new()
+        .add_attribute("action", "attest_anchor")
+        .add_attribute("anchor_type", parsed.as_str())
+        .add_attribute("hash", entry.hash_hex)
+        .add_attribute("attester", info.sender)
+        .add_attribute("attestations", attestations.len().to_string()))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+    ensure_operator(deps.as_ref(), &info)?;
+    write_paused(deps.storage, paused)
+}
+
+#[cfg(feature = "cosmwasm")]
+fn write_paused(storage: &mut dyn Storage, paused: bool) -> Result<Response, ContractError> {
+    CONFIG.update(storage, |mut config| -> Result<_, ContractError> {
+        config.paused = paused;
+        Ok(config)
+    })?;
+
+    Ok(Response::new().add_attribute("action", if paused { "pause" } else { "unpause" }))
+}
+
+/// Reject registrations while the registry is paused.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn ensure_not_paused(deps: Deps) -> Result<(), ContractError> {
+    if CONFIG.load(deps.storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
+
+/// Whether `sender` is the current owner; always false once renounced.
+#[cfg(feature = "cosmwasm")]
+fn is_admin(storage: &dyn Storage, sender: &str) -> StdResult<bool> {
+    cw_ownable::is_owner(storage, &Addr::unchecked(sender))
+}
+
+/// Fail with `NoOwner` once ownership has been renounced.
+#[cfg(feature = "cosmwasm")]
+fn ensure_owned(storage: &dyn Storage) -> Result<(), ContractError> {
+    if cw_ownable::get_ownership(storage)?.owner.is_none() {
+        return Err(cw_ownable::OwnershipError::NoOwner.into());
+    }
+    Ok(())
+}
+
+/// Reject senders other than the owner.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn ensure_admin(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    ensure_owned(deps.storage)?;
+    if !is_admin(deps.storage, info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+/// The owner or one of the operators.
+#[cfg(feature = "cosmwasm")]
+fn ensure_operator(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    ensure_owned(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let sender = info.sender.as_str();
+    if !is_admin(deps.storage, sender)? && !config.operators.iter().any(|operator| operator == sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cosmwasm")]
+fn block_hash(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hash: Binary,
+    reason: String,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let limits = CONFIG.load(deps.storage)?.limits;
+    validate_text("reason", &reason, limits.max_reason_len as usize)?;
+    let block = BlockReason {
+        reason,
+        blocked_at: env.block.height,
+        blocked_by: info.sender.to_string(),
+    };
+    BLOCKLIST.save(deps.storage, hash.as_slice(), &block)?;
+    touch_existing(deps.storage, hash.as_slice(), env.block.height, "block")?;
+
+    Ok(Response::new()
+        .add_attribute("action", "block_hash")
+        .add_attribute("hash", hex::encode(hash.as_slice()))
+        .add_attribute("reason", block.reason))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn unblock_hash(deps: DepsMut, env: Env, info: MessageInfo, hash: Binary) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    if !BLOCKLIST.has(deps.storage, hash.as_slice()) {
+        return Err(ContractError::NotBlocked { hash_hex: hex::encode(hash.as_slice()) });
+    }
+    BLOCKLIST.remove(deps.storage, hash.as_slice());
+    touch_existing(deps.storage, hash.as_slice(), env.block.height, "unblock")?;
+
+    Ok(Response::new()
+        .add_attribute("action", "unblock_hash")
+        .add_attribute("hash", hex::encode(hash.as_slice())))
+}
+
+/// Caller-supplied metadata stored on a new entry.
+#[cfg(feature = "cosmwasm")]
+struct EntryMeta {
+    tags: Vec<String>,
+    memo: Option<String>,
+    stability_class: Option<String>,
+    previous_root: Option<String>,
+    claim_id: Option<u64>,
+    depends_on_root: Option<String>,
+    ttl_blocks: Option<u64>,
+    payload_version: u16,
+}
+
+#[cfg(feature = "cosmwasm")]
+impl EntryMeta {
+    fn new(tags: Option<Vec<String>>, memo: Option<String>) -> Self {
+        EntryMeta {
+            tags: tags.unwrap_or_default(),
+            memo,
+            stability_class: None,
+            previous_root: None,
+            claim_id: None,
+            depends_on_root: None,
+            ttl_blocks: None,
+            payload_version: PAYLOAD_FORMAT_VERSION,
+        }
+    }
+
+    fn expiring(self, ttl_blocks: Option<u64>) -> Self {
+        EntryMeta { ttl_blocks, ..self }
+    }
+
+    fn payload_version(self, payload_version: Option<u16>) -> Self {
+        EntryMeta { payload_version: payload_version.unwrap_or_else(default_payload_version), ..self }
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+fn register_hash(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hash: Binary,
+    anchor_type: &str,
+    meta: EntryMeta,
+) -> Result<Response, ContractError> {
+    let anchor_type = check_single_registration(deps.as_ref(), &env, &info.sender, anchor_type, &hash, &meta)?;
+    consume_rate_limit(deps.storage, &env, &info.sender, 1)?;
+    let mut collisions: Vec<&str> = vec![];
+    for other in AnchorType::ALL {
+        if other != anchor_type && is_registered(deps.storage, other, &hash)? {
+            collisions.push(other.as_str());
+        }
+    }
+    let entry = new_entry(&env, &info, &hash, anchor_type, meta);
+    let notifications = notify_subscribers(deps.storage, std::slice::from_ref(&entry))?;
+    let mut response = store_anchor(deps.branch(), &env, hash.as_slice(), entry, "register")?;
+    // The same hash under another type usually means an upstream pipeline
+    // bug; flag it for monitoring rather than reject the registration
+    if !collisions.is_empty() {
+        response = response.add_attribute("cross_type_collision", collisions.join(","));
+    }
+    Ok(response.add_submessages(notifications))
+}
+
+/// Register a root linked to `previous_root`, which must be a registered,
+/// unrevoked root or `GENESIS_ROOT`.
+#[cfg(feature = "cosmwasm")]
+fn register_root_chained(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hash: Binary,
+    previous_root: Binary,
+    mut meta: EntryMeta,
+) -> Result<Response, ContractError> {
+    check_hash_len(&CONFIG.load(deps.storage)?, &previous_root)?;
+    let previous_hex = hex::encode(previous_root.as_slice());
+    if previous_root.as_slice() != GENESIS_ROOT {
+        // A root may not link to itself, even when overwrites are allowed
+        if previous_root == hash {
+            return Err(ContractError::UnknownPreviousRoot { hash_hex: previous_hex });
+        }
+        let previous = may_load_anchor(deps.storage, AnchorType::MerkleRoot, previous_root.as_slice())?
+            .ok_or_else(|| ContractError::UnknownPreviousRoot { hash_hex: previous_hex.clone() })?;
+        if let Some(height) = previous.revoked_at {
+            return Err(ContractError::PreviousRootRevoked { hash_hex: previous_hex, height });
+        }
+    }
+    meta.previous_root = Some(previous_hex);
+    register_hash(deps, env, info, hash, "root", meta)
+}
+
+/// Register an equation proof computed from `depends_on_root`, which must be
+/// a registered, unrevoked root.
+#[cfg(feature = "cosmwasm")]
+fn register_equation_proof_linked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hash: Binary,
+    depends_on_root: Binary,
+    mut meta: EntryMeta,
+) -> Result<Response, ContractError> {
+    check_hash_len(&CONFIG.load(deps.storage)?, &depends_on_root)?;
+    let root_hex = hex::encode(depends_on_root.as_slice());
+    let root = may_load_anchor(deps.storage, AnchorType::MerkleRoot, depends_on_root.as_slice())?
+        .ok_or_else(|| ContractError::UnknownDependency { hash_hex: root_hex.clone() })?;
+    if let Some(height) = root.revoked_at {
+        return Err(ContractError::DependencyRevoked { hash_hex: root_hex, height });
+    }
+    meta.depends_on_root = Some(root_hex);
+    register_hash(deps, env, info, hash, "equation_proof", meta)
+}
+
+/// Register a hash under a caller-defined namespace. Each namespace is a
+/// separate key space, so the same hash may be anchored in several.
+#[cfg(feature = "cosmwasm")]
+fn register_namespaced(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    namespace: String,
+    hash: Binary,
+) -> Result<Response, ContractError> {
+    validate_charset("namespace", &namespace, MAX_NAMESPACE_LEN, is_namespace_char)?;
+    if namespace.parse::<AnchorType>().is_ok() {
+        return Err(ContractError::ReservedNamespace { namespace });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    check_sender_and_hash(deps.as_ref(), &config, info.sender.as_str(), &hash)?;
+    if let Some(existing) = NAMESPACED.may_load(deps.storage, (&namespace, hash.as_slice()))? {
+        return Err(ContractError::AlreadyRegistered {
+            hash_hex: existing.hash_hex,
+            height: existing.registered_at,
+            registrant: existing.registrant,
+        });
+    }
+
+    check_quota(deps.storage, &info.sender, 1)?;
+    consume_rate_limit(deps.storage, &env, &info.sender, 1)?;
+    let entry = NamespacedEntry {
+        namespace: namespace.clone(),
+        hash_hex: hex::encode(hash.as_slice()),
+        registered_at: env.block.height,
+        registered_at_time: env.block.time.seconds(),
+        registrant: info.sender.to_string(),
+        chain_id: env.block.chain_id.clone(),
+        tx_index: env.transaction.as_ref().map(|tx| tx.index),
+    };
+    NAMESPACED.save(deps.storage, (&namespace, hash.as_slice()), &entry)?;
+    NAMESPACES.update(deps.storage, &namespace, |count| checked_count("namespaces", count.unwrap_or(0), 1))?;
+    REGISTRANT_STATS.update(deps.storage, &info.sender, |stats| -> Result<_, ContractError> {
+        let mut stats = stats.unwrap_or_default();
+        stats.namespaced = checked_count("registrant_stats", stats.namespaced, 1)?;
+        Ok(stats)
+    })?;
+    increment_total(deps.storage)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_namespaced")
+        .add_attribute("registry_id", registry_id(&config, &env))
+        .add_attribute("namespace", namespace)
+        .add_attribute("hash", entry.hash_hex)
+        .add_attribute("registrant", entry.registrant)
+        .add_attribute("block_height", entry.registered_at.to_string())
+        .add_attribute("block_time", entry.registered_at_time.to_string())
+        .add_attribute("chain_id", entry.chain_id))
+}
+
+/// Register several anchors in one call. Any invalid or repeated anchor
+/// fails the whole batch.
+#[cfg(feature = "cosmwasm")]
+fn register_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    anchors: Vec<BatchEntry>,
+) -> Result<Response, ContractError> {
+    let max = CONFIG.load(deps.storage)?.max_batch_size as usize;
+    if anchors.len() > max {
+        return Err(ContractError::BatchTooLarge { got: anchors.len(), max });
+    }
+    if anchors.is_empty() {
+        return Err(ContractError::InvalidBatchSize { max, got: 0 });
+    }
+
+    // Validate every anchor before writing any of them
+    let mut seen = std::collections::BTreeSet::new();
+    let mut entries = Vec::with_capacity(anchors.len());
+    for BatchEntry { anchor_type, hash, tags, memo } in anchors {
+        // Aliases name the same anchor, so dedupe on the parsed type
+        let parsed = parse_anchor_type(&anchor_type)?;
+        if !seen.insert((parsed, hash.clone())) {
+            return Err(ContractError::DuplicateInBatch {
+                anchor_type: parsed.to_string(),
+                hash_hex: hex::encode(hash.as_slice()),
+            });
+        }
+        let meta = EntryMeta::new(tags, memo);
+        check_registration(deps.as_ref(), &env, info.sender.as_str(), &anchor_type, &hash, &meta)?;
+        let entry = new_entry(&env, &info, &hash, parsed, meta);
+        entries.push((hash, entry));
+    }
+
+    check_quota(deps.storage, &info.sender, entries.len() as u64)?;
+    consume_rate_limit(deps.storage, &env, &info.sender, entries.len() as u32)?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut receipts = Vec::with_capacity(entries.len());
+    let mut stored = Vec::with_capacity(entries.len());
+    for (hash, entry) in entries {
+        let (entry, replaced) = write_anchor(deps.storage, env.block.height, hash.as_slice(), entry, "register")?;
+        if !replaced {
+            count_added(deps.storage, &entry, hash.as_slice())?;
+        }
+        receipts.push(anchor_receipt(&config, &env, &entry));
+        stored.push(entry);
+    }
+    // Per-type summary such as "root:1,claim_score:3"
+    let count_by_type = AnchorType::ALL
+        .into_iter()
+        .filter_map(|anchor_type| {
+            let count = stored.iter().filter(|entry| entry.anchor_type == anchor_type).count();
+            (count > 0).then(|| format!("{}:{}", anchor_type, count))
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    Ok(Response::new()
+        .add_submessages(notify_subscribers(deps.storage, &stored)?)
+        .add_events(receipts.iter().map(registered_event))
+        .set_data(to_json_binary(&receipts)?)
+        .add_attribute("action", "register_batch")
+        .add_attribute("registrant", info.sender.to_string())
+        .add_attribute("count", receipts.len().to_string())
+        .add_attribute("count_by_type", count_by_type))
+}
+
+/// A fresh entry for a registration by `info.sender` at the current block.
+#[cfg(feature = "cosmwasm")]
+fn new_entry(
+    env: &Env,
+    info: &MessageInfo,
+    hash: &[u8],
+    anchor_type: AnchorType,
+    meta: EntryMeta,
+) -> AnchorEntry {
+    AnchorEntry {
+        hash_hex: hex::encode(hash),
+        digest_len: hash.len() as u32,
+        payload_version: meta.payload_version,
+        anchor_type,
+        registered_at: env.block.height,
+        registered_at_time: env.block.time.seconds(),
+        imported_at: None,
+        registrant: info.sender.to_string(),
+        tags: meta.tags,
+        last_modified_height: 0,
+        stability_class: meta.stability_class,
+        memo: meta.memo,
+        revoked_at: None,
+        revocation_reason: None,
+        previous_root: meta.previous_root,
+        superseded_by: None,
+        chain_id: Some(env.block.chain_id.clone()),
+        tx_index: env.transaction.as_ref().map(|tx| tx.index),
+        claim_id: meta.claim_id,
+        depends_on_root: meta.depends_on_root,
+        source_channel: None,
+        origin_registrant: None,
+        origin_height: None,
+        index: None,
+        previous_registration: None,
+        expires_at: meta.ttl_blocks.map(|ttl| env.block.height.saturating_add(ttl)),
+    }
+}
+
+/// Admin: import an anchor with its original height and time, recording the
+/// current height as `imported_at`.
+#[cfg(feature = "cosmwasm")]
+fn register_historical(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: HistoricalAnchor,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let meta = EntryMeta::new(msg.tags, msg.memo);
+    let anchor_type = check_registration(deps.as_ref(), &env, info.sender.as_str(), &msg.anchor_type, &msg.hash, &meta)?;
+    if msg.original_height > env.block.height || msg.original_time > env.block.time.seconds() {
+        return Err(ContractError::FutureImport {});
+    }
+
+    let entry = AnchorEntry {
+        hash_hex: hex::encode(msg.hash.as_slice()),
+        digest_len: msg.hash.len() as u32,
+        payload_version: default_payload_version(),
+        anchor_type,
+        registered_at: msg.original_height,
+        registered_at_time: msg.original_time,
+        imported_at: Some(env.block.height),
+        registrant: info.sender.to_string(),
+        tags: meta.tags,
+        last_modified_height: 0,
+        stability_class: None,
+        memo: meta.memo,
+        revoked_at: None,
+        revocation_reason: None,
+        previous_root: None,
+        superseded_by: None,
+        chain_id: Some(env.block.chain_id.clone()),
+        tx_index: None,
+        claim_id: None,
+        depends_on_root: None,
+        source_channel: None,
+        origin_registrant: None,
+        origin_height: None,
+        index: None,
+        previous_registration: None,
+        expires_at: None,
+    };
+    store_anchor(deps, &env, msg.hash.as_slice(), entry, "import")
+}
+
+/// Store anchors from another registry verbatim, with `imported_at` set to the
+/// current height and no block time. Every anchor is checked before any is
+/// written; hashes already held here are rejected.
+#[cfg(feature = "cosmwasm")]
+fn import_anchors(storage: &mut dyn Storage, env: &Env, anchors: Vec<ImportEntry>) -> Result<usize, ContractError> {
+    if anchors.len() > MAX_IMPORT_BATCH {
+        return Err(ContractError::TooManyItems { what: "Import", max: MAX_IMPORT_BATCH, got: anchors.len() });
+    }
+    let config = CONFIG.load(storage)?;
+    let mut seen = std::collections::BTreeSet::new();
+    let mut entries = Vec::with_capacity(anchors.len());
+    for ImportEntry { anchor_type, hash, registrant, original_height, chain_id } in anchors {
+        let parsed = parse_anchor_type(&anchor_type)?;
+        check_hash(storage, &config, &hash)?;
+        validate_text("registrant", &registrant, config.limits.max_field_len as usize)?;
+        if let Some(chain_id) = &chain_id {
+            validate_text("chain_id", chain_id, config.limits.max_field_len as usize)?;
+        }
+        if original_height > env.block.height {
+            return Err(ContractError::FutureImport {});
+        }
+        if !seen.insert((parsed, hash.clone())) {
+            return Err(ContractError::DuplicateInBatch { anchor_type, hash_hex: hex::encode(hash.as_slice()) });
+        }
+        if let Some(existing) = may_load_anchor(storage, parsed, &hash)? {
+            return Err(ContractError::AlreadyRegistered {
+                hash_hex: existing.hash_hex,
+                height: existing.registered_at,
+                registrant: existing.registrant,
+            });
+        }
+        let entry = AnchorEntry {
+            hash_hex: hex::encode(hash.as_slice()),
+            digest_len: hash.len() as u32,
+            payload_version: default_payload_version(),
+            anchor_type: parsed,
+            registered_at: original_height,
+            registered_at_time: 0,
+            imported_at: Some(env.block.height),
+            registrant,
+            tags: vec![],
+            last_modified_height: 0,
+            stability_class: None,
+            memo: None,
+            revoked_at: None,
+            revocation_reason: None,
+            previous_root: None,
+            superseded_by: None,
+            chain_id,
+            tx_index: None,
+            claim_id: None,
+            depends_on_root: None,
+            source_channel: None,
+            origin_registrant: None,
+            origin_height: None,
+            index: None,
+            previous_registration: None,
+            expires_at: None,
+        };
+        entries.push((hash, entry));
+    }
+
+    let count = entries.len();
+    for (hash, entry) in entries {
+        let (entry, _) = write_anchor(storage, env.block.height, &hash, entry, "import")?;
+        count_added(storage, &entry, &hash)?;
+    }
+    Ok(count)
+}
+
+/// Persist a validated new entry, update indexes and counters, and build the
+/// registration response.
+#[cfg(feature = "cosmwasm")]
+fn store_anchor(
+    deps: DepsMut,
+    env: &Env,
+    hash: &[u8],
+    entry: AnchorEntry,
+    kind: &str,
+) -> Result<Response, ContractError> {
+    let (entry, replaced) = write_anchor(deps.storage, env.block.height, hash, entry, kind)?;
+
+    // Increment the counters; an overwrite does not add one
+    if !replaced {
+        count_added(deps.storage, &entry, hash)?;
+    }
+
+    let receipt = anchor_receipt(&CONFIG.load(deps.storage)?, env, &entry);
+
+    // The flat attributes below predate `anchor_registered` and are kept for
+    // one release so existing indexers can migrate
+    let mut response = Response::new()
+        .add_event(registered_event(&receipt))
+        .set_data(to_json_binary(&receipt)?)
+        .add_attribute("action", format!("register_{}", entry.anchor_type))
+        .add_attribute("registry_id", &receipt.registry_id)
+        .add_attribute("hash", &entry.hash_hex)
+        .add_attribute("registrant", &entry.registrant)
+        .add_attribute("block_height", entry.registered_at.to_string())
+        .add_attribute("block_time", entry.registered_at_time.to_string());
+    if let Some(chain_id) = &entry.chain_id {
+        response = response.add_attribute("chain_id", chain_id);
+    }
+    if let Some(tx_index) = entry.tx_index {
+        response = response.add_attribute("tx_index", tx_index.to_string());
+    }
+    if let Some(index) = entry.index {
+        response = response.add_attribute("index", index.to_string());
+    }
+    if let Some(imported_at) = entry.imported_at {
+        response = response.add_attribute("imported_at", imported_at.to_string());
+    }
+    if !entry.tags.is_empty() {
+        response = response.add_attribute("tags", entry.tags.join(","));
+    }
+    if let Some(memo) = &entry.memo {
+        response = response.add_attribute("memo", memo);
+    }
+
+    Ok(response)
+}
+
+/// Write a validated new entry and its indexes, replacing any previous entry.
+/// Returns the stored entry and whether it replaced one.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn write_anchor(
+    storage: &mut dyn Storage,
+    height: u64,
+    hash: &[u8],
+    mut entry: AnchorEntry,
+    kind: &str,
+) -> Result<(AnchorEntry, bool), ContractError> {
+    // Drop index entries of an overwritten anchor before re-indexing
+    let previous = may_load_anchor(storage, entry.anchor_type, hash)?;
+    match &previous {
+        Some(previous) => {
+            unindex_anchor(storage, previous, hash);
+            entry.index = previous.index;
+            if entry.anchor_type == AnchorType::MerkleRoot {
+                ROOT_METADATA.remove(storage, hash);
+            }
+            // Attestations vouched for the revoked entry, not its replacement
+            if previous.revoked_at.is_some() {
+                entry.previous_registration = Some(previous.registered_at);
+                ATTESTATIONS.remove(storage, (entry.anchor_type.as_str(), hash));
+            }
+        }
+        None => entry.index = Some(next_anchor_index(storage, entry.anchor_type, hash)?),
+    }
+    touch_anchor(storage, hash, &mut entry, height, kind)?;
+    index_anchor(storage, &entry, hash)?;
+    // Imported roots are historical and never become the head
+    if entry.anchor_type == AnchorType::MerkleRoot && kind == "register" {
+        advance_latest_root(storage, &entry)?;
+    }
+    Ok((entry, previous.is_some()))
+}
+
+/// Allocate the next registration sequence number to (anchor_type, hash).
+/// Only called once an anchor is being written, so a rejected registration
+/// never consumes a number.
+#[cfg(feature = "cosmwasm")]
+fn next_anchor_index(storage: &mut dyn Storage, anchor_type: AnchorType, hash: &[u8]) -> Result<u64, ContractError> {
+    let index = checked_count("anchor_sequence", ANCHOR_SEQUENCE.may_load(storage)?.unwrap_or_default(), 1)?;
+    ANCHOR_SEQUENCE.save(storage, &index)?;
+    ANCHOR_BY_INDEX.save(storage, index, &(anchor_type.as_str().to_string(), Binary::from(hash)))?;
+    Ok(index)
+}
+
+/// Move `LATEST_ROOT` to a newly registered root. A chained root only
+/// advances it when it links to the current head; unchained roots always do.
+#[cfg(feature = "cosmwasm")]
+fn advance_latest_root(storage: &mut dyn Storage, entry: &AnchorEntry) -> Result<(), ContractError> {
+    let head = LATEST_ROOT.may_load(storage)?;
+    let links_to_head = match (&entry.previous_root, &head) {
+        (Some(previous), Some(head)) => *previous == head.hash_hex,
+        _ => true,
+    };
+    if links_to_head {
+        let latest = LatestRoot {
+            hash_hex: entry.hash_hex.clone(),
+            height: entry.registered_at,
+            registrant: entry.registrant.clone(),
+        };
+        LATEST_ROOT.save(storage, &latest)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn anchor_receipt(config: &Config, env: &Env, entry: &AnchorEntry) -> AnchorReceipt {
+    let mut receipt = AnchorReceipt {
+        registry_id: registry_id(config, env),
+        anchor_type: entry.anchor_type.to_string(),
+        hash_hex: entry.hash_hex.clone(),
+        block_height: entry.registered_at,
+        block_time: entry.registered_at_time,
+        registrant: entry.registrant.clone(),
+        chain_id: entry.chain_id.clone(),
+        tx_index: entry.tx_index,
+        index: entry.index,
+        version: RECEIPT_FORMAT_VERSION,
+        receipt_hash: String::new(),
+    };
+    receipt.receipt_hash = receipt.compute_receipt_hash();
+    receipt
+}
+
+/// The `anchor_registered` event for a registration receipt.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn registered_event(receipt: &AnchorReceipt) -> Event {
+    let mut event = Event::new(EVENT_ANCHOR_REGISTERED)
+        .add_attribute("registry_id", &receipt.registry_id)
+        .add_attribute("anchor_type", &receipt.anchor_type)
+        .add_attribute("hash", &receipt.hash_hex)
+        .add_attribute("registrant", &receipt.registrant)
+        .add_attribute("block_height", receipt.block_height.to_string())
+        .add_attribute("block_time", receipt.block_time.to_string());
+    if let Some(chain_id) = &receipt.chain_id {
+        event = event.add_attribute("chain_id", chain_id);
+    }
+    if let Some(tx_index) = receipt.tx_index {
+        event = event.add_attribute("tx_index", tx_index.to_string());
+    }
+    if let Some(index) = receipt.index {
+        event = event.add_attribute("index", index.to_string());
+    }
+    event
+}
+
+/// Register a claim score from its full payload, recording the stability
+/// class on the entry. The payload hash must match its contents.
+#[cfg(feature = "cosmwasm")]
+fn register_claim_score_payload(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payload: ClaimScorePayload,
+    mut meta: EntryMeta,
+) -> Result<Response, ContractError> {
+    let limits = CONFIG.load(deps.storage)?.limits;
+    validate_fields(payload.text_fields(), &limits)?;
+    if !payload.verify() {
+        return Err(ContractError::PayloadHashMismatch {});
+    }
+    payload.check_scores().map_err(ContractError::InvalidPayload)?;
+    let hash = Binary::from(payload.hash_bytes());
+    meta.stability_class = Some(payload.stability_class.clone());
+    let response = register_hash(deps.branch(), env, info, hash, "claim_score", meta)?;
+
+    let sequence = CLAIM_PAYLOADS
+        .prefix(payload.claim_id)
+        .keys(deps.storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map_or(Ok(1), |last| checked_count("claim_payloads", last, 1))?;
+    CLAIM_PAYLOADS.save(deps.storage, (payload.claim_id, sequence), &payload)?;
+    Ok(response.add_attribute("claim_id", payload.claim_id.to_string()).add_attribute("sequence", sequence.to_string()))
+}
+
+/// Register a Merkle root from its full payload and store the payload under
+/// the root. The payload hash must match its contents and the tree must have
+/// leaves.
+#[cfg(feature = "cosmwasm")]
+fn register_root_with_metadata(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payload: MerkleRootPayload,
+    meta: EntryMeta,
+) -> Result<Response, ContractError> {
+    let size = to_json_vec(&payload)?.len();
+    if size > MAX_ROOT_METADATA_BYTES {
+        return Err(ContractError::PayloadTooLarge { size, max: MAX_ROOT_METADATA_BYTES });
+    }
+    if !payload.verify() {
+        return Err(ContractError::PayloadHashMismatch {});
+    }
+    if payload.leaf_count == 0 {
+        return Err(ContractError::EmptyMerkleTree {});
+    }
+    let hash = payload.root_bytes().ok_or_else(|| ContractError::InvalidHashHex { value: payload.root_hash.clone() })?;
+    let response = register_hash(deps.branch(), env, info, Binary::from(hash), "root", meta)?;
+    ROOT_METADATA.save(deps.storage, &hash, &payload)?;
+    Ok(response.add_attribute("leaf_count", payload.leaf_count.to_string()))
+}
+
+/// Register an equation proof from its full payload and index the payload
+/// under its equation name. The payload hash must match its contents.
+#[cfg(feature = "cosmwasm")]
+fn register_equation_proof_payload(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payload: EquationProofPayload,
+    meta: EntryMeta,
+) -> Result<Response, ContractError> {
+    let limits = CONFIG.load(deps.storage)?.limits;
+    validate_fields(payload.text_fields(), &limits)?;
+    validate_charset("equation_name", &payload.equation_name, MAX_EQUATION_NAME_LEN, is_equation_name_char)?;
+    if !payload.verify() {
+        return Err(ContractError::PayloadHashMismatch {});
+    }
+    let hash = payload.hash_bytes();
+    let response = register_hash(deps.branch(), env, info, Binary::from(hash), "equation_proof", meta)?;
+    PROOFS_BY_EQUATION.save(deps.storage, (payload.equation_name.as_str(), &hash), &payload)?;
+    Ok(response.add_attribute("equation_name", payload.equation_name))
+}
+
+/// The sender's anchor count for the current block after adding `count`,
+/// failing when it would exceed the per-block limit. `None` when no limit is
+/// configured, in which case nothing is tracked.
+#[cfg(feature = "cosmwasm")]
+fn check_rate_limit(storage: &dyn Storage, env: &Env, sender: &Addr, count: u32) -> Result<Option<u32>, ContractError> {
+    let Some(max) = CONFIG.load(storage)?.max_anchors_per_block_per_registrant else {
+        return Ok(None);
+    };
+    let used = match BLOCK_COUNTS.may_load(storage, sender)? {
+        Some((height, used)) if height == env.block.height => used,
+        _ => 0,
+    };
+    let total = used.saturating_add(count);
+    if total > max {
+        return Err(ContractError::RateLimited { max });
+    }
+    Ok(Some(total))
+}
+
+/// Fail when registering `count` more anchors would take the sender past its
+/// lifetime quota.
+#[cfg(feature = "cosmwasm")]
+fn check_quota(storage: &dyn Storage, sender: &Addr, count: u64) -> Result<(), ContractError> {
+    let Some(max) = QUOTAS.may_load(storage, sender)? else {
+        return Ok(());
+    };
+    let used = REGISTRANT_STATS.may_load(storage, sender)?.unwrap_or_default().total()?;
+    if used.saturating_add(count) > max {
+        return Err(ContractError::QuotaExceeded { registrant: sender.to_string(), max, used });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cosmwasm")]
+fn consume_rate_limit(storage: &mut dyn Storage, env: &Env, sender: &Addr, count: u32) -> Result<(), ContractError> {
+    if let Some(total) = check_rate_limit(storage, env, sender, count)? {
+        BLOCK_COUNTS.save(storage, sender, &(env.block.height, total))?;
+    }
+    Ok(())
+}
+
+/// Allowlist check plus `check_hash`, common to every local registration.
+/// The admin may always register, so a restricted registry with an empty
+/// allowlist is admin-only.
+#[cfg(feature = "cosmwasm")]
+fn check_sender_and_hash(deps: Deps, config: &Config, sender: &str, hash: &[u8]) -> Result<(), ContractError> {
+    if config.restricted && !is_admin(deps.storage, sender)? && !REGISTRANTS.has(deps.storage, &Addr::unchecked(sender)) {
+        return Err(ContractError::NotRegistrant { sender: sender.to_string() });
+    }
+
+    check_hash(deps.storage, config, hash)
+}
+
+/// Reject a hash whose length is not among `Config.accepted_hash_lengths`.
+#[cfg(feature = "cosmwasm")]
+fn check_hash_len(config: &Config, hash: &[u8]) -> Result<(), ContractError> {
+    let allowed: Vec<usize> = config.accepted_hash_lengths.iter().map(|len| *len as usize).collect();
+    if !validate_hash_len(hash, &allowed) {
+        return Err(ContractError::InvalidHashLength { got: hash.len() });
+    }
+    Ok(())
+}
+
+/// Hash length, degenerate digest and blocklist checks, which also apply to
+/// relayed anchors.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn check_hash(storage: &dyn Storage, config: &Config, hash: &[u8]) -> Result<(), ContractError> {
+    check_hash_len(config, hash)?;
+    if is_degenerate_hash(hash) {
+        return Err(ContractError::DegenerateHash { hash_hex: hex::encode(hash) });
+    }
+
+    if let Some(block) = BLOCKLIST.may_load(storage, hash)? {
+        return Err(ContractError::Blocklisted { reason: block.reason });
+    }
+    Ok(())
+}
+
+/// Every precondition of registering one anchor, in the order
+/// `register_hash` applies them, so `QueryMsg::SimulateRegister` reports
+/// the same first failure as the real registration.
+#[cfg(feature = "cosmwasm")]
+fn check_single_registration(
+    deps: Deps,
+    env: &Env,
+    sender: &Addr,
+    anchor_type: &str,
+    hash: &[u8],
+    meta: &EntryMeta,
+) -> Result<AnchorType, ContractError> {
+    let parsed = check_registration(deps, env, sender.as_str(), anchor_type, hash, meta)?;
+    check_quota(deps.storage, sender, 1)?;
+    check_rate_limit(deps.storage, env, sender, 1)?;
+    Ok(parsed)
+}
+
+/// The per-anchor preconditions of a registration, shared by every local
+/// registration path.
+#[cfg(feature = "cosmwasm")]
+fn check_registration(
+    deps: Deps,
+    env: &Env,
+    sender: &str,
+    anchor_type: &str,
+    hash: &[u8],
+    meta: &EntryMeta,
+) -> Result<AnchorType, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // `execute` rejects registrations while paused before anything else
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+    let parsed = parse_anchor_type(anchor_type)?;
+    check_sender_and_hash(deps, &config, sender, hash)?;
+
+    // Loaded rather than looked up in the presence map, which misses
+    // anchors stored before it until `BackfillPresence` has run
+    if let Some(existing) = may_load_anchor(deps.storage, parsed, hash)? {
+        // A tombstone is only replaced by its registrant or the admin once
+        // the lockout has passed, even when overwrites are allowed
+        if let Some(height) = existing.revoked_at {
+            let may_reregister = is_admin(deps.storage, sender)? || sender == existing.registrant;
+            match config.reregistration_lockout_blocks {
+                Some(lockout) if may_reregister => {
+                    let unlocks_at = height.saturating_add(lockout);
+                    if env.block.height < unlocks_at {
+                        return Err(ContractError::ReregistrationLocked { revoked_at: height, unlocks_at });
+                    }
+                }
+                _ => return Err(ContractError::Revoked { height }),
+            }
+        } else if !config.allow_overwrite {
+            return Err(ContractError::AlreadyRegistered {
+                hash_hex: existing.hash_hex,
+                height: existing.registered_at,
+                registrant: existing.registrant,
+            });
+        }
+    }
+
+    validate_tags(&meta.tags, &config.limits)?;
+    if let Some(memo) = &meta.memo {
+        validate_text("memo", memo, MAX_MEMO_LEN)?;
+    }
+    if meta.ttl_blocks == Some(0) {
+        return Err(ContractError::ZeroTtl {});
+    }
+    if !SUPPORTED_PAYLOAD_VERSIONS.contains(&meta.payload_version) {
+        return Err(ContractError::UnsupportedPayloadVersion { version: meta.payload_version });
+    }
+    Ok(parsed)
+}
+
+/// Persist an anchor entry and record a change of `kind` at `height`.
+///
+/// The only writer of anchor entries: every mutating path goes through here
+/// so that `ListModifiedSince` cannot miss a change.
+#[cfg(feature = "cosmwasm")]
+fn touch_anchor(
+    storage: &mut dyn Storage,
+    hash: &[u8],
+    entry: &mut AnchorEntry,
+    height: u64,
+    kind: &str,
+) -> Result<(), ContractError> {
+    let anchor_type = entry.anchor_type.as_str();
+    MODIFIED_INDEX.remove(storage, (entry.last_modified_height, hash, anchor_type));
+    // A fresh entry replaces the tombstone of a pruned one
+    if entry.last_modified_height == 0 {
+        if let Some(pruned_at) = PRUNE_TOMBSTONES.may_load(storage, (anchor_type, hash))? {
+            MODIFIED_INDEX.remove(storage, (pruned_at, hash, anchor_type));
+            PRUNE_TOMBSTONES.remove(storage, (anchor_type, hash));
+        }
+    }
+    entry.last_modified_height = height;
+    MODIFIED_INDEX.save(storage, (height, hash, anchor_type), &())?;
+    CHANGE_LOG.update(storage, (hash, anchor_type, height), |kinds| -> Result<_, ContractError> {
+        let mut kinds = kinds.unwrap_or_default();
+        if !kinds.iter().any(|k| k == kind) {
+            kinds.push(kind.to_string());
+        }
+        Ok(kinds)
+    })?;
+    let compact = CompactEntry { registered_at: entry.registered_at };
+    PRESENCE.save(storage, (anchor_type, hash), &compact)?;
+    save_anchor(storage, entry.anchor_type, hash, entry)?;
+    Ok(())
+}
+
+/// Whether `hash` is registered under `anchor_type`, without loading its
+/// entry once `MigrateMsg::BackfillPresence` has completed.
+#[cfg(feature = "cosmwasm")]
+fn is_registered(storage: &dyn Storage, anchor_type: AnchorType, hash: &[u8]) -> StdResult<bool> {
+    if PRESENCE.has(storage, (anchor_type.as_str(), hash)) {
+        return Ok(true);
+    }
+    // Anchors stored before the presence map have no entry in it yet
+    if PRESENCE_COMPLETE.may_load(storage)?.unwrap_or(false) {
+        return Ok(false);
+    }
+    Ok(may_load_anchor(storage, anchor_type, hash)?.is_some())
+}
+
+/// Touch every registered anchor of `hash`, across all anchor types.
+#[cfg(feature = "cosmwasm")]
+fn touch_existing(storage: &mut dyn Storage, hash: &[u8], height: u64, kind: &str) -> Result<(), ContractError> {
+    for anchor_type in AnchorType::ALL {
+        if let Some(mut entry) = may_load_anchor(storage, anchor_type, hash)? {
+            touch_anchor(storage, hash, &mut entry, height, kind)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the secondary index entries for an anchor.
+#[cfg(feature = "cosmwasm")]
+fn index_anchor(storage: &mut dyn Storage, entry: &AnchorEntry, hash: &[u8]) -> Result<(), ContractError> {
+    let anchor_type = entry.anchor_type.as_str();
+    HEIGHT_INDEX.save(storage, (entry.registered_at, hash, anchor_type), &())?;
+    TYPE_HEIGHT_INDEX.save(storage, (anchor_type, entry.registered_at, hash), &())?;
+    if entry.registered_at_time != 0 {
+        TIME_INDEX.save(storage, (entry.registered_at_time, hash, anchor_type), &())?;
+    }
+    for tag in &entry.tags {
+        TAG_INDEX.save(storage, (tag.as_str(), anchor_type, hash), &())?;
+    }
+    if has_local_registrant(entry) {
+        REGISTRANT_INDEX.save(storage, (entry.registrant.as_str(), anchor_type, hash), &())?;
+    }
+    if let Some(claim_id) = entry.claim_id {
+        CLAIM_INDEX.save(storage, (claim_id, entry.registered_at, hash), &())?;
+    }
+    if let Some(root) = &entry.depends_on_root {
+        DEPENDENTS.save(storage, (root.as_str(), hash), &())?;
+    }
+    if let Some(expires_at) = entry.expires_at {
+        EXPIRY_INDEX.save(storage, (expires_at, hash, anchor_type), &())?;
+    }
+    Ok(())
+}
+
+/// Remove the secondary index entries written by `index_anchor`.
+#[cfg(feature = "cosmwasm")]
+fn unindex_anchor(storage: &mut dyn Storage, entry: &AnchorEntry, hash: &[u8]) {
+    let anchor_type = entry.anchor_type.as_str();
+    HEIGHT_INDEX.remove(storage, (entry.registered_at, hash, anchor_type));
+    TYPE_HEIGHT_INDEX.remove(storage, (anchor_type, entry.registered_at, hash));
+    MODIFIED_INDEX.remove(storage, (entry.last_modified_height, hash, anchor_type));
+    TIME_INDEX.remove(storage, (entry.registered_at_time, hash, anchor_type));
+    for tag in &entry.tags {
+        TAG_INDEX.remove(storage, (tag.as_str(), anchor_type, hash));
+    }
+    REGISTRANT_INDEX.remove(storage, (entry.registrant.as_str(), anchor_type, hash));
+    if let Some(claim_id) = entry.claim_id {
+        CLAIM_INDEX.remove(storage, (claim_id, entry.registered_at, hash));
+    }
+    if let Some(root) = &entry.depends_on_root {
+        DEPENDENTS.remove(storage, (root.as_str(), hash));
+    }
+    if let Some(expires_at) = entry.expires_at {
+        EXPIRY_INDEX.remove(storage, (expires_at, hash, anchor_type));
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    let binary = match msg {
+        QueryMsg::VerifyRoot { hash, hash_hex } => {
+            to_json_binary(&verify_hash(deps, &env, resolve_hash(hash, hash_hex)?, "root")?)
+        }
+        QueryMsg::VerifyClaimScore { hash, hash_hex } => {
+            to_json_binary(&verify_hash(deps, &env, resolve_hash(hash, hash_hex)?, "claim_score")?)
+        }
+        QueryMsg::VerifyEquationProof { hash, hash_hex } => {
+            to_json_binary(&verify_hash(deps, &env, resolve_hash(hash, hash_hex)?, "equation_proof")?)
+        }
+        QueryMsg::VerifyClaimScorePayload { payload } => {
+            let hash = compute_sha256(payload.to_canonical_string().as_bytes());
+            to_json_binary(&verify_payload(deps, &env, payload.verify(), Some(hash), "claim_score")?)
+        }
+        QueryMsg::VerifyEquationProofPayload { payload } => {
+            let hash = compute_sha256(payload.to_canonical_string().as_bytes());
+            to_json_binary(&verify_payload(deps, &env, payload.verify(), Some(hash), "equation_proof")?)
+        }
+        QueryMsg::VerifyMerklePayload { payload } => {
+            to_json_binary(&verify_payload(deps, &env, payload.verify(), payload.root_bytes(), "root")?)
+        }
+        QueryMsg::Ownership {} => to_json_binary(&cw_ownable::get_ownership(deps.storage)?),
+        QueryMsg::GetConfig {} => {
+            let config = CONFIG.load(deps.storage)?;
+            to_json_binary(&ConfigResponse {
+                registry_id: registry_id(&config, &env),
+                admin: cw_ownable::get_ownership(deps.storage)?.owner.map(String::from).unwrap_or_default(),
+                name: config.name,
+                total_anchors: TOTAL_ANCHORS.may_load(deps.storage)?.unwrap_or_default(),
+                total_roots: type_count(deps.storage, AnchorType::MerkleRoot)?,
+                total_claim_scores: type_count(deps.storage, AnchorType::ClaimScore)?,
+                total_equation_proofs: type_count(deps.storage, AnchorType::EquationProof)?,
+                limits: config.limits,
+                allow_overwrite: config.allow_overwrite,
+                reregistration_lockout_blocks: config.reregistration_lockout_blocks,
+                restricted: config.restricted,
+                public_registration: !config.restricted,
+                paused: config.paused,
+                max_anchors_per_block_per_registrant: config.max_anchors_per_block_per_registrant,
+                operators: config.operators,
+                max_batch_size: config.max_batch_size,
+                accepted_hash_lengths: config.accepted_hash_lengths,
+                subscriber_gas_limit: config.subscriber_gas_limit,
+            })
+        }
+        QueryMsg::GetTotals {} => to_json_binary(&TotalsResponse {
+            roots: type_count(deps.storage, AnchorType::MerkleRoot)?,
+            claim_scores: type_count(deps.storage, AnchorType::ClaimScore)?,
+            equation_proofs: type_count(deps.storage, AnchorType::EquationProof)?,
+            total: TOTAL_ANCHORS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::GetContractInfo {} => to_json_binary(&ContractInfoResponse {
+            contract_name: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
+            payload_format_version: PAYLOAD_FORMAT_VERSION,
+            supported_payload_versions: SUPPORTED_PAYLOAD_VERSIONS.to_vec(),
+            stored_version: cw2::CONTRACT.may_load(deps.storage)?.map(|stored| stored.version),
+        }),
+        QueryMsg::GetAnchor { hash, hash_hex, anchor_type } => {
+            to_json_binary(&verify_hash(deps, &env, resolve_hash(hash, hash_hex)?, anchor_type.as_str())?)
+        }
+        QueryMsg::FindHash { hash } => {
+            let anchors = AnchorType::ALL
+                .into_iter()
+                .filter_map(|anchor_type| may_load_anchor(deps.storage, anchor_type, hash.as_slice()).transpose())
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&AnchorListResponse { anchors })
+        }
+        QueryMsg::AnchorExists { anchor_type, hash } => {
+            let parsed = parse_anchor_type(&anchor_type)?;
+            to_json_binary(&AnchorExistsResponse { exists: is_registered(deps.storage, parsed, &hash)? })
+        }
+        QueryMsg::IsBlocked { hash } => {
+            let reason = BLOCKLIST.may_load(deps.storage, hash.as_slice())?;
+            to_json_binary(&BlockedResponse {
+                blocked: reason.is_some(),
+                reason,
+            })
+        }
+        QueryMsg::ListBlocked { start_after, limit } => {
+            to_json_binary(&list_blocked(deps, start_after, limit)?)
+        }
+        QueryMsg::GetOldest { anchor_type } => {
+            to_json_binary(&get_oldest(deps, &env, &anchor_type)?)
+        }
+        QueryMsg::GetAnchorByIndex { index } => {
+            to_json_binary(&get_anchor_by_index(deps, index)?)
+        }
+        QueryMsg::GetAttestations { hash, anchor_type } => {
+            let key = (parse_anchor_type(&anchor_type)?.as_str(), hash.as_slice());
+            let attestations = ATTESTATIONS.may_load(deps.storage, key)?.unwrap_or_default();
+            to_json_binary(&AttestationsResponse { attestations })
+        }
+        QueryMsg::VerifyLeaf { root, leaf, proof } => {
+            to_json_binary(&verify_leaf(deps, &env, root, leaf, proof)?)
+        }
+        QueryMsg::VerifyInclusionBatch { root, items } => {
+            to_json_binary(&verify_inclusion_batch(deps, &env, root, items)?)
+        }
+        QueryMsg::ActivityHistogram { anchor_type, bucket_blocks, buckets } => {
+            to_json_binary(&activity_histogram(deps, &env, anchor_type, bucket_blocks, buckets)?)
+        }
+        QueryMsg::StabilityHistogram {} => to_json_binary(&stability_histogram(deps)?),
+        QueryMsg::SimulateRegister { anchor_type, hash, sender, tags } => {
+            let verdict = deps
+                .api
+                .addr_validate(&sender)
+                .map_err(ContractError::from)
+                .and_then(|sender| check_single_registration(deps, &env, &sender, &anchor_type, &hash, &EntryMeta::new(tags, None)));
+            to_json_binary(&SimulateResponse {
+                would_succeed: verdict.is_ok(),
+                failure_reason: verdict.err().map(|e| e.to_string()),
+            })
+        }
+        QueryMsg::ListByTimeRange { start, end, anchor_type, start_after, limit } => {
+            to_json_binary(&list_by_time_range(deps, start, end, anchor_type, start_after, limit)?)
+        }
+        QueryMsg::ListModifiedSince { height, start_after, limit } => {
+            to_json_binary(&list_modified_since(deps, height, start_after, limit)?)
+        }
+        QueryMsg::ExportState { start_after, limit } => to_json_binary(&export_state(deps, start_after, limit)?),
+        QueryMsg::GetAnchorsSince { height, start_after, limit, order } => {
+            to_json_binary(&anchors_since(deps, height, start_after, limit, order)?)
+        }
+        QueryMsg::GetAnchorsAtHeight { height, start_after, limit, order } => {
+            to_json_binary(&anchors_at_height(deps, height, start_after, limit, order)?)
+        }
+        QueryMsg::Multi { queries } => to_json_binary(&multi_query(deps, env, queries)?),
+        QueryMsg::ListByTag { tag, start_after, limit, order } => {
+            to_json_binary(&list_by_tag(deps, tag, None, start_after, limit, order)?)
+        }
+        QueryMsg::GetAnchorsByTag { tag, anchor_type, start_after, limit, order } => {
+            to_json_binary(&list_by_tag(deps, tag, anchor_type, start_after, limit, order)?)
+        }
+        QueryMsg::GetAnchorsByRegistrant { registrant, anchor_type, start_after, limit, order } => {
+            to_json_binary(&anchors_by_registrant(deps, registrant, anchor_type, start_after, limit, order)?)
+        }
+        QueryMsg::ListRegistrants { start_after, limit } => {
+            to_json_binary(&list_registrants(deps, start_after, limit)?)
+        }
+        QueryMsg::VerifyNamespaced { namespace, hash } => {
+            let entry = NAMESPACED.may_load(deps.storage, (&namespace, hash.as_slice()))?;
+            to_json_binary(&NamespacedVerifyResponse {
+                exists: entry.is_some(),
+                hash_hex: hex::encode(hash.as_slice()),
+                entry,
+                blocked: BLOCKLIST.has(deps.storage, hash.as_slice()),
+            })
+        }
+        QueryMsg::ListNamespaces { start_after, limit } => {
+            to_json_binary(&list_namespaces(deps, start_after, limit)?)
+        }
+        QueryMsg::GetClaimScoreHistory { claim_id, start_after, limit } => {
+            to_json_binary(&claim_score_history(deps, claim_id, start_after, limit)?)
+        }
+        QueryMsg::GetClaimScorePayloads { claim_id, start_after, limit } => {
+            to_json_binary(&claim_score_payloads(deps, claim_id, start_after, limit)?)
+        }
+        QueryMsg::GetProofsByEquation { equation_name, start_after, limit } => {
+            to_json_binary(&proofs_by_equation(deps, equation_name, start_after, limit)?)
+        }
+        QueryMsg::GetDependents { root_hash, start_after, limit } => {
+            to_json_binary(&dependents(deps, root_hash, start_after, limit)?)
+        }
+        QueryMsg::ListSubscribers {} => {
+            let subscribers = SUBSCRIBERS
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(contract, msg_template)| Subscriber { contract: contract.into(), msg_template }))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&SubscribersResponse { subscribers })
+        }
+        QueryMsg::ListFailedDispatches { start_after, limit } => {
+            let limit = clamp_limit(limit)?;
+            let dispatches = FAILED_DISPATCHES
+                .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+                .take(limit)
+                .map(|item| item.map(|(_, dispatch)| dispatch))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&FailedDispatchesResponse { dispatches })
+        }
+        QueryMsg::GetRegistrantStats { registrant } => {
+            let addr = deps.api.addr_validate(&registrant)?;
+            let by_type = REGISTRANT_STATS.may_load(deps.storage, &addr)?.unwrap_or_default();
+            let quota = QUOTAS.may_load(deps.storage, &addr)?;
+            to_json_binary(&RegistrantStatsResponse { registrant, total: by_type.total()?, by_type, quota })
+        }
+        QueryMsg::GetLatestRoot {} => {
+            to_json_binary(&LatestRootResponse { latest: LATEST_ROOT.may_load(deps.storage)? })
+        }
+        QueryMsg::GetRootChain { head, limit } => to_json_binary(&root_chain(deps, head, limit)?),
+        QueryMsg::GetRootMetadata { hash } => {
+            to_json_binary(&RootMetadataResponse { payload: ROOT_METADATA.may_load(deps.storage, hash.as_slice())? })
+        }
+        QueryMsg::ListRoots { start_after, limit, order } => {
+            to_json_binary(&list_anchors(deps, "root", start_after, limit, order)?)
+        }
+        QueryMsg::ListClaimScores { start_after, limit, order } => {
+            to_json_binary(&list_anchors(deps, "claim_score", start_after, limit, order)?)
+        }
+        QueryMsg::ListEquationProofs { start_after, limit, order } => {
+            to_json_binary(&list_anchors(deps, "equation_proof", start_after, limit, order)?)
+        }
+    };
+    Ok(binary?)
+}
+
+#[cfg(feature = "cosmwasm")]
+fn verify_inclusion_batch(
+    deps: Deps,
+    env: &Env,
+    root: Binary,
+    items: Vec<(Binary, Vec<Binary>, Vec<bool>)>,
+) -> Result<InclusionBatchResponse, ContractError> {
+    if items.len() > MAX_INCLUSION_BATCH {
+        return Err(ContractError::TooManyItems {
+            what: "Inclusion batch",
+            max: MAX_INCLUSION_BATCH,
+            got: items.len(),
+        });
+    }
+
+    let root_bytes = to_hash32(&root);
+    let root_registered = root_bytes.is_some() && root_in_force(deps.storage, env, &root)?;
+    let results = items
+        .iter()
+        .map(|(leaf, siblings, directions)| {
+            let (Some(root), Some(leaf), true) = (root_bytes, to_hash32(leaf), root_registered) else {
+                return false;
+            };
+            siblings
+                .iter()
+                .map(to_hash32)
+                .collect::<Option<Vec<_>>>()
+                .is_some_and(|siblings| verify_inclusion(&root, &leaf, &siblings, directions))
+        })
+        .collect();
+
+    Ok(InclusionBatchResponse { root_registered, results })
+}
+
+/// Whether `root` is a registered root that is neither revoked nor expired,
+/// so proofs against it may be trusted.
+#[cfg(feature = "cosmwasm")]
+fn root_in_force(storage: &dyn Storage, env: &Env, root: &[u8]) -> StdResult<bool> {
+    Ok(may_load_anchor(storage, AnchorType::MerkleRoot, root)?
+        .is_some_and(|entry| entry.revoked_at.is_none() && !entry.is_expired(env.block.height)))
+}
+
+#[cfg(feature = "cosmwasm")]
+fn verify_leaf(deps: Deps, env: &Env, root: Binary, leaf: Binary, proof: Vec<ProofStep>) -> Result<LeafVerifyResponse, ContractError> {
+    if proof.len() > MAX_PROOF_DEPTH {
+        return Err(ContractError::TooManyItems { what: "Proof", max: MAX_PROOF_DEPTH, got: proof.len() });
+    }
+    let hash32 = |bytes: &Binary| to_hash32(bytes).ok_or(ContractError::InvalidHashLength { got: bytes.len() });
+    let siblings = proof
+        .iter()
+        .map(|step| {
+            let side = match step.side.as_str() {
+                "left" => Side::Left,
+                "right" => Side::Right,
+                other => return Err(ContractError::InvalidProofSide { side: other.to_string() }),
+            };
+            Ok(ProofNode { hash: hash32(&step.hash)?, side: Some(side) })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let root_bytes = hash32(&root)?;
+    let proof = MerkleProof { leaf: hash32(&leaf)?, siblings, leaf_index: 0 };
+
+    Ok(LeafVerifyResponse {
+        root_registered: root_in_force(deps.storage, env, &root_bytes)?,
+        proof_valid: verify_proof(&root_bytes, &proof),
+    })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn stability_histogram(deps: Deps) -> Result<StabilityHistogramResponse, ContractError> {
+    let mut counts = std::collections::BTreeMap::<String, u64>::new();
+    let mut unclassified = 0;
+    let mut truncated = false;
+    for (scanned, item) in range_anchors(deps.storage, AnchorType::ClaimScore, None, Order::Ascending).enumerate() {
+        if scanned == MAX_STABILITY_SCAN {
+            truncated = true;
+            break;
+        }
+        match item?.1.stability_class {
+            Some(class) => *counts.entry(class).or_default() += 1,
+            None => unclassified += 1,
+        }
+    }
+    let classes = counts
+        .into_iter()
+        .map(|(stability_class, count)| StabilityCount { stability_class, count })
+        .collect();
+    Ok(StabilityHistogramResponse { classes, unclassified, truncated })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn activity_histogram(
+    deps: Deps,
+    env: &Env,
+    anchor_type: Option<String>,
+    bucket_blocks: u64,
+    buckets: u32,
+) -> Result<HistogramResponse, ContractError> {
+    let anchor_type = anchor_type.as_deref().map(parse_anchor_type).transpose()?;
+    if bucket_blocks == 0 || buckets == 0 || buckets > MAX_HISTOGRAM_BUCKETS {
+        return Err(ContractError::InvalidHistogram { max_buckets: MAX_HISTOGRAM_BUCKETS });
+    }
+    let span = bucket_blocks.saturating_mul(buckets as u64);
+    if span > MAX_HISTOGRAM_SPAN {
+        return Err(ContractError::HistogramSpanTooLarge { span, max: MAX_HISTOGRAM_SPAN });
+    }
+
+    // Windows end at the current height; windows before genesis are clipped
+    // (possibly to empty ranges) but keep their slot so counts stay aligned.
+    let end = env.block.height;
+    let window_start = end as i128 + 1 - span as i128;
+    let mut result: Vec<HistogramBucket> = (0..buckets as i128)
+        .map(|i| {
+            let start = window_start + i * bucket_blocks as i128;
+            let stop = start + bucket_blocks as i128 - 1;
+            HistogramBucket {
+                start_height: start.max(0) as u64,
+                end_height: stop.max(0) as u64,
+                count: 0,
+            }
+        })
+        .collect();
+    let first = window_start.max(0) as u64;
+
+    for key in HEIGHT_INDEX.keys(
+        deps.storage,
+        Some(height_bound(first)),
+        Some(height_bound(end.saturating_add(1))),
+        Order::Ascending,
+    ) {
+        let (height, _, indexed_type) = key?;
+        if anchor_type.is_some_and(|t| t.as_str() != indexed_type) {
+            continue;
+        }
+        let slot = ((height as i128 - window_start) / bucket_blocks as i128) as usize;
+        if let Some(bucket) = result.get_mut(slot) {
+            bucket.count += 1;
+        }
+    }
+
+    Ok(HistogramResponse { buckets: result })
+}
+
+/// Page size for a listing query: `DEFAULT_LIMIT` when omitted, rejecting
+/// explicit requests above `MAX_LIMIT`.
+#[cfg(feature = "cosmwasm")]
+fn clamp_limit(limit: Option<u32>) -> Result<usize, ContractError> {
+    match limit {
+        Some(limit) if limit > MAX_LIMIT => Err(ContractError::LimitTooLarge { limit, max: MAX_LIMIT }),
+        limit => Ok(limit.unwrap_or(DEFAULT_LIMIT) as usize),
+    }
+}
+
+/// (min, max) range bounds for a page in `order`: `start` is the lower bound
+/// when ascending and the upper bound when descending.
+#[cfg(feature = "cosmwasm")]
+fn page_bounds<'a, K: PrimaryKey<'a>>(start: Option<Bound<'a, K>>, order: Order) -> (Option<Bound<'a, K>>, Option<Bound<'a, K>>) {
+    match order {
+        Order::Ascending => (start, None),
+        Order::Descending => (None, start),
+    }
+}
+
+/// Inclusive lower bound positioned before every height- or time-index key
+/// whose leading component is `height`.
+#[cfg(feature = "cosmwasm")]
+fn height_bound<'a>(height: u64) -> Bound<'a, (u64, &'a [u8], &'a str)> {
+    Bound::inclusive((height, &[][..], ""))
+}
+
+/// Convert a `Binary` into a 32-byte array, if it has exactly 32 bytes.
+#[cfg(feature = "cosmwasm")]
+fn to_hash32(bytes: &Binary) -> Option<[u8; 32]> {
+    bytes.as_slice().try_into().ok()
+}
+
+/// Dispatch each sub-query independently. A failing sub-query yields
+/// `MultiResult::Err` without affecting the others; only exceeding
+/// `MAX_MULTI_QUERIES` fails the whole call. Nested `Multi` is rejected per item.
+#[cfg(feature = "cosmwasm")]
+fn multi_query(deps: Deps, env: Env, queries: Vec<QueryMsg>) -> Result<MultiResponse, ContractError> {
+    if queries.len() > MAX_MULTI_QUERIES {
+        return Err(ContractError::TooManyItems {
+            what: "Multi query",
+            max: MAX_MULTI_QUERIES,
+            got: queries.len(),
+        });
+    }
+
+    let results = queries
+        .into_iter()
+        .map(|sub| match sub {
+            QueryMsg::Multi { .. } => MultiResult::Err("Nested multi queries are not allowed".into()),
+            sub => match query(deps, env.clone(), sub) {
+                Ok(data) => MultiResult::Ok(data),
+                Err(err) => MultiResult::Err(err.to_string()),
+            },
+        })
+        .collect();
+
+    Ok(MultiResponse { results })
+}
+
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn parse_anchor_type(value: &str) -> Result<AnchorType, ContractError> {
+    value.parse().map_err(|_| ContractError::UnknownAnchorType { value: value.to_string() })
+}
+
+/// The canonical key for a caller-supplied anchor type, so an alias in a
+/// cursor lands on the same index keys as the name it stands for.
+#[cfg(feature = "cosmwasm")]
+fn key_type(value: &str) -> Result<&'static str, ContractError> {
+    Ok(parse_anchor_type(value)?.as_str())
+}
+
+/// Load an anchor, falling back to its legacy per-type map until
+/// `MigrateMsg::MoveAnchors` has moved it.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn may_load_anchor(
+    storage: &dyn Storage,
+    anchor_type: AnchorType,
+    hash: &[u8],
+) -> StdResult<Option<AnchorEntry>> {
+    match ANCHORS.may_load(storage, (anchor_type.as_str(), hash))? {
+        Some(entry) => Ok(Some(entry)),
+        None => anchor_type.legacy_store().may_load(storage, hash),
+    }
+}
+
+/// Load an anchor that is known to exist.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn load_anchor(storage: &dyn Storage, anchor_type: AnchorType, hash: &[u8]) -> StdResult<AnchorEntry> {
+    may_load_anchor(storage, anchor_type, hash)?
+        .ok_or_else(|| StdError::not_found(std::any::type_name::<AnchorEntry>()))
+}
+
+/// Load an anchor through an index key, whose type is stored as a string.
+#[cfg(feature = "cosmwasm")]
+fn load_indexed(storage: &dyn Storage, anchor_type: &str, hash: &[u8]) -> Result<AnchorEntry, ContractError> {
+    Ok(load_anchor(storage, parse_anchor_type(anchor_type)?, hash)?)
+}
+
+/// Store an anchor. A legacy copy is left shadowed for `MoveAnchors`.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn save_anchor(
+    storage: &mut dyn Storage,
+    anchor_type: AnchorType,
+    hash: &[u8],
+    entry: &AnchorEntry,
+) -> StdResult<()> {
+    ANCHORS.save(storage, (anchor_type.as_str(), hash), entry)
+}
+
+/// Anchors of one type in hash order, starting from `start`, merging in
+/// entries not yet moved out of the legacy map. A shadowed legacy copy is
+/// skipped.
+#[cfg(feature = "cosmwasm")]
+pub(crate) fn range_anchors<'a>(
+    storage: &'a dyn Storage,
+    anchor_type: AnchorType,
+    start: Option<Bound<'a, &'a [u8]>>,
+    order: Order,
+) -> impl Iterator<Item = StdResult<(Vec<u8>, AnchorEntry)>> + 'a {
+    let (min, max) = page_bounds(start, order);
+    let mut current = ANCHORS
+        .prefix(anchor_type.as_str())
+        .range(storage, min.clone(), max.clone(), order)
+        .peekable();
+    let mut legacy = anchor_type.legacy_store().range(storage, min, max, order).peekable();
+    std::iter::from_fn(move || {
+        let ordering = match (current.peek(), legacy.peek()) {
+            (Some(Ok((a, _))), Some(Ok((b, _)))) if order == Order::Ascending => a.cmp(b),
+            (Some(Ok((a, _))), Some(Ok((b, _)))) => b.cmp(a),
+            (Some(_), Some(_)) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (_, None) => return current.next(),
+        };
+        match ordering {
+            std::cmp::Ordering::Less => current.next(),
+            std::cmp::Ordering::Equal => {
+                legacy.next();
+                current.next()
+            }
+            std::cmp::Ordering::Greater => legacy.next(),
+        }
+    })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn get_oldest(deps: Deps, env: &Env, anchor_type: &str) -> Result<AnchorResponse, ContractError> {
+    let parsed = parse_anchor_type(anchor_type)?;
+    if !TYPE_INDEX_COMPLETE.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::TypeIndexBackfillPending {});
+    }
+    let keys = TYPE_HEIGHT_INDEX.sub_prefix(parsed.as_str()).keys(deps.storage, None, None, Order::Ascending);
+    for (scanned, key) in keys.enumerate() {
+        // Running out of budget is not the same as having no live anchor
+        if scanned == MAX_OLDEST_SCAN {
+            return Err(ContractError::OldestScanTruncated { anchor_type: parsed.to_string(), scanned });
+        }
+        let (_, hash) = key?;
+        let entry = load_anchor(deps.storage, parsed, &hash)?;
+        // Expired entries count as gone even before `PruneExpired` runs
+        if entry.revoked_at.is_none() && !entry.is_expired(env.block.height) {
+            return Ok(AnchorResponse { entry: Some(entry) });
+        }
+    }
+    Ok(AnchorResponse { entry: None })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn get_anchor_by_index(deps: Deps, index: u64) -> Result<AnchorResponse, ContractError> {
+    let entry = match ANCHOR_BY_INDEX.may_load(deps.storage, index)? {
+        Some((anchor_type, hash)) => Some(load_indexed(deps.storage, &anchor_type, &hash)?),
+        None => None,
+    };
+    Ok(AnchorResponse { entry })
+}
+
+/// Follow `previous_root` links from `head`. A missing head is an error; a
+/// missing link ends the walk with `ChainEnd::Broken`.
+#[cfg(feature = "cosmwasm")]
+fn root_chain(deps: Deps, head: Binary, limit: Option<u32>) -> Result<RootChainResponse, ContractError> {
+    let limit = clamp_limit(limit)?.max(1);
+    let mut next = may_load_anchor(deps.storage, AnchorType::MerkleRoot, head.as_slice())?.ok_or_else(|| ContractError::AnchorNotFound {
+        anchor_type: "root".to_string(),
+        hash_hex: hex::encode(head.as_slice()),
+    })?;
+
+    let mut roots = Vec::new();
+    loop {
+        let link = next.previous_root.clone();
+        roots.push(next);
+        let Some(previous_hex) = link else {
+            return Ok(RootChainResponse { roots, end: ChainEnd::Unlinked });
+        };
+        let previous = hex::decode(&previous_hex).map_err(|e| StdError::generic_err(e.to_string()))?;
+        if previous == GENESIS_ROOT {
+            return Ok(RootChainResponse { roots, end: ChainEnd::Genesis });
+        }
+        if roots.len() == limit {
+            let next_head = Binary::from(previous).to_base64();
+            return Ok(RootChainResponse { roots, end: ChainEnd::Limit { next_head } });
+        }
+        next = match may_load_anchor(deps.storage, AnchorType::MerkleRoot, &previous)? {
+            Some(entry) => entry,
+            None => return Ok(RootChainResponse { roots, end: ChainEnd::Broken { missing_hex: previous_hex } }),
+        };
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+fn list_blocked(
+    deps: Deps,
+    start_after: Option<Binary>,
+    limit: Option<u32>,
+) -> Result<BlocklistResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let start = start_after.as_ref().map(|h| Bound::exclusive(h.as_slice()));
+
+    let entries = BLOCKLIST
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (hash, reason) = item?;
+            Ok(BlockedHash { hash_hex: hex::encode(hash), reason })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BlocklistResponse { entries })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn list_namespaces(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<NamespacesResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let namespaces = NAMESPACES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(namespace, count)| NamespaceCount { namespace, count }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(NamespacesResponse { namespaces })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn list_registrants(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<RegistrantsResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let start_after = start_after.map(Addr::unchecked);
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let registrants = REGISTRANTS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|addr| addr.map(String::from))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(RegistrantsResponse { registrants })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn list_anchors(
+    deps: Deps,
+    anchor_type: &str,
+    start_after: Option<Binary>,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
+) -> Result<ListResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let start = start_after.as_ref().map(|h| Bound::exclusive(h.as_slice()));
+
+    // Read one extra entry to learn whether another page exists
+    let mut page = range_anchors(deps.storage, parse_anchor_type(anchor_type)?, start, order.map_or(Order::Ascending, Order::from))
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let next_start_after = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|(hash, _)| Binary::from(hash.as_slice()).to_base64())
+    } else {
+        None
+    };
+
+    Ok(ListResponse { anchors: page.into_iter().map(|(_, entry)| entry).collect(), next_start_after })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn list_by_tag(
+    deps: Deps,
+    tag: String,
+    anchor_type: Option<String>,
+    start_after: Option<AnchorKey>,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
+) -> Result<AnchorListResponse, ContractError> {
+    list_indexed(deps, &TAG_INDEX, &tag, anchor_type, start_after, limit, order)
+}
+
+#[cfg(feature = "cosmwasm")]
+fn anchors_by_registrant(
+    deps: Deps,
+    registrant: String,
+    anchor_type: Option<String>,
+    start_after: Option<AnchorKey>,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
+) -> Result<AnchorListResponse, ContractError> {
+    list_indexed(deps, &REGISTRANT_INDEX, &registrant, anchor_type, start_after, limit, order)
+}
+
+/// Page through an index keyed by (owner, anchor_type, hash), such as
+/// `TAG_INDEX` or `REGISTRANT_INDEX`, optionally within one anchor type.
+#[cfg(feature = "cosmwasm")]
+fn list_indexed<'a>(
+    deps: Deps,
+    index: &Map<(&'a str, &'a str, &'a [u8]), ()>,
+    owner: &'a str,
+    anchor_type: Option<String>,
+    start_after: Option<AnchorKey>,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
+) -> Result<AnchorListResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let order = order.map_or(Order::Ascending, Order::from);
+    let keys: Vec<(String, Vec<u8>)> = match &anchor_type {
+        Some(anchor_type) => {
+            // Keyed by the canonical name, whichever alias was queried
+            let anchor_type = parse_anchor_type(anchor_type)?.as_str();
+            // A cursor from another type cannot fall inside this type's range, so
+            // reject it rather than silently restart from the first page
+            if let Some(cursor) = start_after.as_ref().filter(|k| key_type(&k.anchor_type).ok() != Some(anchor_type)) {
+                return Err(ContractError::CursorTypeMismatch { cursor: cursor.anchor_type.clone(), anchor_type: anchor_type.to_string() });
+            }
+            let start = start_after.as_ref().map(|k| Bound::exclusive(k.hash.as_slice()));
+            let (min, max) = page_bounds(start, order);
+            index
+                .prefix((owner, anchor_type))
+                .keys(deps.storage, min, max, order)
+                .take(limit)
+                .map(|hash| Ok((anchor_type.to_string(), hash?)))
+                .collect::<StdResult<_>>()?
+        }
+        None => {
+            let start = match &start_after {
+                Some(k) => Some(Bound::exclusive((key_type(&k.anchor_type)?, k.hash.as_slice()))),
+                None => None,
+            };
+            let (min, max) = page_bounds(start, order);
+            index
+                .sub_prefix(owner)
+                .keys(deps.storage, min, max, order)
+                .take(limit)
+                .collect::<StdResult<_>>()?
+        }
+    };
+
+    let anchors = keys
+        .into_iter()
+        .map(|(anchor_type, hash)| load_indexed(deps.storage, &anchor_type, &hash))
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    Ok(AnchorListResponse { anchors })
+}
+
+/// A claim's stored payloads in sequence order.
+#[cfg(feature = "cosmwasm")]
+fn claim_score_payloads(
+    deps: Deps,
+    claim_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<ClaimPayloadsResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let payloads = CLAIM_PAYLOADS
+        .prefix(claim_id)
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(sequence, payload)| ClaimPayloadRecord { sequence, payload }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ClaimPayloadsResponse { payloads })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn claim_score_history(
+    deps: Deps,
+    claim_id: u64,
+    start_after: Option<ClaimCursor>,
+    limit: Option<u32>,
+) -> Result<AnchorListResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let start = start_after.as_ref().map(|c| Bound::exclusive((c.registered_at, c.hash.as_slice())));
+
+    let anchors = CLAIM_INDEX
+        .sub_prefix(claim_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|key| {
+            let (_, hash) = key?;
+            load_anchor(deps.storage, AnchorType::ClaimScore, &hash)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AnchorListResponse { anchors })
+}
+
+/// Payloads indexed under an equation name, ordered by payload hash.
+#[cfg(feature = "cosmwasm")]
+fn proofs_by_equation(
+    deps: Deps,
+    equation_name: String,
+    start_after: Option<Binary>,
+    limit: Option<u32>,
+) -> Result<EquationProofsResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let start = start_after.as_ref().map(|hash| Bound::exclusive(hash.as_slice()));
+    let proofs = PROOFS_BY_EQUATION
+        .prefix(equation_name.as_str())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, payload)| payload))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(EquationProofsResponse { proofs })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn dependents(
+    deps: Deps,
+    root_hash: Binary,
+    start_after: Option<Binary>,
+    limit: Option<u32>,
+) -> Result<AnchorListResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let root_hex = hex::encode(root_hash.as_slice());
+    let start = start_after.as_ref().map(|hash| Bound::exclusive(hash.as_slice()));
+
+    let anchors = DEPENDENTS
+        .prefix(root_hex.as_str())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|hash| load_anchor(deps.storage, AnchorType::EquationProof, &hash?))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AnchorListResponse { anchors })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn list_by_time_range(
+    deps: Deps,
+    start: u64,
+    end: u64,
+    anchor_type: Option<String>,
+    start_after: Option<TimeCursor>,
+    limit: Option<u32>,
+) -> Result<AnchorListResponse, ContractError> {
+    let anchor_type = anchor_type.as_deref().map(parse_anchor_type).transpose()?;
+    let limit = clamp_limit(limit)?;
+    // Time 0 marks untimed entries, which are never indexed
+    let lower = match &start_after {
+        Some(c) => Bound::exclusive((c.registered_at_time, c.hash.as_slice(), key_type(&c.anchor_type)?)),
+        None => height_bound(start.max(1)),
+    };
+    let upper = end.checked_add(1).map(height_bound);
+
+    let mut anchors = Vec::new();
+    for key in TIME_INDEX.keys(deps.storage, Some(lower), upper, Order::Ascending) {
+        if anchors.len() == limit {
+            break;
+        }
+        let (_, hash, key_type) = key?;
+        if anchor_type.is_none_or(|t| t.as_str() == key_type) {
+            anchors.push(load_indexed(deps.storage, &key_type, &hash)?);
+        }
+    }
+    Ok(AnchorListResponse { anchors })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn list_modified_since(
+    deps: Deps,
+    height: u64,
+    start_after: Option<ModifiedCursor>,
+    limit: Option<u32>,
+) -> Result<ModifiedSinceResponse, ContractError> {
+    let Some(first) = height.checked_add(1) else {
+        return Ok(ModifiedSinceResponse { anchors: vec![] });
+    };
+    let limit = clamp_limit(limit)?;
+    let lower = match &start_after {
+        Some(c) => Bound::exclusive((c.last_modified_height, c.hash.as_slice(), key_type(&c.anchor_type)?)),
+        None => height_bound(first),
+    };
+
+    let anchors = MODIFIED_INDEX
+        .keys(deps.storage, Some(lower), None, Order::Ascending)
+        .take(limit)
+        .map(|key| {
+            let (last_modified_height, hash, anchor_type) = key?;
+            let mut change_kinds: Vec<String> = vec![];
+            for log in CHANGE_LOG.prefix((&hash, &anchor_type)).range(
+                deps.storage,
+                Some(Bound::exclusive(height)),
+                None,
+                Order::Ascending,
+            ) {
+                for kind in log?.1 {
+                    if !change_kinds.contains(&kind) {
+                        change_kinds.push(kind);
+                    }
+                }
+            }
+            let parsed = parse_anchor_type(&anchor_type)?;
+            let entry = may_load_anchor(deps.storage, parsed, &hash)?;
+            Ok(ModifiedAnchor { anchor_type: parsed, hash_hex: hex::encode(&hash), last_modified_height, entry, change_kinds })
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    Ok(ModifiedSinceResponse { anchors })
+}
+
+/// A chunk of `limit` anchors merged from the three per-type maps in
+/// (hash, anchor type) order, resuming after `start_after`.
+#[cfg(feature = "cosmwasm")]
+fn export_state(
+    deps: Deps,
+    start_after: Option<ExportCursor>,
+    limit: Option<u32>,
+) -> Result<StateExport, ContractError> {
+    let limit = clamp_limit(limit)?;
+
+    // The first `limit + 1` of each map contain the first `limit + 1` overall
+    let mut candidates = Vec::with_capacity(3 * (limit + 1));
+    for anchor_type in AnchorType::ALL {
+        let lower = start_after.as_ref().map(|cursor| {
+            if anchor_type <= cursor.anchor_type {
+                Bound::exclusive(cursor.hash.as_slice())
+            } else {
+                Bound::inclusive(cursor.hash.as_slice())
+            }
+        });
+        for item in range_anchors(deps.storage, anchor_type, lower, Order::Ascending).take(limit + 1) {
+            let (hash, entry) = item?;
+            candidates.push((hash, anchor_type, entry));
+        }
+    }
+    candidates.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+    let more = candidates.len() > limit;
+    candidates.truncate(limit);
+
+    let next_start_after = match candidates.last() {
+        Some((hash, anchor_type, _)) if more => {
+            Some(ExportCursor { hash: Binary::from(hash.as_slice()), anchor_type: *anchor_type })
+        }
+        _ => None,
+    };
+    let config = match start_after {
+        None => Some(CONFIG.load(deps.storage)?),
+        Some(_) => None,
+    };
+    let mut chunk = StateExport {
+        version: EXPORT_FORMAT_VERSION,
+        start_after,
+        config,
+        anchors: candidates.into_iter().map(|(_, _, entry)| entry).collect(),
+        next_start_after,
+        chunk_hash: String::new(),
+    };
+    chunk.chunk_hash = chunk.compute_chunk_hash()?;
+    Ok(chunk)
+}
+
+/// Anchors registered at or after `height` in `HEIGHT_INDEX` order. A page
+/// never ends between two types sharing a (height, hash), so it may run up to
+/// two entries past `limit`; the cursor then resumes after all of them. New
+/// registrations land at the current height, after any cursor already handed
+/// out, so repeated calls neither skip nor repeat entries.
+#[cfg(feature = "cosmwasm")]
+fn anchors_since(
+    deps: Deps,
+    height: u64,
+    start_after: Option<(u64, Binary)>,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
+) -> Result<AnchorsSinceResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let order = order.map_or(Order::Ascending, Order::from);
+    let (lower, upper) = match (&start_after, order) {
+        // Past every anchor type under the cursor's (height, hash)
+        (Some((after, hash)), Order::Ascending) => (Bound::exclusive((*after, hash.as_slice(), "\u{10FFFF}")), None),
+        // Before every anchor type under the cursor's (height, hash)
+        (Some((after, hash)), Order::Descending) => (height_bound(height), Some(Bound::exclusive((*after, hash.as_slice(), "")))),
+        (None, _) => (height_bound(height), None),
+    };
+
+    let mut anchors: Vec<AnchorEntry> = Vec::new();
+    let mut last: Option<(u64, Vec<u8>)> = None;
+    let mut more = false;
+    for key in HEIGHT_INDEX.keys(deps.storage, Some(lower), upper, order) {
+        let (key_height, hash, anchor_type) = key?;
+        if anchors.len() >= limit && last.as_ref() != Some(&(key_height, hash.clone())) {
+            more = true;
+            break;
+        }
+        anchors.push(load_indexed(deps.storage, &anchor_type, &hash)?);
+        last = Some((key_height, hash));
+    }
+
+    let next_start_after = last.filter(|_| more).map(|(height, hash)| (height, Binary::from(hash)));
+    Ok(AnchorsSinceResponse { anchors, next_start_after })
+}
+
+#[cfg(feature = "cosmwasm")]
+fn anchors_at_height(
+    deps: Deps,
+    height: u64,
+    start_after: Option<AnchorKey>,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
+) -> Result<AnchorListResponse, ContractError> {
+    let limit = clamp_limit(limit)?;
+    let order = order.map_or(Order::Ascending, Order::from);
+    let start = match &start_after {
+        Some(k) => Some(Bound::exclusive((k.hash.as_slice(), key_type(&k.anchor_type)?))),
+        None => None,
+    };
+    let (min, max) = page_bounds(start, order);
+
+    let anchors = HEIGHT_INDEX
+        .sub_prefix(height)
+        .keys(deps.storage, min, max, order)
+        .take(limit)
+        .map(|key| {
+            let (hash, anchor_type) = key?;
+            load_indexed(deps.storage, &anchor_type, &hash)
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    Ok(AnchorListResponse { anchors })
+}
+
+/// Combine a payload's own verification with a registry lookup of `hash`,
+/// which is `None` when the payload does not yield a 32-byte hash.
+#[cfg(feature = "cosmwasm")]
+fn verify_payload(
+    deps: Deps,
+    env: &Env,
+    payload_valid: bool,
+    hash: Option<[u8; 32]>,
+    anchor_type: &str,
+) -> Result<PayloadVerifyResponse, ContractError> {
+    let entry = match hash {
+        Some(hash) => verify_hash(deps, env, Binary::from(hash), anchor_type)?.entry,
+        None => None,
+    };
+    Ok(PayloadVerifyResponse { payload_valid, registered: entry.is_some(), entry })
+}
+
+/// The hash of a query given as exactly one of raw bytes or hex.
+#[cfg(feature = "cosmwasm")]
+fn resolve_hash(hash: Option<Binary>, hash_hex: Option<String>) -> Result<Binary, ContractError> {
+    match (hash, hash_hex) {
+        (Some(hash), None) => Ok(hash),
+        (None, Some(hash_hex)) => match parse_digest_hex(&hash_hex) {
+            Some(bytes) => Ok(Binary::from(bytes)),
+            None => Err(ContractError::InvalidHashHex { value: hash_hex }),
+        },
+        _ => Err(ContractError::ExactlyOneHash {}),
+    }
+}
+
+#[cfg(feature = "cosmwasm")]
+fn verify_hash(deps: Deps, env: &Env, hash: Binary, anchor_type: &str) -> Result<VerifyResponse, ContractError> {
+    let parsed = parse_anchor_type(anchor_type)?;
+    // The full entry is only loaded for anchors the presence map knows about
+    let entry = if is_registered(deps.storage, parsed, &hash)? {
+        Some(load_anchor(deps.storage, parsed, hash.as_slice())?)
+    } else {
+        None
+    };
+    let attestations = match entry {
+        Some(_) => ATTESTATIONS.may_load(deps.storage, (parsed.as_str(), hash.as_slice()))?.map_or(0, |a| a.len() as u32),
+        None => 0,
+    };
+    Ok(VerifyResponse {
+        exists: entry.is_some(),
+        hash_hex: hex::encode(hash.as_slice()),
+        index: entry.as_ref().and_then(|e| e.index),
+        attestations,
+        revoked: entry.as_ref().is_some_and(|e| e.revoked_at.is_some()),
+        superseded_by: entry.as_ref().and_then(|e| e.superseded_by.clone()),
+        expires_at: entry.as_ref().and_then(|e| e.expires_at),
+        expired: entry.as_ref().is_some_and(|e| e.is_expired(env.block.height)),
+        payload_version: entry.as_ref().map(|e| e.payload_version),
+        entry,
+        blocked: BLOCKLIST.has(deps.storage, hash.as_slice()),
+    })
+}
+
+// ── Pure Functions (no chain dependency) ────────────────────────────────────
+
+/// Parse a hash written as exactly 64 lowercase hex characters.
+pub fn parse_hash_hex(value: &str) -> Option<[u8; 32]> {
+    if value.len() != 64 || !value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(value, &mut hash).ok()?;
+    Some(hash)
+}
+
+/// Parse a digest written as lowercase hex of one of the
+/// `SUPPORTED_HASH_LENGTHS`.
+pub fn parse_digest_hex(value: &str) -> Option<Vec<u8>> {
+    let supported = SUPPORTED_HASH_LENGTHS.iter().any(|len| value.len() == 2 * *len as usize);
+    if !supported || !value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    hex::decode(value).ok()
+}
+
+/// Validate that a hash is exactly 32 bytes.
+pub fn validate_hash(hash: &[u8]) -> bool {
+    hash.len() == 32
+}
+
+/// Validate that a hash has one of the `allowed` byte lengths.
+pub fn validate_hash_len(hash: &[u8], allowed: &[usize]) -> bool {
+    allowed.contains(&hash.len())
+}
+
+/// Whether `hash` is a sentinel rather than a real digest: all zero bytes,
+/// which means an uninitialized buffer, or SHA-256 of the empty input, which
+/// means nothing was hashed.
+pub fn is_degenerate_hash(hash: &[u8]) -> bool {
+    hash.iter().all(|b| *b == 0) || hash == compute_sha256(&[])
+}
+
+/// Compute SHA-256 of arbitrary data (deterministic).
+pub fn compute_sha256(data: &[u8]) -> [u8; 32] {
+    use sha2::{Sha256, Digest};
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    let result = hasher.finalize();
+    let mut output = [0u8; 32];
+    output.copy_from_slice(&result);
+    output
+}
+
+/// Compute Keccak-256 of arbitrary data, as Solidity's `keccak256` does.
+#[cfg(feature = "keccak")]
+pub fn compute_keccak256(data: &[u8]) -> [u8; 32] {
+    use sha3::{Digest, Keccak256};
+    Keccak256::digest(data).into()
+}
+
+/// Format a deterministic anchor payload for off-chain verification.
+pub fn format_anchor_payload(
+    hash: &[u8; 32],
+    anchor_type: &str,
+    timestamp: u64,
+) -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(anchor_type.as_bytes());
+    payload.push(b':');
+    payload.extend_from_slice(hex::encode(hash).as_bytes());
+    payload.push(b':');
+    payload.extend_from_slice(&timestamp.to_be_bytes());
+    payload
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::from_json;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::OwnedDeps;
+    use crate::limits::MAX_FIELD_LEN;
+    use crate::merkle_anchor::TableHash;
+
+    type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+    fn setup() -> MockDeps {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            InstantiateMsg { admin: None, name: None, limits: None, restricted: false, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None },
+        )
+        .unwrap();
+        deps
+    }
+
+    fn tags(values: &[&str]) -> Option<Vec<String>> {
+        Some(values.iter().map(|t| t.to_string()).collect())
+    }
+
+    fn list_by_tag(deps: &MockDeps, tag: &str, start_after: Option<AnchorKey>) -> Vec<AnchorEntry> {
+        let msg = QueryMsg::ListByTag { tag: tag.into(), start_after, limit: None, order: None };
+        let res: AnchorListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.anchors
+    }
+
+    #[test]
+    fn test_validate_hash_valid() {
+        let hash = [0u8; 32];
+        assert!(validate_hash(&hash));
+    }
+
+    #[test]
+    fn test_validate_hash_invalid_length() {
+        let hash = [0u8; 16];
+        assert!(!validate_hash(&hash));
+    }
+
+    #[test]
+    fn test_validate_hash_len() {
+        assert!(validate_hash_len(&[0u8; 20], &[20, 32]));
+        assert!(validate_hash_len(&[0u8; 32], &[20, 32]));
+        assert!(!validate_hash_len(&[0u8; 64], &[20, 32]));
+        assert!(!validate_hash_len(&[], &[]));
+    }
+
+    #[test]
+    fn test_compute_sha256_deterministic() {
+        let data = b"Project Anchor - Gravity Event";
+        let h1 = compute_sha256(data);
+        let h2 = compute_sha256(data);
+        assert_eq!(h1, h2);
+    }
+
+    #[test]
+    fn test_compute_sha256_different_inputs() {
+        let h1 = compute_sha256(b"input_a");
+        let h2 = compute_sha256(b"input_b");
+        assert_ne!(h1, h2);
+    }
+
+    #[test]
+    fn test_format_anchor_payload_deterministic() {
+        let hash = compute_sha256(b"test_root");
+        let p1 = format_anchor_payload(&hash, "root", 12345);
+        let p2 = format_anchor_payload(&hash, "root", 12345);
+        assert_eq!(p1, p2);
+    }
+
+    #[test]
+    fn test_format_anchor_payload_structure() {
+        let hash = [0xABu8; 32];
+        let payload = format_anchor_payload(&hash, "root", 1);
+        let payload_str = String::from_utf8_lossy(&payload);
+        assert!(payload_str.starts_with("root:"));
+        assert!(payload_str.contains(&hex::encode([0xABu8; 32])));
+    }
+
+    #[test]
+    fn test_register_with_multiple_tags() {
+        let mut deps = setup();
+        let hash = Binary::from([1u8; 32]);
+        let msg = ExecuteMsg::RegisterRoot { hash: hash.clone(), tags: tags(&["run:2024-11", "env:prod"]), memo: None, ttl_blocks: None, payload_version: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "tags" && a.value == "run:2024-11,env:prod"));
+
+        let entry = ANCHORS.load(&deps.storage, ("root", hash.as_slice())).unwrap();
+        assert_eq!(entry.tags, vec!["run:2024-11", "env:prod"]);
+        assert_eq!(list_by_tag(&deps, "run:2024-11", None).len(), 1);
+        assert_eq!(list_by_tag(&deps, "env:prod", None).len(), 1);
+        assert!(list_by_tag(&deps, "env:staging", None).is_empty());
+    }
+
+    #[test]
+    fn test_list_by_tag_across_anchor_types() {
+        let mut deps = setup();
+        let info = mock_info("bot", &[]);
+        let msgs = vec![
+            ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: tags(&["env:prod"]), memo: None, ttl_blocks: None, payload_version: None },
+            ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: tags(&["env:prod"]), memo: None, ttl_blocks: None, payload_version: None },
+            ExecuteMsg::RegisterEquationProof { hash: Binary::from([3u8; 32]), tags: tags(&["env:prod", "x"]), memo: None, ttl_blocks: None, payload_version: None },
+            ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None },
+        ];
+        for msg in msgs {
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        let all = list_by_tag(&deps, "env:prod", None);
+        let types: Vec<_> = all.iter().map(|e| e.anchor_type.as_str()).collect();
+        assert_eq!(types, vec!["root", "claim_score", "equation_proof"]);
+
+        let cursor = AnchorKey { anchor_type: "root".into(), hash: Binary::from([1u8; 32]) };
+        let rest = list_by_tag(&deps, "env:prod", Some(cursor));
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest[0].anchor_type, AnchorType::ClaimScore);
+    }
+
+    #[test]
+    fn test_get_anchors_by_tag_filters_type() {
+        let mut deps = setup();
+        let info = mock_info("bot", &[]);
+        let msgs = vec![
+            ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: tags(&["phase2"]), memo: None, ttl_blocks: None, payload_version: None },
+            ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: tags(&["phase2", "audit-2024q3"]), memo: None, ttl_blocks: None, payload_version: None },
+            ExecuteMsg::RegisterClaimScore { hash: Binary::from([3u8; 32]), tags: tags(&["phase2"]), memo: None, ttl_blocks: None, payload_version: None },
+            ExecuteMsg::RegisterEquationProof { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None },
+        ];
+        for msg in msgs {
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        let by_tag = |tag: &str, anchor_type: Option<&str>, start_after: Option<AnchorKey>| -> Vec<(AnchorType, String)> {
+            let msg = QueryMsg::GetAnchorsByTag { tag: tag.into(), anchor_type: anchor_type.map(Into::into), start_after, limit: None, order: None };
+            let res: AnchorListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.anchors.into_iter().map(|e| (e.anchor_type, e.hash_hex)).collect()
+        };
+
+        let roots = by_tag("phase2", Some("root"), None);
+        assert_eq!(roots, vec![(AnchorType::MerkleRoot, hex::encode([1u8; 32])), (AnchorType::MerkleRoot, hex::encode([2u8; 32]))]);
+        let cursor = AnchorKey { anchor_type: "root".into(), hash: Binary::from([1u8; 32]) };
+        assert_eq!(by_tag("phase2", Some("root"), Some(cursor)).len(), 1);
+        assert_eq!(by_tag("phase2", None, None).len(), 3);
+        // The untagged equation proof shares a hash with a tagged root
+        assert!(by_tag("audit-2024q3", Some("equation_proof"), None).is_empty());
+        assert!(by_tag("unused", None, None).is_empty());
+
+        let msg = QueryMsg::GetAnchorsByTag { tag: "phase2".into(), anchor_type: Some("leaf".into()), start_after: None, limit: None, order: None };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+    }
+
+    #[test]
+    fn test_register_rejects_invalid_tags() {
+        let mut deps = setup();
+        let info = mock_info("bot", &[]);
+        let invalid = [
+            tags(&["a", "b", "c", "d", "e", "f"]),
+            tags(&[""]),
+            tags(&[&"t".repeat(crate::limits::MAX_TAG_LEN as usize + 1)]),
+            tags(&["Env:Prod"]),
+            tags(&["has space"]),
+            tags(&["dup", "dup"]),
+        ];
+        for t in invalid {
+            let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([9u8; 32]), tags: t, memo: None, ttl_blocks: None, payload_version: None };
+            assert!(execute(deps.as_mut(), mock_env(), info.clone(), msg).is_err());
+        }
+        assert!(!ANCHORS.has(&deps.storage, ("root", &[9u8; 32])));
+    }
+
+    fn get_oldest(deps: &MockDeps, anchor_type: &str) -> Option<AnchorEntry> {
+        let msg = QueryMsg::GetOldest { anchor_type: anchor_type.into() };
+        let res: AnchorResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.entry
+    }
+
+    #[test]
+    fn test_get_oldest_by_type() {
+        let mut deps = setup();
+        let info = mock_info("bot", &[]);
+        let registrations = [
+            (300, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None }),
+            (100, ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None }),
+            (200, ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None }),
+            (250, ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None }),
+        ];
+        for (height, msg) in registrations {
+            let mut env = mock_env();
+            env.block.height = height;
+            execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+        }
+
+        let oldest_root = get_oldest(&deps, "root").unwrap();
+        assert_eq!(oldest_root.registered_at, 200);
+        assert_eq!(oldest_root.hash_hex, hex::encode([3u8; 32]));
+        assert_eq!(get_oldest(&deps, "claim_score").unwrap().registered_at, 100);
+        assert!(get_oldest(&deps, "equation_proof").is_none());
+    }
+
+    #[test]
+    fn test_get_oldest_skips_expired_entries() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: Some(10), payload_version: None });
+        register_at(&mut deps, 200, ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        // Expired at the query height but not yet pruned
+        assert!(ANCHORS.has(&deps.storage, ("root", &[1u8; 32])));
+        assert!(mock_env().block.height >= 110);
+        assert_eq!(get_oldest(&deps, "root").unwrap().hash_hex, hex::encode([2u8; 32]));
+
+        let mut env = mock_env();
+        env.block.height = 105;
+        let msg = QueryMsg::GetOldest { anchor_type: "root".into() };
+        let res: AnchorResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.entry.unwrap().hash_hex, hex::encode([1u8; 32]));
+    }
+
+    #[test]
+    fn test_get_oldest_scans_only_its_type() {
+        let mut deps = setup();
+        register_at(&mut deps, 5_000, ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        // More older roots than one scan may visit
+        for height in 0..MAX_OLDEST_SCAN as u64 {
+            HEIGHT_INDEX.save(deps.as_mut().storage, (height, &height.to_be_bytes(), "root"), &()).unwrap();
+            TYPE_HEIGHT_INDEX.save(deps.as_mut().storage, ("root", height, &height.to_be_bytes()), &()).unwrap();
+        }
+        assert_eq!(get_oldest(&deps, "claim_score").unwrap().hash_hex, hex::encode([2u8; 32]));
+    }
+
+    #[test]
+    fn test_get_oldest_fails_rather_than_miss_a_live_anchor() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let revoke = ExecuteMsg::RevokeAnchor { hash: Binary::from([1u8; 32]), anchor_type: "root".into(), reason: "bad".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), revoke).unwrap();
+        register_at(&mut deps, 300, ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        // Fill the rest of the scan budget with older tombstones
+        let tombstone = ANCHORS.load(&deps.storage, ("root", &[1u8; 32])).unwrap();
+        for i in 1..MAX_OLDEST_SCAN as u64 {
+            let hash = i.to_be_bytes();
+            ANCHORS.save(deps.as_mut().storage, ("root", &hash), &tombstone).unwrap();
+            TYPE_HEIGHT_INDEX.save(deps.as_mut().storage, ("root", 50, &hash), &()).unwrap();
+        }
+        let msg = QueryMsg::GetOldest { anchor_type: "root".into() };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::OldestScanTruncated { anchor_type: "root".into(), scanned: MAX_OLDEST_SCAN });
+
+        TYPE_HEIGHT_INDEX.remove(deps.as_mut().storage, ("root", 50, &1u64.to_be_bytes()));
+        assert_eq!(get_oldest(&deps, "root").unwrap().hash_hex, hex::encode([2u8; 32]));
+    }
+
+    #[test]
+    fn test_get_oldest_waits_for_type_index_backfill() {
+        let mut deps = setup();
+        register_at(&mut deps, 300, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 200, ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 100, ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        // A registry from before the per-type index
+        TYPE_HEIGHT_INDEX.clear(deps.as_mut().storage);
+        TYPE_INDEX_COMPLETE.remove(deps.as_mut().storage);
+
+        let msg = QueryMsg::GetOldest { anchor_type: "root".into() };
+        assert_eq!(query(deps.as_ref(), mock_env(), msg).unwrap_err(), ContractError::TypeIndexBackfillPending {});
+        let msg = MigrateMsg::BackfillTypeIndex { limit: Some(2) };
+        let res = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::Attribute::new("backfill_complete", "false")));
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::Attribute::new("backfill_complete", "true")));
+        assert_eq!(get_oldest(&deps, "root").unwrap().registered_at, 200);
+        assert_eq!(get_oldest(&deps, "claim_score").unwrap().registered_at, 100);
+    }
+
+    #[test]
+    fn test_get_oldest_empty_and_unknown_type() {
+        let deps = setup();
+        assert!(get_oldest(&deps, "root").is_none());
+        let msg = QueryMsg::GetOldest { anchor_type: "bogus".into() };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+    }
+
+    fn block(deps: &mut MockDeps, sender: &str, hash: [u8; 32]) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::BlockHash { hash: Binary::from(hash), reason: "retracted dataset".into() };
+        execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+    }
+
+    fn register_root(deps: &mut MockDeps, hash: [u8; 32]) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from(hash), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+    }
+
+    fn verify_root(deps: &MockDeps, hash: [u8; 32]) -> VerifyResponse {
+        let msg = QueryMsg::VerifyRoot { hash: Some(Binary::from(hash)), hash_hex: None };
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_register_after_block_fails() {
+        let mut deps = setup();
+        block(&mut deps, "admin", [7u8; 32]).unwrap();
+        let err = register_root(&mut deps, [7u8; 32]).unwrap_err();
+        assert!(err.to_string().contains("retracted dataset"));
+
+        let res: BlockedResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::IsBlocked { hash: Binary::from([7u8; 32]) }).unwrap(),
+        )
+        .unwrap();
+        assert!(res.blocked);
+        assert_eq!(res.reason.unwrap().blocked_by, "admin");
+    }
+
+    #[test]
+    fn test_block_after_register_flags_entry() {
+        let mut deps = setup();
+        register_root(&mut deps, [7u8; 32]).unwrap();
+        block(&mut deps, "admin", [7u8; 32]).unwrap();
+
+        let res = verify_root(&deps, [7u8; 32]);
+        assert!(res.exists);
+        assert!(res.blocked);
+    }
+
+    #[test]
+    fn test_unblock_restores_registrability() {
+        let mut deps = setup();
+        block(&mut deps, "admin", [7u8; 32]).unwrap();
+        let unblock = ExecuteMsg::UnblockHash { hash: Binary::from([7u8; 32]) };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), unblock.clone()).unwrap();
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), unblock).is_err());
+
+        register_root(&mut deps, [7u8; 32]).unwrap();
+        let res = verify_root(&deps, [7u8; 32]);
+        assert!(res.exists);
+        assert!(!res.blocked);
+    }
+
+    #[test]
+    fn test_blocklist_admin_only_and_listing() {
+        let mut deps = setup();
+        assert!(block(&mut deps, "mallory", [1u8; 32]).is_err());
+        for b in [3u8, 1, 2] {
+            block(&mut deps, "admin", [b; 32]).unwrap();
+        }
+
+        let msg = QueryMsg::ListBlocked { start_after: None, limit: Some(2) };
+        let page: BlocklistResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.entries[0].hash_hex, hex::encode([1u8; 32]));
+
+        let msg = QueryMsg::ListBlocked { start_after: Some(Binary::from([2u8; 32])), limit: None };
+        let page: BlocklistResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].hash_hex, hex::encode([3u8; 32]));
+    }
+
+    #[test]
+    fn test_block_reason_length_enforced() {
+        let mut deps = setup();
+        let max = crate::limits::MAX_REASON_LEN as usize;
+        let at_limit = ExecuteMsg::BlockHash { hash: Binary::from([1u8; 32]), reason: "r".repeat(max) };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), at_limit).unwrap();
+
+        let over = ExecuteMsg::BlockHash { hash: Binary::from([2u8; 32]), reason: "r".repeat(max + 1) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), over).unwrap_err();
+        assert!(err.to_string().contains("reason is 257 bytes, exceeding the 256-byte limit"));
+    }
+
+    #[test]
+    fn test_update_limits_applies_to_tags() {
+        let mut deps = setup();
+        let limits = Limits { max_tags: 1, max_tag_len: 4, ..Limits::default() };
+        let msg = ExecuteMsg::UpdateLimits { limits: limits.clone() };
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg.clone()).is_err());
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let config: ConfigResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.limits, limits);
+
+        let ok = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: tags(&["prod"]), memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), ok).unwrap();
+        let long = ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: tags(&["prod1"]), memo: None, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), long).unwrap_err();
+        assert!(err.to_string().contains("tag is 5 bytes, exceeding the 4-byte limit"));
+    }
+
+    #[test]
+    fn test_instantiate_rejects_invalid_limits() {
+        let mut deps = mock_dependencies();
+        let limits = Limits { max_tag_len: 0, ..Limits::default() };
+        let msg = InstantiateMsg { admin: None, name: None, limits: Some(limits), restricted: false, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        assert!(instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).is_err());
+    }
+
+    #[test]
+    fn test_config_without_limits_deserializes_defaults() {
+        let legacy = br#"{"admin":"admin","total_anchors":3}"#;
+        let config: Config = from_json(legacy).unwrap();
+        assert_eq!(config.limits, Limits::default());
+    }
+
+    fn receipt_for(name: &str) -> AnchorReceipt {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { admin: None, name: Some(name.into()), limits: None, restricted: false, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let res = register_root(&mut deps, [5u8; 32]).unwrap();
+        from_json(res.data.unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_receipt_registry_id_differs_by_name() {
+        let a = receipt_for("registry-a");
+        let b = receipt_for("registry-b");
+        assert_eq!(a.registry_id, hex::encode(compute_sha256(b"registry-a")));
+        assert_ne!(a.registry_id, b.registry_id);
+        assert_eq!(a.hash_hex, b.hash_hex);
+        assert_eq!(a.anchor_type, "root");
+        assert_eq!(a.registrant, "bot");
+    }
+
+    #[test]
+    fn test_registry_id_defaults_to_contract_address() {
+        let deps = setup();
+        let env = mock_env();
+        let config: ConfigResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.name, env.contract.address.to_string());
+        assert_eq!(config.registry_id, hex::encode(compute_sha256(env.contract.address.as_bytes())));
+    }
+
+    #[test]
+    fn test_multi_query_preserves_order_and_isolates_errors() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        block(&mut deps, "admin", [2u8; 32]).unwrap();
+
+        let msg = QueryMsg::Multi {
+            queries: vec![
+                QueryMsg::VerifyRoot { hash: Some(Binary::from([1u8; 32])), hash_hex: None },
+                QueryMsg::GetOldest { anchor_type: "bogus".into() },
+                QueryMsg::GetConfig {},
+                QueryMsg::ListBlocked { start_after: None, limit: None },
+                QueryMsg::Multi { queries: vec![] },
+            ],
+        };
+        let res: MultiResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.results.len(), 5);
+
+        let MultiResult::Ok(data) = &res.results[0] else { panic!("verify failed") };
+        assert!(from_json::<VerifyResponse>(data).unwrap().exists);
+        assert!(matches!(&res.results[1], MultiResult::Err(e) if e.contains("Unknown anchor type")));
+        let MultiResult::Ok(data) = &res.results[2] else { panic!("config failed") };
+        assert_eq!(from_json::<ConfigResponse>(data).unwrap().total_anchors, 1);
+        let MultiResult::Ok(data) = &res.results[3] else { panic!("list failed") };
+        assert_eq!(from_json::<BlocklistResponse>(data).unwrap().entries.len(), 1);
+        assert!(matches!(&res.results[4], MultiResult::Err(e) if e.contains("Nested")));
+    }
+
+    #[test]
+    fn test_multi_query_cap() {
+        let deps = setup();
+        let at_cap = QueryMsg::Multi { queries: vec![QueryMsg::GetConfig {}; MAX_MULTI_QUERIES] };
+        assert!(query(deps.as_ref(), mock_env(), at_cap).is_ok());
+        let over = QueryMsg::Multi { queries: vec![QueryMsg::GetConfig {}; MAX_MULTI_QUERIES + 1] };
+        assert!(query(deps.as_ref(), mock_env(), over).is_err());
+    }
+
+    #[test]
+    fn test_verify_inclusion_batch_mixed_proofs() {
+        use crate::merkle_anchor::hash_pair;
+        let mut deps = setup();
+        let leaves: Vec<[u8; 32]> = (0u8..4).map(|i| compute_sha256(&[i])).collect();
+        let n01 = hash_pair(&leaves[0], &leaves[1]);
+        let n23 = hash_pair(&leaves[2], &leaves[3]);
+        let root = hash_pair(&n01, &n23);
+        register_root(&mut deps, root).unwrap();
+
+        let b = |h: &[u8; 32]| Binary::from(h.to_vec());
+        let items = vec![
+            (b(&leaves[0]), vec![b(&leaves[1]), b(&n23)], vec![true, true]),
+            (b(&leaves[2]), vec![b(&leaves[3]), b(&n01)], vec![true, false]),
+            (b(&leaves[1]), vec![b(&leaves[0]), b(&n23)], vec![true, true]),
+            (b(&leaves[3]), vec![Binary::from(vec![1u8; 5]), b(&n01)], vec![false, false]),
+        ];
+        let msg = QueryMsg::VerifyInclusionBatch { root: b(&root), items: items.clone() };
+        let res: InclusionBatchResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.root_registered);
+        assert_eq!(res.results, vec![true, true, false, false]);
+
+        let unregistered = QueryMsg::VerifyInclusionBatch { root: b(&n01), items };
+        let res: InclusionBatchResponse =
+            from_json(query(deps.as_ref(), mock_env(), unregistered).unwrap()).unwrap();
+        assert!(!res.root_registered);
+        assert_eq!(res.results, vec![false; 4]);
+    }
+
+    fn register_at(deps: &mut MockDeps, height: u64, msg: ExecuteMsg) {
+        let mut env = mock_env();
+        env.block.height = height;
+        execute(deps.as_mut(), env, mock_info("bot", &[]), msg).unwrap();
+    }
+
+    fn histogram(deps: &MockDeps, height: u64, anchor_type: Option<&str>, bucket_blocks: u64, buckets: u32) -> Vec<HistogramBucket> {
+        let mut env = mock_env();
+        env.block.height = height;
+        let msg = QueryMsg::ActivityHistogram { anchor_type: anchor_type.map(Into::into), bucket_blocks, buckets };
+        let res: HistogramResponse = from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        res.buckets
+    }
+
+    #[test]
+    fn test_activity_histogram_counts_and_empty_leading_bucket() {
+        let mut deps = setup();
+        register_at(&mut deps, 111, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 115, ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 120, ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 125, ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 95, ExecuteMsg::RegisterRoot { hash: Binary::from([5u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        // Windows: [101,110] [111,120] [121,130]
+        let buckets = histogram(&deps, 130, None, 10, 3);
+        let ranges: Vec<_> = buckets.iter().map(|b| (b.start_height, b.end_height, b.count)).collect();
+        assert_eq!(ranges, vec![(101, 110, 0), (111, 120, 3), (121, 130, 1)]);
+
+        let roots = histogram(&deps, 130, Some("root"), 10, 3);
+        assert_eq!(roots.iter().map(|b| b.count).collect::<Vec<_>>(), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn test_activity_histogram_clips_at_genesis_and_enforces_caps() {
+        let mut deps = setup();
+        register_at(&mut deps, 3, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        // Windows: [-6,-3] [-2,1] [2,5], clipped at height 0
+        let buckets = histogram(&deps, 5, None, 4, 3);
+        let ranges: Vec<_> = buckets.iter().map(|b| (b.start_height, b.end_height, b.count)).collect();
+        assert_eq!(ranges, vec![(0, 0, 0), (0, 1, 0), (2, 5, 1)]);
+
+        let env = mock_env();
+        let too_many = QueryMsg::ActivityHistogram { anchor_type: None, bucket_blocks: 1, buckets: MAX_HISTOGRAM_BUCKETS + 1 };
+        assert!(query(deps.as_ref(), env.clone(), too_many).is_err());
+        let too_wide = QueryMsg::ActivityHistogram { anchor_type: None, bucket_blocks: MAX_HISTOGRAM_SPAN, buckets: 2 };
+        assert!(query(deps.as_ref(), env.clone(), too_wide).is_err());
+        let zero = QueryMsg::ActivityHistogram { anchor_type: None, bucket_blocks: 0, buckets: 2 };
+        assert!(query(deps.as_ref(), env, zero).is_err());
+    }
+
+    fn simulate(deps: &MockDeps, anchor_type: &str, hash: &[u8], sender: &str, tags: Option<Vec<String>>) -> SimulateResponse {
+        let msg = QueryMsg::SimulateRegister {
+            anchor_type: anchor_type.into(),
+            hash: Binary::from(hash),
+            sender: sender.into(),
+            tags,
+        };
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    /// Assert that simulate and execute agree on a registration's outcome.
+    fn assert_parity(deps: &mut MockDeps, anchor_type: &str, hash: &[u8], tags: Option<Vec<String>>) {
+        let sim = simulate(deps, anchor_type, hash, "bot", tags.clone());
+        let hash = Binary::from(hash);
+        let msg = match anchor_type {
+            "root" => ExecuteMsg::RegisterRoot { hash, tags, memo: None, ttl_blocks: None, payload_version: None },
+            "claim_score" => ExecuteMsg::RegisterClaimScore { hash, tags, memo: None, ttl_blocks: None, payload_version: None },
+            _ => ExecuteMsg::RegisterEquationProof { hash, tags, memo: None, ttl_blocks: None, payload_version: None },
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg);
+        assert_eq!(sim.would_succeed, res.is_ok());
+        assert_eq!(sim.failure_reason, res.err().map(|e| e.to_string()));
+    }
+
+    #[test]
+    fn test_simulate_register_parity() {
+        let mut deps = setup();
+        block(&mut deps, "admin", [6u8; 32]).unwrap();
+
+        // blocklisted, bad length, invalid tags, then a success
+        assert_parity(&mut deps, "root", &[6u8; 32], None);
+        assert_parity(&mut deps, "claim_score", &[1u8; 31], None);
+        assert_parity(&mut deps, "equation_proof", &[1u8; 32], tags(&["BAD TAG"]));
+        assert_parity(&mut deps, "root", &[1u8; 32], tags(&["ok"]));
+
+        let sim = simulate(&deps, "root", &[6u8; 32], "bot", None);
+        assert!(!sim.would_succeed);
+        assert!(sim.failure_reason.unwrap().contains("blocklisted"));
+    }
+
+    #[test]
+    fn test_simulate_register_parity_when_several_checks_fail() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        let admin = |deps: &mut MockDeps, msg: ExecuteMsg| execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // Over quota and a duplicate: the duplicate is reported first
+        admin(&mut deps, ExecuteMsg::SetQuota { registrant: "bot".into(), max_anchors: Some(1) });
+        assert_parity(&mut deps, "root", &[1u8; 32], None);
+        // Over quota, rate limited and a bad tag: the tag is reported first
+        admin(&mut deps, ExecuteMsg::SetRateLimit { max_anchors_per_block_per_registrant: Some(1) });
+        assert_parity(&mut deps, "root", &[2u8; 32], tags(&["BAD TAG"]));
+        // Over quota and rate limited: the quota is reported first
+        assert_parity(&mut deps, "root", &[2u8; 32], None);
+        // Paused with a bad hash length: paused wins
+        admin(&mut deps, ExecuteMsg::Pause {});
+        assert_parity(&mut deps, "root", &[2u8; 31], None);
+        assert_eq!(simulate(&deps, "root", &[2u8; 31], "bot", None).failure_reason, Some(ContractError::Paused {}.to_string()));
+    }
+
+    #[test]
+    fn test_simulate_register_unknown_type_and_success() {
+        let deps = setup();
+        let sim = simulate(&deps, "dataset", &[1u8; 32], "bot", None);
+        assert!(sim.failure_reason.unwrap().contains("Unknown anchor type"));
+        let sim = simulate(&deps, "root", &[1u8; 32], "bot", None);
+        assert_eq!(sim, SimulateResponse { would_succeed: true, failure_reason: None });
+    }
+
+    fn register_payload(deps: &mut MockDeps, payload: ClaimScorePayload) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RegisterClaimScorePayload { payload, tags: None, memo: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+    }
+
+    #[test]
+    fn test_register_claim_score_payload_records_stability() {
+        let mut deps = setup();
+        let payload = ClaimScorePayload::new(7, 0.9, 0.1, 0.8, 4, 0, "stable".into());
+        register_payload(&mut deps, payload.clone()).unwrap();
+
+        let msg = QueryMsg::GetAnchor { hash: Some(Binary::from(payload.hash_bytes())), hash_hex: None, anchor_type: AnchorType::ClaimScore };
+        let res: AnchorResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entry.unwrap().stability_class.as_deref(), Some("stable"));
+
+        let mut tampered = payload;
+        tampered.stability_class = "volatile".into();
+        let err = register_payload(&mut deps, tampered).unwrap_err();
+        assert!(err.to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn test_stability_histogram_mixed_classes() {
+        let mut deps = setup();
+        for (id, class) in [(1, "stable"), (2, "volatile"), (3, "stable"), (4, "converging")] {
+            let payload = ClaimScorePayload::new(id, 0.5, 0.5, 0.5, 1, 1, class.into());
+            register_payload(&mut deps, payload).unwrap();
+        }
+        let msg = ExecuteMsg::RegisterClaimScore { hash: Binary::from([9u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        register_root(&mut deps, [8u8; 32]).unwrap();
+
+        let msg = QueryMsg::StabilityHistogram {};
+        let res: StabilityHistogramResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let count = |class: &str, count| StabilityCount { stability_class: class.into(), count };
+        assert_eq!(res.classes, vec![count("converging", 1), count("stable", 2), count("volatile", 1)]);
+        assert_eq!(res.unclassified, 1);
+        assert!(!res.truncated);
+    }
+
+    fn register_at_time(deps: &mut MockDeps, seconds: u64, msg: ExecuteMsg) -> AnchorReceipt {
+        let mut env = mock_env();
+        env.block.time = cosmwasm_std::Timestamp::from_seconds(seconds);
+        let res = execute(deps.as_mut(), env, mock_info("bot", &[]), msg).unwrap();
+        from_json(res.data.unwrap()).unwrap()
+    }
+
+    fn time_range(deps: &MockDeps, start: u64, end: u64, anchor_type: Option<&str>, start_after: Option<TimeCursor>, limit: Option<u32>) -> Vec<AnchorEntry> {
+        let msg = QueryMsg::ListByTimeRange { start, end, anchor_type: anchor_type.map(Into::into), start_after, limit };
+        let res: AnchorListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.anchors
+    }
+
+    #[test]
+    fn test_list_by_time_range() {
+        let mut deps = setup();
+        let receipt = register_at_time(&mut deps, 1_000, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        assert_eq!(receipt.block_time, 1_000);
+        register_at_time(&mut deps, 2_000, ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at_time(&mut deps, 3_000, ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at_time(&mut deps, 4_000, ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        let times = |entries: Vec<AnchorEntry>| entries.iter().map(|e| e.registered_at_time).collect::<Vec<_>>();
+        assert_eq!(times(time_range(&deps, 2_000, 3_000, None, None, None)), vec![2_000, 3_000]);
+        assert_eq!(times(time_range(&deps, 0, u64::MAX, Some("root"), None, None)), vec![1_000, 3_000, 4_000]);
+
+        let page = time_range(&deps, 0, u64::MAX, None, None, Some(2));
+        assert_eq!(times(page.clone()), vec![1_000, 2_000]);
+        let last = page.last().unwrap();
+        let cursor = TimeCursor {
+            registered_at_time: last.registered_at_time,
+            hash: Binary::from(hex::decode(&last.hash_hex).unwrap()),
+            anchor_type: last.anchor_type.to_string(),
+        };
+        assert_eq!(times(time_range(&deps, 0, u64::MAX, None, Some(cursor), Some(2))), vec![3_000, 4_000]);
+    }
+
+    #[test]
+    fn test_list_by_time_range_excludes_untimed_entries() {
+        let mut deps = setup();
+        register_at_time(&mut deps, 500, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        // An entry persisted before block times were recorded
+        let legacy = br#"{"hash_hex":"02","anchor_type":"root","registered_at":1,"registrant":"bot"}"#;
+        let legacy: AnchorEntry = from_json(legacy).unwrap();
+        assert_eq!(legacy.registered_at_time, 0);
+        ROOTS.save(deps.as_mut().storage, &[2u8; 32], &legacy).unwrap();
+        index_anchor(deps.as_mut().storage, &legacy, &[2u8; 32]).unwrap();
+
+        let anchors = time_range(&deps, 0, u64::MAX, None, None, None);
+        assert_eq!(anchors.len(), 1);
+        assert_eq!(anchors[0].registered_at_time, 500);
+    }
+
+    fn modified_since(deps: &MockDeps, height: u64) -> Vec<ModifiedAnchor> {
+        let msg = QueryMsg::ListModifiedSince { height, start_after: None, limit: None };
+        let res: ModifiedSinceResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.anchors
+    }
+
+    #[test]
+    fn test_list_modified_since_includes_later_changes() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 120, ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        let mut env = mock_env();
+        env.block.height = 200;
+        let msg = ExecuteMsg::BlockHash { hash: Binary::from([1u8; 32]), reason: "disputed".into() };
+        execute(deps.as_mut(), env, mock_info("admin", &[]), msg).unwrap();
+
+        // Since 150 excludes both registrations but not the later block
+        let changed = modified_since(&deps, 150);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].hash_hex, hex::encode([1u8; 32]));
+        assert_eq!(changed[0].last_modified_height, 200);
+        assert_eq!(changed[0].change_kinds, vec!["block"]);
+
+        let changed = modified_since(&deps, 99);
+        let kinds: Vec<_> = changed.iter().map(|a| a.change_kinds.clone()).collect();
+        assert_eq!(kinds, vec![vec!["register".to_string()], vec!["register".to_string(), "block".to_string()]]);
+        assert!(modified_since(&deps, 200).is_empty());
+    }
+
+    fn historical(hash: [u8; 32], original_height: u64, original_time: u64) -> ExecuteMsg {
+        ExecuteMsg::RegisterHistorical(HistoricalAnchor {
+            hash: Binary::from(hash),
+            anchor_type: "root".into(),
+            original_height,
+            original_time,
+            tags: None,
+            memo: None,
+        })
+    }
+
+    #[test]
+    fn test_register_historical_keeps_original_and_import_heights() {
+        let mut deps = setup();
+        let env = mock_env();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), historical([1u8; 32], 42, 1_000)).unwrap();
+        let receipt: AnchorReceipt = from_json(res.data.unwrap()).unwrap();
+        assert_eq!((receipt.block_height, receipt.block_time), (42, 1_000));
+
+        let entry = verify_root(&deps, [1u8; 32]).entry.unwrap();
+        assert_eq!(entry.registered_at, 42);
+        assert_eq!(entry.registered_at_time, 1_000);
+        assert_eq!(entry.imported_at, Some(env.block.height));
+        assert_eq!(get_oldest(&deps, "root").unwrap().registered_at, 42);
+
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        assert_eq!(verify_root(&deps, [2u8; 32]).entry.unwrap().imported_at, None);
+    }
+
+    #[test]
+    fn test_register_historical_rejects_non_admin_and_future() {
+        let mut deps = setup();
+        let env = mock_env();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), historical([1u8; 32], 1, 1)).unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let future = historical([1u8; 32], env.block.height + 1, 1);
+        assert!(execute(deps.as_mut(), env, mock_info("admin", &[]), future).is_err());
+    }
+
+    #[test]
+    fn test_registration_attributes_rebuild_receipt() {
+        let mut deps = setup();
+        let res = register_root(&mut deps, [3u8; 32]).unwrap();
+        let event = cosmwasm_std::Event::new("wasm").add_attributes(res.attributes);
+        let parsed = crate::client::parse_registration_event(&[event]).unwrap();
+        let receipt: AnchorReceipt = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(parsed, receipt);
+    }
+
+    /// Storage wrapper counting the value bytes returned by reads.
+    struct CountingStorage<'a> {
+        inner: &'a MockStorage,
+        read: std::cell::Cell<usize>,
+    }
+
+    impl Storage for CountingStorage<'_> {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            let value = self.inner.get(key);
+            self.read.set(self.read.get() + value.as_ref().map_or(0, Vec::len));
+            value
+        }
+
+        fn range<'b>(
+            &'b self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+        ) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'b> {
+            self.inner.range(start, end, order)
+        }
+
+        fn set(&mut self, _key: &[u8], _value: &[u8]) {
+            unimplemented!("read-only")
+        }
+
+        fn remove(&mut self, _key: &[u8]) {
+            unimplemented!("read-only")
+        }
+    }
+
+    #[test]
+    fn test_existence_check_reads_less_than_full_entry() {
+        let mut deps = setup();
+        let msg = ExecuteMsg::RegisterRoot {
+            hash: Binary::from([1u8; 32]),
+            tags: tags(&["run:2024-11", "env:prod", "team:forensics", "stage:final"]),
+            memo: None,
+            ttl_blocks: None,
+            payload_version: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        let counting = CountingStorage { inner: &deps.storage, read: Default::default() };
+        assert!(is_registered(&counting, AnchorType::MerkleRoot, &[1u8; 32]).unwrap());
+        let presence_bytes = counting.read.take();
+        ANCHORS.load(&counting, ("root", &[1u8; 32])).unwrap();
+        let entry_bytes = counting.read.take();
+        assert!(presence_bytes * 4 < entry_bytes, "{presence_bytes} vs {entry_bytes}");
+    }
+
+    #[test]
+    fn test_presence_and_metadata_maps_agree() {
+        let mut deps = setup();
+        register_at(&mut deps, 10, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 11, ExecuteMsg::RegisterClaimScore { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 12, ExecuteMsg::RegisterEquationProof { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let import = historical([3u8; 32], 5, 5);
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), import).unwrap();
+        block(&mut deps, "admin", [1u8; 32]).unwrap();
+
+        let mut metadata = vec![];
+        for anchor_type in AnchorType::ALL {
+            for item in range_anchors(&deps.storage, anchor_type, None, Order::Ascending) {
+                let (hash, entry) = item.unwrap();
+                metadata.push((anchor_type.to_string(), hash, entry.registered_at));
+            }
+        }
+        let mut presence: Vec<_> = PRESENCE
+            .range(&deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let ((anchor_type, hash), compact) = item.unwrap();
+                (anchor_type, hash, compact.registered_at)
+            })
+            .collect();
+        metadata.sort();
+        presence.sort();
+        assert_eq!(metadata.len(), 4);
+        assert_eq!(metadata, presence);
+        assert!(!verify_root(&deps, [9u8; 32]).exists);
+    }
+
+    #[test]
+    fn test_over_length_equation_name_rejected() {
+        let mut deps = setup();
+        let name = "n".repeat(MAX_FIELD_LEN as usize + 1);
+        let payload = EquationProofPayload::new(
+            name, "a".repeat(64), "b".repeat(64), "stable".into(), 0.5, 0.5, true,
+        );
+        let msg = ExecuteMsg::RegisterEquationProofPayload { payload, tags: None, memo: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::FieldTooLong {
+                field: "equation_name".into(),
+                len: MAX_FIELD_LEN as usize + 1,
+                max: MAX_FIELD_LEN as usize,
+            }
+        );
+
+        let payload = EquationProofPayload::new(
+            "newton".into(), "a".repeat(64), "b".repeat(64), "stable".into(), 0.5, 0.5, true,
+        );
+        let hash = Binary::from(payload.hash_bytes());
+        let msg = ExecuteMsg::RegisterEquationProofPayload { payload, tags: None, memo: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        let res: VerifyResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::VerifyEquationProof { hash: Some(hash), hash_hex: None }).unwrap()).unwrap();
+        assert!(res.exists);
+    }
+
+    fn verify_payload_query(deps: &MockDeps, msg: QueryMsg) -> PayloadVerifyResponse {
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_claim_score_payload() {
+        let mut deps = setup();
+        let registered = ClaimScorePayload::new(1, 0.9, 0.1, 0.8, 4, 0, "stable".into());
+        register_payload(&mut deps, registered.clone()).unwrap();
+        let unregistered = ClaimScorePayload::new(2, 0.9, 0.1, 0.8, 4, 0, "stable".into());
+        let mut tampered = registered.clone();
+        tampered.support_count = 99;
+
+        let check = |payload: &ClaimScorePayload| {
+            let res = verify_payload_query(&deps, QueryMsg::VerifyClaimScorePayload { payload: payload.clone() });
+            (res.payload_valid, res.registered, res.entry.is_some())
+        };
+        assert_eq!(check(&registered), (true, true, true));
+        assert_eq!(check(&unregistered), (true, false, false));
+        assert_eq!(check(&tampered), (false, false, false));
+    }
+
+    #[test]
+    fn test_verify_equation_proof_payload() {
+        let mut deps = setup();
+        let proof = |name: &str| {
+            EquationProofPayload::new(name.into(), "a".repeat(64), "b".repeat(64), "stable".into(), 0.5, 0.5, true)
+        };
+        let registered = proof("newton");
+        let msg = ExecuteMsg::RegisterEquationProofPayload { payload: registered.clone(), tags: None, memo: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        let mut tampered = registered.clone();
+        tampered.dimensional_valid = false;
+
+        let check = |payload: &EquationProofPayload| {
+            let res = verify_payload_query(&deps, QueryMsg::VerifyEquationProofPayload { payload: payload.clone() });
+            (res.payload_valid, res.registered)
+        };
+        assert_eq!(check(&registered), (true, true));
+        assert_eq!(check(&proof("einstein")), (true, false));
+        assert_eq!(check(&tampered), (false, false));
+    }
+
+    #[test]
+    fn test_verify_merkle_payload() {
+        let mut deps = setup();
+        register_root(&mut deps, [0xab; 32]).unwrap();
+        let registered = MerkleRootPayload::new(hex::encode([0xab; 32]), 10, None, None);
+        let unregistered = MerkleRootPayload::new(hex::encode([0xcd; 32]), 10, None, None);
+        let mut tampered = registered.clone();
+        tampered.leaf_count = 11;
+
+        let check = |payload: &MerkleRootPayload| {
+            let res = verify_payload_query(&deps, QueryMsg::VerifyMerklePayload { payload: payload.clone() });
+            (res.payload_valid, res.registered)
+        };
+        assert_eq!(check(&registered), (true, true));
+        assert_eq!(check(&unregistered), (true, false));
+        // The root itself is anchored, so a tampered payload still shows as registered
+        assert_eq!(check(&tampered), (false, true));
+    }
+
+    fn batch(hashes: impl IntoIterator<Item = [u8; 32]>) -> ExecuteMsg {
+        let anchors = hashes
+            .into_iter()
+            .map(|h| BatchEntry { anchor_type: "root".into(), hash: Binary::from(h), tags: None, memo: None })
+            .collect();
+        ExecuteMsg::RegisterBatch { anchors }
+    }
+
+    #[test]
+    fn test_register_batch_of_fifty_roots() {
+        let mut deps = setup();
+        let hashes: Vec<[u8; 32]> = (1..=MAX_BATCH_SIZE as u8).map(|i| [i; 32]).collect();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), batch(hashes.clone())).unwrap();
+        let receipts: Vec<AnchorReceipt> = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(receipts.len(), MAX_BATCH_SIZE);
+        for hash in hashes {
+            assert!(verify_root(&deps, hash).exists);
+        }
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.total_anchors, MAX_BATCH_SIZE as u64);
+    }
+
+    #[test]
+    fn test_register_batch_is_atomic() {
+        let mut deps = setup();
+        let duplicate = batch([[1u8; 32], [2u8; 32], [1u8; 32]]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), duplicate).unwrap_err();
+        assert!(err.to_string().contains("Duplicate anchor in batch"));
+        assert!(!verify_root(&deps, [1u8; 32]).exists);
+        assert!(!verify_root(&deps, [2u8; 32]).exists);
+
+        let mut invalid = batch([[3u8; 32]]);
+        if let ExecuteMsg::RegisterBatch { anchors } = &mut invalid {
+            anchors.push(BatchEntry { anchor_type: "root".into(), hash: Binary::from([4u8; 31]), tags: None, memo: None });
+        }
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), invalid).is_err());
+        assert!(!verify_root(&deps, [3u8; 32]).exists);
+
+        let too_big = batch((0..=MAX_BATCH_SIZE as u8).map(|i| [i; 32]));
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), too_big).is_err());
+
+        // "merkle_root" is an alias of "root", so the pair is one anchor twice
+        let aliased = ExecuteMsg::RegisterBatch {
+            anchors: ["root", "merkle_root"]
+                .into_iter()
+                .map(|anchor_type| BatchEntry { anchor_type: anchor_type.into(), hash: Binary::from([5u8; 32]), tags: None, memo: None })
+                .collect(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), aliased).unwrap_err();
+        assert_eq!(err, ContractError::DuplicateInBatch { anchor_type: "root".into(), hash_hex: hex::encode([5u8; 32]) });
+        assert!(!verify_root(&deps, [5u8; 32]).exists);
+        assert_eq!(config_response(&deps).total_anchors, 0);
+    }
+
+    #[test]
+    fn test_register_mixed_batch() {
+        let mut deps = setup();
+        let mixed = |entries: &[(AnchorType, u8)]| ExecuteMsg::RegisterMixedBatch {
+            entries: entries
+                .iter()
+                .map(|(anchor_type, byte)| MixedEntry { anchor_type: *anchor_type, hash: Binary::from([*byte; 32]), memo: None })
+                .collect(),
+        };
+        let nightly = [
+            (AnchorType::MerkleRoot, 1),
+            (AnchorType::ClaimScore, 1),
+            (AnchorType::ClaimScore, 2),
+            (AnchorType::EquationProof, 3),
+        ];
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), mixed(&nightly)).unwrap();
+        let events: Vec<_> = res.events.iter().filter(|e| e.ty == EVENT_ANCHOR_REGISTERED).collect();
+        assert_eq!(events.len(), 4);
+        let summary = res.attributes.iter().find(|a| a.key == "count_by_type").unwrap();
+        assert_eq!(summary.value, "root:1,claim_score:2,equation_proof:1");
+
+        let config = config_response(&deps);
+        assert_eq!((config.total_anchors, config.total_roots, config.total_claim_scores, config.total_equation_proofs), (4, 1, 2, 1));
+        assert!(verify_root(&deps, [1u8; 32]).exists);
+        let msg = QueryMsg::VerifyEquationProof { hash: Some(Binary::from([3u8; 32])), hash_hex: None };
+        assert!(from_json::<VerifyResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().exists);
+
+        // A duplicate or an already registered entry fails the whole batch
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), mixed(&[(AnchorType::MerkleRoot, 5), (AnchorType::MerkleRoot, 5)])).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateInBatch { .. }));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), mixed(&[(AnchorType::MerkleRoot, 6), (AnchorType::ClaimScore, 2)])).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyRegistered { .. }));
+        assert!(!verify_root(&deps, [5u8; 32]).exists);
+        assert!(!verify_root(&deps, [6u8; 32]).exists);
+        assert_eq!(config_response(&deps).total_anchors, 4);
+    }
+
+    #[test]
+    fn test_max_batch_size_boundary() {
+        let mut deps = setup();
+        assert_eq!(config_response(&deps).max_batch_size, MAX_BATCH_SIZE as u32);
+        let set = |max_batch_size| ExecuteMsg::SetMaxBatchSize { max_batch_size };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), set(3)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), set(3)).unwrap();
+        assert_eq!(config_response(&deps).max_batch_size, 3);
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), batch((1..=4u8).map(|i| [i; 32]))).unwrap_err();
+        assert_eq!(err, ContractError::BatchTooLarge { got: 4, max: 3 });
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), batch((1..=3u8).map(|i| [i; 32]))).unwrap();
+
+        // Zero turns batching off; single registrations are unaffected
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), set(0)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), batch([[9u8; 32]])).unwrap_err();
+        assert_eq!(err, ContractError::BatchTooLarge { got: 1, max: 0 });
+        register_root(&mut deps, [9u8; 32]).unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), set(HARD_MAX_BATCH_SIZE + 1)).unwrap_err();
+        assert_eq!(err, ContractError::MaxBatchSizeTooLarge { value: HARD_MAX_BATCH_SIZE + 1, max: HARD_MAX_BATCH_SIZE });
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), set(HARD_MAX_BATCH_SIZE)).unwrap();
+    }
+
+    #[test]
+    fn test_max_batch_size_at_instantiate() {
+        let mut deps = mock_dependencies();
+        let msg = |max_batch_size| InstantiateMsg {
+            admin: None,
+            name: None,
+            limits: None,
+            restricted: false,
+            public_registration: None,
+            initial_anchors: None,
+            max_batch_size,
+            accepted_hash_lengths: None,
+        };
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg(Some(HARD_MAX_BATCH_SIZE + 1))).unwrap_err();
+        assert!(matches!(err, ContractError::MaxBatchSizeTooLarge { .. }));
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg(Some(2))).unwrap();
+        assert_eq!(config_response(&deps).max_batch_size, 2);
+
+        // Configs stored before the setting existed get the default
+        let mut stored: serde_json::Value = from_json(deps.storage.get(CONFIG.as_slice()).unwrap()).unwrap();
+        stored.as_object_mut().unwrap().remove("max_batch_size");
+        deps.storage.set(CONFIG.as_slice(), &serde_json::to_vec(&stored).unwrap());
+        assert_eq!(config_response(&deps).max_batch_size, MAX_BATCH_SIZE as u32);
+    }
+
+    fn total_anchors(deps: &MockDeps) -> u64 {
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        config.total_anchors
+    }
+
+    #[test]
+    fn test_reregistration_rejected_first_writer_wins() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        let mut env = mock_env();
+        env.block.height = 200;
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), env, mock_info("mallory", &[]), msg).unwrap_err();
+        let hash_hex = "01".repeat(32);
+        assert_eq!(err, ContractError::AlreadyRegistered { hash_hex: hash_hex.clone(), height: 100, registrant: "bot".into() });
+        assert_eq!(err.to_string(), format!("Hash {} already registered at height 100 by bot", hash_hex));
+
+        let entry = verify_root(&deps, [1u8; 32]).entry.unwrap();
+        assert_eq!((entry.registered_at, entry.registrant.as_str()), (100, "bot"));
+        assert_eq!(total_anchors(&deps), 1);
+
+        // Simulation reports the same verdict
+        let sim = simulate(&deps, "root", &[1u8; 32], "mallory", None);
+        assert_eq!(sim.failure_reason, Some(err.to_string()));
+    }
+
+    #[test]
+    fn test_reregistration_rejected_without_presence_entry() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        // A legacy entry from before the presence map
+        let entry = ANCHORS.load(&deps.storage, ("root", &[1u8; 32])).unwrap();
+        ANCHORS.remove(deps.as_mut().storage, ("root", &[1u8; 32]));
+        PRESENCE.remove(deps.as_mut().storage, ("root", &[1u8; 32]));
+        PRESENCE_COMPLETE.remove(deps.as_mut().storage);
+        ROOTS.save(deps.as_mut().storage, &[1u8; 32], &entry).unwrap();
+
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("mallory", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyRegistered { hash_hex: "01".repeat(32), height: 100, registrant: "bot".into() });
+        assert_eq!(verify_root(&deps, [1u8; 32]).entry.unwrap().registrant, "bot");
+    }
+
+    #[test]
+    fn test_admin_opt_in_overwrite_does_not_double_count() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+
+        let enable = ExecuteMsg::SetAllowOverwrite { allow: true };
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), enable.clone()).is_err());
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), enable).unwrap();
+
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), msg).unwrap();
+        assert_eq!(verify_root(&deps, [1u8; 32]).entry.unwrap().registrant, "carol");
+        assert_eq!(total_anchors(&deps), 1);
+    }
+
+    #[test]
+    fn test_list_roots_paginates_by_hash_bytes() {
+        let mut deps = setup();
+        for byte in [3u8, 1, 2] {
+            register_root(&mut deps, [byte; 32]).unwrap();
+        }
+        let msg = ExecuteMsg::RegisterClaimScore { hash: Binary::from([9u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        let list = |start_after: Option<Binary>| -> ListResponse {
+            let msg = QueryMsg::ListRoots { start_after, limit: Some(2), order: None };
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        let first = list(None);
+        let hashes: Vec<_> = first.anchors.iter().map(|e| e.hash_hex.clone()).collect();
+        assert_eq!(hashes, vec!["01".repeat(32), "02".repeat(32)]);
+
+        let cursor = Binary::from_base64(first.next_start_after.as_deref().unwrap()).unwrap();
+        let second = list(Some(cursor));
+        assert_eq!(second.anchors.len(), 1);
+        assert_eq!(second.anchors[0].hash_hex, "03".repeat(32));
+        assert_eq!(second.next_start_after, None);
+
+        let msg = QueryMsg::ListClaimScores { start_after: None, limit: Some(MAX_LIMIT), order: None };
+        let scores: ListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(scores.anchors.len(), 1);
+        assert_eq!(scores.next_start_after, None);
+    }
+
+    #[test]
+    fn test_anchors_by_registrant() {
+        let mut deps = setup();
+        let register = |deps: &mut MockDeps, sender: &str, msg: ExecuteMsg| {
+            execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap();
+        };
+        register(&mut deps, "bot_a", ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register(&mut deps, "bot_a", ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register(&mut deps, "bot_a", ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register(&mut deps, "bot_b", ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        let by_registrant = |anchor_type: Option<&str>, start_after: Option<AnchorKey>| -> Vec<AnchorEntry> {
+            let msg = QueryMsg::GetAnchorsByRegistrant {
+                registrant: "bot_a".into(),
+                anchor_type: anchor_type.map(str::to_string),
+                start_after,
+                limit: Some(2),
+                order: None,
+            };
+            let res: AnchorListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.anchors
+        };
+
+        let first = by_registrant(None, None);
+        let keys: Vec<_> = first.iter().map(|e| (e.anchor_type.as_str(), e.registered_at)).collect();
+        assert_eq!(keys, vec![("root", 12_345), ("root", 12_345)]);
+        assert_eq!(first[1].hash_hex, "03".repeat(32));
+
+        let cursor = AnchorKey { anchor_type: "root".into(), hash: Binary::from([3u8; 32]) };
+        let rest = by_registrant(None, Some(cursor));
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].anchor_type, AnchorType::ClaimScore);
+
+        let cursor = AnchorKey { anchor_type: "root".into(), hash: Binary::from([1u8; 32]) };
+        let roots = by_registrant(Some("root"), Some(cursor));
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].hash_hex, "03".repeat(32));
+        assert!(by_registrant(Some("equation_proof"), None).is_empty());
+
+        // A cursor for another type is an error, not a restart from the first page
+        let cursor = AnchorKey { anchor_type: "claim_score".into(), hash: Binary::from([2u8; 32]) };
+        let msg = QueryMsg::GetAnchorsByRegistrant { registrant: "bot_a".into(), anchor_type: Some("root".into()), start_after: Some(cursor), limit: None, order: None };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::CursorTypeMismatch { cursor: "claim_score".into(), anchor_type: "root".into() });
+        let cursor = AnchorKey { anchor_type: "merkle_root".into(), hash: Binary::from([1u8; 32]) };
+        assert_eq!(by_registrant(Some("root"), Some(cursor)).len(), 1);
+    }
+
+    fn config_response(deps: &MockDeps) -> ConfigResponse {
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_per_type_counters() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        let anchors = vec![
+            BatchEntry { anchor_type: "root".into(), hash: Binary::from([2u8; 32]), tags: None, memo: None },
+            BatchEntry { anchor_type: "equation_proof".into(), hash: Binary::from([3u8; 32]), tags: None, memo: None },
+        ];
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), ExecuteMsg::RegisterBatch { anchors }).unwrap();
+
+        let config = config_response(&deps);
+        assert_eq!(config.total_anchors, 3);
+        assert_eq!(config.total_roots, 2);
+        assert_eq!(config.total_claim_scores, 0);
+        assert_eq!(config.total_equation_proofs, 1);
+    }
+
+    #[test]
+    fn test_backfill_counters_in_pages() {
+        let mut deps = setup();
+        for byte in 1..=4u8 {
+            register_root(&mut deps, [byte; 32]).unwrap();
+        }
+        let msg = ExecuteMsg::RegisterClaimScore { hash: Binary::from([5u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        // Simulate a registry that predates per-type counters
+        TYPE_COUNTS.clear(deps.as_mut().storage);
+
+        let backfill = |deps: &mut MockDeps| {
+            let msg = MigrateMsg::BackfillCounters { limit: Some(2) };
+            let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+            res.attributes.iter().find(|a| a.key == "backfill_complete").unwrap().value == "true"
+        };
+        assert!(!backfill(&mut deps));
+        assert!(COUNTER_BACKFILL.may_load(deps.as_ref().storage).unwrap().is_some());
+
+        // Registrations during the run are counted exactly once on either side of the cursor
+        register_root(&mut deps, numbered(1)).unwrap();
+        register_root(&mut deps, [9u8; 32]).unwrap();
+
+        assert!(!backfill(&mut deps));
+        assert!(backfill(&mut deps));
+        assert!(COUNTER_BACKFILL.may_load(deps.as_ref().storage).unwrap().is_none());
+
+        let config = config_response(&deps);
+        assert_eq!(config.total_anchors, 7);
+        assert_eq!(config.total_roots, 6);
+        assert_eq!(config.total_claim_scores, 1);
+        let totals = totals(&deps);
+        assert_eq!((totals.roots, totals.claim_scores, totals.equation_proofs, totals.total), (6, 1, 0, 7));
+    }
+
+    fn totals(deps: &MockDeps) -> TotalsResponse {
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetTotals {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_totals_track_every_type() {
+        let mut deps = setup();
+        assert_eq!(totals(&deps), TotalsResponse { roots: 0, claim_scores: 0, equation_proofs: 0, total: 0 });
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        register_as(&mut deps, "bot", ExecuteMsg::RegisterClaimScore { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let anchors = vec![
+            BatchEntry { anchor_type: "equation_proof".into(), hash: Binary::from([4u8; 32]), tags: None, memo: None },
+            BatchEntry { anchor_type: "equation_proof".into(), hash: Binary::from([5u8; 32]), tags: None, memo: None },
+            BatchEntry { anchor_type: "claim_score".into(), hash: Binary::from([6u8; 32]), tags: None, memo: None },
+        ];
+        register_as(&mut deps, "bot", ExecuteMsg::RegisterBatch { anchors });
+        register_as(&mut deps, "bot", ExecuteMsg::RegisterNamespaced { namespace: "dataset".into(), hash: Binary::from([7u8; 32]) });
+        // Failed registrations leave the counters untouched
+        assert!(register_root(&mut deps, [1u8; 32]).is_err());
+
+        assert_eq!(totals(&deps), TotalsResponse { roots: 2, claim_scores: 2, equation_proofs: 2, total: 7 });
+    }
+
+    #[test]
+    fn test_restricted_registrant_permissions() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted: true, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let add = |address: &str| ExecuteMsg::AddRegistrant { address: address.into() };
+
+        // Only the admin manages the allowlist
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), add("bot")).is_err());
+        let err = register_root(&mut deps, [1u8; 32]).unwrap_err();
+        assert_eq!(err, ContractError::NotRegistrant { sender: "bot".into() });
+        assert!(!simulate(&deps, "root", &[1u8; 32], "bot", None).would_succeed);
+
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), add("bot")).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), add("auditor")).unwrap();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+
+        let msg = QueryMsg::ListRegistrants { start_after: Some("auditor".into()), limit: None };
+        let res: RegistrantsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.registrants, vec!["bot"]);
+
+        let remove = ExecuteMsg::RemoveRegistrant { address: "bot".into() };
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), remove.clone()).is_err());
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), remove.clone()).unwrap();
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), remove).is_err());
+        assert!(register_root(&mut deps, [2u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_unrestricted_ignores_allowlist() {
+        let mut deps = setup();
+        assert!(!config_response(&deps).restricted);
+        register_root(&mut deps, [1u8; 32]).unwrap();
+    }
+
+    #[test]
+    fn test_public_registration_flag() {
+        let instantiate_with = |restricted, public_registration| {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted, public_registration, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+            instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).map(|_| deps)
+        };
+
+        // Public: anyone may register, allowlisted or not
+        let mut deps = instantiate_with(false, Some(true)).unwrap();
+        assert!(config_response(&deps).public_registration);
+        register_root(&mut deps, [1u8; 32]).unwrap();
+
+        // Permissioned with an empty allowlist: admin only
+        let mut deps = instantiate_with(false, Some(false)).unwrap();
+        let config = config_response(&deps);
+        assert!(!config.public_registration && config.restricted);
+        assert_eq!(register_root(&mut deps, [1u8; 32]).unwrap_err(), ContractError::NotRegistrant { sender: "bot".into() });
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // Permissioned with the sender allowlisted
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::AddRegistrant { address: "bot".into() }).unwrap();
+        register_root(&mut deps, [2u8; 32]).unwrap();
+
+        assert!(!config_response(&instantiate_with(false, None).unwrap()).restricted);
+        assert!(config_response(&instantiate_with(true, None).unwrap()).restricted);
+        assert!(matches!(instantiate_with(true, Some(true)), Err(ContractError::ConflictingRegistrationMode {})));
+    }
+
+    #[test]
+    fn test_pause_blocks_registrations_only() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), ExecuteMsg::Pause {}).is_err());
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::Pause {}).unwrap();
+        assert_eq!(res.attributes[0].value, "pause");
+        assert!(config_response(&deps).paused);
+
+        assert_eq!(register_root(&mut deps, [2u8; 32]).unwrap_err(), ContractError::Paused {});
+        let anchors = vec![BatchEntry { anchor_type: "root".into(), hash: Binary::from([3u8; 32]), tags: None, memo: None }];
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), ExecuteMsg::RegisterBatch { anchors });
+        assert_eq!(err.unwrap_err(), ContractError::Paused {});
+        assert!(!simulate(&deps, "root", &[2u8; 32], "bot", None).would_succeed);
+
+        // Queries and admin actions keep working
+        assert!(verify_root(&deps, [1u8; 32]).exists);
+        block(&mut deps, "admin", [4u8; 32]).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::Unpause {}).unwrap();
+        assert_eq!(res.attributes[0].value, "unpause");
+        register_root(&mut deps, [2u8; 32]).unwrap();
+    }
+
+    fn revoke(deps: &mut MockDeps, sender: &str, height: u64, hash: [u8; 32]) -> Result<Response, ContractError> {
+        let mut env = mock_env();
+        env.block.height = height;
+        let msg = ExecuteMsg::RevokeAnchor {
+            hash: Binary::from(hash),
+            anchor_type: "root".into(),
+            reason: "corrupted snapshot".into(),
+        };
+        execute(deps.as_mut(), env, mock_info(sender, &[]), msg)
+    }
+
+    #[test]
+    fn test_revoke_keeps_tombstone() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 110, ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        assert!(revoke(&mut deps, "stranger", 150, [1u8; 32]).is_err());
+        assert!(revoke(&mut deps, "bot", 150, [9u8; 32]).is_err());
+        revoke(&mut deps, "bot", 150, [1u8; 32]).unwrap();
+        assert!(revoke(&mut deps, "admin", 160, [1u8; 32]).is_err());
+        revoke(&mut deps, "admin", 160, [2u8; 32]).unwrap();
+
+        let res = verify_root(&deps, [1u8; 32]);
+        assert!(res.exists && res.revoked);
+        let entry = res.entry.unwrap();
+        assert_eq!(entry.revoked_at, Some(150));
+        assert_eq!(entry.revocation_reason.as_deref(), Some("corrupted snapshot"));
+        assert!(!verify_root(&deps, [3u8; 32]).revoked);
+        assert_eq!(get_oldest(&deps, "root"), None);
+
+        let changed = modified_since(&deps, 140);
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed[0].change_kinds, vec!["revoke"]);
+        assert_eq!(changed[0].last_modified_height, 150);
+    }
+
+    #[test]
+    fn test_revoked_hash_cannot_be_reregistered() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        revoke(&mut deps, "admin", 12_345, [1u8; 32]).unwrap();
+
+        let enable = ExecuteMsg::SetAllowOverwrite { allow: true };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), enable).unwrap();
+        assert_eq!(register_root(&mut deps, [1u8; 32]).unwrap_err(), ContractError::Revoked { height: 12_345 });
+        assert!(!simulate(&deps, "root", &[1u8; 32], "bot", None).would_succeed);
+    }
+
+    #[test]
+    fn test_typed_errors() {
+        let mut deps = setup();
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 31]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidHashLength { got: 31 });
+
+        let err = block(&mut deps, "bot", [1u8; 32]).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let msg = QueryMsg::GetOldest { anchor_type: "bogus".into() };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert_eq!(err, ContractError::UnknownAnchorType { value: "bogus".into() });
+        assert_eq!(err.to_string(), "Unknown anchor type: bogus");
+    }
+
+    #[test]
+    fn test_execute_rejects_funds() {
+        let mut deps = setup();
+        let funds = cosmwasm_std::coins(1, "ujuno");
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &funds), msg).unwrap_err();
+        assert_eq!(err, ContractError::FundsNotAccepted {});
+        assert_eq!(err.to_string(), "This contract does not accept funds");
+        assert!(!verify_root(&deps, [1u8; 32]).exists);
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &funds), ExecuteMsg::Pause {}).unwrap_err();
+        assert_eq!(err, ContractError::FundsNotAccepted {});
+    }
+
+    #[test]
+    fn test_register_with_memo() {
+        let mut deps = setup();
+        let memo = Some("phase2 snapshot 2024-06-01".to_string());
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: memo.clone(), ttl_blocks: None, payload_version: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "memo" && a.value == "phase2 snapshot 2024-06-01"));
+
+        let entry = verify_root(&deps, [1u8; 32]).entry.unwrap();
+        assert_eq!(entry.memo, memo);
+        assert_eq!(entry.hash_hex, "01".repeat(32));
+
+        let res = register_root(&mut deps, [2u8; 32]).unwrap();
+        assert!(!res.attributes.iter().any(|a| a.key == "memo"));
+        assert_eq!(verify_root(&deps, [2u8; 32]).entry.unwrap().memo, None);
+
+        let memo = Some("m".repeat(crate::limits::MAX_MEMO_LEN + 1));
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::FieldTooLong { field: "memo".into(), len: 257, max: 256 });
+    }
+
+    #[test]
+    fn test_registration_emits_anchor_registered_events() {
+        let mut deps = setup();
+        let res = register_root(&mut deps, [1u8; 32]).unwrap();
+        assert_eq!(res.events.len(), 1);
+        let event = &res.events[0];
+        assert_eq!(event.ty, EVENT_ANCHOR_REGISTERED);
+        let attr = |key: &str| event.attributes.iter().find(|a| a.key == key).unwrap().value.clone();
+        assert_eq!(attr("anchor_type"), "root");
+        assert_eq!(attr("hash"), "01".repeat(32));
+        assert_eq!(attr("registrant"), "bot");
+        assert_eq!(attr("block_height"), "12345");
+        // Legacy attributes are still present
+        assert_eq!(res.attributes[0].value, "register_root");
+
+        let anchors = vec![
+            BatchEntry { anchor_type: "root".into(), hash: Binary::from([2u8; 32]), tags: None, memo: None },
+            BatchEntry { anchor_type: "claim_score".into(), hash: Binary::from([3u8; 32]), tags: None, memo: None },
+        ];
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), ExecuteMsg::RegisterBatch { anchors }).unwrap();
+        let types: Vec<_> = res.events.iter().map(|e| e.ty.as_str()).collect();
+        assert_eq!(types, vec![EVENT_ANCHOR_REGISTERED, EVENT_ANCHOR_REGISTERED]);
+    }
+
+    #[test]
+    fn test_merkle_root_alias_answers_like_root() {
+        let mut deps = setup();
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: tags(&["env:prod"]), memo: None, ttl_blocks: None, payload_version: None };
+        register_at_time(&mut deps, 1_000, msg);
+        let attest = ExecuteMsg::AttestAnchor { hash: Binary::from([1u8; 32]), anchor_type: "root".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("auditor", &[]), attest).unwrap();
+
+        let queries = |anchor_type: &str| {
+            [
+                QueryMsg::AnchorExists { anchor_type: anchor_type.into(), hash: Binary::from([1u8; 32]) },
+                QueryMsg::GetOldest { anchor_type: anchor_type.into() },
+                QueryMsg::GetAttestations { hash: Binary::from([1u8; 32]), anchor_type: anchor_type.into() },
+                QueryMsg::GetAnchorsByTag { tag: "env:prod".into(), anchor_type: Some(anchor_type.into()), start_after: None, limit: None, order: None },
+                QueryMsg::GetAnchorsByRegistrant { registrant: "bot".into(), anchor_type: Some(anchor_type.into()), start_after: None, limit: None, order: None },
+                QueryMsg::ListByTimeRange { start: 0, end: u64::MAX, anchor_type: Some(anchor_type.into()), start_after: None, limit: None },
+                QueryMsg::ActivityHistogram { anchor_type: Some(anchor_type.into()), bucket_blocks: 10, buckets: 1 },
+            ]
+        };
+        for (canonical, alias) in queries("root").into_iter().zip(queries("merkle_root")) {
+            let expected = query(deps.as_ref(), mock_env(), canonical.clone()).unwrap();
+            assert_eq!(query(deps.as_ref(), mock_env(), alias).unwrap(), expected, "{:?}", canonical);
+        }
+        // The canonical answers are not empty
+        let answers: Vec<Binary> = queries("root").into_iter().map(|msg| query(deps.as_ref(), mock_env(), msg).unwrap()).collect();
+        assert!(from_json::<AnchorExistsResponse>(&answers[0]).unwrap().exists);
+        assert!(from_json::<AnchorResponse>(&answers[1]).unwrap().entry.is_some());
+        assert_eq!(from_json::<AttestationsResponse>(&answers[2]).unwrap().attestations.len(), 1);
+        for answer in &answers[3..6] {
+            assert_eq!(from_json::<AnchorListResponse>(answer).unwrap().anchors.len(), 1);
+        }
+        assert_eq!(from_json::<HistogramResponse>(&answers[6]).unwrap().buckets[0].count, 1);
+    }
+
+    #[test]
+    fn test_alias_cursors_resume_like_canonical_ones() {
+        let mut deps = setup();
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: tags(&["env:prod"]), memo: None, ttl_blocks: None, payload_version: None };
+        register_at_time(&mut deps, 1_000, msg);
+        let height = mock_env().block.height;
+        let hash = Binary::from([1u8; 32]);
+
+        // Each cursor points at the only anchor, so every page after it is empty
+        let after = |anchor_type: &str| {
+            [
+                QueryMsg::ListByTag { tag: "env:prod".into(), start_after: Some(AnchorKey { anchor_type: anchor_type.into(), hash: hash.clone() }), limit: None, order: None },
+                QueryMsg::GetAnchorsAtHeight { height, start_after: Some(AnchorKey { anchor_type: anchor_type.into(), hash: hash.clone() }), limit: None, order: None },
+                QueryMsg::ListByTimeRange {
+                    start: 0,
+                    end: u64::MAX,
+                    anchor_type: None,
+                    start_after: Some(TimeCursor { registered_at_time: 1_000, hash: hash.clone(), anchor_type: anchor_type.into() }),
+                    limit: None,
+                },
+            ]
+        };
+        for (canonical, alias) in after("root").into_iter().zip(after("merkle_root")) {
+            let expected = query(deps.as_ref(), mock_env(), canonical.clone()).unwrap();
+            assert!(from_json::<AnchorListResponse>(&expected).unwrap().anchors.is_empty(), "{:?}", canonical);
+            assert_eq!(query(deps.as_ref(), mock_env(), alias).unwrap(), expected, "{:?}", canonical);
+        }
+        let cursor = ModifiedCursor { last_modified_height: height, hash: hash.clone(), anchor_type: "merkle_root".into() };
+        let msg = QueryMsg::ListModifiedSince { height: 0, start_after: Some(cursor), limit: None };
+        assert!(from_json::<ModifiedSinceResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().anchors.is_empty());
+    }
+
+    #[test]
+    fn test_anchor_type_parsing() {
+        for anchor_type in AnchorType::ALL {
+            assert_eq!(anchor_type.to_string().parse::<AnchorType>(), Ok(anchor_type));
+            let json = cosmwasm_std::to_json_vec(&anchor_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", anchor_type).into_bytes());
+        }
+        assert_eq!(from_json::<AnchorType>(br#""merkle_root""#).unwrap(), AnchorType::MerkleRoot);
+        assert_eq!("merkle_root".parse::<AnchorType>(), Ok(AnchorType::MerkleRoot));
+        assert!("bogus".parse::<AnchorType>().is_err());
+        assert!(from_json::<AnchorType>(br#""roots""#).is_err());
+
+        let unknown = br#"{"get_anchor":{"hash":"AQ==","anchor_type":"bogus"}}"#;
+        assert!(from_json::<QueryMsg>(unknown).is_err());
+
+        // Entries stored with the legacy strings still load
+        let legacy = br#"{"hash_hex":"01","anchor_type":"claim_score","registered_at":1,"registrant":"bot"}"#;
+        assert_eq!(from_json::<AnchorEntry>(legacy).unwrap().anchor_type, AnchorType::ClaimScore);
+    }
+
+    fn chain_root(deps: &mut MockDeps, hash: [u8; 32], previous_root: [u8; 32]) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RegisterRootChained {
+            hash: Binary::from(hash),
+            previous_root: Binary::from(previous_root),
+            tags: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+    }
+
+    fn root_chain(deps: &MockDeps, head: [u8; 32], limit: Option<u32>) -> RootChainResponse {
+        let msg = QueryMsg::GetRootChain { head: Binary::from(head), limit };
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_register_root_chained_checks_link() {
+        let mut deps = setup();
+        chain_root(&mut deps, [1u8; 32], GENESIS_ROOT).unwrap();
+        chain_root(&mut deps, [2u8; 32], [1u8; 32]).unwrap();
+        let entry = verify_root(&deps, [2u8; 32]).entry.unwrap();
+        assert_eq!(entry.previous_root, Some(hex::encode([1u8; 32])));
+
+        let err = chain_root(&mut deps, [3u8; 32], [9u8; 32]).unwrap_err();
+        assert_eq!(err, ContractError::UnknownPreviousRoot { hash_hex: hex::encode([9u8; 32]) });
+        assert!(!verify_root(&deps, [3u8; 32]).exists);
+
+        let short = ExecuteMsg::RegisterRootChained {
+            hash: Binary::from([3u8; 32]),
+            previous_root: Binary::from([1u8; 31]),
+            tags: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), short).unwrap_err();
+        assert_eq!(err, ContractError::InvalidHashLength { got: 31 });
+
+        revoke(&mut deps, "admin", 150, [2u8; 32]).unwrap();
+        let err = chain_root(&mut deps, [3u8; 32], [2u8; 32]).unwrap_err();
+        assert_eq!(err, ContractError::PreviousRootRevoked { hash_hex: hex::encode([2u8; 32]), height: 150 });
+
+        // Plain registrations carry no link
+        register_root(&mut deps, [4u8; 32]).unwrap();
+        assert_eq!(verify_root(&deps, [4u8; 32]).entry.unwrap().previous_root, None);
+    }
+
+    #[test]
+    fn test_get_root_chain_walks_links() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        chain_root(&mut deps, [2u8; 32], [1u8; 32]).unwrap();
+        chain_root(&mut deps, [3u8; 32], [2u8; 32]).unwrap();
+        chain_root(&mut deps, [5u8; 32], GENESIS_ROOT).unwrap();
+        chain_root(&mut deps, [6u8; 32], [5u8; 32]).unwrap();
+
+        let hashes = |res: &RootChainResponse| res.roots.iter().map(|e| e.hash_hex.clone()).collect::<Vec<_>>();
+        let res = root_chain(&deps, [3u8; 32], None);
+        assert_eq!(hashes(&res), vec![hex::encode([3u8; 32]), hex::encode([2u8; 32]), hex::encode([1u8; 32])]);
+        assert_eq!(res.end, ChainEnd::Unlinked);
+
+        let res = root_chain(&deps, [6u8; 32], None);
+        assert_eq!(res.roots.len(), 2);
+        assert_eq!(res.end, ChainEnd::Genesis);
+
+        let res = root_chain(&deps, [3u8; 32], Some(2));
+        assert_eq!(res.roots.len(), 2);
+        let ChainEnd::Limit { next_head } = res.end else { panic!("expected a limit, got {:?}", res.end) };
+        let rest = root_chain(&deps, Binary::from_base64(&next_head).unwrap().to_vec().try_into().unwrap(), Some(2));
+        assert_eq!(hashes(&rest), vec![hex::encode([1u8; 32])]);
+
+        // A link to a root that is gone is reported, not skipped
+        ANCHORS.remove(deps.as_mut().storage, ("root", &[2u8; 32]));
+        let res = root_chain(&deps, [3u8; 32], None);
+        assert_eq!(res.roots.len(), 1);
+        assert_eq!(res.end, ChainEnd::Broken { missing_hex: hex::encode([2u8; 32]) });
+
+        let msg = QueryMsg::GetRootChain { head: Binary::from([9u8; 32]), limit: None };
+        let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AnchorNotFound { .. }));
+    }
+
+    #[test]
+    fn test_latest_root_skips_imports() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), historical([2u8; 32], 10, 1_000)).unwrap();
+
+        let res: LatestRootResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetLatestRoot {}).unwrap()).unwrap();
+        assert_eq!(res.latest.unwrap().hash_hex, hex::encode([1u8; 32]));
+    }
+
+    #[test]
+    fn test_contract_info() {
+        let mut deps = mock_dependencies();
+        let msg = QueryMsg::GetContractInfo {};
+        let res: ContractInfoResponse = from_json(query(deps.as_ref(), mock_env(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.stored_version, None);
+
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let expected = format!(
+            r#"{{"contract_name":"{}","version":"{}","payload_format_version":1,"supported_payload_versions":[1],"stored_version":"0.0.1"}}"#,
+            CONTRACT_NAME, CONTRACT_VERSION,
+        );
+        assert_eq!(String::from_utf8(res.to_vec()).unwrap(), expected);
+        assert_eq!(from_json::<QueryMsg>(br#"{"get_contract_info":{}}"#).unwrap(), QueryMsg::GetContractInfo {});
+    }
+
+    #[test]
+    fn test_entry_records_chain_and_tx() {
+        let mut deps = setup();
+        let res = register_root(&mut deps, [1u8; 32]).unwrap();
+        let env = mock_env();
+        let entry = verify_root(&deps, [1u8; 32]).entry.unwrap();
+        assert_eq!(entry.chain_id, Some(env.block.chain_id.clone()));
+        assert_eq!(entry.tx_index, env.transaction.map(|tx| tx.index));
+        assert!(res.attributes.iter().any(|a| a.key == "chain_id" && a.value == env.block.chain_id));
+
+        let receipt: AnchorReceipt = from_json(res.data.unwrap()).unwrap();
+        assert_eq!(receipt.chain_id, entry.chain_id);
+        assert_eq!(receipt.tx_index, entry.tx_index);
+
+        let legacy = br#"{"hash_hex":"01","anchor_type":"root","registered_at":1,"registrant":"bot"}"#;
+        let legacy = from_json::<AnchorEntry>(legacy).unwrap();
+        assert_eq!((legacy.chain_id, legacy.tx_index), (None, None));
+    }
+
+    fn register_namespaced(deps: &mut MockDeps, namespace: &str, hash: [u8; 32]) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RegisterNamespaced { namespace: namespace.into(), hash: Binary::from(hash) };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+    }
+
+    fn verify_namespaced(deps: &MockDeps, namespace: &str, hash: [u8; 32]) -> NamespacedVerifyResponse {
+        let msg = QueryMsg::VerifyNamespaced { namespace: namespace.into(), hash: Binary::from(hash) };
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_namespaces_hold_same_hash_independently() {
+        let mut deps = setup();
+        register_namespaced(&mut deps, "dataset", [1u8; 32]).unwrap();
+        assert!(verify_namespaced(&deps, "dataset", [1u8; 32]).exists);
+        assert!(!verify_namespaced(&deps, "model_weights", [1u8; 32]).exists);
+
+        register_namespaced(&mut deps, "model_weights", [1u8; 32]).unwrap();
+        register_namespaced(&mut deps, "model_weights", [2u8; 32]).unwrap();
+        let entry = verify_namespaced(&deps, "model_weights", [1u8; 32]).entry.unwrap();
+        assert_eq!(entry.namespace, "model_weights");
+        assert_eq!(entry.hash_hex, hex::encode([1u8; 32]));
+        assert!(!verify_root(&deps, [1u8; 32]).exists);
+
+        let err = register_namespaced(&mut deps, "dataset", [1u8; 32]).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyRegistered { .. }));
+
+        let msg = QueryMsg::ListNamespaces { start_after: None, limit: None };
+        let res: NamespacesResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let counts: Vec<_> = res.namespaces.iter().map(|n| (n.namespace.as_str(), n.count)).collect();
+        assert_eq!(counts, vec![("dataset", 1), ("model_weights", 2)]);
+        let msg = QueryMsg::ListNamespaces { start_after: Some("dataset".into()), limit: None };
+        let res: NamespacesResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.namespaces.len(), 1);
+
+        let config: ConfigResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(config.total_anchors, 3);
+    }
+
+    #[test]
+    fn test_register_namespaced_validates_namespace() {
+        let mut deps = setup();
+        for reserved in ["root", "claim_score", "equation_proof", "merkle_root"] {
+            let err = register_namespaced(&mut deps, reserved, [1u8; 32]).unwrap_err();
+            assert_eq!(err, ContractError::ReservedNamespace { namespace: reserved.into() });
+        }
+        assert!(register_namespaced(&mut deps, "", [1u8; 32]).is_err());
+        assert!(register_namespaced(&mut deps, "Dataset", [1u8; 32]).is_err());
+        assert!(register_namespaced(&mut deps, "data set", [1u8; 32]).is_err());
+        assert!(register_namespaced(&mut deps, &"a".repeat(33), [1u8; 32]).is_err());
+        register_namespaced(&mut deps, &"a".repeat(32), [1u8; 32]).unwrap();
+
+        let msg = ExecuteMsg::RegisterNamespaced { namespace: "dataset".into(), hash: Binary::from([1u8; 31]) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidHashLength { got: 31 });
+    }
+
+    fn registrant_stats(deps: &MockDeps, registrant: &str) -> RegistrantStatsResponse {
+        let msg = QueryMsg::GetRegistrantStats { registrant: registrant.into() };
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    fn register_as(deps: &mut MockDeps, sender: &str, msg: ExecuteMsg) {
+        execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn test_registrant_stats_count_and_never_decrement() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        register_as(&mut deps, "alice", ExecuteMsg::RegisterClaimScore { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let anchors = vec![BatchEntry { anchor_type: "equation_proof".into(), hash: Binary::from([4u8; 32]), tags: None, memo: None }];
+        register_as(&mut deps, "alice", ExecuteMsg::RegisterBatch { anchors });
+        register_as(&mut deps, "alice", ExecuteMsg::RegisterNamespaced { namespace: "dataset".into(), hash: Binary::from([5u8; 32]) });
+
+        let bot = registrant_stats(&deps, "bot");
+        assert_eq!((bot.total, bot.by_type.roots), (2, 2));
+        let alice = registrant_stats(&deps, "alice");
+        assert_eq!(alice.total, 3);
+        assert_eq!(alice.by_type, RegistrantStats { roots: 0, claim_scores: 1, equation_proofs: 1, namespaced: 1 });
+
+        revoke(&mut deps, "bot", 150, [1u8; 32]).unwrap();
+        assert_eq!(registrant_stats(&deps, "bot").total, 2);
+        assert_eq!(registrant_stats(&deps, "nobody").total, 0);
+    }
+
+    #[test]
+    fn test_registrant_stats_total_overflow_is_an_error() {
+        let mut deps = setup();
+        let stats = RegistrantStats { roots: u64::MAX, claim_scores: 0, equation_proofs: 0, namespaced: 1 };
+        assert!(stats.total().is_err());
+        REGISTRANT_STATS.save(deps.as_mut().storage, &Addr::unchecked("bot"), &stats).unwrap();
+
+        let msg = QueryMsg::GetRegistrantStats { registrant: "bot".into() };
+        assert!(query(deps.as_ref(), mock_env(), msg).unwrap_err().to_string().contains("Overflow"));
+        let msg = ExecuteMsg::SetQuota { registrant: "bot".into(), max_anchors: Some(2) };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        assert!(matches!(register_root(&mut deps, [1u8; 32]).unwrap_err(), ContractError::Std(StdError::Overflow { .. })));
+    }
+
+    #[test]
+    fn test_backfill_registrant_stats_in_pages() {
+        let mut deps = setup();
+        for byte in 1..=3u8 {
+            register_root(&mut deps, [byte; 32]).unwrap();
+        }
+        register_as(&mut deps, "alice", ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_as(&mut deps, "alice", ExecuteMsg::RegisterNamespaced { namespace: "dataset".into(), hash: Binary::from([5u8; 32]) });
+
+        // Simulate a registry that predates per-registrant stats
+        for registrant in ["alice", "bot"] {
+            let stats = RegistrantStats { namespaced: if registrant == "alice" { 1 } else { 0 }, ..Default::default() };
+            REGISTRANT_STATS.save(deps.as_mut().storage, &Addr::unchecked(registrant), &stats).unwrap();
+        }
+
+        let backfill = |deps: &mut MockDeps| {
+            let msg = MigrateMsg::BackfillRegistrantStats { limit: Some(2) };
+            let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+            res.attributes.iter().find(|a| a.key == "backfill_complete").unwrap().value == "true"
+        };
+        assert!(!backfill(&mut deps));
+        assert!(STATS_BACKFILL.may_load(deps.as_ref().storage).unwrap().is_some());
+
+        // Registrations during the run are counted exactly once on either side of the cursor
+        register_as(&mut deps, "alice", ExecuteMsg::RegisterRoot { hash: Binary::from(numbered(1)), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_root(&mut deps, [9u8; 32]).unwrap();
+
+        assert!(!backfill(&mut deps));
+        assert!(backfill(&mut deps));
+        assert!(STATS_BACKFILL.may_load(deps.as_ref().storage).unwrap().is_none());
+
+        assert_eq!(registrant_stats(&deps, "alice").by_type, RegistrantStats { roots: 2, namespaced: 1, ..Default::default() });
+        assert_eq!(registrant_stats(&deps, "bot").total, 4);
+    }
+
+    fn claim_history(deps: &MockDeps, claim_id: u64, start_after: Option<ClaimCursor>, limit: Option<u32>) -> Vec<AnchorEntry> {
+        let msg = QueryMsg::GetClaimScoreHistory { claim_id, start_after, limit };
+        from_json::<AnchorListResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().anchors
+    }
+
+    #[test]
+    fn test_claim_score_history_in_registration_order() {
+        let mut deps = setup();
+        let indexed = |hash: [u8; 32], claim_id| ExecuteMsg::RegisterClaimScoreIndexed {
+            hash: Binary::from(hash),
+            claim_id,
+            tags: None,
+            memo: None,
+        };
+        register_at(&mut deps, 100, indexed([9u8; 32], 42));
+        register_at(&mut deps, 110, indexed([1u8; 32], 42));
+        register_at(&mut deps, 120, indexed([5u8; 32], 7));
+        register_at(&mut deps, 130, indexed([3u8; 32], 42));
+        register_at(&mut deps, 140, ExecuteMsg::RegisterClaimScore { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        let hashes = |entries: Vec<AnchorEntry>| entries.into_iter().map(|e| e.hash_hex[..2].to_string()).collect::<Vec<_>>();
+        assert_eq!(hashes(claim_history(&deps, 42, None, None)), vec!["09", "01", "03"]);
+        assert_eq!(hashes(claim_history(&deps, 7, None, None)), vec!["05"]);
+        assert!(claim_history(&deps, 8, None, None).is_empty());
+
+        let first = claim_history(&deps, 42, None, Some(1));
+        let cursor = ClaimCursor { registered_at: first[0].registered_at, hash: Binary::from([9u8; 32]) };
+        assert_eq!(hashes(claim_history(&deps, 42, Some(cursor), Some(1))), vec!["01"]);
+
+        // Indexed scores verify like any other; plain ones keep no claim id
+        let msg = QueryMsg::VerifyClaimScore { hash: Some(Binary::from([1u8; 32])), hash_hex: None };
+        let res: VerifyResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entry.unwrap().claim_id, Some(42));
+        let msg = QueryMsg::VerifyClaimScore { hash: Some(Binary::from([4u8; 32])), hash_hex: None };
+        let res: VerifyResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entry.unwrap().claim_id, None);
+    }
+
+    #[test]
+    fn test_rate_limit_counts_batches_and_simulation() {
+        let mut deps = setup();
+        let msg = ExecuteMsg::SetRateLimit { max_anchors_per_block_per_registrant: Some(2) };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        let anchors = (1..=3u8)
+            .map(|byte| BatchEntry { anchor_type: "root".into(), hash: Binary::from([byte; 32]), tags: None, memo: None })
+            .collect();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), ExecuteMsg::RegisterBatch { anchors }).unwrap_err();
+        assert_eq!(err, ContractError::RateLimited { max: 2 });
+
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        assert!(simulate(&deps, "root", &[2u8; 32], "bot", None).would_succeed);
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        assert!(!simulate(&deps, "root", &[3u8; 32], "bot", None).would_succeed);
+        assert_eq!(config_response(&deps).max_anchors_per_block_per_registrant, Some(2));
+    }
+
+    #[test]
+    fn test_operator_role() {
+        let mut deps = setup();
+        let run = |deps: &mut MockDeps, sender: &str, msg: ExecuteMsg| execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg);
+        let add_operator = ExecuteMsg::AddOperator { address: "oncall".into() };
+        assert_eq!(run(&mut deps, "oncall", add_operator.clone()).unwrap_err(), ContractError::Unauthorized {});
+        run(&mut deps, "admin", add_operator.clone()).unwrap();
+        run(&mut deps, "admin", add_operator).unwrap();
+        assert_eq!(config_response(&deps).operators, vec!["oncall"]);
+
+        // Day-to-day duties
+        run(&mut deps, "oncall", ExecuteMsg::AddRegistrant { address: "bot".into() }).unwrap();
+        run(&mut deps, "oncall", ExecuteMsg::Pause {}).unwrap();
+        run(&mut deps, "oncall", ExecuteMsg::Unpause {}).unwrap();
+        run(&mut deps, "oncall", ExecuteMsg::RemoveRegistrant { address: "bot".into() }).unwrap();
+
+        // Admin-only powers stay with the admin
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        let denied = [
+            ExecuteMsg::AddOperator { address: "mallory".into() },
+            ExecuteMsg::SetAllowOverwrite { allow: true },
+            ExecuteMsg::BlockHash { hash: Binary::from([2u8; 32]), reason: "spam".into() },
+            ExecuteMsg::RevokeAnchor { hash: Binary::from([1u8; 32]), anchor_type: "root".into(), reason: "bad".into() },
+        ];
+        for msg in denied {
+            assert_eq!(run(&mut deps, "oncall", msg).unwrap_err(), ContractError::Unauthorized {});
+        }
+
+        let remove = ExecuteMsg::RemoveOperator { address: "oncall".into() };
+        run(&mut deps, "admin", remove.clone()).unwrap();
+        assert_eq!(run(&mut deps, "admin", remove).unwrap_err(), ContractError::UnknownOperator { address: "oncall".into() });
+        assert!(run(&mut deps, "oncall", ExecuteMsg::Pause {}).is_err());
+        assert!(config_response(&deps).operators.is_empty());
+    }
+
+    fn anchors_since(deps: &MockDeps, height: u64, start_after: Option<(u64, Binary)>, limit: u32) -> AnchorsSinceResponse {
+        let msg = QueryMsg::GetAnchorsSince { height, start_after, limit: Some(limit), order: None };
+        from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    fn numbered(i: u32) -> [u8; 32] {
+        // The ones after the counter keep `numbered(0)` from being the all-zero hash
+        let mut hash = [1u8; 32];
+        hash[..4].copy_from_slice(&i.to_be_bytes());
+        hash
+    }
+
+    #[test]
+    fn test_anchors_since_pages_through_250() {
+        let mut deps = setup();
+        for i in 0..250 {
+            let msg = ExecuteMsg::RegisterRoot { hash: Binary::from(numbered(i)), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+            register_at(&mut deps, 100 + (i / 3) as u64, msg);
+        }
+
+        let mut seen = vec![];
+        let mut cursor = None;
+        let mut pages = vec![];
+        loop {
+            let page = anchors_since(&deps, 100, cursor, 100);
+            pages.push(page.anchors.len());
+            seen.extend(page.anchors.into_iter().map(|a| (a.registered_at, a.hash_hex)));
+            cursor = page.next_start_after;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(pages, vec![100, 100, 50]);
+        let mut expected = seen.clone();
+        expected.sort();
+        expected.dedup();
+        assert_eq!(seen, expected);
+        assert_eq!(seen.len(), 250);
+
+        // Entries exactly at `height` are included
+        let page = anchors_since(&deps, 110, None, 100);
+        let heights: Vec<u64> = page.anchors.iter().take(4).map(|a| a.registered_at).collect();
+        assert_eq!(heights, vec![110, 110, 110, 111]);
+    }
+
+    #[test]
+    fn test_anchors_since_cursor_survives_new_anchors() {
+        let mut deps = setup();
+        for i in 0..4 {
+            register_at(&mut deps, 100 + i as u64, ExecuteMsg::RegisterRoot { hash: Binary::from(numbered(i)), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        }
+        let first = anchors_since(&deps, 0, None, 2);
+        assert_eq!(first.next_start_after, Some((101, Binary::from(numbered(1)))));
+
+        // Arrivals while paging sort after the cursor and are picked up once
+        register_at(&mut deps, 200, ExecuteMsg::RegisterRoot { hash: Binary::from([0xffu8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let second = anchors_since(&deps, 0, first.next_start_after.clone(), 2);
+        let heights: Vec<u64> = second.anchors.iter().map(|a| a.registered_at).collect();
+        assert_eq!(heights, vec![102, 103]);
+        let third = anchors_since(&deps, 0, second.next_start_after, 2);
+        assert_eq!(third.anchors.len(), 1);
+        assert_eq!(third.anchors[0].registered_at, 200);
+        assert_eq!(third.next_start_after, None);
+        assert_eq!(anchors_since(&deps, 0, first.next_start_after, 2).anchors, second.anchors);
+    }
+
+    #[test]
+    fn test_anchors_since_keeps_one_hash_on_one_page() {
+        let mut deps = setup();
+        let hash = Binary::from([3u8; 32]);
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: hash.clone(), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 100, ExecuteMsg::RegisterClaimScore { hash: hash.clone(), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 101, ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        let page = anchors_since(&deps, 100, None, 1);
+        assert_eq!(page.anchors.len(), 2);
+        assert_eq!(page.next_start_after, Some((100, hash)));
+        let rest = anchors_since(&deps, 100, page.next_start_after, 1);
+        assert_eq!(rest.anchors.len(), 1);
+        assert_eq!(rest.anchors[0].hash_hex, hex::encode([4u8; 32]));
+    }
+
+    fn export_chunk(deps: &MockDeps, start_after: Option<ExportCursor>, limit: u32) -> Binary {
+        let msg = QueryMsg::ExportState { start_after, limit: Some(limit) };
+        query(deps.as_ref(), mock_env(), msg).unwrap()
+    }
+
+    fn export_all(deps: &MockDeps, limit: u32) -> Vec<Binary> {
+        let mut chunks = vec![];
+        let mut cursor = None;
+        loop {
+            let bytes = export_chunk(deps, cursor, limit);
+            let chunk: StateExport = from_json(&bytes).unwrap();
+            chunks.push(bytes);
+            cursor = chunk.next_start_after;
+            if cursor.is_none() {
+                return chunks;
+            }
+        }
+    }
+
+    fn exported_registry() -> MockDeps {
+        let mut deps = setup();
+        for i in (0..20).rev() {
+            let hash = Binary::from(numbered(i));
+            register_as(&mut deps, "bot", ExecuteMsg::RegisterRoot { hash: hash.clone(), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+            if i % 2 == 0 {
+                register_as(&mut deps, "bot", ExecuteMsg::RegisterEquationProof { hash: hash.clone(), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+            }
+            if i % 3 == 0 {
+                register_as(&mut deps, "bot", ExecuteMsg::RegisterClaimScore { hash, tags: None, memo: None, ttl_blocks: None, payload_version: None });
+            }
+        }
+        deps
+    }
+
+    #[test]
+    fn test_export_state_interleaves_types_in_fixed_order() {
+        let deps = exported_registry();
+        let chunks: Vec<StateExport> = export_all(&deps, 7).iter().map(|b| from_json(b).unwrap()).collect();
+        assert!(chunks[0].config.is_some());
+        assert!(chunks[1..].iter().all(|c| c.config.is_none() && c.anchors.len() <= 7));
+        assert!(chunks.iter().all(|c| c.chunk_hash == c.compute_chunk_hash().unwrap()));
+
+        let order: Vec<(String, AnchorType)> =
+            chunks.iter().flat_map(|c| c.anchors.iter().map(|a| (a.hash_hex.clone(), a.anchor_type))).collect();
+        assert_eq!(order.len(), 20 + 10 + 7);
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(order, sorted);
+        assert_eq!(
+            order[..3].iter().map(|(_, t)| *t).collect::<Vec<_>>(),
+            vec![AnchorType::MerkleRoot, AnchorType::ClaimScore, AnchorType::EquationProof]
+        );
+    }
+
+    #[test]
+    fn test_export_state_is_byte_identical_across_parties() {
+        let first = exported_registry();
+        let second = exported_registry();
+        assert_eq!(export_all(&first, 5), export_all(&second, 5));
+        assert_eq!(export_all(&first, 5), export_all(&first, 5));
+    }
+
+    fn import_entry(anchor_type: &str, hash: [u8; 32], original_height: u64) -> ImportEntry {
+        ImportEntry { anchor_type: anchor_type.into(), hash: Binary::from(hash), registrant: "old-chain-bot".into(), original_height, chain_id: None }
+    }
+
+    fn seeded(initial_anchors: Vec<ImportEntry>) -> Result<MockDeps, ContractError> {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted: false, public_registration: None, initial_anchors: Some(initial_anchors), max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg)?;
+        Ok(deps)
+    }
+
+    #[test]
+    fn test_instantiate_seeds_initial_anchors() {
+        let mut deps = seeded(vec![
+            import_entry("root", [1; 32], 10),
+            import_entry("root", [2; 32], 11),
+            import_entry("claim_score", [1; 32], 12),
+        ])
+        .unwrap();
+        register_root(&mut deps, [3; 32]).unwrap();
+
+        let imported = verify_root(&deps, [1; 32]).entry.unwrap();
+        assert_eq!(imported.imported_at, Some(mock_env().block.height));
+        assert_eq!(imported.registered_at, 10);
+        assert_eq!(imported.registered_at_time, 0);
+        assert_eq!(imported.registrant, "old-chain-bot");
+        assert_eq!(imported.chain_id, None);
+        assert_eq!(verify_root(&deps, [3; 32]).entry.unwrap().imported_at, None);
+
+        let config = config_response(&deps);
+        assert_eq!((config.total_anchors, config.total_roots, config.total_claim_scores), (4, 3, 1));
+    }
+
+    #[test]
+    fn test_import_anchors_validates_and_rejects_duplicates() {
+        let too_many = (0..=MAX_IMPORT_BATCH as u32).map(|i| import_entry("root", numbered(i), 1)).collect();
+        assert!(matches!(seeded(too_many), Err(ContractError::TooManyItems { .. })));
+        assert!(matches!(
+            seeded(vec![import_entry("root", [1; 32], 1), import_entry("root", [1; 32], 2)]),
+            Err(ContractError::DuplicateInBatch { .. })
+        ));
+        let future = import_entry("root", [1; 32], mock_env().block.height + 1);
+        assert!(matches!(seeded(vec![future]), Err(ContractError::FutureImport {})));
+
+        let mut deps = seeded(vec![import_entry("root", [1; 32], 10)]).unwrap();
+        let import = |anchors| ExecuteMsg::ImportAnchors { anchors };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), import(vec![import_entry("root", [2; 32], 10)]))
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // A re-import fails the whole batch, even with overwrites allowed
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::SetAllowOverwrite { allow: true }).unwrap();
+        let batch = vec![import_entry("root", [2; 32], 10), import_entry("root", [1; 32], 10)];
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), import(batch)).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyRegistered { height: 10, .. }));
+        assert!(!verify_root(&deps, [2; 32]).exists);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), import(vec![import_entry("root", [2; 32], 10)]))
+            .unwrap();
+        assert_eq!(res.attributes[1].value, "1");
+        assert_eq!(config_response(&deps).total_anchors, 2);
+
+        // The origin chain is recorded as given, never as this chain
+        let origin = ImportEntry { chain_id: Some("old-chain-1".into()), ..import_entry("root", [3; 32], 10) };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), import(vec![origin])).unwrap();
+        assert_eq!(verify_root(&deps, [3; 32]).entry.unwrap().chain_id.as_deref(), Some("old-chain-1"));
+        assert_eq!(verify_root(&deps, [2; 32]).entry.unwrap().chain_id, None);
+    }
+
+    /// Storage recording the keys written to it.
+    #[derive(Default)]
+    struct RecordingStorage {
+        inner: MockStorage,
+        written: Vec<Vec<u8>>,
+    }
+
+    impl Storage for RecordingStorage {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.inner.get(key)
+        }
+
+        fn range<'a>(
+            &'a self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+        ) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'a> {
+            self.inner.range(start, end, order)
+        }
+
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.written.push(key.to_vec());
+            self.inner.set(key, value);
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.written.push(key.to_vec());
+            self.inner.remove(key);
+        }
     }
 
     #[test]
-    fn test_format_anchor_payload_structure() {
-        let hash = [0xABu8; 32];
-        let payload = format_anchor_payload(&hash, "root", 1);
-        let payload_str = String::from_utf8_lossy(&payload);
-        assert!(payload_str.starts_with("root:"));
-        assert!(payload_str.contains(&hex::encode([0xABu8; 32])));
+    fn test_registration_does_not_rewrite_config() {
+        let mut deps = OwnedDeps {
+            storage: RecordingStorage::default(),
+            api: MockApi::default(),
+            querier: MockQuerier::default(),
+            custom_query_type: std::marker::PhantomData,
+        };
+        let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted: false, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        deps.storage.written.clear();
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        assert!(deps.storage.written.iter().any(|key| key.as_slice() == TOTAL_ANCHORS.as_slice()));
+        assert!(!deps.storage.written.iter().any(|key| key.as_slice() == CONFIG.as_slice()));
+        assert_eq!(config_response_of(&deps).total_anchors, 1);
+    }
+
+    fn config_response_of<S: Storage>(deps: &OwnedDeps<S, MockApi, MockQuerier>) -> ConfigResponse {
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_migrate_moves_counters_out_of_config() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        let before = config_response(&deps);
+
+        // A registry whose counters still live in the config
+        TOTAL_ANCHORS.remove(deps.as_mut().storage);
+        TYPE_COUNTS.clear(deps.as_mut().storage);
+        let mut legacy: serde_json::Value = from_json(deps.storage.get(CONFIG.as_slice()).unwrap()).unwrap();
+        legacy["total_anchors"] = 4.into();
+        legacy["total_roots"] = 3.into();
+        legacy["total_equation_proofs"] = 1.into();
+        deps.storage.set(CONFIG.as_slice(), &serde_json::to_vec(&legacy).unwrap());
+        assert_eq!(config_response(&deps).total_anchors, 0);
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::V1 {}).unwrap();
+        let after = config_response(&deps);
+        assert_eq!((after.total_anchors, after.total_roots, after.total_claim_scores, after.total_equation_proofs), (4, 3, 0, 1));
+        assert_eq!(ConfigResponse { total_anchors: 1, total_roots: 1, total_equation_proofs: 0, ..after }, before);
+        let raw = String::from_utf8(deps.storage.get(CONFIG.as_slice()).unwrap()).unwrap();
+        assert!(!raw.contains("total_anchors"));
+
+        // A second migration leaves the moved counters alone
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::V1 {}).unwrap();
+        assert_eq!(config_response(&deps).total_anchors, 4);
+    }
+
+    #[test]
+    fn test_migrate_v1_layout_through_every_step() {
+        // Raw v1 storage: version, config carrying the admin and counters,
+        // and entries in the per-type maps, with no presence or index keys
+        let mut deps = mock_dependencies();
+        let version = format!(r#"{{"contract":"{}","version":"0.1.0"}}"#, CONTRACT_NAME);
+        deps.storage.set(b"contract_info", version.as_bytes());
+        let config = br#"{"admin":"admin","total_anchors":3,"total_roots":2,"total_claim_scores":1,"total_equation_proofs":0}"#;
+        deps.storage.set(b"config", config);
+        for (namespace, anchor_type, byte, height) in [("roots", "root", 1u8, 10), ("roots", "root", 2, 11), ("claim_scores", "claim_score", 3, 12)] {
+            let mut key = (namespace.len() as u16).to_be_bytes().to_vec();
+            key.extend_from_slice(namespace.as_bytes());
+            key.extend_from_slice(&[byte; 32]);
+            let entry = format!(
+                r#"{{"hash_hex":"{}","anchor_type":"{}","registered_at":{},"registrant":"legacy"}}"#,
+                hex::encode([byte; 32]),
+                anchor_type,
+                height,
+            );
+            deps.storage.set(&key, entry.as_bytes());
+        }
+
+        let check = |deps: &MockDeps| {
+            let root = verify_root(deps, [2u8; 32]);
+            assert!(root.exists);
+            assert_eq!(root.entry.unwrap().registered_at, 11);
+            let msg = QueryMsg::GetAnchor { hash: Some(Binary::from([3u8; 32])), hash_hex: None, anchor_type: AnchorType::ClaimScore };
+            let res: AnchorResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(res.entry.unwrap().registrant, "legacy");
+            for (anchor_type, byte, exists) in [("root", 1u8, true), ("claim_score", 3, true), ("claim_score", 1, false)] {
+                let msg = QueryMsg::AnchorExists { anchor_type: anchor_type.into(), hash: Binary::from([byte; 32]) };
+                let res: AnchorExistsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+                assert_eq!(res.exists, exists);
+            }
+        };
+        // Partial while a `BackfillCounters` run is in progress
+        let totals = |deps: &MockDeps| {
+            let config = config_response(deps);
+            assert_eq!((config.total_anchors, config.total_roots, config.total_claim_scores, config.total_equation_proofs), (3, 2, 1, 0));
+            assert_eq!(config.admin, "admin");
+        };
+        let run = |deps: &mut MockDeps, msg: MigrateMsg, done: &str| loop {
+            let res = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+            check(deps);
+            if res.attributes.iter().any(|a| a.key == done && a.value == "true") {
+                totals(deps);
+                break;
+            }
+        };
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::V1 {}).unwrap();
+        check(&deps);
+        totals(&deps);
+        // Counting the presence map before it is complete would drop the
+        // legacy anchors from the carried-over counts
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::BackfillCounters { limit: None }).unwrap_err();
+        assert_eq!(err, ContractError::PresenceBackfillPending {});
+        totals(&deps);
+
+        run(&mut deps, MigrateMsg::BackfillPresence { limit: Some(2) }, "backfill_complete");
+        run(&mut deps, MigrateMsg::MoveAnchors { limit: Some(2) }, "move_complete");
+        run(&mut deps, MigrateMsg::BackfillCounters { limit: Some(2) }, "backfill_complete");
+        run(&mut deps, MigrateMsg::BackfillRegistrantStats { limit: Some(2) }, "backfill_complete");
+        run(&mut deps, MigrateMsg::BackfillTypeIndex { limit: Some(2) }, "backfill_complete");
+        let msg = QueryMsg::GetOldest { anchor_type: "root".into() };
+        let res: AnchorResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entry.unwrap().registered_at, 10);
+        assert_eq!(PRESENCE.keys(&deps.storage, None, None, Order::Ascending).count(), 3);
+        assert!(ROOTS.is_empty(&deps.storage) && CLAIM_SCORES.is_empty(&deps.storage));
+
+        // The legacy registrant still owns its anchor
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyRegistered { .. }));
+    }
+
+    #[test]
+    fn test_anchor_storage_keys_are_stable() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        let msg = ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        // Length-prefixed namespace, then length-prefixed type, then the raw hash
+        let key = |anchor_type: &str, byte: u8| {
+            let mut key = b"\x00\x07anchors".to_vec();
+            key.extend_from_slice(&(anchor_type.len() as u16).to_be_bytes());
+            key.extend_from_slice(anchor_type.as_bytes());
+            key.extend_from_slice(&[byte; 32]);
+            key
+        };
+        assert_eq!(ANCHORS.key(("root", &[1u8; 32])).to_vec(), key("root", 1));
+        let raw = deps.storage.get(&key("root", 1)).unwrap();
+        assert_eq!(from_json::<AnchorEntry>(&raw).unwrap().hash_hex, hex::encode([1u8; 32]));
+        assert!(deps.storage.get(&key("claim_score", 2)).is_some());
+        assert!(deps.storage.get(&[b"\x00\x05roots".as_slice(), &[1u8; 32]].concat()).is_none());
+    }
+
+    #[test]
+    fn test_queries_identical_across_anchor_move() {
+        let mut deps = setup();
+        let mut previous = Binary::from(GENESIS_ROOT);
+        for byte in [3u8, 1, 4] {
+            let msg = ExecuteMsg::RegisterRootChained { hash: Binary::from([byte; 32]), previous_root: previous, tags: None, memo: None };
+            execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+            previous = Binary::from([byte; 32]);
+        }
+        for (byte, msg) in [
+            (5u8, ExecuteMsg::RegisterClaimScore { hash: Binary::from([5u8; 32]), tags: tags(&["env:prod"]), memo: None, ttl_blocks: None, payload_version: None }),
+            (1, ExecuteMsg::RegisterClaimScore { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None }),
+            (9, ExecuteMsg::RegisterEquationProof { hash: Binary::from([9u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None }),
+        ] {
+            register_at(&mut deps, 20 + byte as u64, msg);
+        }
+        let queries = [
+            QueryMsg::ListRoots { start_after: None, limit: None, order: None },
+            QueryMsg::ListRoots { start_after: Some(Binary::from([1u8; 32])), limit: Some(1), order: None },
+            QueryMsg::ListClaimScores { start_after: None, limit: None, order: None },
+            QueryMsg::ListEquationProofs { start_after: None, limit: None, order: None },
+            QueryMsg::VerifyRoot { hash: Some(Binary::from([4u8; 32])), hash_hex: None },
+            QueryMsg::VerifyClaimScore { hash: Some(Binary::from([1u8; 32])), hash_hex: None },
+            QueryMsg::GetAnchor { hash: Some(Binary::from([9u8; 32])), hash_hex: None, anchor_type: AnchorType::EquationProof },
+            QueryMsg::GetOldest { anchor_type: "claim_score".into() },
+            QueryMsg::GetRootChain { head: Binary::from([4u8; 32]), limit: None },
+            QueryMsg::StabilityHistogram {},
+            QueryMsg::ListByTag { tag: "env:prod".into(), start_after: None, limit: None, order: None },
+            QueryMsg::ExportState { start_after: None, limit: Some(2) },
+            QueryMsg::ExportState { start_after: None, limit: None },
+            QueryMsg::GetAnchorsSince { height: 0, start_after: None, limit: None, order: None },
+        ];
+        let answers = |deps: &MockDeps| -> Vec<Binary> {
+            queries.iter().map(|msg| query(deps.as_ref(), mock_env(), msg.clone()).unwrap()).collect()
+        };
+        let expected = answers(&deps);
+
+        // Rewrite every anchor into the pre-unification per-type layout
+        let entries: Vec<_> = ANCHORS
+            .range(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        for ((anchor_type, hash), entry) in entries {
+            ANCHORS.remove(deps.as_mut().storage, (anchor_type.as_str(), &hash));
+            let legacy = parse_anchor_type(&anchor_type).unwrap().legacy_store();
+            legacy.save(deps.as_mut().storage, &hash, &entry).unwrap();
+        }
+        assert_eq!(answers(&deps), expected);
+
+        // A legacy entry rewritten before the move shadows its old copy
+        block(&mut deps, "admin", [1u8; 32]).unwrap();
+        assert!(CLAIM_SCORES.has(&deps.storage, &[1u8; 32]));
+        assert!(ANCHORS.has(&deps.storage, ("claim_score", &[1u8; 32])));
+        let expected = answers(&deps);
+        let claims: ListResponse = from_json(&expected[2]).unwrap();
+        assert_eq!(claims.anchors.len(), 2);
+        assert_eq!(claims.anchors[0].last_modified_height, mock_env().block.height);
+
+        let move_anchors = |deps: &mut MockDeps| {
+            let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::MoveAnchors { limit: Some(2) }).unwrap();
+            assert_eq!(answers(deps), expected);
+            res.attributes.iter().find(|a| a.key == "move_complete").unwrap().value == "true"
+        };
+        let mut runs = 1;
+        while !move_anchors(&mut deps) {
+            runs += 1;
+        }
+        assert_eq!(runs, 3);
+        for anchor_type in AnchorType::ALL {
+            assert!(anchor_type.legacy_store().is_empty(&deps.storage));
+        }
+        assert_eq!(ANCHORS.range(&deps.storage, None, None, Order::Ascending).count(), 6);
+    }
+
+    #[test]
+    fn test_anchors_before_presence_map_stay_registered_until_backfilled() {
+        let mut deps = setup();
+        for byte in [1u8, 2, 3] {
+            register_root(&mut deps, [byte; 32]).unwrap();
+        }
+        let msg = ExecuteMsg::RegisterClaimScore { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        // Roll back to a registry that predates the presence map, with one
+        // root still in its legacy per-type map
+        PRESENCE.clear(deps.as_mut().storage);
+        PRESENCE_COMPLETE.remove(deps.as_mut().storage);
+        let legacy = ANCHORS.load(&deps.storage, ("root", &[2u8; 32])).unwrap();
+        ANCHORS.remove(deps.as_mut().storage, ("root", &[2u8; 32]));
+        ROOTS.save(deps.as_mut().storage, &[2u8; 32], &legacy).unwrap();
+
+        let exists = |deps: &MockDeps, anchor_type: &str, byte: u8| {
+            let msg = QueryMsg::AnchorExists { anchor_type: anchor_type.into(), hash: Binary::from([byte; 32]) };
+            from_json::<AnchorExistsResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().exists
+        };
+        for byte in [1u8, 2, 3] {
+            assert!(exists(&deps, "root", byte));
+            assert!(verify_root(&deps, [byte; 32]).exists);
+        }
+        assert!(exists(&deps, "claim_score", 4));
+        assert!(!exists(&deps, "root", 4));
+
+        // The original registrant is kept
+        let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("mallory", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyRegistered { .. }));
+
+        let backfill = |deps: &mut MockDeps| {
+            let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::BackfillPresence { limit: Some(3) }).unwrap();
+            res.attributes.iter().find(|a| a.key == "backfill_complete").unwrap().value == "true"
+        };
+        assert!(!backfill(&mut deps));
+        assert!(!PRESENCE_COMPLETE.may_load(&deps.storage).unwrap().unwrap_or(false));
+        assert!(backfill(&mut deps));
+        assert!(PRESENCE_COMPLETE.load(&deps.storage).unwrap());
+        assert!(PRESENCE_BACKFILL.may_load(&deps.storage).unwrap().is_none());
+        assert_eq!(PRESENCE.keys(&deps.storage, None, None, Order::Ascending).count(), 4);
+        assert_eq!(PRESENCE.load(&deps.storage, ("root", &[2u8; 32])).unwrap().registered_at, legacy.registered_at);
+        assert!(exists(&deps, "root", 2));
+        assert!(!exists(&deps, "root", 4));
+    }
+
+    #[test]
+    fn test_hex_and_binary_hash_queries_agree() {
+        let mut deps = setup();
+        register_root(&mut deps, [0xabu8; 32]).unwrap();
+        let msg = ExecuteMsg::RegisterClaimScore { hash: Binary::from([0xabu8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        let binary = || Some(Binary::from([0xabu8; 32]));
+        let hex = || Some("ab".repeat(32));
+        let pairs = [
+            (QueryMsg::VerifyRoot { hash: binary(), hash_hex: None }, QueryMsg::VerifyRoot { hash: None, hash_hex: hex() }),
+            (
+                QueryMsg::VerifyClaimScore { hash: binary(), hash_hex: None },
+                QueryMsg::VerifyClaimScore { hash: None, hash_hex: hex() },
+            ),
+            (
+                QueryMsg::VerifyEquationProof { hash: binary(), hash_hex: None },
+                QueryMsg::VerifyEquationProof { hash: None, hash_hex: hex() },
+            ),
+            (
+                QueryMsg::GetAnchor { hash: binary(), hash_hex: None, anchor_type: AnchorType::MerkleRoot },
+                QueryMsg::GetAnchor { hash: None, hash_hex: hex(), anchor_type: AnchorType::MerkleRoot },
+            ),
+        ];
+        for (by_binary, by_hex) in pairs {
+            let expected: VerifyResponse = from_json(query(deps.as_ref(), mock_env(), by_binary).unwrap()).unwrap();
+            let actual: VerifyResponse = from_json(query(deps.as_ref(), mock_env(), by_hex).unwrap()).unwrap();
+            assert_eq!(actual, expected);
+        }
+
+        // Messages written before hash_hex existed still parse
+        let legacy: QueryMsg = from_json(br#"{"verify_root":{"hash":"q6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq6s="}}"#).unwrap();
+        assert_eq!(legacy, QueryMsg::VerifyRoot { hash: binary(), hash_hex: None });
+    }
+
+    #[test]
+    fn test_hash_hex_is_strict() {
+        let deps = setup();
+        let verify = |hash: Option<Binary>, hash_hex: Option<String>| {
+            query(deps.as_ref(), mock_env(), QueryMsg::VerifyRoot { hash, hash_hex }).unwrap_err()
+        };
+        for bad in ["AB".repeat(32), "ab".repeat(31), "ab".repeat(33), format!("0x{}", "ab".repeat(31)), "zz".repeat(32)] {
+            assert_eq!(verify(None, Some(bad.clone())), ContractError::InvalidHashHex { value: bad });
+        }
+        assert_eq!(verify(None, None), ContractError::ExactlyOneHash {});
+        assert_eq!(verify(Some(Binary::from([1u8; 32])), Some("01".repeat(32))), ContractError::ExactlyOneHash {});
+        assert_eq!(parse_hash_hex(&"0f".repeat(32)), Some([0x0fu8; 32]));
+    }
+
+    fn anchor_by_index(deps: &MockDeps, index: u64) -> Option<AnchorEntry> {
+        let res: AnchorResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetAnchorByIndex { index }).unwrap()).unwrap();
+        res.entry
+    }
+
+    #[test]
+    fn test_anchor_indexes_follow_registration_order() {
+        let mut deps = setup();
+        let res = register_root(&mut deps, [7u8; 32]).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "index" && a.value == "1"));
+        let batch = ExecuteMsg::RegisterBatch {
+            anchors: vec![
+                BatchEntry { anchor_type: "equation_proof".into(), hash: Binary::from([1u8; 32]), tags: None, memo: None },
+                BatchEntry { anchor_type: "claim_score".into(), hash: Binary::from([7u8; 32]), tags: None, memo: None },
+            ],
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), batch).unwrap();
+        let indexes: Vec<_> = res.events.iter().flat_map(|e| &e.attributes).filter(|a| a.key == "index").map(|a| a.value.as_str()).collect();
+        assert_eq!(indexes, ["2", "3"]);
+
+        let registered: Vec<_> = (1..=3).map(|i| anchor_by_index(&deps, i).unwrap()).collect();
+        let keys: Vec<_> = registered.iter().map(|e| (e.anchor_type, e.hash_hex.clone(), e.index)).collect();
+        assert_eq!(
+            keys,
+            vec![
+                (AnchorType::MerkleRoot, hex::encode([7u8; 32]), Some(1)),
+                (AnchorType::EquationProof, hex::encode([1u8; 32]), Some(2)),
+                (AnchorType::ClaimScore, hex::encode([7u8; 32]), Some(3)),
+            ]
+        );
+        assert_eq!(verify_root(&deps, [7u8; 32]).index, Some(1));
+        assert_eq!(anchor_by_index(&deps, 4), None);
+        assert_eq!(anchor_by_index(&deps, 0), None);
+    }
+
+    #[test]
+    fn test_rejected_registrations_leave_no_index_gap() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+
+        // The batch fails on its second anchor; the first may not take a number
+        let batch = ExecuteMsg::RegisterBatch {
+            anchors: vec![
+                BatchEntry { anchor_type: "root".into(), hash: Binary::from([2u8; 32]), tags: None, memo: None },
+                BatchEntry { anchor_type: "root".into(), hash: Binary::from([1u8; 32]), tags: None, memo: None },
+            ],
+        };
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), batch).is_err());
+        assert!(register_root(&mut deps, [1u8; 32]).is_err());
+        register_root(&mut deps, [3u8; 32]).unwrap();
+        assert_eq!(verify_root(&deps, [3u8; 32]).index, Some(2));
+
+        // An overwrite keeps the anchor's original number
+        let enable = ExecuteMsg::SetAllowOverwrite { allow: true };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), enable).unwrap();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        assert_eq!(verify_root(&deps, [1u8; 32]).index, Some(1));
+        assert_eq!(ANCHOR_SEQUENCE.load(&deps.storage).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_reply_settles_pending_dispatches() {
+        let mut deps = setup();
+        let add = |contract: &str| ExecuteMsg::AddSubscriber { contract: contract.into(), msg_template: "on_anchor".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), add("listener")).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), add("broken")).unwrap();
+        let res = register_root(&mut deps, [1u8; 32]).unwrap();
+        let ids: Vec<u64> = res.messages.iter().map(|m| m.id).collect();
+        assert_eq!(ids, [1, 2]);
+        assert!(res.messages.iter().all(|m| m.reply_on == cosmwasm_std::ReplyOn::Always));
+        assert!(res.messages.iter().all(|m| m.gas_limit == Some(SUBSCRIBER_GAS_LIMIT)));
+
+        // Subscribers are notified in address order: "broken" first
+        let ok = SubMsgResult::Ok(cosmwasm_std::SubMsgResponse { events: vec![], data: None });
+        reply(deps.as_mut(), mock_env(), Reply { id: 2, result: ok }).unwrap();
+        let failed = Reply { id: 1, result: SubMsgResult::Err("subscriber down".into()) };
+        let res = reply(deps.as_mut(), mock_env(), failed.clone()).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "error" && a.value == "subscriber down"));
+        assert!(PENDING_DISPATCHES.is_empty(&deps.storage));
+        assert!(reply(deps.as_mut(), mock_env(), failed).is_err());
+
+        let list = |start_after| {
+            let msg = QueryMsg::ListFailedDispatches { start_after, limit: None };
+            from_json::<FailedDispatchesResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().dispatches
+        };
+        let expected = FailedDispatch {
+            id: 1,
+            contract: "broken".into(),
+            anchor_type: "root".into(),
+            hash_hex: hex::encode([1u8; 32]),
+            height: mock_env().block.height,
+            error: "subscriber down".into(),
+        };
+        assert_eq!(list(None), vec![expected]);
+        assert!(list(Some(1)).is_empty());
+        assert!(verify_root(&deps, [1u8; 32]).exists);
+    }
+
+    #[test]
+    fn test_attest_anchor() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        let attest = |deps: &mut MockDeps, sender: &str, byte: u8| {
+            let msg = ExecuteMsg::AttestAnchor { hash: Binary::from([byte; 32]), anchor_type: "root".into() };
+            execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+        };
+
+        attest(&mut deps, "auditor-a", 1).unwrap();
+        let res = attest(&mut deps, "auditor-b", 1).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "attestations" && a.value == "2"));
+        assert_eq!(verify_root(&deps, [1u8; 32]).attestations, 2);
+
+        assert_eq!(attest(&mut deps, "auditor-a", 1).unwrap_err(), ContractError::AlreadyAttested { attester: "auditor-a".into() });
+        assert_eq!(attest(&mut deps, "bot", 1).unwrap_err(), ContractError::SelfAttestation {});
+        assert!(matches!(attest(&mut deps, "auditor-a", 2).unwrap_err(), ContractError::AnchorNotFound { .. }));
+        // An attestation on the root says nothing about a claim score of the same hash
+        let msg = ExecuteMsg::AttestAnchor { hash: Binary::from([1u8; 32]), anchor_type: "claim_score".into() };
+        assert!(execute(deps.as_mut(), mock_env(), mock_info("auditor-c", &[]), msg).is_err());
+
+        let msg = QueryMsg::GetAttestations { hash: Binary::from([1u8; 32]), anchor_type: "root".into() };
+        let res: AttestationsResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let attesters: Vec<_> = res.attestations.iter().map(|a| a.attester.as_str()).collect();
+        assert_eq!(attesters, ["auditor-a", "auditor-b"]);
+        assert_eq!(res.attestations[0].height, mock_env().block.height);
+
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::Pause {}).unwrap();
+        assert_eq!(attest(&mut deps, "auditor-c", 1).unwrap_err(), ContractError::Paused {});
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::Unpause {}).unwrap();
+
+        let revoke = ExecuteMsg::RevokeAnchor { hash: Binary::from([1u8; 32]), anchor_type: "root".into(), reason: "bad".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), revoke).unwrap();
+        assert!(matches!(attest(&mut deps, "auditor-c", 1).unwrap_err(), ContractError::Revoked { .. }));
+    }
+
+    #[test]
+    fn test_attestations_show_in_modified_since() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let mut env = mock_env();
+        env.block.height = 200;
+        let msg = ExecuteMsg::AttestAnchor { hash: Binary::from([1u8; 32]), anchor_type: "root".into() };
+        execute(deps.as_mut(), env, mock_info("auditor", &[]), msg).unwrap();
+
+        let changed = modified_since(&deps, 150);
+        assert_eq!(changed.len(), 1);
+        assert_eq!((changed[0].hash_hex.as_str(), changed[0].last_modified_height), (hex::encode([1u8; 32]).as_str(), 200));
+        assert_eq!(changed[0].change_kinds, vec!["attest"]);
+    }
+
+    #[test]
+    fn test_attestations_are_bounded() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        for i in 0..MAX_ATTESTATIONS {
+            let msg = ExecuteMsg::AttestAnchor { hash: Binary::from([1u8; 32]), anchor_type: "root".into() };
+            execute(deps.as_mut(), mock_env(), mock_info(&format!("auditor-{i}"), &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::AttestAnchor { hash: Binary::from([1u8; 32]), anchor_type: "root".into() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("one-too-many", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::TooManyItems { what: "attestations", max: MAX_ATTESTATIONS, got: MAX_ATTESTATIONS + 1 });
+        assert_eq!(verify_root(&deps, [1u8; 32]).attestations, MAX_ATTESTATIONS as u32);
+    }
+
+    #[test]
+    fn test_quota_caps_lifetime_registrations() {
+        let mut deps = setup();
+        let msg = ExecuteMsg::SetQuota { registrant: "bot".into(), max_anchors: Some(2) };
+        assert_eq!(
+            execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg.clone()).unwrap_err(),
+            ContractError::Unauthorized {}
+        );
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+
+        // A batch that would overshoot is rejected whole
+        let anchors = (1..=3u8)
+            .map(|byte| BatchEntry { anchor_type: "root".into(), hash: Binary::from([byte; 32]), tags: None, memo: None })
+            .collect();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), ExecuteMsg::RegisterBatch { anchors }).unwrap_err();
+        assert_eq!(err, ContractError::QuotaExceeded { registrant: "bot".into(), max: 2, used: 0 });
+
+        // Exactly at the quota succeeds; one over fails
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        assert_eq!(
+            register_root(&mut deps, [3u8; 32]).unwrap_err(),
+            ContractError::QuotaExceeded { registrant: "bot".into(), max: 2, used: 2 }
+        );
+        assert!(!simulate(&deps, "root", &[3u8; 32], "bot", None).would_succeed);
+        let stats = registrant_stats(&deps, "bot");
+        assert_eq!((stats.total, stats.quota), (2, Some(2)));
+
+        // Other registrants are unaffected, and removing the quota lifts it
+        register_as(&mut deps, "alice", ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let msg = ExecuteMsg::SetQuota { registrant: "bot".into(), max_anchors: None };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        register_root(&mut deps, [3u8; 32]).unwrap();
+        assert_eq!(registrant_stats(&deps, "bot").quota, None);
+    }
+
+    fn link_proof(deps: &mut MockDeps, hash: [u8; 32], root: [u8; 32]) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RegisterEquationProofLinked {
+            hash: Binary::from(hash),
+            depends_on_root: Binary::from(root),
+            tags: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+    }
+
+    fn dependents_of(deps: &MockDeps, root: [u8; 32], start_after: Option<[u8; 32]>) -> Vec<String> {
+        let msg = QueryMsg::GetDependents { root_hash: Binary::from(root), start_after: start_after.map(Binary::from), limit: None };
+        let res: AnchorListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.anchors.into_iter().map(|entry| entry.hash_hex).collect()
+    }
+
+    #[test]
+    fn test_linked_equation_proofs_require_live_root() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        link_proof(&mut deps, [12u8; 32], [1u8; 32]).unwrap();
+        link_proof(&mut deps, [11u8; 32], [1u8; 32]).unwrap();
+        link_proof(&mut deps, [21u8; 32], [2u8; 32]).unwrap();
+
+        let msg = QueryMsg::VerifyEquationProof { hash: Some(Binary::from([11u8; 32])), hash_hex: None };
+        let res: VerifyResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.entry.unwrap().depends_on_root, Some(hex::encode([1u8; 32])));
+        assert_eq!(dependents_of(&deps, [1u8; 32], None), vec![hex::encode([11u8; 32]), hex::encode([12u8; 32])]);
+        assert_eq!(dependents_of(&deps, [1u8; 32], Some([11u8; 32])), vec![hex::encode([12u8; 32])]);
+        assert_eq!(dependents_of(&deps, [2u8; 32], None), vec![hex::encode([21u8; 32])]);
+        assert!(dependents_of(&deps, [3u8; 32], None).is_empty());
+
+        // Only roots qualify: the proof hashes themselves are not roots
+        let err = link_proof(&mut deps, [13u8; 32], [11u8; 32]).unwrap_err();
+        assert_eq!(err, ContractError::UnknownDependency { hash_hex: hex::encode([11u8; 32]) });
+        let err = link_proof(&mut deps, [13u8; 32], [9u8; 32]).unwrap_err();
+        assert_eq!(err, ContractError::UnknownDependency { hash_hex: hex::encode([9u8; 32]) });
+
+        revoke(&mut deps, "admin", 150, [2u8; 32]).unwrap();
+        let err = link_proof(&mut deps, [22u8; 32], [2u8; 32]).unwrap_err();
+        assert_eq!(err, ContractError::DependencyRevoked { hash_hex: hex::encode([2u8; 32]), height: 150 });
+        // Proofs registered before the revocation stay listed
+        assert_eq!(dependents_of(&deps, [2u8; 32], None), vec![hex::encode([21u8; 32])]);
+    }
+
+    #[test]
+    fn test_cross_type_collision_is_flagged_and_findable() {
+        let mut deps = setup();
+        let collision = |res: &Response| {
+            res.attributes.iter().find(|a| a.key == "cross_type_collision").map(|a| a.value.clone())
+        };
+        let res = register_root(&mut deps, [1u8; 32]).unwrap();
+        assert_eq!(collision(&res), None);
+        let msg = ExecuteMsg::RegisterClaimScore { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        assert_eq!(collision(&res).as_deref(), Some("root"));
+        let msg = ExecuteMsg::RegisterEquationProof { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        assert_eq!(collision(&res).as_deref(), Some("root,claim_score"));
+
+        let find = |hash: [u8; 32]| -> Vec<AnchorType> {
+            let msg = QueryMsg::FindHash { hash: Binary::from(hash) };
+            let res: AnchorListResponse = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.anchors.into_iter().map(|entry| entry.anchor_type).collect()
+        };
+        assert_eq!(find([1u8; 32]), AnchorType::ALL.to_vec());
+        assert!(find([2u8; 32]).is_empty());
+    }
+
+    fn supersede(deps: &mut MockDeps, sender: &str, old_root: [u8; 32], new_root: [u8; 32]) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::SupersedeRoot { old_root: Binary::from(old_root), new_root: Binary::from(new_root) };
+        execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+    }
+
+    #[test]
+    fn test_supersede_root_links_once() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        register_root(&mut deps, [3u8; 32]).unwrap();
+
+        assert_eq!(supersede(&mut deps, "mallory", [1u8; 32], [2u8; 32]).unwrap_err(), ContractError::Unauthorized {});
+        assert!(matches!(supersede(&mut deps, "bot", [1u8; 32], [9u8; 32]), Err(ContractError::AnchorNotFound { .. })));
+        assert!(matches!(supersede(&mut deps, "bot", [9u8; 32], [1u8; 32]), Err(ContractError::AnchorNotFound { .. })));
+        assert_eq!(supersede(&mut deps, "bot", [1u8; 32], [1u8; 32]).unwrap_err(), ContractError::SelfSupersession {});
+
+        supersede(&mut deps, "bot", [1u8; 32], [2u8; 32]).unwrap();
+        let res = verify_root(&deps, [1u8; 32]);
+        assert_eq!(res.superseded_by, Some(hex::encode([2u8; 32])));
+        assert_eq!(res.entry.unwrap().superseded_by, Some(hex::encode([2u8; 32])));
+        assert_eq!(verify_root(&deps, [2u8; 32]).superseded_by, None);
+
+        // No second successor, and no link back into a superseded root
+        let already = ContractError::AlreadySuperseded { hash_hex: hex::encode([1u8; 32]), successor: hex::encode([2u8; 32]) };
+        assert_eq!(supersede(&mut deps, "admin", [1u8; 32], [3u8; 32]).unwrap_err(), already);
+        assert_eq!(supersede(&mut deps, "bot", [2u8; 32], [1u8; 32]).unwrap_err(), already);
+
+        // The admin may extend the chain on the registrant's behalf
+        supersede(&mut deps, "admin", [2u8; 32], [3u8; 32]).unwrap();
+        assert_eq!(verify_root(&deps, [2u8; 32]).superseded_by, Some(hex::encode([3u8; 32])));
+    }
+
+    #[test]
+    fn test_accepted_hash_lengths() {
+        let mut deps = mock_dependencies();
+        let msg = |accepted_hash_lengths| InstantiateMsg {
+            admin: None,
+            name: None,
+            limits: None,
+            restricted: false,
+            public_registration: None,
+            initial_anchors: None,
+            max_batch_size: None,
+            accepted_hash_lengths,
+        };
+        for bad in [vec![], vec![16], vec![32, 32]] {
+            let err = instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg(Some(bad.clone()))).unwrap_err();
+            assert_eq!(err, ContractError::InvalidHashLengths { lengths: bad });
+        }
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg(Some(vec![20, 64]))).unwrap();
+        assert_eq!(config_response(&deps).accepted_hash_lengths, vec![20, 64]);
+
+        assert_eq!(register_root(&mut deps, [1u8; 32]).unwrap_err(), ContractError::InvalidHashLength { got: 32 });
+        let register = |deps: &mut MockDeps, hash: Vec<u8>| {
+            let msg = ExecuteMsg::RegisterRoot { hash: Binary::from(hash), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+            execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+        };
+        register(&mut deps, vec![0xab; 20]).unwrap();
+        register(&mut deps, vec![0xcd; 64]).unwrap();
+
+        // Queries take raw bytes or hex of the actual length
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::VerifyRoot { hash: None, hash_hex: Some("ab".repeat(20)) }).unwrap();
+        let res: VerifyResponse = from_json(res).unwrap();
+        let entry = res.entry.unwrap();
+        assert_eq!((entry.hash_hex, entry.digest_len), ("ab".repeat(20), 20));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::VerifyRoot { hash: Some(Binary::from(vec![0xcd; 64])), hash_hex: None }).unwrap();
+        let entry = from_json::<VerifyResponse>(res).unwrap().entry.unwrap();
+        assert_eq!((entry.hash_hex, entry.digest_len), ("cd".repeat(64), 64));
+
+        // Entries stored before digest lengths were recorded are 32 bytes
+        let legacy = br#"{"hash_hex":"01","anchor_type":"root","registered_at":1,"registrant":"bot"}"#;
+        assert_eq!(from_json::<AnchorEntry>(legacy).unwrap().digest_len, 32);
+    }
+
+    #[test]
+    fn test_reregistration_after_lockout() {
+        let mut deps = setup();
+        let at = |deps: &mut MockDeps, sender: &str, height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+            execute(deps.as_mut(), env, mock_info(sender, &[]), msg)
+        };
+        at(&mut deps, "bot", 100).unwrap();
+        revoke(&mut deps, "bot", 150, [1u8; 32]).unwrap();
+
+        // Without a lockout window revoked hashes stay blocked
+        assert_eq!(at(&mut deps, "bot", 10_000).unwrap_err(), ContractError::Revoked { height: 150 });
+
+        let set = ExecuteMsg::SetReregistrationLockout { blocks: Some(50) };
+        assert_eq!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), set.clone()).unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), set).unwrap();
+        assert_eq!(config_response(&deps).reregistration_lockout_blocks, Some(50));
+
+        let locked = ContractError::ReregistrationLocked { revoked_at: 150, unlocks_at: 200 };
+        assert_eq!(at(&mut deps, "bot", 199).unwrap_err(), locked);
+        assert_eq!(at(&mut deps, "mallory", 200).unwrap_err(), ContractError::Revoked { height: 150 });
+        at(&mut deps, "bot", 200).unwrap();
+
+        let entry = verify_root(&deps, [1u8; 32]).entry.unwrap();
+        assert_eq!((entry.registered_at, entry.revoked_at, entry.previous_registration), (200, None, Some(100)));
+        assert_eq!(entry.index, Some(1));
+        assert_eq!(config_response(&deps).total_roots, 1);
+
+        // The admin may re-register too, and plain registrations record nothing
+        revoke(&mut deps, "admin", 300, [1u8; 32]).unwrap();
+        at(&mut deps, "admin", 350).unwrap();
+        assert_eq!(verify_root(&deps, [1u8; 32]).entry.unwrap().previous_registration, Some(200));
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        assert_eq!(verify_root(&deps, [2u8; 32]).entry.unwrap().previous_registration, None);
+    }
+
+    fn update_ownership_as(deps: &mut MockDeps, sender: &str, action: cw_ownable::Action) -> Result<Response, ContractError> {
+        execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), ExecuteMsg::UpdateOwnership(action))
+    }
+
+    #[test]
+    fn test_ownership_transfer_needs_acceptance() {
+        let mut deps = setup();
+        let transfer = cw_ownable::Action::TransferOwnership { new_owner: "council".into(), expiry: None };
+        assert_eq!(update_ownership_as(&mut deps, "bot", transfer.clone()).unwrap_err(), ContractError::Ownership(cw_ownable::OwnershipError::NotOwner));
+        update_ownership_as(&mut deps, "admin", transfer).unwrap();
+
+        // The old owner keeps control until the transfer is accepted
+        let ownership: cw_ownable::Ownership<Addr> = from_json(query(deps.as_ref(), mock_env(), QueryMsg::Ownership {}).unwrap()).unwrap();
+        assert_eq!((ownership.owner, ownership.pending_owner), (Some(Addr::unchecked("admin")), Some(Addr::unchecked("council"))));
+        assert_eq!(config_response(&deps).admin, "admin");
+
+        update_ownership_as(&mut deps, "council", cw_ownable::Action::AcceptOwnership).unwrap();
+        assert_eq!(config_response(&deps).admin, "council");
+        let set = ExecuteMsg::SetAllowOverwrite { allow: true };
+        assert_eq!(execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), set.clone()).unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("council", &[]), set).unwrap();
+    }
+
+    #[test]
+    fn test_renounce_ownership_makes_registry_permissionless() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted: true, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::AddOperator { address: "ops".into() }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("ops", &[]), ExecuteMsg::Pause {}).unwrap();
+        assert!(register_root(&mut deps, [1u8; 32]).is_err());
+
+        update_ownership_as(&mut deps, "admin", cw_ownable::Action::RenounceOwnership).unwrap();
+        let config = config_response(&deps);
+        assert_eq!((config.admin.as_str(), config.restricted, config.paused), ("", false, false));
+        assert!(config.operators.is_empty());
+
+        // Anyone may register, and nobody may pause, allowlist or revoke
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        let no_owner = ContractError::Ownership(cw_ownable::OwnershipError::NoOwner);
+        for (sender, msg) in [
+            ("admin", ExecuteMsg::Pause {}),
+            ("ops", ExecuteMsg::Pause {}),
+            ("admin", ExecuteMsg::AddRegistrant { address: "bot".into() }),
+            ("admin", ExecuteMsg::SetAllowOverwrite { allow: true }),
+        ] {
+            assert_eq!(execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg).unwrap_err(), no_owner);
+        }
+        assert_eq!(revoke(&mut deps, "bot", 12_346, [1u8; 32]).unwrap_err(), no_owner);
+        let transfer = cw_ownable::Action::TransferOwnership { new_owner: "admin".into(), expiry: None };
+        assert!(update_ownership_as(&mut deps, "admin", transfer).is_err());
+    }
+
+    #[test]
+    fn test_migrate_moves_admin_to_ownership() {
+        let mut deps = setup();
+        // A registry whose admin still lives in the config
+        deps.storage.remove(b"ownership");
+        let mut legacy: serde_json::Value = from_json(deps.storage.get(CONFIG.as_slice()).unwrap()).unwrap();
+        legacy["admin"] = "council".into();
+        deps.storage.set(CONFIG.as_slice(), &serde_json::to_vec(&legacy).unwrap());
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::V1 {}).unwrap();
+        assert_eq!(config_response(&deps).admin, "council");
+        let raw = String::from_utf8(deps.storage.get(CONFIG.as_slice()).unwrap()).unwrap();
+        assert!(!raw.contains("admin"));
+
+        // A second migration leaves the owner alone
+        update_ownership_as(&mut deps, "council", cw_ownable::Action::RenounceOwnership).unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::V1 {}).unwrap();
+        assert_eq!(config_response(&deps).admin, "");
+    }
+
+    fn verify_root_at(deps: &MockDeps, height: u64, hash: [u8; 32]) -> VerifyResponse {
+        let mut env = mock_env();
+        env.block.height = height;
+        let msg = QueryMsg::VerifyRoot { hash: Some(Binary::from(hash)), hash_hex: None };
+        from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap()
+    }
+
+    fn prune_at(deps: &mut MockDeps, height: u64, limit: u32) -> Response {
+        let mut env = mock_env();
+        env.block.height = height;
+        execute(deps.as_mut(), env, mock_info("anyone", &[]), ExecuteMsg::PruneExpired { limit }).unwrap()
+    }
+
+    #[test]
+    fn test_expired_anchor_exists_until_pruned() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: Some(10), payload_version: None });
+
+        let live = verify_root_at(&deps, 109, [1u8; 32]);
+        assert_eq!((live.exists, live.expires_at, live.expired), (true, Some(110), false));
+        let expired = verify_root_at(&deps, 110, [1u8; 32]);
+        assert_eq!((expired.exists, expired.expired), (true, true));
+
+        // Nothing has expired yet at 109
+        assert_eq!(prune_at(&mut deps, 109, 10).events.len(), 0);
+        let res = prune_at(&mut deps, 110, 10);
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, EVENT_ANCHOR_PRUNED);
+        let gone = verify_root_at(&deps, 110, [1u8; 32]);
+        assert!(!gone.exists && !gone.expired);
+        assert_eq!(totals(&deps).total, 0);
+    }
+
+    #[test]
+    fn test_prune_expired_respects_limit_and_keeps_permanent_anchors() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: tags(&["staging"]), memo: None, ttl_blocks: Some(5), payload_version: None });
+        register_at(&mut deps, 101, ExecuteMsg::RegisterClaimScore { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: Some(1), payload_version: None });
+        register_at(&mut deps, 102, ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 103, ExecuteMsg::RegisterRoot { hash: Binary::from([4u8; 32]), tags: None, memo: None, ttl_blocks: Some(1_000), payload_version: None });
+        assert_eq!(totals(&deps).total, 4);
+
+        // The earliest expiry goes first
+        let res = prune_at(&mut deps, 500, 1);
+        assert_eq!(res.events[0].attributes[1].value, hex::encode([2u8; 32]));
+        assert_eq!(totals(&deps), TotalsResponse { roots: 3, claim_scores: 0, equation_proofs: 0, total: 3 });
+        prune_at(&mut deps, 500, 10);
+        assert_eq!(totals(&deps), TotalsResponse { roots: 2, claim_scores: 0, equation_proofs: 0, total: 2 });
+        assert_eq!(registrant_stats(&deps, "bot").by_type.roots, 2);
+        assert!(TAG_INDEX.prefix(("staging", "root")).keys(&deps.storage, None, None, Order::Ascending).next().is_none());
+        assert!(verify_root_at(&deps, 500, [3u8; 32]).exists);
+        assert!(verify_root_at(&deps, 500, [4u8; 32]).exists);
+
+        let zero = ExecuteMsg::RegisterRoot { hash: Binary::from([5u8; 32]), tags: None, memo: None, ttl_blocks: Some(0), payload_version: None };
+        assert_eq!(execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), zero).unwrap_err(), ContractError::ZeroTtl {});
+    }
+
+    #[test]
+    fn test_pruning_the_head_root_falls_back_to_newest_live_root() {
+        let mut deps = setup();
+        let latest = |deps: &MockDeps| {
+            let res: LatestRootResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetLatestRoot {}).unwrap()).unwrap();
+            res.latest.map(|head| head.hash_hex)
+        };
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        register_at(&mut deps, 101, ExecuteMsg::RegisterRoot { hash: Binary::from([2u8; 32]), tags: None, memo: None, ttl_blocks: Some(50), payload_version: None });
+        register_at(&mut deps, 102, ExecuteMsg::RegisterRoot { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: Some(10), payload_version: None });
+        assert_eq!(latest(&deps), Some(hex::encode([3u8; 32])));
+
+        // [2] is newer than [1] but expires before the prune runs
+        prune_at(&mut deps, 160, 1);
+        assert_eq!(latest(&deps), Some(hex::encode([1u8; 32])));
+        prune_at(&mut deps, 160, 10);
+        assert_eq!(latest(&deps), Some(hex::encode([1u8; 32])));
+    }
+
+    #[test]
+    fn test_pruning_a_root_drops_its_dependents_index() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: Some(10), payload_version: None });
+        let mut env = mock_env();
+        env.block.height = 105;
+        let link = ExecuteMsg::RegisterEquationProofLinked { hash: Binary::from([11u8; 32]), depends_on_root: Binary::from([1u8; 32]), tags: None, memo: None };
+        execute(deps.as_mut(), env, mock_info("bot", &[]), link).unwrap();
+        assert_eq!(dependents_of(&deps, [1u8; 32], None), vec![hex::encode([11u8; 32])]);
+
+        prune_at(&mut deps, 110, 10);
+        assert!(dependents_of(&deps, [1u8; 32], None).is_empty());
+        assert!(DEPENDENTS.prefix(hex::encode([1u8; 32]).as_str()).keys(&deps.storage, None, None, Order::Ascending).next().is_none());
+    }
+
+    #[test]
+    fn test_pruned_anchors_show_in_modified_since_until_reregistered() {
+        let mut deps = setup();
+        register_at(&mut deps, 100, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: Some(10), payload_version: None });
+        prune_at(&mut deps, 110, 10);
+
+        let changed = modified_since(&deps, 105);
+        assert_eq!(changed.len(), 1);
+        assert_eq!((changed[0].anchor_type, changed[0].hash_hex.as_str()), (AnchorType::MerkleRoot, hex::encode([1u8; 32]).as_str()));
+        assert_eq!((changed[0].last_modified_height, changed[0].entry.is_none()), (110, true));
+        assert_eq!(changed[0].change_kinds, vec!["prune"]);
+
+        // Registering the hash again replaces the tombstone
+        register_at(&mut deps, 120, ExecuteMsg::RegisterRoot { hash: Binary::from([1u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        let changed = modified_since(&deps, 105);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].last_modified_height, 120);
+        assert_eq!(changed[0].change_kinds, vec!["prune", "register"]);
+        assert!(changed[0].entry.is_some());
+    }
+
+    #[test]
+    fn test_is_degenerate_hash() {
+        assert!(is_degenerate_hash(&[0u8; 32]));
+        assert!(is_degenerate_hash(&compute_sha256(b"")));
+        assert!(!is_degenerate_hash(&compute_sha256(b"gravity")));
+        assert!(!is_degenerate_hash(&numbered(1)));
+    }
+
+    #[test]
+    fn test_register_rejects_degenerate_hashes() {
+        let mut deps = setup();
+        for hash in [[0u8; 32], compute_sha256(b"")] {
+            assert_eq!(register_root(&mut deps, hash).unwrap_err(), ContractError::DegenerateHash { hash_hex: hex::encode(hash) });
+        }
+        register_root(&mut deps, compute_sha256(b"gravity")).unwrap();
+    }
+
+    fn paged_hashes(query_page: impl Fn(Option<AnchorEntry>) -> Vec<AnchorEntry>) -> Vec<String> {
+        let mut seen = vec![];
+        let mut last = None;
+        loop {
+            let page = query_page(last);
+            if page.is_empty() {
+                return seen;
+            }
+            seen.extend(page.iter().map(|a| format!("{}:{}", a.anchor_type, a.hash_hex)));
+            last = page.last().cloned();
+        }
+    }
+
+    #[test]
+    fn test_listings_page_in_both_directions() {
+        let mut deps = setup();
+        for i in 0..7 {
+            register_at(&mut deps, 100 + i as u64 / 2, ExecuteMsg::RegisterRoot { hash: Binary::from(numbered(i)), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+        }
+        register_at(&mut deps, 101, ExecuteMsg::RegisterClaimScore { hash: Binary::from(numbered(2)), tags: None, memo: None, ttl_blocks: None, payload_version: None });
+
+        let roots = |order: SortOrder| {
+            paged_hashes(|last| {
+                let start_after = last.map(|a| Binary::from(hex::decode(a.hash_hex).unwrap()));
+                let msg = QueryMsg::ListRoots { start_after, limit: Some(3), order: Some(order) };
+                from_json::<ListResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().anchors
+            })
+        };
+        let by_registrant = |order: SortOrder| {
+            paged_hashes(|last| {
+                let start_after = last.map(|a| AnchorKey { anchor_type: a.anchor_type.to_string(), hash: Binary::from(hex::decode(a.hash_hex).unwrap()) });
+                let msg = QueryMsg::GetAnchorsByRegistrant { registrant: "bot".into(), anchor_type: None, start_after, limit: Some(3), order: Some(order) };
+                from_json::<AnchorListResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().anchors
+            })
+        };
+        let since = |order: SortOrder| {
+            paged_hashes(|last| {
+                let start_after = last.map(|a| (a.registered_at, Binary::from(hex::decode(a.hash_hex).unwrap())));
+                let msg = QueryMsg::GetAnchorsSince { height: 101, start_after, limit: Some(2), order: Some(order) };
+                from_json::<AnchorsSinceResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().anchors
+            })
+        };
+
+        for list in [&roots as &dyn Fn(SortOrder) -> Vec<String>, &by_registrant, &since] {
+            let ascending = list(SortOrder::Ascending);
+            let mut descending = list(SortOrder::Descending);
+            assert!(ascending.len() > 3);
+            descending.reverse();
+            assert_eq!(ascending, descending);
+        }
+        assert_eq!(roots(SortOrder::Ascending).len(), 7);
+        assert_eq!(by_registrant(SortOrder::Descending).len(), 8);
+        // Heights 101..=103 hold roots 2..=6 and the claim score
+        assert_eq!(since(SortOrder::Descending).len(), 6);
+    }
+
+    #[test]
+    fn test_payload_version_is_recorded_and_checked() {
+        let mut deps = setup();
+        register_root(&mut deps, [1u8; 32]).unwrap();
+        assert_eq!(verify_root(&deps, [1u8; 32]).payload_version, Some(1));
+        assert_eq!(verify_root(&deps, [2u8; 32]).payload_version, None);
+
+        let claim = |version| ExecuteMsg::RegisterClaimScore { hash: Binary::from([3u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: Some(version) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), claim(2)).unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedPayloadVersion { version: 2 });
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), claim(1)).unwrap();
+
+        // Entries stored before versioning read as version 1
+        let legacy = br#"{"hash_hex":"01","anchor_type":"root","registered_at":1,"registrant":"bot"}"#;
+        assert_eq!(from_json::<AnchorEntry>(legacy).unwrap().payload_version, 1);
+    }
+
+    fn root_metadata(deps: &MockDeps, hash: [u8; 32]) -> Option<MerkleRootPayload> {
+        let msg = QueryMsg::GetRootMetadata { hash: Binary::from(hash) };
+        from_json::<RootMetadataResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().payload
+    }
+
+    #[test]
+    fn test_register_root_with_metadata() {
+        let mut deps = setup();
+        let register = |deps: &mut MockDeps, payload: MerkleRootPayload| {
+            let msg = ExecuteMsg::RegisterRootWithMetadata { payload, tags: None, memo: None };
+            execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+        };
+        let table = |name: &str| TableHash { table_name: name.into(), hash_hex: hex::encode(compute_sha256(name.as_bytes())) };
+        let tables = Some(vec![table("claims"), table("scores")]);
+        let payload = MerkleRootPayload::new(hex::encode([1u8; 32]), 12, tables, Some(hex::encode([9u8; 32])));
+        register(&mut deps, payload.clone()).unwrap();
+        assert!(verify_root(&deps, [1u8; 32]).exists);
+        assert_eq!(root_metadata(&deps, [1u8; 32]), Some(payload));
+
+        register_root(&mut deps, [2u8; 32]).unwrap();
+        assert_eq!(root_metadata(&deps, [2u8; 32]), None);
+
+        let mut tampered = MerkleRootPayload::new(hex::encode([3u8; 32]), 12, None, None);
+        tampered.leaf_count = 13;
+        assert_eq!(register(&mut deps, tampered).unwrap_err(), ContractError::PayloadHashMismatch {});
+        let empty = MerkleRootPayload::new(hex::encode([3u8; 32]), 0, None, None);
+        assert_eq!(register(&mut deps, empty).unwrap_err(), ContractError::EmptyMerkleTree {});
+        let tables = (0..64u8).map(|i| TableHash { table_name: format!("table_{:02}", i), hash_hex: hex::encode([i; 32]) }).collect();
+        let oversized = MerkleRootPayload::new(hex::encode([3u8; 32]), 12, Some(tables), None);
+        assert!(matches!(register(&mut deps, oversized).unwrap_err(), ContractError::PayloadTooLarge { .. }));
+        assert!(!verify_root(&deps, [3u8; 32]).exists);
+    }
+
+    #[test]
+    fn test_claim_score_payload_history() {
+        let mut deps = setup();
+        let register = |deps: &mut MockDeps, payload: &ClaimScorePayload| {
+            let msg = ExecuteMsg::RegisterClaimScorePayload { payload: payload.clone(), tags: None, memo: None };
+            execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+        };
+        let first = ClaimScorePayload::new(7, 0.5, 1.25, 0.1, 3, 0, "stable".into());
+        let second = ClaimScorePayload::new(7, 0.75, 1.0, 0.2, 4, 1, "stable".into());
+        let other = ClaimScorePayload::new(8, 0.1, 0.0, 0.0, 1, 0, "volatile".into());
+        for payload in [&first, &other, &second] {
+            register(&mut deps, payload).unwrap();
+        }
+        assert!(verify_payload_query(&deps, QueryMsg::VerifyClaimScorePayload { payload: second.clone() }).registered);
+
+        let history = |deps: &MockDeps, start_after| {
+            let msg = QueryMsg::GetClaimScorePayloads { claim_id: 7, start_after, limit: None };
+            from_json::<ClaimPayloadsResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().payloads
+        };
+        assert_eq!(
+            history(&deps, None),
+            vec![ClaimPayloadRecord { sequence: 1, payload: first }, ClaimPayloadRecord { sequence: 2, payload: second.clone() }]
+        );
+        assert_eq!(history(&deps, Some(1)), vec![ClaimPayloadRecord { sequence: 2, payload: second }]);
+
+        // Scores must keep the fixed eight-decimal spelling
+        let mut loose = ClaimScorePayload::new(9, 0.5, 1.0, 0.0, 1, 0, "stable".into());
+        loose.composite_score = "0.5".into();
+        loose.refresh_hash();
+        assert!(matches!(register(&mut deps, &loose).unwrap_err(), ContractError::InvalidPayload(_)));
+        let mut out_of_range = ClaimScorePayload::new(9, 1.5, 1.0, 0.0, 1, 0, "stable".into());
+        out_of_range.refresh_hash();
+        assert!(matches!(register(&mut deps, &out_of_range).unwrap_err(), ContractError::InvalidPayload(_)));
+    }
+
+    #[test]
+    fn test_proofs_by_equation() {
+        let mut deps = setup();
+        let proof = |name: &str, stability: &str| {
+            EquationProofPayload::new(name.into(), "a".repeat(64), "b".repeat(64), stability.into(), 0.5, 0.5, true)
+        };
+        let register = |deps: &mut MockDeps, payload: EquationProofPayload| {
+            let msg = ExecuteMsg::RegisterEquationProofPayload { payload, tags: None, memo: None };
+            execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg)
+        };
+        let stable = proof("newton_gravity", "stable");
+        let marginal = proof("newton_gravity", "marginal");
+        for payload in [stable.clone(), marginal.clone(), proof("maxwell_gauss", "stable")] {
+            register(&mut deps, payload).unwrap();
+        }
+
+        let listed = |deps: &MockDeps, start_after: Option<Binary>| {
+            let msg = QueryMsg::GetProofsByEquation { equation_name: "newton_gravity".into(), start_after, limit: None };
+            from_json::<EquationProofsResponse>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap().proofs
+        };
+        let mut expected = vec![stable, marginal];
+        expected.sort_by_key(|payload| payload.hash_bytes());
+        assert_eq!(listed(&deps, None), expected);
+        let cursor = Binary::from(expected[0].hash_bytes());
+        assert_eq!(listed(&deps, Some(cursor)), expected[1..]);
+
+        let err = register(&mut deps, proof("ns:newton", "stable")).unwrap_err();
+        assert_eq!(err, ContractError::Text(TextError::InvalidCharacter { field: "equation_name".into(), ch: ':' }));
+        let err = register(&mut deps, proof(&"n".repeat(MAX_EQUATION_NAME_LEN + 1), "stable")).unwrap_err();
+        assert!(matches!(err, ContractError::FieldTooLong { .. }));
+    }
+
+    #[test]
+    fn test_execute_msg_into_cosmos_msg() {
+        let wasm_json = |msg: ExecuteMsg| match msg.into_cosmos_msg("registry").unwrap() {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, funds }) => {
+                assert_eq!((contract_addr.as_str(), funds.len()), ("registry", 0));
+                String::from_utf8(msg.to_vec()).unwrap()
+            }
+            other => panic!("unexpected message {:?}", other),
+        };
+        let hash = Binary::from([1u8; 32]);
+        let fields = format!(r#"{{"hash":"{}","tags":null,"memo":null,"ttl_blocks":null,"payload_version":null}}"#, hash.to_base64());
+        assert_eq!(wasm_json(ExecuteMsg::register_root(hash.clone())), format!(r#"{{"register_root":{}}}"#, fields));
+        assert_eq!(wasm_json(ExecuteMsg::register_claim_score(hash.clone())), format!(r#"{{"register_claim_score":{}}}"#, fields));
+        assert_eq!(wasm_json(ExecuteMsg::register_equation_proof(hash.clone())), format!(r#"{{"register_equation_proof":{}}}"#, fields));
+
+        let entry = BatchEntry { anchor_type: "root".into(), hash: hash.clone(), tags: None, memo: None };
+        assert_eq!(
+            wasm_json(ExecuteMsg::register_batch(vec![entry])),
+            format!(r#"{{"register_batch":{{"anchors":[{{"anchor_type":"root","hash":"{}","tags":null,"memo":null}}]}}}}"#, hash.to_base64())
+        );
+
+        // The built message round-trips into a registration the contract accepts
+        let mut deps = setup();
+        let CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) = ExecuteMsg::register_root(hash.clone()).into_cosmos_msg("registry").unwrap() else {
+            unreachable!()
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), from_json(&msg).unwrap()).unwrap();
+        assert!(verify_root(&deps, [1u8; 32]).exists);
+    }
+
+    #[test]
+    fn test_page_limits() {
+        let mut deps = setup();
+        for i in 0..(DEFAULT_LIMIT + 5) {
+            register_root(&mut deps, numbered(i)).unwrap();
+        }
+        let list = |limit: Option<u32>| query(deps.as_ref(), mock_env(), QueryMsg::ListRoots { start_after: None, limit, order: None });
+
+        let page: ListResponse = from_json(list(None).unwrap()).unwrap();
+        assert_eq!(page.anchors.len(), DEFAULT_LIMIT as usize);
+        let page: ListResponse = from_json(list(Some(MAX_LIMIT)).unwrap()).unwrap();
+        assert_eq!(page.anchors.len(), DEFAULT_LIMIT as usize + 5);
+        assert_eq!(list(Some(10_000)).unwrap_err(), ContractError::LimitTooLarge { limit: 10_000, max: MAX_LIMIT });
+
+        let msg = QueryMsg::GetAnchorsByRegistrant { registrant: "bot".into(), anchor_type: None, start_after: None, limit: Some(MAX_LIMIT + 1), order: None };
+        assert!(matches!(query(deps.as_ref(), mock_env(), msg).unwrap_err(), ContractError::LimitTooLarge { .. }));
+    }
+
+    #[test]
+    fn test_counter_overflow_fails_gracefully() {
+        let overflow = |counter: &str| ContractError::CounterOverflow { counter: counter.into() };
+        let mut deps = setup();
+        TOTAL_ANCHORS.save(deps.as_mut().storage, &u64::MAX).unwrap();
+        assert_eq!(register_root(&mut deps, [1u8; 32]).unwrap_err(), overflow("total_anchors"));
+
+        let mut deps = setup();
+        TYPE_COUNTS.save(deps.as_mut().storage, "root", &u64::MAX).unwrap();
+        assert_eq!(register_root(&mut deps, [1u8; 32]).unwrap_err(), overflow("type_counts"));
+
+        let mut deps = setup();
+        ANCHOR_SEQUENCE.save(deps.as_mut().storage, &u64::MAX).unwrap();
+        assert_eq!(register_root(&mut deps, [1u8; 32]).unwrap_err(), overflow("anchor_sequence"));
+
+        let mut deps = setup();
+        let stats = RegistrantStats { roots: u64::MAX, ..Default::default() };
+        REGISTRANT_STATS.save(deps.as_mut().storage, &Addr::unchecked("bot"), &stats).unwrap();
+        assert_eq!(register_root(&mut deps, [1u8; 32]).unwrap_err(), overflow("registrant_stats"));
     }
 }
@@ -0,0 +1,14 @@
+//! Regenerate the committed cross-implementation test vectors.
+//!
+//! Usage: cargo run --features test-vectors --bin gen_test_vectors [OUTPUT]
+
+use gravity_anchor_contracts::test_vectors::{generate, to_json, VECTORS_PATH};
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| format!("{}/{}", env!("CARGO_MANIFEST_DIR"), VECTORS_PATH));
+    let vectors = generate();
+    std::fs::write(&path, to_json(&vectors)).expect("failed to write test vectors");
+    println!("wrote {} payload and {} Merkle tree vectors to {}", vectors.payloads.len(), vectors.merkle_trees.len(), path);
+}
@@ -0,0 +1,270 @@
+//! Canonical Encoding – Shared primitives for payload canonicalization.
+//!
+//! Payloads have two canonical encodings:
+//!   - Text: the colon-delimited strings hashed by the payload builders
+//!     (e.g. "merkle_root:{root}:{leaves}:{prev}")
+//!   - Binary: a length-prefixed layout with no delimiter ambiguity
+//!
+//! Optional fields go through `encode_optional_hash` in both modes so that
+//! `None` and `Some` are treated identically by each encoder.
+
+use std::io::{self, Write};
+
+use thiserror::Error;
+
+/// Canonical encoding mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanonicalMode {
+    /// Colon-delimited text (the hashed v1 format)
+    Text,
+    /// Length-prefixed binary
+    Binary,
+}
+
+/// Presence byte for an absent optional field (binary mode).
+pub const ABSENT: u8 = 0x00;
+
+/// Presence byte for a present optional field (binary mode).
+pub const PRESENT: u8 = 0x01;
+
+/// Errors raised while decoding canonical bytes.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CanonicalError {
+    #[error("Unexpected end of input")]
+    Truncated,
+    #[error("Invalid presence byte {0:#04x}")]
+    InvalidPresence(u8),
+    #[error("Field is not valid UTF-8")]
+    InvalidUtf8,
+}
+
+/// Errors raised while parsing a text canonical string.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ParseError {
+    #[error("Expected a \"{expected}:\" canonical string")]
+    WrongPrefix { expected: &'static str },
+    #[error("Expected {expected} fields, found {found}")]
+    FieldCount { expected: usize, found: usize },
+    #[error(
+        "Expected {expected} fields, found {found}: a text field contains ':', which the \
+         colon-delimited form cannot represent unambiguously; use the length-prefixed \
+         binary canonical form instead"
+    )]
+    AmbiguousDelimiter { expected: usize, found: usize },
+    #[error("Invalid {field}: {value:?}")]
+    InvalidField { field: &'static str, value: String },
+    #[error("{field} out of range: {value}")]
+    OutOfRange { field: &'static str, value: String },
+}
+
+/// Split a text canonical string into the `count` fields following `prefix`.
+///
+/// More fields than expected means a free-text field contained the `:`
+/// delimiter, which is reported as `ParseError::AmbiguousDelimiter`.
+pub fn split_fields<'a>(
+    input: &'a str,
+    prefix: &'static str,
+    count: usize,
+) -> Result<Vec<&'a str>, ParseError> {
+    let rest = input
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .ok_or(ParseError::WrongPrefix { expected: prefix })?;
+    let fields: Vec<&str> = rest.split(':').collect();
+    let found = fields.len();
+    match found.cmp(&count) {
+        std::cmp::Ordering::Equal => Ok(fields),
+        std::cmp::Ordering::Greater => Err(ParseError::AmbiguousDelimiter { expected: count, found }),
+        std::cmp::Ordering::Less => Err(ParseError::FieldCount { expected: count, found }),
+    }
+}
+
+fn invalid(field: &'static str, value: &str) -> ParseError {
+    ParseError::InvalidField { field, value: value.to_string() }
+}
+
+/// Parse a fixed-precision (`{:.8}`) decimal, rejecting any other spelling.
+pub fn parse_fixed8(field: &'static str, value: &str) -> Result<f64, ParseError> {
+    let parsed: f64 = value.parse().map_err(|_| invalid(field, value))?;
+    if !parsed.is_finite() || format!("{:.8}", parsed) != value {
+        return Err(invalid(field, value));
+    }
+    Ok(parsed)
+}
+
+/// Parse a decimal u64 in its canonical spelling (no sign, no leading zeros).
+pub fn parse_u64(field: &'static str, value: &str) -> Result<u64, ParseError> {
+    let parsed: u64 = value.parse().map_err(|_| invalid(field, value))?;
+    if parsed.to_string() != value {
+        return Err(invalid(field, value));
+    }
+    Ok(parsed)
+}
+
+/// Require a 32-byte digest in lowercase hex.
+pub fn check_hex_digest(field: &'static str, value: &str) -> Result<(), ParseError> {
+    let is_hex = value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+    if value.len() != 64 || !is_hex {
+        return Err(invalid(field, value));
+    }
+    Ok(())
+}
+
+/// Require a non-empty free-text field.
+pub fn check_non_empty(field: &'static str, value: &str) -> Result<(), ParseError> {
+    if value.is_empty() {
+        return Err(invalid(field, value));
+    }
+    Ok(())
+}
+
+/// Write a u32 big-endian length prefix followed by the bytes.
+pub fn write_len_prefixed(out: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    out.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    out.write_all(bytes)
+}
+
+/// Read a u32 big-endian length-prefixed field, returning it and the bytes consumed.
+pub fn read_len_prefixed(input: &[u8]) -> Result<(&[u8], usize), CanonicalError> {
+    let len_bytes: [u8; 4] = input
+        .get(..4)
+        .ok_or(CanonicalError::Truncated)?
+        .try_into()
+        .map_err(|_| CanonicalError::Truncated)?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    let value = input.get(4..4 + len).ok_or(CanonicalError::Truncated)?;
+    Ok((value, 4 + len))
+}
+
+/// Encode an optional hash field.
+///
+///   Text:   `None` → "" ; `Some(h)` → h
+///   Binary: `None` → 0x00 ; `Some(h)` → 0x01 ‖ u32_be(len) ‖ h
+///
+/// `Some("")` is indistinguishable from `None` in text mode, so both modes
+/// normalize it to `None`.
+pub fn encode_optional_hash(
+    out: &mut impl Write,
+    value: &Option<String>,
+    mode: CanonicalMode,
+) -> io::Result<()> {
+    let value = value.as_deref().filter(|v| !v.is_empty());
+    match (mode, value) {
+        (CanonicalMode::Text, None) => Ok(()),
+        (CanonicalMode::Text, Some(v)) => out.write_all(v.as_bytes()),
+        (CanonicalMode::Binary, None) => out.write_all(&[ABSENT]),
+        (CanonicalMode::Binary, Some(v)) => {
+            out.write_all(&[PRESENT])?;
+            write_len_prefixed(out, v.as_bytes())
+        }
+    }
+}
+
+/// Decode an optional hash field written by `encode_optional_hash`,
+/// returning the value and the number of bytes consumed. In text mode the
+/// whole input is the field.
+pub fn decode_optional_hash(
+    input: &[u8],
+    mode: CanonicalMode,
+) -> Result<(Option<String>, usize), CanonicalError> {
+    match mode {
+        CanonicalMode::Text => {
+            let text = std::str::from_utf8(input).map_err(|_| CanonicalError::InvalidUtf8)?;
+            let value = (!text.is_empty()).then(|| text.to_string());
+            Ok((value, input.len()))
+        }
+        CanonicalMode::Binary => match input.first() {
+            None => Err(CanonicalError::Truncated),
+            Some(&ABSENT) => Ok((None, 1)),
+            Some(&PRESENT) => {
+                let (bytes, used) = read_len_prefixed(&input[1..])?;
+                let text = std::str::from_utf8(bytes).map_err(|_| CanonicalError::InvalidUtf8)?;
+                Ok((Some(text.to_string()), 1 + used))
+            }
+            Some(&other) => Err(CanonicalError::InvalidPresence(other)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(value: &Option<String>, mode: CanonicalMode) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode_optional_hash(&mut out, value, mode).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_none_and_some_distinct_in_both_modes() {
+        let some = Some("ab".repeat(32));
+        for mode in [CanonicalMode::Text, CanonicalMode::Binary] {
+            assert_ne!(encode(&None, mode), encode(&some, mode));
+        }
+    }
+
+    #[test]
+    fn test_empty_some_normalized_in_both_modes() {
+        for mode in [CanonicalMode::Text, CanonicalMode::Binary] {
+            assert_eq!(encode(&Some(String::new()), mode), encode(&None, mode));
+        }
+    }
+
+    #[test]
+    fn test_binary_layout() {
+        assert_eq!(encode(&None, CanonicalMode::Binary), vec![ABSENT]);
+        let encoded = encode(&Some("abcd".into()), CanonicalMode::Binary);
+        assert_eq!(encoded, [&[PRESENT, 0, 0, 0, 4][..], b"abcd"].concat());
+    }
+
+    #[test]
+    fn test_round_trip_both_modes() {
+        for value in [None, Some("c".repeat(64))] {
+            for mode in [CanonicalMode::Text, CanonicalMode::Binary] {
+                let encoded = encode(&value, mode);
+                let (decoded, used) = decode_optional_hash(&encoded, mode).unwrap();
+                assert_eq!(decoded, value);
+                assert_eq!(used, encoded.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_binary() {
+        assert_eq!(decode_optional_hash(&[], CanonicalMode::Binary), Err(CanonicalError::Truncated));
+        assert_eq!(
+            decode_optional_hash(&[0x02], CanonicalMode::Binary),
+            Err(CanonicalError::InvalidPresence(0x02))
+        );
+        assert_eq!(
+            decode_optional_hash(&[PRESENT, 0, 0, 0, 9, b'a'], CanonicalMode::Binary),
+            Err(CanonicalError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_split_fields() {
+        assert_eq!(split_fields("p:a:b", "p", 2).unwrap(), vec!["a", "b"]);
+        assert_eq!(split_fields("q:a:b", "p", 2), Err(ParseError::WrongPrefix { expected: "p" }));
+        assert_eq!(split_fields("p:a", "p", 2), Err(ParseError::FieldCount { expected: 2, found: 1 }));
+        let err = split_fields("p:a:b:c", "p", 2).unwrap_err();
+        assert_eq!(err, ParseError::AmbiguousDelimiter { expected: 2, found: 3 });
+        assert!(err.to_string().contains("length-prefixed"));
+    }
+
+    #[test]
+    fn test_parse_rejects_non_canonical_numbers() {
+        assert_eq!(parse_fixed8("x", "0.50000000"), Ok(0.5));
+        for bad in ["0.5", "+0.50000000", "NaN", "inf", ""] {
+            assert!(parse_fixed8("x", bad).is_err(), "{bad}");
+        }
+        assert_eq!(parse_u64("n", "42"), Ok(42));
+        for bad in ["042", "+42", "-1", ""] {
+            assert!(parse_u64("n", bad).is_err(), "{bad}");
+        }
+        assert!(check_hex_digest("h", &"a".repeat(64)).is_ok());
+        assert!(check_hex_digest("h", &"A".repeat(64)).is_err());
+        assert!(check_hex_digest("h", &"a".repeat(63)).is_err());
+    }
+}
@@ -1,16 +1,27 @@
-/// Claim Score Anchor – Deterministic anchoring for epistemic claim scores.
-///
-/// Encapsulates Bayesian confidence scores, mutation entropy metrics,
-/// and citation density data into a deterministic, hashable payload
-/// for on-chain integrity anchoring.
+//! Claim Score Anchor – Deterministic anchoring for epistemic claim scores.
+//!
+//! Encapsulates Bayesian confidence scores, mutation entropy metrics,
+//! and citation density data into a deterministic, hashable payload
+//! for on-chain integrity anchoring.
 
+#[cfg(feature = "schema")]
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::anchor_registry::compute_sha256;
+use crate::canonical::{check_non_empty, parse_fixed8, parse_u64, split_fields, ParseError};
+
+/// Errors raised while building claim score payloads.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PayloadError {
+    #[error("Score at index {index} is not finite")]
+    NonFiniteScore { index: usize },
+}
 
 /// A claim score anchor payload.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct ClaimScorePayload {
     /// Claim ID from the evidence graph
     pub claim_id: u64,
@@ -48,15 +59,7 @@ impl ClaimScorePayload {
         let entropy_str = format!("{:.8}", shannon_entropy);
         let density_str = format!("{:.8}", citation_density);
 
-        let canonical = format!(
-            "claim_score:{}:{}:{}:{}:{}:{}:{}",
-            claim_id, composite_str, entropy_str, density_str,
-            support_count, contradict_count, stability_class
-        );
-        let hash = compute_sha256(canonical.as_bytes());
-        let payload_hash = hex::encode(hash);
-
-        ClaimScorePayload {
+        let mut payload = ClaimScorePayload {
             claim_id,
             composite_score: composite_str,
             shannon_entropy: entropy_str,
@@ -64,19 +67,75 @@ impl ClaimScorePayload {
             support_count,
             contradict_count,
             stability_class,
-            payload_hash,
-        }
+            payload_hash: String::new(),
+        };
+        payload.payload_hash = hex::encode(compute_sha256(payload.to_canonical_string().as_bytes()));
+        payload
     }
 
-    /// Verify payload integrity by recomputing the hash.
-    pub fn verify(&self) -> bool {
-        let canonical = format!(
+    /// Canonical string the payload hash is computed over.
+    pub fn to_canonical_string(&self) -> String {
+        format!(
             "claim_score:{}:{}:{}:{}:{}:{}:{}",
             self.claim_id, self.composite_score, self.shannon_entropy,
             self.citation_density, self.support_count, self.contradict_count,
             self.stability_class
-        );
-        let hash = compute_sha256(canonical.as_bytes());
+        )
+    }
+
+    /// Free-form text fields, for length validation before storage.
+    pub fn text_fields(&self) -> [(&'static str, &str); 5] {
+        [
+            ("composite_score", &self.composite_score),
+            ("shannon_entropy", &self.shannon_entropy),
+            ("citation_density", &self.citation_density),
+            ("stability_class", &self.stability_class),
+            ("payload_hash", &self.payload_hash),
+        ]
+    }
+
+    /// Parse a text canonical string, recomputing `payload_hash`.
+    ///
+    /// Scores must use the `{:.8}` spelling and the composite must lie in
+    /// [0, 1]; entropy and density must be non-negative. A stability class
+    /// containing `:` cannot be parsed back.
+    pub fn from_canonical_string(input: &str) -> Result<Self, ParseError> {
+        let f = split_fields(input, "claim_score", 7)?;
+        check_scores(f[1], f[2], f[3])?;
+        check_non_empty("stability_class", f[6])?;
+
+        let mut payload = ClaimScorePayload {
+            claim_id: parse_u64("claim_id", f[0])?,
+            composite_score: f[1].into(),
+            shannon_entropy: f[2].into(),
+            citation_density: f[3].into(),
+            support_count: parse_u64("support_count", f[4])?,
+            contradict_count: parse_u64("contradict_count", f[5])?,
+            stability_class: f[6].into(),
+            payload_hash: String::new(),
+        };
+        payload.payload_hash = hex::encode(compute_sha256(payload.to_canonical_string().as_bytes()));
+        Ok(payload)
+    }
+
+    /// Recompute `payload_hash` from the current fields, returning whether
+    /// the stored hash was stale.
+    pub fn refresh_hash(&mut self) -> bool {
+        let fresh = hex::encode(compute_sha256(self.to_canonical_string().as_bytes()));
+        let stale = fresh != self.payload_hash;
+        self.payload_hash = fresh;
+        stale
+    }
+
+    /// Require the `{:.8}` score spelling, a composite in [0, 1] and
+    /// non-negative entropy and density, as `from_canonical_string` does.
+    pub fn check_scores(&self) -> Result<(), ParseError> {
+        check_scores(&self.composite_score, &self.shannon_entropy, &self.citation_density)
+    }
+
+    /// Verify payload integrity by recomputing the hash.
+    pub fn verify(&self) -> bool {
+        let hash = compute_sha256(self.to_canonical_string().as_bytes());
         hex::encode(hash) == self.payload_hash
     }
 
@@ -91,6 +150,58 @@ impl ClaimScorePayload {
     }
 }
 
+fn check_scores(composite: &str, entropy: &str, density: &str) -> Result<(), ParseError> {
+    if !(0.0..=1.0).contains(&parse_fixed8("composite_score", composite)?) {
+        return Err(ParseError::OutOfRange { field: "composite_score", value: composite.into() });
+    }
+    for (field, value) in [("shannon_entropy", entropy), ("citation_density", density)] {
+        if parse_fixed8(field, value)? < 0.0 {
+            return Err(ParseError::OutOfRange { field, value: value.into() });
+        }
+    }
+    Ok(())
+}
+
+/// Whether two payloads anchor contradictory scores for the same claim.
+///
+/// True when the claim ids match and the parsed composite scores differ by
+/// more than `threshold`. Unparseable scores never count as a conflict.
+pub fn detect_conflict(a: &ClaimScorePayload, b: &ClaimScorePayload, threshold: f64) -> bool {
+    if a.claim_id != b.claim_id {
+        return false;
+    }
+    match (a.composite_score.parse::<f64>(), b.composite_score.parse::<f64>()) {
+        (Ok(x), Ok(y)) => (x - y).abs() > threshold,
+        _ => false,
+    }
+}
+
+/// Hash a vector of sub-scores deterministically.
+///
+/// Each score is fixed-precision formatted (8 decimals) and length-prefixed:
+///   "score_vector:" ‖ u32_be(count) ‖ { u32_be(len) ‖ score_str }*
+///
+/// Order is significant. Non-finite scores (NaN, ±inf) are rejected.
+pub fn score_vector_hash(scores: &[f64]) -> Result<[u8; 32], PayloadError> {
+    Ok(compute_sha256(&score_vector_canonical(scores)?))
+}
+
+/// Canonical bytes hashed by `score_vector_hash`.
+pub(crate) fn score_vector_canonical(scores: &[f64]) -> Result<Vec<u8>, PayloadError> {
+    let mut canonical = Vec::new();
+    canonical.extend_from_slice(b"score_vector:");
+    canonical.extend_from_slice(&(scores.len() as u32).to_be_bytes());
+    for (index, score) in scores.iter().enumerate() {
+        if !score.is_finite() {
+            return Err(PayloadError::NonFiniteScore { index });
+        }
+        let formatted = format!("{:.8}", score);
+        canonical.extend_from_slice(&(formatted.len() as u32).to_be_bytes());
+        canonical.extend_from_slice(formatted.as_bytes());
+    }
+    Ok(canonical)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,7 +230,7 @@ mod tests {
     fn test_claim_score_fixed_precision() {
         let payload = ClaimScorePayload::new(1, 0.1 + 0.2, 0.0, 0.0, 0, 0, "unknown".into());
         // Fixed precision should produce consistent string
-        assert!(payload.composite_score.len() > 0);
+        assert!(!payload.composite_score.is_empty());
         assert!(payload.verify());
     }
 
@@ -129,4 +240,79 @@ mod tests {
         let bytes = payload.hash_bytes();
         assert_eq!(bytes.len(), 32);
     }
+
+    #[test]
+    fn test_score_vector_hash_deterministic() {
+        let scores = [0.85, 0.1 + 0.2, 1.0];
+        assert_eq!(score_vector_hash(&scores).unwrap(), score_vector_hash(&scores).unwrap());
+    }
+
+    #[test]
+    fn test_score_vector_hash_order_sensitive() {
+        let h1 = score_vector_hash(&[0.1, 0.2, 0.3]).unwrap();
+        let h2 = score_vector_hash(&[0.3, 0.2, 0.1]).unwrap();
+        assert_ne!(h1, h2);
+    }
+
+    #[test]
+    fn test_score_vector_hash_length_prefixed() {
+        let empty = score_vector_hash(&[]).unwrap();
+        let single = score_vector_hash(&[0.0]).unwrap();
+        assert_ne!(empty, single);
+        assert_ne!(single, score_vector_hash(&[0.0, 0.0]).unwrap());
+    }
+
+    #[test]
+    fn test_score_vector_hash_rejects_non_finite() {
+        assert_eq!(
+            score_vector_hash(&[0.5, f64::NAN]),
+            Err(PayloadError::NonFiniteScore { index: 1 })
+        );
+        assert!(score_vector_hash(&[f64::INFINITY]).is_err());
+        assert!(score_vector_hash(&[f64::NEG_INFINITY]).is_err());
+    }
+
+    #[test]
+    fn test_detect_conflict() {
+        let a = ClaimScorePayload::new(3, 0.90, 0.5, 0.5, 1, 0, "stable".into());
+        let far = ClaimScorePayload::new(3, 0.20, 0.5, 0.5, 1, 0, "stable".into());
+        let near = ClaimScorePayload::new(3, 0.88, 0.5, 0.5, 1, 0, "stable".into());
+        let other = ClaimScorePayload::new(4, 0.20, 0.5, 0.5, 1, 0, "stable".into());
+        assert!(detect_conflict(&a, &far, 0.25));
+        assert!(!detect_conflict(&a, &near, 0.25));
+        assert!(!detect_conflict(&a, &other, 0.25));
+    }
+
+    #[test]
+    fn test_claim_score_canonical_round_trip() {
+        let payload = ClaimScorePayload::new(42, 0.92, 1.5, 0.88, 10, 1, "converging".into());
+        let parsed = ClaimScorePayload::from_canonical_string(&payload.to_canonical_string()).unwrap();
+        assert_eq!(parsed, payload);
+    }
+
+    #[test]
+    fn test_claim_score_canonical_rejects_malformed() {
+        let parse = ClaimScorePayload::from_canonical_string;
+        assert!(matches!(parse("claim_score:1:0.5:0.5"), Err(ParseError::FieldCount { .. })));
+        assert!(matches!(
+            parse("claim_score:1:1.50000000:0.00000000:0.00000000:1:0:stable"),
+            Err(ParseError::OutOfRange { field: "composite_score", .. })
+        ));
+        assert!(parse("claim_score:1:0.5:0.00000000:0.00000000:1:0:stable").is_err());
+        let colon = ClaimScorePayload::new(1, 0.5, 0.5, 0.5, 1, 0, "a:b".into());
+        assert!(matches!(
+            parse(&colon.to_canonical_string()),
+            Err(ParseError::AmbiguousDelimiter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_claim_score_refresh_hash() {
+        let mut payload = ClaimScorePayload::new(1, 0.5, 0.5, 0.5, 3, 3, "volatile".into());
+        assert!(!payload.refresh_hash());
+        payload.support_count = 4;
+        assert!(payload.refresh_hash());
+        assert!(payload.verify());
+        assert_eq!(payload, ClaimScorePayload::new(1, 0.5, 0.5, 0.5, 4, 3, "volatile".into()));
+    }
 }
@@ -1,15 +1,18 @@
-/// Equation Proof Anchor – Deterministic anchoring for formal mathematical proofs.
-///
-/// Encapsulates equation proof trees, stability analyses, and optimization
-/// results into a deterministic, hashable payload for on-chain anchoring.
+//! Equation Proof Anchor – Deterministic anchoring for formal mathematical proofs.
+//!
+//! Encapsulates equation proof trees, stability analyses, and optimization
+//! results into a deterministic, hashable payload for on-chain anchoring.
 
+#[cfg(feature = "schema")]
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::anchor_registry::compute_sha256;
+use crate::canonical::{check_hex_digest, check_non_empty, parse_fixed8, split_fields, ParseError};
 
 /// An equation proof anchor payload.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct EquationProofPayload {
     /// Name of the equation
     pub equation_name: String,
@@ -45,17 +48,8 @@ impl EquationProofPayload {
     ) -> Self {
         let si_str = format!("{:.8}", solvability_index);
         let cr_str = format!("{:.8}", compression_ratio);
-        let dim_str = if dimensional_valid { "1" } else { "0" };
 
-        let canonical = format!(
-            "equation_proof:{}:{}:{}:{}:{}:{}:{}",
-            equation_name, equation_hash, proof_tree_hash,
-            stability_class, si_str, cr_str, dim_str
-        );
-        let hash = compute_sha256(canonical.as_bytes());
-        let payload_hash = hex::encode(hash);
-
-        EquationProofPayload {
+        let mut payload = EquationProofPayload {
             equation_name,
             equation_hash,
             proof_tree_hash,
@@ -63,20 +57,87 @@ impl EquationProofPayload {
             solvability_index: si_str,
             compression_ratio: cr_str,
             dimensional_valid,
-            payload_hash,
-        }
+            payload_hash: String::new(),
+        };
+        payload.payload_hash = hex::encode(compute_sha256(payload.to_canonical_string().as_bytes()));
+        payload
     }
 
-    /// Verify payload integrity by recomputing the hash.
-    pub fn verify(&self) -> bool {
+    /// Canonical string the payload hash is computed over.
+    pub fn to_canonical_string(&self) -> String {
         let dim_str = if self.dimensional_valid { "1" } else { "0" };
-        let canonical = format!(
+        format!(
             "equation_proof:{}:{}:{}:{}:{}:{}:{}",
             self.equation_name, self.equation_hash, self.proof_tree_hash,
             self.stability_class, self.solvability_index,
             self.compression_ratio, dim_str
-        );
-        let hash = compute_sha256(canonical.as_bytes());
+        )
+    }
+
+    /// Free-form text fields, for length validation before storage.
+    pub fn text_fields(&self) -> [(&'static str, &str); 7] {
+        [
+            ("equation_name", &self.equation_name),
+            ("equation_hash", &self.equation_hash),
+            ("proof_tree_hash", &self.proof_tree_hash),
+            ("stability_class", &self.stability_class),
+            ("solvability_index", &self.solvability_index),
+            ("compression_ratio", &self.compression_ratio),
+            ("payload_hash", &self.payload_hash),
+        ]
+    }
+
+    /// Parse a text canonical string, recomputing `payload_hash`.
+    ///
+    /// Both digests must be 64 lowercase hex characters, the solvability
+    /// index must lie in [0, 1] and the compression ratio be non-negative.
+    /// Names or classes containing `:` cannot be parsed back.
+    pub fn from_canonical_string(input: &str) -> Result<Self, ParseError> {
+        let f = split_fields(input, "equation_proof", 7)?;
+        check_non_empty("equation_name", f[0])?;
+        check_hex_digest("equation_hash", f[1])?;
+        check_hex_digest("proof_tree_hash", f[2])?;
+        check_non_empty("stability_class", f[3])?;
+        if !(0.0..=1.0).contains(&parse_fixed8("solvability_index", f[4])?) {
+            return Err(ParseError::OutOfRange { field: "solvability_index", value: f[4].into() });
+        }
+        if parse_fixed8("compression_ratio", f[5])? < 0.0 {
+            return Err(ParseError::OutOfRange { field: "compression_ratio", value: f[5].into() });
+        }
+        let dimensional_valid = match f[6] {
+            "1" => true,
+            "0" => false,
+            other => {
+                return Err(ParseError::InvalidField { field: "dimensional_valid", value: other.into() })
+            }
+        };
+
+        let mut payload = EquationProofPayload {
+            equation_name: f[0].into(),
+            equation_hash: f[1].into(),
+            proof_tree_hash: f[2].into(),
+            stability_class: f[3].into(),
+            solvability_index: f[4].into(),
+            compression_ratio: f[5].into(),
+            dimensional_valid,
+            payload_hash: String::new(),
+        };
+        payload.payload_hash = hex::encode(compute_sha256(payload.to_canonical_string().as_bytes()));
+        Ok(payload)
+    }
+
+    /// Recompute `payload_hash` from the current fields, returning whether
+    /// the stored hash was stale.
+    pub fn refresh_hash(&mut self) -> bool {
+        let fresh = hex::encode(compute_sha256(self.to_canonical_string().as_bytes()));
+        let stale = fresh != self.payload_hash;
+        self.payload_hash = fresh;
+        stale
+    }
+
+    /// Verify payload integrity by recomputing the hash.
+    pub fn verify(&self) -> bool {
+        let hash = compute_sha256(self.to_canonical_string().as_bytes());
         hex::encode(hash) == self.payload_hash
     }
 
@@ -151,4 +212,39 @@ mod tests {
         );
         assert_ne!(stable.payload_hash, unstable.payload_hash);
     }
+
+    #[test]
+    fn test_equation_proof_canonical_round_trip() {
+        let payload = EquationProofPayload::new(
+            "newton_gravity".into(), "a".repeat(64), "b".repeat(64),
+            "stable".into(), 0.95, 0.45, true,
+        );
+        let parsed = EquationProofPayload::from_canonical_string(&payload.to_canonical_string()).unwrap();
+        assert_eq!(parsed, payload);
+    }
+
+    #[test]
+    fn test_equation_proof_canonical_rejects_ambiguous_name() {
+        let payload = EquationProofPayload::new(
+            "ns:newton".into(), "a".repeat(64), "b".repeat(64),
+            "stable".into(), 0.95, 0.45, true,
+        );
+        let err = EquationProofPayload::from_canonical_string(&payload.to_canonical_string()).unwrap_err();
+        assert!(matches!(err, ParseError::AmbiguousDelimiter { expected: 7, found: 8 }));
+
+        let bad_flag = format!("equation_proof:eq:{}:{}:stable:0.50000000:0.50000000:2", "a".repeat(64), "b".repeat(64));
+        assert!(EquationProofPayload::from_canonical_string(&bad_flag).is_err());
+    }
+
+    #[test]
+    fn test_equation_proof_refresh_hash() {
+        let mut payload = EquationProofPayload::new(
+            "eq".into(), "a".repeat(64), "b".repeat(64),
+            "stable".into(), 0.5, 0.5, true,
+        );
+        assert!(!payload.refresh_hash());
+        payload.payload_hash = "0".repeat(64);
+        assert!(payload.refresh_hash());
+        assert!(payload.verify());
+    }
 }
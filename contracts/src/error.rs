@@ -0,0 +1,214 @@
+//! Contract Errors – Typed failures returned by the registry entry points.
+
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+use crate::canonical::ParseError;
+use crate::limits::TextError;
+
+/// Errors returned by the registry entry points.
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{field} is {len} bytes, exceeding the {max}-byte limit")]
+    FieldTooLong { field: String, len: usize, max: usize },
+
+    #[error("{0}")]
+    Text(TextError),
+
+    #[error("This contract does not accept funds")]
+    FundsNotAccepted {},
+
+    #[error("Unauthorized: sender may not perform this action")]
+    Unauthorized {},
+
+    #[error("{0}")]
+    Ownership(#[from] cw_ownable::OwnershipError),
+
+    #[error("Unknown anchor type: {value}")]
+    UnknownAnchorType { value: String },
+
+    #[error("Hash length {got} is not an accepted digest length")]
+    InvalidHashLength { got: usize },
+
+    #[error("Accepted hash lengths {lengths:?} must be distinct values from 20, 32 and 64")]
+    InvalidHashLengths { lengths: Vec<u32> },
+
+    #[error("Hash {hash_hex} is a sentinel value, not a real digest")]
+    DegenerateHash { hash_hex: String },
+
+    #[error("Hash is blocklisted: {reason}")]
+    Blocklisted { reason: String },
+
+    #[error("Hash hex must be 64 lowercase hex characters, got {value:?}")]
+    InvalidHashHex { value: String },
+
+    #[error("Provide exactly one of hash and hash_hex")]
+    ExactlyOneHash {},
+
+    #[error("Hash {hash_hex} is not blocklisted")]
+    NotBlocked { hash_hex: String },
+
+    #[error("Hash {hash_hex} already registered at height {height} by {registrant}")]
+    AlreadyRegistered { hash_hex: String, height: u64, registrant: String },
+
+    #[error("No {anchor_type} anchor for hash {hash_hex}")]
+    AnchorNotFound { anchor_type: String, hash_hex: String },
+
+    #[error("Namespace {namespace} is reserved for a built-in anchor type")]
+    ReservedNamespace { namespace: String },
+
+    #[error("Previous root {hash_hex} is not a registered root")]
+    UnknownPreviousRoot { hash_hex: String },
+
+    #[error("Previous root {hash_hex} was revoked at height {height}")]
+    PreviousRootRevoked { hash_hex: String, height: u64 },
+
+    #[error("Dependency {hash_hex} is not a registered root")]
+    UnknownDependency { hash_hex: String },
+
+    #[error("Dependency {hash_hex} was revoked at height {height}")]
+    DependencyRevoked { hash_hex: String, height: u64 },
+
+    #[error("The registrant of an anchor may not attest to it")]
+    SelfAttestation {},
+
+    #[error("{attester} already attested to this anchor")]
+    AlreadyAttested { attester: String },
+
+    #[error("Anchor is already revoked at height {height}")]
+    AlreadyRevoked { height: u64 },
+
+    #[error("restricted and public_registration disagree")]
+    ConflictingRegistrationMode {},
+
+    #[error("A root cannot supersede itself")]
+    SelfSupersession {},
+
+    #[error("Root {hash_hex} is already superseded by {successor}")]
+    AlreadySuperseded { hash_hex: String, successor: String },
+
+    #[error("Payload hash does not match its contents")]
+    PayloadHashMismatch {},
+
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(ParseError),
+
+    #[error("Payload is {size} bytes, exceeding the {max}-byte limit")]
+    PayloadTooLarge { size: usize, max: usize },
+
+    #[error("A Merkle root must cover at least one leaf")]
+    EmptyMerkleTree {},
+
+    #[error("Batch must contain 1 to {max} anchors, got {got}")]
+    InvalidBatchSize { max: usize, got: usize },
+
+    #[error("Batch of {got} anchors exceeds the limit of {max}")]
+    BatchTooLarge { got: usize, max: usize },
+
+    #[error("max_batch_size {value} exceeds the ceiling of {max}")]
+    MaxBatchSizeTooLarge { value: u32, max: u32 },
+
+    #[error("subscriber_gas_limit must be between 1 and {max}, got {value}")]
+    InvalidSubscriberGasLimit { value: u64, max: u64 },
+
+    #[error("Duplicate anchor in batch: {anchor_type} {hash_hex}")]
+    DuplicateInBatch { anchor_type: String, hash_hex: String },
+
+    #[error("Original height and time may not be in the future")]
+    FutureImport {},
+
+    #[error("{what} accepts at most {max} items, got {got}")]
+    TooManyItems { what: &'static str, max: usize, got: usize },
+
+    #[error("Counter {counter} would overflow")]
+    CounterOverflow { counter: String },
+
+    #[error("Proof step side must be \"left\" or \"right\", got {side:?}")]
+    InvalidProofSide { side: String },
+
+    #[error("Page limit {limit} exceeds the maximum of {max}")]
+    LimitTooLarge { limit: u32, max: u32 },
+
+    #[error("Cursor is for {cursor} anchors but the query lists {anchor_type}")]
+    CursorTypeMismatch { cursor: String, anchor_type: String },
+
+    #[error("Histogram needs bucket_blocks > 0 and 1-{max_buckets} buckets")]
+    InvalidHistogram { max_buckets: u32 },
+
+    #[error("Histogram span {span} exceeds {max} blocks")]
+    HistogramSpanTooLarge { span: u64, max: u64 },
+
+    #[error("Hash was revoked at height {height} and may not be re-registered")]
+    Revoked { height: u64 },
+
+    #[error("Hash was revoked at height {revoked_at} and may be re-registered from height {unlocks_at}")]
+    ReregistrationLocked { revoked_at: u64, unlocks_at: u64 },
+
+    #[error("ttl_blocks must be at least 1")]
+    ZeroTtl {},
+
+    #[error("Payload version {version} is not supported")]
+    UnsupportedPayloadVersion { version: u16 },
+
+    #[error("Sender {sender} is not an allowed registrant")]
+    NotRegistrant { sender: String },
+
+    #[error("Address {address} is not a registrant")]
+    UnknownRegistrant { address: String },
+
+    #[error("Registrant may register at most {max} anchors per block")]
+    RateLimited { max: u32 },
+
+    #[error("Registrant {registrant} has used {used} of its {max}-anchor quota")]
+    QuotaExceeded { registrant: String, max: u64, used: u64 },
+
+    #[error("Contract {contract} is not a subscriber")]
+    UnknownSubscriber { contract: String },
+
+    #[error("Address {address} is not an operator")]
+    UnknownOperator { address: String },
+
+    #[error("Registry is paused")]
+    Paused {},
+
+    #[error("Cannot migrate from contract {contract}")]
+    WrongContract { contract: String },
+
+    #[error("Cannot migrate from version {stored} to older version {current}")]
+    Downgrade { stored: String, current: String },
+
+    #[error("Presence entries are still being backfilled; finish MigrateMsg::BackfillPresence first")]
+    PresenceBackfillPending {},
+
+    #[error("The per-type height index is still being backfilled; finish MigrateMsg::BackfillTypeIndex first")]
+    TypeIndexBackfillPending {},
+
+    #[error("No live {anchor_type} among the first {scanned} registrations; revoked and expired ones must be pruned first")]
+    OldestScanTruncated { anchor_type: String, scanned: usize },
+
+    #[error("Anchor relay channels must be unordered")]
+    OrderedChannel {},
+
+    #[error("Unsupported IBC channel version {version}, expected {expected}")]
+    InvalidIbcVersion { version: String, expected: String },
+
+    #[error("No open anchor relay channel {channel_id}")]
+    UnknownChannel { channel_id: String },
+
+    #[error("IBC counterparty port {port_id} on {connection_id} is not allowed")]
+    CounterpartyNotAllowed { connection_id: String, port_id: String },
+}
+
+impl From<TextError> for ContractError {
+    fn from(err: TextError) -> Self {
+        match err {
+            TextError::FieldTooLong { field, len, max } => {
+                ContractError::FieldTooLong { field, len, max }
+            }
+            other => ContractError::Text(other),
+        }
+    }
+}
@@ -0,0 +1,574 @@
+//! IBC Relay – Mirror anchors between registries on different chains.
+//!
+//! Registries connect over unordered channels with version `IBC_VERSION`, and
+//! only to counterparties the admin has allowed with
+//! `ExecuteMsg::AllowIbcCounterparty`.
+//! Anchors are independent of one another and receiving one is idempotent,
+//! so ordering buys nothing, while a single timeout on an ordered channel
+//! would close it and halt every later relay.
+//!
+//! `ExecuteMsg::RelayAnchor` sends a registered anchor as a versioned
+//! `PacketData`, built from the stored entry so it cannot be forged. The
+//! receiving registry stores it tagged with the channel it arrived on and
+//! acknowledges with the `ibc_ack` envelope. The receiving registry itself is
+//! the local registrant of a relayed anchor, with the origin registrant kept
+//! alongside, so relays neither count against anyone's quota nor grant
+//! revoke or supersede rights. Relaying a hash the receiver already holds
+//! acknowledges the stored entry unchanged; revoked, blocklisted or malformed
+//! anchors get an error ack and write nothing.
+
+use cosmwasm_std::{
+    entry_point, from_json, to_json_binary, Binary, DepsMut, Env, Ibc3ChannelOpenResponse, IbcBasicResponse,
+    MessageInfo, Storage,
+    IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcMsg,
+    IbcOrder, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, IbcTimeout, Response,
+};
+use cw_storage_plus::Map;
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::anchor_registry::{
+    anchor_receipt, check_hash, count_added, ensure_admin, ensure_not_paused, may_load_anchor, notify_subscribers,
+    parse_anchor_type, registered_event, write_anchor, AnchorEntry, AnchorType, CONFIG,
+};
+use crate::error::ContractError;
+use crate::ibc_ack::{error_ack_bytes, AckEnvelope, AnchorAck};
+use crate::limits::validate_text;
+
+/// Channel version both ends must agree on
+pub const IBC_VERSION: &str = "gravity-anchor-1";
+
+/// Seconds a relayed packet may wait for delivery before timing out
+pub const PACKET_TIMEOUT_SECONDS: u64 = 60 * 60;
+
+/// Connected relay channels, by local channel id
+pub const CHANNELS: Map<&str, IbcChannel> = Map::new("ibc_channels");
+
+/// Counterparties the admin allows channels to: (connection id,
+/// counterparty port id) → ()
+pub const ALLOWED_COUNTERPARTIES: Map<(&str, &str), ()> = Map::new("ibc_allowed_counterparties");
+
+/// An anchor as relayed between registries.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorPacket {
+    pub anchor_type: AnchorType,
+    /// The 32-byte SHA-256 hash
+    pub hash: Binary,
+    /// Registrant on the origin chain
+    pub origin_registrant: String,
+    /// Block height of the registration on the origin chain
+    pub origin_height: u64,
+}
+
+/// Packet payload, tagged with its format version.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PacketData {
+    V1(AnchorPacket),
+}
+
+#[entry_point]
+pub fn ibc_channel_open(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    check_channel(msg.channel(), msg.counterparty_version())?;
+    check_counterparty(deps.storage, msg.channel())?;
+    Ok(Some(Ibc3ChannelOpenResponse { version: IBC_VERSION.to_string() }))
+}
+
+#[entry_point]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    check_channel(channel, msg.counterparty_version())?;
+    check_counterparty(deps.storage, channel)?;
+    CHANNELS.save(deps.storage, &channel.endpoint.channel_id, channel)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "channel_connect")
+        .add_attribute("channel_id", &channel.endpoint.channel_id)
+        .add_attribute("counterparty_channel_id", &channel.counterparty_endpoint.channel_id))
+}
+
+#[entry_point]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel_id = &msg.channel().endpoint.channel_id;
+    CHANNELS.remove(deps.storage, channel_id);
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "channel_close")
+        .add_attribute("channel_id", channel_id))
+}
+
+/// Reject ordered channels and versions other than `IBC_VERSION`. An empty
+/// proposed version on `OpenInit` lets this end choose it.
+fn check_channel(channel: &IbcChannel, counterparty_version: Option<&str>) -> Result<(), ContractError> {
+    if channel.order != IbcOrder::Unordered {
+        return Err(ContractError::OrderedChannel {});
+    }
+    let proposed = Some(channel.version.as_str()).filter(|version| !version.is_empty());
+    for version in proposed.into_iter().chain(counterparty_version) {
+        if version != IBC_VERSION {
+            return Err(ContractError::InvalidIbcVersion {
+                version: version.to_string(),
+                expected: IBC_VERSION.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reject channels to a counterparty not in `ALLOWED_COUNTERPARTIES`.
+fn check_counterparty(storage: &dyn Storage, channel: &IbcChannel) -> Result<(), ContractError> {
+    let port_id = &channel.counterparty_endpoint.port_id;
+    if !ALLOWED_COUNTERPARTIES.has(storage, (&channel.connection_id, port_id)) {
+        return Err(ContractError::CounterpartyNotAllowed {
+            connection_id: channel.connection_id.clone(),
+            port_id: port_id.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// A connected channel whose counterparty is still allowed.
+fn allowed_channel(storage: &dyn Storage, channel_id: &str) -> Result<IbcChannel, ContractError> {
+    let channel = CHANNELS
+        .may_load(storage, channel_id)?
+        .ok_or_else(|| ContractError::UnknownChannel { channel_id: channel_id.to_string() })?;
+    check_counterparty(storage, &channel)?;
+    Ok(channel)
+}
+
+/// Admin: allow or disallow channels to `port_id` over `connection_id`.
+/// Disallowing stops relays over channels already connected to it.
+pub(crate) fn set_counterparty_allowed(
+    deps: DepsMut,
+    info: MessageInfo,
+    connection_id: String,
+    port_id: String,
+    allowed: bool,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), &info)?;
+    let max_len = CONFIG.load(deps.storage)?.limits.max_field_len as usize;
+    validate_text("connection_id", &connection_id, max_len)?;
+    validate_text("port_id", &port_id, max_len)?;
+    if allowed {
+        ALLOWED_COUNTERPARTIES.save(deps.storage, (&connection_id, &port_id), &())?;
+    } else {
+        ALLOWED_COUNTERPARTIES.remove(deps.storage, (&connection_id, &port_id));
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", if allowed { "allow_ibc_counterparty" } else { "disallow_ibc_counterparty" })
+        .add_attribute("connection_id", connection_id)
+        .add_attribute("port_id", port_id))
+}
+
+/// Store a relayed anchor. Failures become error acks rather than errors, so
+/// a rejected packet is still acknowledged to the sender.
+#[entry_point]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let channel_id = msg.packet.dest.channel_id;
+    match receive_anchor(deps, &env, &channel_id, &msg.packet.data) {
+        Ok(response) => Ok(response),
+        Err(err) => Ok(IbcReceiveResponse::new()
+            .set_ack(error_ack_bytes(err.to_string())?)
+            .add_attribute("action", "receive_anchor")
+            .add_attribute("channel_id", channel_id)
+            .add_attribute("error", err.to_string())),
+    }
+}
+
+/// Validate and store a relayed anchor. Nothing is written until every check
+/// has passed, so an error ack leaves state untouched.
+fn receive_anchor(deps: DepsMut, env: &Env, channel_id: &str, data: &Binary) -> Result<IbcReceiveResponse, ContractError> {
+    allowed_channel(deps.storage, channel_id)?;
+    let PacketData::V1(packet) = from_json(data)?;
+    ensure_not_paused(deps.as_ref())?;
+    let config = CONFIG.load(deps.storage)?;
+    check_hash(deps.storage, &config, &packet.hash)?;
+    validate_text("origin_registrant", &packet.origin_registrant, config.limits.max_field_len as usize)?;
+
+    let hash = packet.hash.as_slice();
+    let response = IbcReceiveResponse::new()
+        .add_attribute("action", "receive_anchor")
+        .add_attribute("channel_id", channel_id);
+
+    // A replay, or a hash anchored here independently: relays never overwrite
+    if let Some(existing) = may_load_anchor(deps.storage, packet.anchor_type, hash)? {
+        if let Some(height) = existing.revoked_at {
+            return Err(ContractError::Revoked { height });
+        }
+        let receipt = anchor_receipt(&config, env, &existing);
+        return Ok(response
+            .set_ack(AnchorAck::from_receipt(&receipt).to_ack_bytes()?)
+            .add_attribute("hash", existing.hash_hex)
+            .add_attribute("duplicate", "true"));
+    }
+
+    let entry = AnchorEntry {
+        hash_hex: hex::encode(hash),
+        digest_len: hash.len() as u32,
+        payload_version: 1,
+        anchor_type: packet.anchor_type,
+        registered_at: env.block.height,
+        registered_at_time: env.block.time.seconds(),
+        imported_at: None,
+        // Not the origin registrant, who holds no rights on this chain
+        registrant: env.contract.address.to_string(),
+        origin_registrant: Some(packet.origin_registrant),
+        tags: vec![],
+        last_modified_height: 0,
+        stability_class: None,
+        memo: None,
+        revoked_at: None,
+        revocation_reason: None,
+        previous_root: None,
+        superseded_by: None,
+        chain_id: Some(env.block.chain_id.clone()),
+        tx_index: env.transaction.as_ref().map(|tx| tx.index),
+        claim_id: None,
+        depends_on_root: None,
+        source_channel: Some(channel_id.to_string()),
+        origin_height: Some(packet.origin_height),
+        index: None,
+        previous_registration: None,
+        expires_at: None,
+    };
+    let (entry, _) = write_anchor(deps.storage, env.block.height, hash, entry, "relay")?;
+    count_added(deps.storage, &entry, hash)?;
+
+    let receipt = anchor_receipt(&config, env, &entry);
+    Ok(response
+        .set_ack(AnchorAck::from_receipt(&receipt).to_ack_bytes()?)
+        .add_submessages(notify_subscribers(deps.storage, std::slice::from_ref(&entry))?)
+        .add_event(registered_event(&receipt))
+        .add_attribute("hash", entry.hash_hex)
+        .add_attribute("duplicate", "false"))
+}
+
+/// Record the receiver's verdict. Nothing is stored on send, so a failed
+/// relay has nothing to roll back and may simply be sent again.
+#[entry_point]
+pub fn ibc_packet_ack(_deps: DepsMut, _env: Env, msg: IbcPacketAckMsg) -> Result<IbcBasicResponse, ContractError> {
+    let response = packet_response("relay_ack", &msg.original_packet.src.channel_id, &msg.original_packet.data);
+    // An unreadable ack is reported rather than failing the relayer's transaction
+    Ok(match from_json::<AckEnvelope>(&msg.acknowledgement.data) {
+        Ok(AckEnvelope::Result(_)) => response.add_attribute("success", "true"),
+        Ok(AckEnvelope::Error(error)) => response.add_attribute("success", "false").add_attribute("error", error),
+        Err(err) => response.add_attribute("success", "false").add_attribute("error", err.to_string()),
+    })
+}
+
+#[entry_point]
+pub fn ibc_packet_timeout(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    Ok(packet_response("relay_timeout", &msg.packet.src.channel_id, &msg.packet.data))
+}
+
+fn packet_response(action: &str, channel_id: &str, data: &Binary) -> IbcBasicResponse {
+    let response = IbcBasicResponse::new()
+        .add_attribute("action", action)
+        .add_attribute("channel_id", channel_id);
+    match from_json(data) {
+        Ok(PacketData::V1(packet)) => response.add_attribute("hash", hex::encode(packet.hash.as_slice())),
+        Err(_) => response,
+    }
+}
+
+/// Send a registered, unrevoked anchor over `channel_id`. Anchors that were
+/// themselves relayed keep their original registrant and height.
+pub(crate) fn relay_anchor(
+    deps: DepsMut,
+    env: Env,
+    channel_id: String,
+    anchor_type: &str,
+    hash: Binary,
+) -> Result<Response, ContractError> {
+    allowed_channel(deps.storage, &channel_id)?;
+    let entry = may_load_anchor(deps.storage, parse_anchor_type(anchor_type)?, hash.as_slice())?
+        .ok_or_else(|| ContractError::AnchorNotFound {
+            anchor_type: anchor_type.to_string(),
+            hash_hex: hex::encode(hash.as_slice()),
+        })?;
+    if let Some(height) = entry.revoked_at {
+        return Err(ContractError::Revoked { height });
+    }
+
+    let packet = PacketData::V1(AnchorPacket {
+        anchor_type: entry.anchor_type,
+        hash,
+        origin_registrant: entry.origin_registrant.unwrap_or(entry.registrant),
+        origin_height: entry.origin_height.unwrap_or(entry.registered_at),
+    });
+    let send = IbcMsg::SendPacket {
+        channel_id: channel_id.clone(),
+        data: to_json_binary(&packet)?,
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(PACKET_TIMEOUT_SECONDS)),
+    };
+
+    Ok(Response::new()
+        .add_message(send)
+        .add_attribute("action", "relay_anchor")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("hash", entry.hash_hex))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor_registry::{
+        execute, instantiate, ExecuteMsg, InstantiateMsg, ANCHORS, REGISTRANT_INDEX, REGISTRANT_STATS, TOTAL_ANCHORS,
+        TYPE_COUNTS,
+    };
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_ibc_channel_close_init, mock_ibc_channel_connect_ack,
+        mock_ibc_channel_open_init, mock_ibc_channel_open_try, mock_ibc_packet_ack, mock_ibc_packet_recv,
+        mock_ibc_packet_timeout, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{Addr, CosmosMsg, IbcAcknowledgement, OwnedDeps};
+
+    type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+    fn setup() -> MockDeps {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted: false, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        allow_mock_counterparty(&mut deps);
+        let connect = mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_connect(deps.as_mut(), mock_env(), connect).unwrap();
+        deps
+    }
+
+    /// Allow the counterparty of the `mock_ibc_channel` channels.
+    fn allow_mock_counterparty(deps: &mut MockDeps) {
+        let msg = ExecuteMsg::AllowIbcCounterparty { connection_id: "connection-2".into(), port_id: "their_port".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+    }
+
+    fn packet(hash: [u8; 32]) -> PacketData {
+        PacketData::V1(AnchorPacket {
+            anchor_type: AnchorType::MerkleRoot,
+            hash: Binary::from(hash),
+            origin_registrant: "cosmos1origin".into(),
+            origin_height: 77,
+        })
+    }
+
+    fn receive(deps: &mut MockDeps, data: &impl Serialize) -> AckEnvelope {
+        let msg = mock_ibc_packet_recv("channel-0", data).unwrap();
+        let response = ibc_packet_receive(deps.as_mut(), mock_env(), msg).unwrap();
+        from_json(response.acknowledgement).unwrap()
+    }
+
+    fn attribute<'a>(attributes: &'a [cosmwasm_std::Attribute], key: &str) -> Option<&'a str> {
+        attributes.iter().find(|attr| attr.key == key).map(|attr| attr.value.as_str())
+    }
+
+    #[test]
+    fn test_channel_handshake_requires_unordered_and_version() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted: false, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        allow_mock_counterparty(&mut deps);
+        let ordered = mock_ibc_channel_open_init("channel-0", IbcOrder::Ordered, IBC_VERSION);
+        assert_eq!(
+            ibc_channel_open(deps.as_mut(), mock_env(), ordered).unwrap_err(),
+            ContractError::OrderedChannel {}
+        );
+        let wrong = mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, "ics20-1");
+        assert!(matches!(
+            ibc_channel_open(deps.as_mut(), mock_env(), wrong).unwrap_err(),
+            ContractError::InvalidIbcVersion { .. }
+        ));
+
+        let open = mock_ibc_channel_open_init("channel-0", IbcOrder::Unordered, "");
+        let response = ibc_channel_open(deps.as_mut(), mock_env(), open).unwrap();
+        assert_eq!(response, Some(Ibc3ChannelOpenResponse { version: IBC_VERSION.into() }));
+        let open = mock_ibc_channel_open_try("channel-0", IbcOrder::Unordered, IBC_VERSION);
+        assert!(ibc_channel_open(deps.as_mut(), mock_env(), open).is_ok());
+
+        let connect = mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_connect(deps.as_mut(), mock_env(), connect).unwrap();
+        assert!(CHANNELS.has(&deps.storage, "channel-0"));
+        let close = mock_ibc_channel_close_init("channel-0", IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_close(deps.as_mut(), mock_env(), close).unwrap();
+        assert!(!CHANNELS.has(&deps.storage, "channel-0"));
+    }
+
+    #[test]
+    fn test_receive_stores_entry_tagged_with_channel() {
+        let mut deps = setup();
+        let AckEnvelope::Result(inner) = receive(&mut deps, &packet([4; 32])) else { panic!("expected result ack") };
+        let ack: AnchorAck = from_json(&inner).unwrap();
+        assert_eq!(ack.hash_hex, hex::encode([4; 32]));
+        assert_eq!(ack.height, mock_env().block.height);
+
+        let entry = ANCHORS.load(&deps.storage, ("root", &[4; 32])).unwrap();
+        assert_eq!(entry.source_channel.as_deref(), Some("channel-0"));
+        assert_eq!(entry.origin_height, Some(77));
+        assert_eq!(entry.origin_registrant.as_deref(), Some("cosmos1origin"));
+        assert_eq!(entry.registrant, mock_env().contract.address.as_str());
+        assert_eq!(TYPE_COUNTS.load(&deps.storage, "root").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_channels_need_an_allowed_counterparty() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { admin: None, name: None, limits: None, restricted: false, public_registration: None, initial_anchors: None, max_batch_size: None, accepted_hash_lengths: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let refused = ContractError::CounterpartyNotAllowed { connection_id: "connection-2".into(), port_id: "their_port".into() };
+        let open = mock_ibc_channel_open_init("channel-0", IbcOrder::Unordered, IBC_VERSION);
+        assert_eq!(ibc_channel_open(deps.as_mut(), mock_env(), open).unwrap_err(), refused);
+        let connect = mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION);
+        assert_eq!(ibc_channel_connect(deps.as_mut(), mock_env(), connect).unwrap_err(), refused);
+
+        let allow = ExecuteMsg::AllowIbcCounterparty { connection_id: "connection-2".into(), port_id: "their_port".into() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("mallory", &[]), allow).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        allow_mock_counterparty(&mut deps);
+        let connect = mock_ibc_channel_connect_ack("channel-0", IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_connect(deps.as_mut(), mock_env(), connect).unwrap();
+        assert!(matches!(receive(&mut deps, &packet([4; 32])), AckEnvelope::Result(_)));
+
+        // Disallowing stops relays over the open channel
+        let disallow = ExecuteMsg::DisallowIbcCounterparty { connection_id: "connection-2".into(), port_id: "their_port".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), disallow).unwrap();
+        assert_eq!(receive(&mut deps, &packet([5; 32])), AckEnvelope::Error(refused.to_string()));
+        assert!(!ANCHORS.has(&deps.storage, ("root", &[5; 32])));
+    }
+
+    #[test]
+    fn test_relayed_anchors_grant_no_local_rights() {
+        let mut deps = setup();
+        let quota = ExecuteMsg::SetQuota { registrant: "cosmos1origin".into(), max_anchors: Some(1) };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), quota).unwrap();
+        receive(&mut deps, &packet([4; 32]));
+        receive(&mut deps, &packet([5; 32]));
+
+        // Neither the origin registrant nor this registry is charged for them
+        for registrant in ["cosmos1origin", mock_env().contract.address.as_str()] {
+            assert!(REGISTRANT_STATS.may_load(&deps.storage, &Addr::unchecked(registrant)).unwrap().is_none());
+            assert!(REGISTRANT_INDEX.sub_prefix(registrant).is_empty(&deps.storage));
+        }
+        let register = ExecuteMsg::RegisterRoot { hash: Binary::from([6; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("cosmos1origin", &[]), register).unwrap();
+
+        let revoke = ExecuteMsg::RevokeAnchor { hash: Binary::from([4; 32]), anchor_type: "root".into(), reason: "mine".into() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("cosmos1origin", &[]), revoke).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let supersede = ExecuteMsg::SupersedeRoot { old_root: Binary::from([4; 32]), new_root: Binary::from([6; 32]) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("cosmos1origin", &[]), supersede).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn test_replay_acks_stored_entry_unchanged() {
+        let mut deps = setup();
+        let first = receive(&mut deps, &packet([4; 32]));
+        let stored = ANCHORS.load(&deps.storage, ("root", &[4; 32])).unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 10;
+        let msg = mock_ibc_packet_recv("channel-0", &packet([4; 32])).unwrap();
+        let response = ibc_packet_receive(deps.as_mut(), env, msg).unwrap();
+        assert_eq!(attribute(&response.attributes, "duplicate"), Some("true"));
+        assert_eq!(from_json::<AckEnvelope>(response.acknowledgement).unwrap(), first);
+        assert_eq!(ANCHORS.load(&deps.storage, ("root", &[4; 32])).unwrap(), stored);
+        assert_eq!(TOTAL_ANCHORS.load(&deps.storage).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_rejected_packets_get_error_acks() {
+        let mut deps = setup();
+        let block = ExecuteMsg::BlockHash { hash: Binary::from([5; 32]), reason: "leaked".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), block).unwrap();
+        assert_eq!(receive(&mut deps, &packet([5; 32])), AckEnvelope::Error("Hash is blocklisted: leaked".into()));
+
+        let PacketData::V1(mut short) = packet([6; 32]);
+        short.hash = Binary::from([6; 31]);
+        assert!(matches!(receive(&mut deps, &PacketData::V1(short)), AckEnvelope::Error(_)));
+
+        let future = serde_json::json!({ "v2": { "hash": Binary::from([6; 32]) } });
+        assert!(matches!(receive(&mut deps, &future), AckEnvelope::Error(_)));
+
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), ExecuteMsg::Pause {}).unwrap();
+        assert_eq!(receive(&mut deps, &packet([6; 32])), AckEnvelope::Error("Registry is paused".into()));
+        assert!(!ANCHORS.has(&deps.storage, ("root", &[6; 32])));
+        assert_eq!(TOTAL_ANCHORS.load(&deps.storage).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_relay_anchor_sends_versioned_packet() {
+        let mut deps = setup();
+        let hash = Binary::from([8; 32]);
+        let register = ExecuteMsg::RegisterRoot { hash: hash.clone(), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), register).unwrap();
+
+        let relay = |channel_id: &str| ExecuteMsg::RelayAnchor {
+            channel_id: channel_id.into(),
+            anchor_type: "root".into(),
+            hash: hash.clone(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), relay("channel-9")).unwrap_err();
+        assert_eq!(err, ContractError::UnknownChannel { channel_id: "channel-9".into() });
+
+        let response = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), relay("channel-0")).unwrap();
+        let CosmosMsg::Ibc(IbcMsg::SendPacket { channel_id, data, timeout }) = &response.messages[0].msg else {
+            panic!("expected a packet")
+        };
+        assert_eq!(channel_id, "channel-0");
+        assert_eq!(timeout.timestamp(), Some(mock_env().block.time.plus_seconds(PACKET_TIMEOUT_SECONDS)));
+        let expected = format!(
+            r#"{{"v1":{{"anchor_type":"root","hash":"{}","origin_registrant":"alice","origin_height":{}}}}}"#,
+            hash.to_base64(),
+            mock_env().block.height
+        );
+        assert_eq!(data.as_slice(), expected.as_bytes());
+
+        let revoke = ExecuteMsg::RevokeAnchor { hash: hash.clone(), anchor_type: "root".into(), reason: "bad".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), revoke).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), relay("channel-0")).unwrap_err();
+        assert!(matches!(err, ContractError::Revoked { .. }));
+    }
+
+    #[test]
+    fn test_ack_and_timeout_are_reported() {
+        let mut deps = setup();
+        let data = packet([4; 32]);
+        let ack = IbcAcknowledgement::new(error_ack_bytes("paused").unwrap());
+        let msg = mock_ibc_packet_ack("channel-0", &data, ack).unwrap();
+        let response = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(attribute(&response.attributes, "success"), Some("false"));
+        assert_eq!(attribute(&response.attributes, "error"), Some("paused"));
+        assert_eq!(attribute(&response.attributes, "hash"), Some(hex::encode([4; 32]).as_str()));
+
+        let ack = IbcAcknowledgement::new(b"not json".to_vec());
+        let msg = mock_ibc_packet_ack("channel-0", &data, ack).unwrap();
+        let response = ibc_packet_ack(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(attribute(&response.attributes, "success"), Some("false"));
+
+        let msg = mock_ibc_packet_timeout("channel-0", &data).unwrap();
+        let response = ibc_packet_timeout(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(attribute(&response.attributes, "action"), Some("relay_timeout"));
+    }
+}
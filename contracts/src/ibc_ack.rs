@@ -0,0 +1,110 @@
+//! IBC Acknowledgement – Relay-ready acks for anchor registrations.
+//!
+//! Builds the acknowledgement a relayer forwards after an anchor has been
+//! registered. Acks use the ICS-20 style JSON envelope,
+//! `{"result":"<base64>"}` or `{"error":"<message>"}`, with the result
+//! carrying the JSON-encoded `AnchorAck`. Field order is fixed by the struct
+//! definition, so the bytes are deterministic for a given anchor.
+
+use cosmwasm_std::{to_json_binary, to_json_vec, Binary, StdResult};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::anchor_registry::AnchorReceipt;
+
+/// Acknowledgement payload for a registered anchor.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AnchorAck {
+    pub hash_hex: String,
+    pub anchor_type: String,
+    pub registry_id: String,
+    pub height: u64,
+}
+
+/// Acknowledgement envelope written to the ack packet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AckEnvelope {
+    Result(Binary),
+    Error(String),
+}
+
+impl AnchorAck {
+    /// Build the ack for a registration receipt.
+    pub fn from_receipt(receipt: &AnchorReceipt) -> Self {
+        AnchorAck {
+            hash_hex: receipt.hash_hex.clone(),
+            anchor_type: receipt.anchor_type.clone(),
+            registry_id: receipt.registry_id.clone(),
+            height: receipt.block_height,
+        }
+    }
+
+    /// Bytes of a successful acknowledgement carrying this ack.
+    pub fn to_ack_bytes(&self) -> StdResult<Binary> {
+        to_json_binary(&AckEnvelope::Result(Binary::from(to_json_vec(self)?)))
+    }
+}
+
+/// Bytes of an error acknowledgement.
+pub fn error_ack_bytes(message: impl Into<String>) -> StdResult<Binary> {
+    to_json_binary(&AckEnvelope::Error(message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::from_json;
+
+    fn known_ack() -> AnchorAck {
+        AnchorAck {
+            hash_hex: "ab".repeat(32),
+            anchor_type: "root".into(),
+            registry_id: "cd".repeat(32),
+            height: 12_345,
+        }
+    }
+
+    #[test]
+    fn test_ack_bytes_deterministic() {
+        let ack = known_ack();
+        let inner = format!(
+            r#"{{"hash_hex":"{}","anchor_type":"root","registry_id":"{}","height":12345}}"#,
+            "ab".repeat(32),
+            "cd".repeat(32)
+        );
+        let expected = format!(r#"{{"result":"{}"}}"#, Binary::from(inner.as_bytes()).to_base64());
+        assert_eq!(ack.to_ack_bytes().unwrap().as_slice(), expected.as_bytes());
+        assert_eq!(ack.to_ack_bytes().unwrap(), known_ack().to_ack_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_ack_round_trip_and_error() {
+        let ack = known_ack();
+        let envelope: AckEnvelope = from_json(ack.to_ack_bytes().unwrap()).unwrap();
+        let AckEnvelope::Result(inner) = envelope else { panic!("expected result ack") };
+        assert_eq!(from_json::<AnchorAck>(&inner).unwrap(), ack);
+        assert_eq!(error_ack_bytes("blocked").unwrap().as_slice(), br#"{"error":"blocked"}"#);
+    }
+
+    #[test]
+    fn test_ack_from_receipt() {
+        let receipt = AnchorReceipt {
+            registry_id: "cd".repeat(32),
+            anchor_type: "root".into(),
+            hash_hex: "ab".repeat(32),
+            block_height: 12_345,
+            block_time: 1,
+            registrant: "bot".into(),
+            chain_id: None,
+            tx_index: None,
+            index: None,
+            version: 0,
+            receipt_hash: String::new(),
+        };
+        assert_eq!(AnchorAck::from_receipt(&receipt), known_ack());
+    }
+}
@@ -1,21 +1,41 @@
-/// Gravity- Anchor Contracts
-///
-/// Deterministic, integrity-only smart contract layer for anchoring:
-///   - Merkle root hashes
-///   - Claim score hashes
-///   - Equation proof hashes
-///
-/// No token logic. No external randomness. Content-hash addressed.
-/// Compatible with CosmWasm, with Substrate/EVM wrapper stubs.
+//! Gravity- Anchor Contracts
+//!
+//! Deterministic, integrity-only smart contract layer for anchoring:
+//!   - Merkle root hashes
+//!   - Claim score hashes
+//!   - Equation proof hashes
+//!
+//! No token logic. No external randomness. Content-hash addressed.
+//! Compatible with CosmWasm, with Substrate/EVM wrapper stubs.
 
 pub mod anchor_registry;
+pub mod canonical;
+#[cfg(feature = "cosmwasm")]
+pub mod client;
+#[cfg(feature = "cosmwasm")]
+pub mod error;
+#[cfg(feature = "cosmwasm-ibc")]
+pub mod ibc;
+#[cfg(feature = "ibc")]
+pub mod ibc_ack;
+pub mod limits;
 pub mod merkle_anchor;
+#[cfg(feature = "cosmwasm")]
+pub mod querier;
 pub mod claim_score_anchor;
 pub mod equation_proof_anchor;
 
+#[cfg(any(test, feature = "test-vectors"))]
+pub mod test_vectors;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+
 #[cfg(feature = "cosmwasm")]
 pub use anchor_registry::{
     execute as registry_execute,
     instantiate as registry_instantiate,
+    migrate as registry_migrate,
     query as registry_query,
 };
+#[cfg(feature = "cosmwasm")]
+pub use querier::AnchorRegistryQuerier;
@@ -0,0 +1,287 @@
+//! Limits – Bounds on every string the registry persists.
+//!
+//! Unbounded strings are a state-bloat and gas risk, so every execute path
+//! that stores text routes it through `validate_text`. Defaults live here;
+//! deployments may override them through `Config.limits`, but never beyond
+//! the hard ceilings below.
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Default maximum number of tags attached to a single anchor
+pub const MAX_TAGS: u32 = 4;
+
+/// Default maximum byte length of a single tag
+pub const MAX_TAG_LEN: u32 = 32;
+
+/// Default maximum byte length of a blocklist reason
+pub const MAX_REASON_LEN: u32 = 256;
+
+/// Default maximum byte length of a free-form payload field
+pub const MAX_FIELD_LEN: u32 = 256;
+
+/// Maximum byte length of an anchor memo (fixed)
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// Maximum byte length of an anchor namespace (fixed)
+pub const MAX_NAMESPACE_LEN: usize = 32;
+
+/// Maximum byte length of an indexed equation name (fixed)
+pub const MAX_EQUATION_NAME_LEN: usize = 64;
+
+/// Maximum byte length of the registry name (fixed at instantiate)
+pub const MAX_NAME_LEN: usize = 64;
+
+/// Ceiling on any configurable text length, regardless of `Config.limits`
+pub const HARD_MAX_TEXT_LEN: u32 = 1024;
+
+/// Ceiling on any configurable item count, regardless of `Config.limits`
+pub const HARD_MAX_ITEMS: u32 = 32;
+
+/// Per-deployment text limits stored in `Config`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Limits {
+    /// Maximum number of tags per anchor
+    pub max_tags: u32,
+    /// Maximum byte length of a tag
+    pub max_tag_len: u32,
+    /// Maximum byte length of a blocklist reason
+    pub max_reason_len: u32,
+    /// Maximum byte length of any other free-form field (payload text)
+    #[serde(default = "default_max_field_len")]
+    pub max_field_len: u32,
+}
+
+fn default_max_field_len() -> u32 {
+    MAX_FIELD_LEN
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_tags: MAX_TAGS,
+            max_tag_len: MAX_TAG_LEN,
+            max_reason_len: MAX_REASON_LEN,
+            max_field_len: MAX_FIELD_LEN,
+        }
+    }
+}
+
+/// Text validation failures, naming the offending field and limit.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TextError {
+    #[error("{field} must not be empty")]
+    Empty { field: String },
+    #[error("{field} is {len} bytes, exceeding the {max}-byte limit")]
+    FieldTooLong { field: String, len: usize, max: usize },
+    #[error("{field} contains a control character")]
+    ControlCharacter { field: String },
+    #[error("{field} contains disallowed character {ch:?}")]
+    InvalidCharacter { field: String, ch: char },
+    #[error("{field} has {count} entries, exceeding the limit of {max}")]
+    TooManyItems { field: String, count: usize, max: usize },
+    #[error("{field} contains duplicate value {value:?}")]
+    Duplicate { field: String, value: String },
+    #[error("limit {field} = {value} is outside 1..={max}")]
+    InvalidLimit { field: String, value: u32, max: u32 },
+}
+
+impl Limits {
+    /// Reject zero limits and overrides above the hard ceilings.
+    pub fn validate(&self) -> Result<(), TextError> {
+        let checks = [
+            ("max_tags", self.max_tags, HARD_MAX_ITEMS),
+            ("max_tag_len", self.max_tag_len, HARD_MAX_TEXT_LEN),
+            ("max_reason_len", self.max_reason_len, HARD_MAX_TEXT_LEN),
+            ("max_field_len", self.max_field_len, HARD_MAX_TEXT_LEN),
+        ];
+        for (field, value, max) in checks {
+            if value == 0 || value > max {
+                return Err(TextError::InvalidLimit { field: field.to_string(), value, max });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validate a persisted string: non-empty, at most `max_len` bytes, and free
+/// of control characters. UTF-8 validity is guaranteed by `&str` (message
+/// deserialization rejects invalid UTF-8 before it reaches the contract).
+pub fn validate_text(field: &str, value: &str, max_len: usize) -> Result<(), TextError> {
+    if value.is_empty() {
+        return Err(TextError::Empty { field: field.to_string() });
+    }
+    if value.len() > max_len {
+        return Err(TextError::FieldTooLong {
+            field: field.to_string(),
+            len: value.len(),
+            max: max_len,
+        });
+    }
+    if value.chars().any(char::is_control) {
+        return Err(TextError::ControlCharacter { field: field.to_string() });
+    }
+    Ok(())
+}
+
+/// Validate named payload fields with `validate_text` against `max_field_len`.
+pub fn validate_fields<'a>(
+    fields: impl IntoIterator<Item = (&'static str, &'a str)>,
+    limits: &Limits,
+) -> Result<(), TextError> {
+    for (field, value) in fields {
+        validate_text(field, value, limits.max_field_len as usize)?;
+    }
+    Ok(())
+}
+
+/// Validate a string against a restricted charset, after `validate_text`.
+pub fn validate_charset(
+    field: &str,
+    value: &str,
+    max_len: usize,
+    allowed: impl Fn(char) -> bool,
+) -> Result<(), TextError> {
+    validate_text(field, value, max_len)?;
+    match value.chars().find(|c| !allowed(*c)) {
+        Some(ch) => Err(TextError::InvalidCharacter { field: field.to_string(), ch }),
+        None => Ok(()),
+    }
+}
+
+/// Tag charset: `[a-z0-9:._-]`.
+pub fn is_tag_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, ':' | '.' | '_' | '-')
+}
+
+/// Namespace charset: `[a-z0-9_]`.
+pub fn is_namespace_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'
+}
+
+/// Equation name charset: `[A-Za-z0-9_.-]`. Excludes `:`, the canonical
+/// field separator.
+pub fn is_equation_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+/// Validate a tag list against `limits`: bounded count, unique entries, and
+/// each tag within the tag charset and length.
+pub fn validate_tags(tags: &[String], limits: &Limits) -> Result<(), TextError> {
+    if tags.len() > limits.max_tags as usize {
+        return Err(TextError::TooManyItems {
+            field: "tags".to_string(),
+            count: tags.len(),
+            max: limits.max_tags as usize,
+        });
+    }
+    for (i, tag) in tags.iter().enumerate() {
+        validate_charset("tag", tag, limits.max_tag_len as usize, is_tag_char)?;
+        if tags[..i].contains(tag) {
+            return Err(TextError::Duplicate { field: "tags".to_string(), value: tag.clone() });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_text_at_below_and_above_limit() {
+        assert!(validate_text("memo", &"a".repeat(9), 10).is_ok());
+        assert!(validate_text("memo", &"a".repeat(10), 10).is_ok());
+        assert_eq!(
+            validate_text("memo", &"a".repeat(11), 10),
+            Err(TextError::FieldTooLong { field: "memo".into(), len: 11, max: 10 })
+        );
+    }
+
+    #[test]
+    fn test_validate_text_multibyte_boundary() {
+        // "é" is two bytes: "aé" is exactly 3 bytes, "éé" is 4
+        assert!(validate_text("reason", "aé", 3).is_ok());
+        assert_eq!(
+            validate_text("reason", "éé", 3),
+            Err(TextError::FieldTooLong { field: "reason".into(), len: 4, max: 3 })
+        );
+    }
+
+    #[test]
+    fn test_validate_text_rejects_empty_and_control_chars() {
+        assert_eq!(validate_text("reason", "", 10), Err(TextError::Empty { field: "reason".into() }));
+        assert_eq!(
+            validate_text("reason", "line\nbreak", 20),
+            Err(TextError::ControlCharacter { field: "reason".into() })
+        );
+        assert!(validate_text("reason", "nul\0", 20).is_err());
+    }
+
+    #[test]
+    fn test_validate_charset() {
+        assert!(validate_charset("tag", "env:prod", 32, is_tag_char).is_ok());
+        assert_eq!(
+            validate_charset("tag", "Env", 32, is_tag_char),
+            Err(TextError::InvalidCharacter { field: "tag".into(), ch: 'E' })
+        );
+    }
+
+    #[test]
+    fn test_equation_name_charset() {
+        assert!(validate_charset("equation_name", "newton_gravity-2.0", MAX_EQUATION_NAME_LEN, is_equation_name_char).is_ok());
+        assert_eq!(
+            validate_charset("equation_name", "ns:newton", MAX_EQUATION_NAME_LEN, is_equation_name_char),
+            Err(TextError::InvalidCharacter { field: "equation_name".into(), ch: ':' })
+        );
+    }
+
+    #[test]
+    fn test_validate_tags_limits() {
+        let limits = Limits::default();
+        let max: Vec<String> = (0..MAX_TAGS).map(|i| format!("tag-{}", i)).collect();
+        assert!(validate_tags(&max, &limits).is_ok());
+        assert!(validate_tags(&["t".repeat(MAX_TAG_LEN as usize)], &limits).is_ok());
+        assert!(validate_tags(&[], &limits).is_ok());
+
+        let too_many: Vec<String> = (0..=MAX_TAGS).map(|i| format!("tag-{}", i)).collect();
+        assert!(matches!(validate_tags(&too_many, &limits), Err(TextError::TooManyItems { .. })));
+        assert!(matches!(
+            validate_tags(&["t".repeat(MAX_TAG_LEN as usize + 1)], &limits),
+            Err(TextError::FieldTooLong { .. })
+        ));
+        assert!(matches!(
+            validate_tags(&["a".into(), "a".into()], &limits),
+            Err(TextError::Duplicate { .. })
+        ));
+    }
+
+    #[test]
+    fn test_limits_validate_bounds() {
+        assert!(Limits::default().validate().is_ok());
+        let zero = Limits { max_tags: 0, ..Limits::default() };
+        assert!(matches!(zero.validate(), Err(TextError::InvalidLimit { .. })));
+        let huge = Limits { max_reason_len: HARD_MAX_TEXT_LEN + 1, ..Limits::default() };
+        assert!(huge.validate().is_err());
+    }
+
+    #[test]
+    fn test_max_field_len_defaults_for_stored_limits() {
+        let stored = r#"{"max_tags":5,"max_tag_len":32,"max_reason_len":256}"#;
+        let limits: Limits = serde_json::from_str(stored).unwrap();
+        assert_eq!(limits.max_field_len, MAX_FIELD_LEN);
+
+        let long = "x".repeat(MAX_FIELD_LEN as usize + 1);
+        assert_eq!(
+            validate_fields([("ok", "fine"), ("equation_name", long.as_str())], &limits),
+            Err(TextError::FieldTooLong {
+                field: "equation_name".into(),
+                len: MAX_FIELD_LEN as usize + 1,
+                max: MAX_FIELD_LEN as usize,
+            })
+        );
+    }
+}
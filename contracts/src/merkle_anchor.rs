@@ -1,61 +1,337 @@
-/// Merkle Anchor – Specialized sub-module for Merkle root anchoring.
-///
-/// Provides deterministic payload construction and verification
-/// for Merkle tree root hashes from the Phase II snapshot engine.
+//! Merkle Anchor – Specialized sub-module for Merkle root anchoring.
+//!
+//! Provides deterministic payload construction and verification
+//! for Merkle tree root hashes from the Phase II snapshot engine, and the
+//! reference tree construction (`MerkleTree`) those roots are computed with,
+//! including inclusion proofs (`MerkleProof`) for single leaves and an
+//! append-only variant (`IncrementalMerkleTree`) that keeps only a frontier.
+//! `SparseMerkleTree` covers keyed snapshots, with proofs of membership and
+//! of non-membership.
+//!
+//! Trees and proofs are generic over a `Hasher`: SHA-256 by default, or
+//! Keccak-256 (feature `keccak`) for roots that must match Solidity tooling.
+
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
 
+#[cfg(feature = "schema")]
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+
+#[cfg(feature = "keccak")]
+use crate::anchor_registry::compute_keccak256;
+use crate::anchor_registry::{compute_sha256, format_anchor_payload};
+use crate::canonical::{
+    check_hex_digest, decode_optional_hash, encode_optional_hash, parse_u64, split_fields,
+    write_len_prefixed, CanonicalMode, ParseError,
+};
+
+/// Original Merkle payload format, whose hash does not cover `table_hashes`.
+pub const MERKLE_PAYLOAD_V1: u16 = 1;
+
+/// Merkle payload format whose hash commits to the table list.
+pub const MERKLE_PAYLOAD_V2: u16 = 2;
+
+/// Merkle payload format produced by `MerkleRootPayload::new`; its hash
+/// also commits to `hash_algo`.
+pub const MERKLE_PAYLOAD_VERSION: u16 = 3;
+
+/// Hash function a Merkle root was computed with.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgo {
+    #[default]
+    Sha256,
+    Keccak256,
+}
+
+impl HashAlgo {
+    /// Name used in canonical strings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Keccak256 => "keccak256",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, ParseError> {
+        match value {
+            "sha256" => Ok(HashAlgo::Sha256),
+            "keccak256" => Ok(HashAlgo::Keccak256),
+            _ => Err(ParseError::InvalidField { field: "hash_algo", value: value.into() }),
+        }
+    }
+}
+
+fn merkle_payload_v1() -> u16 {
+    MERKLE_PAYLOAD_V1
+}
 
-use crate::anchor_registry::{compute_sha256, validate_hash, format_anchor_payload};
+/// The hash of one snapshot table covered by a Merkle root.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct TableHash {
+    pub table_name: String,
+    /// SHA-256 of the table contents (32 bytes, hex-encoded)
+    pub hash_hex: String,
+}
+
+impl TableHash {
+    /// Whether the entry encodes unambiguously in the v2 text form: a
+    /// non-empty name free of `:`, `,` and `=`, and a lowercase hex digest.
+    pub fn is_well_formed(&self) -> bool {
+        !self.table_name.is_empty()
+            && !self.table_name.contains([':', ',', '='])
+            && check_hex_digest("hash_hex", &self.hash_hex).is_ok()
+    }
+}
 
 /// A Merkle root registration request with metadata.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct MerkleRootPayload {
+    /// Payload format; payloads stored before versioning read as v1
+    #[serde(default = "merkle_payload_v1")]
+    pub version: u16,
+    /// Hash function behind `root_hash`; covered by the payload hash from v3
+    #[serde(default)]
+    pub hash_algo: HashAlgo,
     /// The Merkle root hash (32 bytes, hex-encoded)
     pub root_hash: String,
     /// Number of leaves in the tree
     pub leaf_count: u64,
-    /// Table hashes included (JSON array)
-    pub table_hashes: Option<String>,
+    /// Per-table hashes, sorted by table name. The v1 free-form JSON string
+    /// was never covered by the payload hash and reads back as `None`.
+    #[serde(default, deserialize_with = "deserialize_table_hashes")]
+    pub table_hashes: Option<Vec<TableHash>>,
     /// Previous root hash for chain linking
     pub previous_root: Option<String>,
     /// SHA-256 of the full payload
     pub payload_hash: String,
 }
 
+fn deserialize_table_hashes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<TableHash>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Typed(Vec<TableHash>),
+        // Only matched to accept the v1 form; its contents are dropped
+        Legacy(#[allow(dead_code)] String),
+    }
+    Ok(match Option::<Stored>::deserialize(deserializer)? {
+        Some(Stored::Typed(tables)) => Some(tables),
+        Some(Stored::Legacy(_)) | None => None,
+    })
+}
+
 impl MerkleRootPayload {
-    /// Construct a deterministic Merkle root payload.
+    /// Construct a deterministic Merkle root payload in the current format.
     ///
-    /// The payload hash is computed from the canonical concatenation:
-    ///   SHA-256("merkle_root:" + root_hash + ":" + leaf_count + ":" + previous_root)
+    /// The root is taken to be SHA-256 (see `with_hash_algo`). Table entries
+    /// are sorted by name and an empty list is stored as `None`. The payload
+    /// hash is computed from the canonical concatenation:
+    ///   SHA-256("merkle_root_v3:" + hash_algo + ":" + root_hash + ":"
+    ///           + leaf_count + ":" + previous_root + ":" + tables)
     pub fn new(
         root_hash: String,
         leaf_count: u64,
-        table_hashes: Option<String>,
+        table_hashes: Option<Vec<TableHash>>,
         previous_root: Option<String>,
     ) -> Self {
-        let prev = previous_root.clone().unwrap_or_default();
-        let canonical = format!("merkle_root:{}:{}:{}", root_hash, leaf_count, prev);
-        let hash = compute_sha256(canonical.as_bytes());
-        let payload_hash = hex::encode(hash);
+        Self::with_version(MERKLE_PAYLOAD_VERSION, root_hash, leaf_count, table_hashes, previous_root)
+    }
 
-        MerkleRootPayload {
+    /// Construct a payload in a specific format `version`, e.g. to
+    /// reproduce a v1 payload hash.
+    pub fn with_version(
+        version: u16,
+        root_hash: String,
+        leaf_count: u64,
+        table_hashes: Option<Vec<TableHash>>,
+        previous_root: Option<String>,
+    ) -> Self {
+        let table_hashes = table_hashes.filter(|tables| !tables.is_empty()).map(|mut tables| {
+            tables.sort_by(|a, b| a.table_name.cmp(&b.table_name));
+            tables
+        });
+        let mut payload = MerkleRootPayload {
+            version,
+            hash_algo: HashAlgo::Sha256,
             root_hash,
             leaf_count,
             table_hashes,
             previous_root,
-            payload_hash,
+            payload_hash: String::new(),
+        };
+        payload.payload_hash = hex::encode(compute_sha256(payload.to_canonical_string().as_bytes()));
+        payload
+    }
+
+    /// Record that the root was computed with `hash_algo`, recomputing the
+    /// payload hash.
+    pub fn with_hash_algo(mut self, hash_algo: HashAlgo) -> Self {
+        self.hash_algo = hash_algo;
+        self.refresh_hash();
+        self
+    }
+
+    /// Canonical string the payload hash is computed over.
+    pub fn to_canonical_string(&self) -> String {
+        let bytes = self.canonical_bytes(CanonicalMode::Text);
+        String::from_utf8(bytes).expect("text canonical form is UTF-8")
+    }
+
+    /// Canonical encoding of the payload.
+    ///
+    /// v1 (`table_hashes` not covered):
+    ///   Text:   "merkle_root:" ‖ root_hash ‖ ":" ‖ leaf_count ‖ ":" ‖ previous_root
+    ///   Binary: "merkle_root" ‖ 0x00 ‖ u32_be(len) ‖ root_hash ‖ u64_be(leaf_count)
+    ///           ‖ optional(previous_root)
+    ///
+    /// v2 appends the table list to the v1 layout under a "merkle_root_v2"
+    /// prefix:
+    ///   Text:   ... ‖ ":" ‖ name "=" hash_hex, comma-separated
+    ///   Binary: ... ‖ u32_be(count) ‖ (u32_be(len) ‖ name ‖ u32_be(len) ‖ hash_hex)*
+    ///
+    /// v3 is v2 under a "merkle_root_v3" prefix with `hash_algo` inserted
+    /// before `root_hash` (text: "sha256" ‖ ":"; binary: u32_be(len) ‖ name).
+    ///
+    /// `previous_root` goes through `encode_optional_hash` in both modes. The
+    /// payload hash is computed over the text form.
+    pub fn canonical_bytes(&self, mode: CanonicalMode) -> Vec<u8> {
+        let prefix = match self.version {
+            MERKLE_PAYLOAD_V1 => "merkle_root",
+            MERKLE_PAYLOAD_V2 => "merkle_root_v2",
+            _ => "merkle_root_v3",
+        };
+        let algo = (self.version > MERKLE_PAYLOAD_V2).then(|| self.hash_algo.as_str());
+        let mut out = Vec::new();
+        match mode {
+            CanonicalMode::Text => {
+                out.extend_from_slice(format!("{}:", prefix).as_bytes());
+                if let Some(algo) = algo {
+                    out.extend_from_slice(format!("{}:", algo).as_bytes());
+                }
+                out.extend_from_slice(format!("{}:{}:", self.root_hash, self.leaf_count).as_bytes());
+            }
+            CanonicalMode::Binary => {
+                out.extend_from_slice(prefix.as_bytes());
+                out.push(0);
+                if let Some(algo) = algo {
+                    write_len_prefixed(&mut out, algo.as_bytes()).expect("Vec write");
+                }
+                write_len_prefixed(&mut out, self.root_hash.as_bytes()).expect("Vec write");
+                out.extend_from_slice(&self.leaf_count.to_be_bytes());
+            }
+        }
+        encode_optional_hash(&mut out, &self.previous_root, mode).expect("Vec write");
+        if self.version >= MERKLE_PAYLOAD_V2 {
+            let tables = self.table_hashes.as_deref().unwrap_or_default();
+            match mode {
+                CanonicalMode::Text => {
+                    let entries: Vec<String> =
+                        tables.iter().map(|t| format!("{}={}", t.table_name, t.hash_hex)).collect();
+                    out.push(b':');
+                    out.extend_from_slice(entries.join(",").as_bytes());
+                }
+                CanonicalMode::Binary => {
+                    out.extend_from_slice(&(tables.len() as u32).to_be_bytes());
+                    for table in tables {
+                        write_len_prefixed(&mut out, table.table_name.as_bytes()).expect("Vec write");
+                        write_len_prefixed(&mut out, table.hash_hex.as_bytes()).expect("Vec write");
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Parse a text canonical string of any version, recomputing
+    /// `payload_hash`.
+    ///
+    /// A v1 string carries no tables, so `table_hashes` parses as `None`, and
+    /// strings before v3 carry no algorithm, so `hash_algo` is SHA-256.
+    /// Both root hashes and every table hash must be 64 lowercase hex
+    /// characters.
+    pub fn from_canonical_string(input: &str) -> Result<Self, ParseError> {
+        let (version, hash_algo, f) = match split_fields(input, "merkle_root_v3", 5) {
+            Ok(mut f) => {
+                let algo = HashAlgo::parse(f.remove(0))?;
+                (MERKLE_PAYLOAD_VERSION, algo, f)
+            }
+            Err(ParseError::WrongPrefix { .. }) => match split_fields(input, "merkle_root_v2", 4) {
+                Ok(f) => (MERKLE_PAYLOAD_V2, HashAlgo::Sha256, f),
+                Err(ParseError::WrongPrefix { .. }) => {
+                    (MERKLE_PAYLOAD_V1, HashAlgo::Sha256, split_fields(input, "merkle_root", 3)?)
+                }
+                Err(e) => return Err(e),
+            },
+            Err(e) => return Err(e),
+        };
+        check_hex_digest("root_hash", f[0])?;
+        let leaf_count = parse_u64("leaf_count", f[1])?;
+        let (previous_root, _) = decode_optional_hash(f[2].as_bytes(), CanonicalMode::Text)
+            .expect("text field is UTF-8");
+        if let Some(previous) = &previous_root {
+            check_hex_digest("previous_root", previous)?;
         }
+        let tables = match f.get(3) {
+            Some(list) if !list.is_empty() => Some(parse_table_list(list)?),
+            _ => None,
+        };
+        Ok(MerkleRootPayload::with_version(version, f[0].into(), leaf_count, tables, previous_root)
+            .with_hash_algo(hash_algo))
+    }
+
+    /// Recompute `payload_hash` from the current fields, returning whether
+    /// the stored hash was stale.
+    pub fn refresh_hash(&mut self) -> bool {
+        let fresh = hex::encode(compute_sha256(self.to_canonical_string().as_bytes()));
+        let stale = fresh != self.payload_hash;
+        self.payload_hash = fresh;
+        stale
     }
 
-    /// Verify payload integrity by recomputing the hash.
+    /// Verify payload integrity by recomputing the hash under the rules of
+    /// the payload's own version.
+    ///
+    /// v2 and later payloads additionally need well-formed table entries in
+    /// strictly ascending name order, so that no two table lists share a
+    /// canonical string. Before v3 `hash_algo` is not covered and must be
+    /// SHA-256, the only algorithm those versions knew.
     pub fn verify(&self) -> bool {
-        let prev = self.previous_root.clone().unwrap_or_default();
-        let canonical = format!("merkle_root:{}:{}:{}", self.root_hash, self.leaf_count, prev);
-        let hash = compute_sha256(canonical.as_bytes());
+        if self.version < MERKLE_PAYLOAD_VERSION && self.hash_algo != HashAlgo::Sha256 {
+            return false;
+        }
+        match self.version {
+            MERKLE_PAYLOAD_V1 => {}
+            MERKLE_PAYLOAD_V2 | MERKLE_PAYLOAD_VERSION => {
+                let tables = self.table_hashes.as_deref().unwrap_or_default();
+                if !tables.iter().all(TableHash::is_well_formed)
+                    || !tables.windows(2).all(|w| w[0].table_name < w[1].table_name)
+                {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+        let hash = compute_sha256(self.to_canonical_string().as_bytes());
         hex::encode(hash) == self.payload_hash
     }
 
+    /// Payload for `tree`'s root, taking `leaf_count` and `hash_algo` from
+    /// the tree itself.
+    pub fn from_tree<H: Hasher>(tree: &MerkleTree<H>, previous_root: Option<String>) -> Self {
+        MerkleRootPayload::new(hex::encode(tree.root()), tree.leaf_count(), None, previous_root).with_hash_algo(H::ALGO)
+    }
+
+    /// Payload for an incremental tree's current root and leaf count.
+    pub fn from_incremental<H: Hasher>(tree: &IncrementalMerkleTree<H>, previous_root: Option<String>) -> Self {
+        MerkleRootPayload::new(hex::encode(tree.root()), tree.leaf_count(), None, previous_root).with_hash_algo(H::ALGO)
+    }
+
     /// Convert the root hash hex string to raw 32-byte array.
     pub fn root_bytes(&self) -> Option<[u8; 32]> {
         let decoded = hex::decode(&self.root_hash).ok()?;
@@ -68,6 +344,770 @@ impl MerkleRootPayload {
     }
 }
 
+/// Maximum number of levels accepted in an inclusion proof
+pub const MAX_PROOF_DEPTH: usize = 64;
+
+/// Hash function used to build Merkle trees and check their proofs.
+pub trait Hasher {
+    /// Recorded as `MerkleRootPayload::hash_algo` for roots built with it
+    const ALGO: HashAlgo;
+
+    fn hash(data: &[u8]) -> [u8; 32];
+
+    /// Hash two child nodes into their parent: H(left ‖ right).
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(left);
+        buf[32..].copy_from_slice(right);
+        Self::hash(&buf)
+    }
+}
+
+/// SHA-256, the default for every tree in this module.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Sha256Hasher;
+
+impl Hasher for Sha256Hasher {
+    const ALGO: HashAlgo = HashAlgo::Sha256;
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        compute_sha256(data)
+    }
+}
+
+/// Keccak-256, matching Solidity's `keccak256` and OpenZeppelin trees.
+#[cfg(feature = "keccak")]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Keccak256Hasher;
+
+#[cfg(feature = "keccak")]
+impl Hasher for Keccak256Hasher {
+    const ALGO: HashAlgo = HashAlgo::Keccak256;
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        compute_keccak256(data)
+    }
+}
+
+/// Hash two child nodes into their parent: SHA-256(left ‖ right).
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256Hasher::hash_pair(left, right)
+}
+
+/// How a parent is hashed from its two children.
+///
+/// `Ordered` is `hash_pair(left, right)` and needs the side of every proof
+/// sibling. `Sorted` hashes the smaller child first, as OpenZeppelin's
+/// `MerkleProof` does, so proofs carry no sides. The two modes give
+/// different roots for the same leaves, and a proof from one never verifies
+/// under the other.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PairingMode {
+    #[default]
+    Ordered,
+    Sorted,
+}
+
+impl PairingMode {
+    /// Hash two sibling nodes with SHA-256, `left` being the one at the
+    /// lower position.
+    pub fn hash_pair(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        self.hash_pair_with::<Sha256Hasher>(left, right)
+    }
+
+    /// `hash_pair` with the hash function `H`.
+    pub fn hash_pair_with<H: Hasher>(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        match self {
+            PairingMode::Sorted if right < left => H::hash_pair(right, left),
+            _ => H::hash_pair(left, right),
+        }
+    }
+}
+
+/// A binary Merkle tree over 32-byte leaf hashes.
+///
+/// Each parent is hashed from its children according to the tree's
+/// `PairingMode`. When a level has an odd number of nodes, the last node is
+/// promoted to the next level unchanged rather than paired with a copy of
+/// itself, so no two distinct leaf lists share a root through duplication.
+/// A single leaf is its own root, matching `verify_inclusion` with an empty
+/// proof; an empty tree's root is the hash of empty input, which the
+/// registry rejects as degenerate for SHA-256.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleTree<H = Sha256Hasher> {
+    mode: PairingMode,
+    /// Levels from the leaves (index 0) up to the single-node root level
+    levels: Vec<Vec<[u8; 32]>>,
+    hasher: PhantomData<H>,
+}
+
+impl MerkleTree {
+    /// Build the tree over `leaves`, in order, with ordered pairing.
+    pub fn from_leaves(leaves: &[[u8; 32]]) -> MerkleTree {
+        MerkleTree::from_leaves_with_mode(leaves, PairingMode::Ordered)
+    }
+
+    /// Build the tree over `leaves`, in order, pairing nodes as `mode` says.
+    pub fn from_leaves_with_mode(leaves: &[[u8; 32]], mode: PairingMode) -> MerkleTree {
+        MerkleTree::from_leaves_with_hasher(leaves, mode)
+    }
+}
+
+impl<H: Hasher> MerkleTree<H> {
+    /// Build the tree over `leaves` with the hash function `H`, e.g.
+    /// `MerkleTree::<Keccak256Hasher>::from_leaves_with_hasher`.
+    pub fn from_leaves_with_hasher(leaves: &[[u8; 32]], mode: PairingMode) -> MerkleTree<H> {
+        let mut levels = vec![leaves.to_vec()];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => mode.hash_pair_with::<H>(left, right),
+                    [promoted] => *promoted,
+                    _ => unreachable!("chunks(2) yields one or two nodes"),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { mode, levels, hasher: PhantomData }
+    }
+
+    /// The pairing mode the tree was built with.
+    pub fn mode(&self) -> PairingMode {
+        self.mode
+    }
+
+    /// The root hash.
+    pub fn root(&self) -> [u8; 32] {
+        match self.levels.last().and_then(|level| level.first()) {
+            Some(root) => *root,
+            None => H::hash(b""),
+        }
+    }
+
+    /// Number of leaves the tree was built from.
+    pub fn leaf_count(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    /// Inclusion proof for the leaf at `index`, or `None` when out of range.
+    /// Levels where the node was promoted contribute no sibling, and sorted
+    /// trees record no sides.
+    pub fn prove(&self, index: usize) -> Option<MerkleProof> {
+        let leaf = *self.levels[0].get(index)?;
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let (sibling, side) = if position.is_multiple_of(2) { (position + 1, Side::Right) } else { (position - 1, Side::Left) };
+            if let Some(node) = level.get(sibling) {
+                let side = (self.mode == PairingMode::Ordered).then_some(side);
+                siblings.push(ProofNode { hash: *node, side });
+            }
+            position /= 2;
+        }
+        Some(MerkleProof { leaf, siblings, leaf_index: index as u64 })
+    }
+
+    /// One proof covering every leaf in `indices`, or `None` when the list
+    /// is empty or any index is out of range.
+    ///
+    /// Walking up level by level, a sibling is recorded only when it can't
+    /// be computed from the proven leaves: when two proven nodes are
+    /// siblings, neither needs a hash. Indices may be unsorted or repeated.
+    pub fn prove_multi(&self, indices: &[usize]) -> Option<MultiProof> {
+        let mut known = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+        if known.last().is_none_or(|&last| last >= self.levels[0].len()) {
+            return None;
+        }
+        let mut hashes = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let mut next = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let position = known[i];
+                let sibling = position ^ 1;
+                if position.is_multiple_of(2) && known.get(i + 1) == Some(&sibling) {
+                    i += 2;
+                } else {
+                    hashes.extend(level.get(sibling));
+                    i += 1;
+                }
+                next.push(position / 2);
+            }
+            known = next;
+        }
+        Some(MultiProof { leaf_count: self.leaf_count(), hashes })
+    }
+}
+
+/// Parse the v2 text table list ("name=hash,name=hash").
+fn parse_table_list(list: &str) -> Result<Vec<TableHash>, ParseError> {
+    list.split(',')
+        .map(|entry| {
+            let (name, hash) = entry
+                .rsplit_once('=')
+                .filter(|(name, _)| !name.is_empty() && !name.contains('='))
+                .ok_or_else(|| ParseError::InvalidField { field: "table_hashes", value: entry.into() })?;
+            check_hex_digest("table_hashes", hash)?;
+            Ok(TableHash { table_name: name.into(), hash_hex: hash.into() })
+        })
+        .collect()
+}
+
+/// An append-only Merkle tree that keeps one subtree root per height (the
+/// frontier) instead of every node, giving the same roots as `MerkleTree`.
+///
+/// `frontier[h]` holds the root of the complete 2^h-leaf subtree not yet
+/// paired, present exactly when bit h of `leaf_count` is set. Under the
+/// promote-odd rule the root folds the frontier from the lowest height up,
+/// each higher subtree on the left. The whole struct serializes as a
+/// checkpoint; deserializing rejects frontiers that disagree with the count
+/// or were built with a different hash function.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(try_from = "FrontierCheckpoint", into = "FrontierCheckpoint", bound = "H: Hasher + Clone")]
+pub struct IncrementalMerkleTree<H = Sha256Hasher> {
+    mode: PairingMode,
+    leaf_count: u64,
+    frontier: Vec<Option<[u8; 32]>>,
+    hasher: PhantomData<H>,
+}
+
+/// Serialized form of an `IncrementalMerkleTree`, validated on the way in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FrontierCheckpoint {
+    #[serde(default)]
+    pub mode: PairingMode,
+    #[serde(default)]
+    pub hash_algo: HashAlgo,
+    pub leaf_count: u64,
+    pub frontier: Vec<Option<[u8; 32]>>,
+}
+
+impl<H: Hasher> TryFrom<FrontierCheckpoint> for IncrementalMerkleTree<H> {
+    type Error = String;
+
+    fn try_from(checkpoint: FrontierCheckpoint) -> Result<Self, String> {
+        let FrontierCheckpoint { mode, hash_algo, leaf_count, frontier } = checkpoint;
+        if hash_algo != H::ALGO {
+            return Err(format!("frontier was built with {}, expected {}", hash_algo.as_str(), H::ALGO.as_str()));
+        }
+        let consistent = frontier.len() == (u64::BITS - leaf_count.leading_zeros()) as usize
+            && frontier.iter().enumerate().all(|(height, node)| node.is_some() == ((leaf_count >> height) & 1 == 1));
+        if !consistent {
+            return Err(format!("frontier does not match a tree of {} leaves", leaf_count));
+        }
+        Ok(IncrementalMerkleTree { mode, leaf_count, frontier, hasher: PhantomData })
+    }
+}
+
+impl<H: Hasher> From<IncrementalMerkleTree<H>> for FrontierCheckpoint {
+    fn from(tree: IncrementalMerkleTree<H>) -> Self {
+        FrontierCheckpoint { mode: tree.mode, hash_algo: H::ALGO, leaf_count: tree.leaf_count, frontier: tree.frontier }
+    }
+}
+
+impl IncrementalMerkleTree {
+    /// An empty tree with ordered pairing.
+    pub fn new() -> Self {
+        IncrementalMerkleTree::default()
+    }
+
+    /// An empty tree pairing nodes as `mode` says.
+    pub fn with_mode(mode: PairingMode) -> Self {
+        IncrementalMerkleTree::with_hasher(mode)
+    }
+}
+
+impl<H: Hasher> IncrementalMerkleTree<H> {
+    /// An empty tree hashing with `H` and pairing nodes as `mode` says.
+    pub fn with_hasher(mode: PairingMode) -> Self {
+        IncrementalMerkleTree { mode, leaf_count: 0, frontier: Vec::new(), hasher: PhantomData }
+    }
+
+    /// Append `leaf`, merging complete subtrees up the frontier.
+    pub fn append(&mut self, leaf: [u8; 32]) {
+        let mut node = leaf;
+        let mut height = 0;
+        while let Some(left) = self.frontier.get_mut(height).and_then(Option::take) {
+            node = self.mode.hash_pair_with::<H>(&left, &node);
+            height += 1;
+        }
+        if height == self.frontier.len() {
+            self.frontier.push(None);
+        }
+        self.frontier[height] = Some(node);
+        self.leaf_count += 1;
+    }
+
+    /// The root hash, equal to `MerkleTree::from_leaves_with_hasher(..).root()`
+    /// over the same leaves.
+    pub fn root(&self) -> [u8; 32] {
+        self.frontier
+            .iter()
+            .flatten()
+            .fold(None, |acc, subtree| Some(acc.map_or(*subtree, |right| self.mode.hash_pair_with::<H>(subtree, &right))))
+            .unwrap_or_else(|| H::hash(b""))
+    }
+
+    /// Number of leaves appended so far.
+    pub fn leaf_count(&self) -> u64 {
+        self.leaf_count
+    }
+}
+
+/// Which side of the running hash a proof sibling sits on.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// One sibling on a proof path.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ProofNode {
+    pub hash: [u8; 32],
+    /// Present in ordered mode only; sorted pairing needs no side
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<Side>,
+}
+
+/// Proof that `leaf` is included under a Merkle root, without the other
+/// leaves. `siblings` run from the leaf level up. `leaf_index` records the
+/// leaf's position for the reader; the hash path alone does not bind it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MerkleProof {
+    pub leaf: [u8; 32],
+    pub siblings: Vec<ProofNode>,
+    pub leaf_index: u64,
+}
+
+impl MerkleProof {
+    /// Compact hex form for archiving next to an anchor:
+    ///
+    ///   hex(u64_be(leaf_index) ‖ leaf ‖ (side ‖ sibling)*)
+    ///
+    /// with side 0x00 for left, 0x01 for right and 0x02 for none.
+    pub fn to_hex(&self) -> String {
+        let mut out = Vec::with_capacity(40 + 33 * self.siblings.len());
+        out.extend_from_slice(&self.leaf_index.to_be_bytes());
+        out.extend_from_slice(&self.leaf);
+        for node in &self.siblings {
+            out.push(match node.side {
+                Some(Side::Left) => 0,
+                Some(Side::Right) => 1,
+                None => 2,
+            });
+            out.extend_from_slice(&node.hash);
+        }
+        hex::encode(out)
+    }
+
+    /// Parse the form written by `to_hex`. Proofs deeper than
+    /// `MAX_PROOF_DEPTH` are rejected.
+    pub fn from_hex(input: &str) -> Option<MerkleProof> {
+        let bytes = hex::decode(input).ok()?;
+        let (head, rest) = bytes.split_at_checked(40)?;
+        if rest.len() % 33 != 0 || rest.len() / 33 > MAX_PROOF_DEPTH {
+            return None;
+        }
+        let siblings = rest
+            .chunks(33)
+            .map(|chunk| {
+                let side = match chunk[0] {
+                    0 => Some(Side::Left),
+                    1 => Some(Side::Right),
+                    2 => None,
+                    _ => return None,
+                };
+                Some(ProofNode { hash: chunk[1..].try_into().ok()?, side })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(MerkleProof {
+            leaf_index: u64::from_be_bytes(head[..8].try_into().ok()?),
+            leaf: head[8..].try_into().ok()?,
+            siblings,
+        })
+    }
+}
+
+/// Proof that several leaves are included under one Merkle root.
+///
+/// `hashes` holds the siblings that can't be derived from the proven leaves,
+/// level by level from the leaves up and left to right within a level.
+/// `leaf_count` fixes the width of every level, and so where odd nodes are
+/// promoted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MultiProof {
+    pub leaf_count: u64,
+    pub hashes: Vec<[u8; 32]>,
+}
+
+impl MultiProof {
+    /// Compact hex form: hex(u64_be(leaf_count) ‖ hash*).
+    pub fn to_hex(&self) -> String {
+        let mut out = Vec::with_capacity(8 + 32 * self.hashes.len());
+        out.extend_from_slice(&self.leaf_count.to_be_bytes());
+        for hash in &self.hashes {
+            out.extend_from_slice(hash);
+        }
+        hex::encode(out)
+    }
+
+    /// Parse the form written by `to_hex`.
+    pub fn from_hex(input: &str) -> Option<MultiProof> {
+        let bytes = hex::decode(input).ok()?;
+        let (head, rest) = bytes.split_at_checked(8)?;
+        if rest.len() % 32 != 0 {
+            return None;
+        }
+        Some(MultiProof {
+            leaf_count: u64::from_be_bytes(head.try_into().ok()?),
+            hashes: rest.chunks(32).map(|chunk| chunk.try_into().ok()).collect::<Option<_>>()?,
+        })
+    }
+}
+
+/// Verify an ordered-mode multiproof for `leaves`, given as
+/// `(index, leaf)` pairs, against `root`.
+pub fn verify_multi_proof(root: &[u8; 32], leaves: &[(usize, [u8; 32])], proof: &MultiProof) -> bool {
+    verify_multi_proof_with_mode(root, leaves, proof, PairingMode::Ordered)
+}
+
+/// Verify a multiproof under `mode` by rebuilding the root from `leaves`
+/// and the proof hashes. Every proof hash must be consumed, and an empty or
+/// out-of-range leaf set, or one index claimed with two different leaves,
+/// is rejected. Sorted pairing does not bind a leaf to its side of a pair,
+/// so only ordered proofs pin down exact indices.
+pub fn verify_multi_proof_with_mode(
+    root: &[u8; 32],
+    leaves: &[(usize, [u8; 32])],
+    proof: &MultiProof,
+    mode: PairingMode,
+) -> bool {
+    verify_multi_proof_with_hasher::<Sha256Hasher>(root, leaves, proof, mode)
+}
+
+/// `verify_multi_proof_with_mode` for a tree hashed with `H`.
+pub fn verify_multi_proof_with_hasher<H: Hasher>(
+    root: &[u8; 32],
+    leaves: &[(usize, [u8; 32])],
+    proof: &MultiProof,
+    mode: PairingMode,
+) -> bool {
+    let mut nodes = leaves.to_vec();
+    nodes.sort_unstable();
+    nodes.dedup();
+    let in_range = nodes.last().is_some_and(|&(last, _)| (last as u64) < proof.leaf_count);
+    if !in_range || nodes.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return false;
+    }
+    let mut hashes = proof.hashes.iter();
+    let mut width = proof.leaf_count;
+    while width > 1 {
+        let mut next = Vec::with_capacity(nodes.len());
+        let mut i = 0;
+        while i < nodes.len() {
+            let (position, node) = nodes[i];
+            i += 1;
+            let parent = if !position.is_multiple_of(2) {
+                let Some(left) = hashes.next() else { return false };
+                mode.hash_pair_with::<H>(left, &node)
+            } else if let Some(&(_, right)) = nodes.get(i).filter(|(next, _)| *next == position + 1) {
+                i += 1;
+                mode.hash_pair_with::<H>(&node, &right)
+            } else if ((position + 1) as u64) < width {
+                let Some(right) = hashes.next() else { return false };
+                mode.hash_pair_with::<H>(&node, right)
+            } else {
+                node
+            };
+            next.push((position / 2, parent));
+        }
+        nodes = next;
+        width = width.div_ceil(2);
+    }
+    hashes.next().is_none() && nodes == [(0, *root)]
+}
+
+/// Verify an ordered-mode `proof` against `root`.
+pub fn verify_proof(root: &[u8; 32], proof: &MerkleProof) -> bool {
+    verify_proof_with_mode(root, proof, PairingMode::Ordered)
+}
+
+/// Verify `proof` against `root` by recomputing the path under `mode`.
+/// Ordered proofs need a side on every sibling and sorted proofs none.
+pub fn verify_proof_with_mode(root: &[u8; 32], proof: &MerkleProof, mode: PairingMode) -> bool {
+    verify_proof_with_hasher::<Sha256Hasher>(root, proof, mode)
+}
+
+/// `verify_proof_with_mode` for a tree hashed with `H`.
+pub fn verify_proof_with_hasher<H: Hasher>(root: &[u8; 32], proof: &MerkleProof, mode: PairingMode) -> bool {
+    if proof.siblings.len() > MAX_PROOF_DEPTH {
+        return false;
+    }
+    let computed = proof.siblings.iter().try_fold(proof.leaf, |node, sibling| match (mode, sibling.side) {
+        (PairingMode::Ordered, Some(Side::Left)) => Some(H::hash_pair(&sibling.hash, &node)),
+        (PairingMode::Ordered, Some(Side::Right)) => Some(H::hash_pair(&node, &sibling.hash)),
+        (PairingMode::Sorted, None) => Some(mode.hash_pair_with::<H>(&node, &sibling.hash)),
+        _ => None,
+    });
+    computed.as_ref() == Some(root)
+}
+
+/// Verify that `leaf` is included under `root`.
+///
+/// `siblings[i]` is the sibling node at level i (leaf level first) and
+/// `directions[i]` is `true` when that sibling is the right-hand child, i.e.
+/// the running hash is on the left. Proofs deeper than `MAX_PROOF_DEPTH` or
+/// with mismatched sibling/direction counts are rejected.
+pub fn verify_inclusion(
+    root: &[u8; 32],
+    leaf: &[u8; 32],
+    siblings: &[[u8; 32]],
+    directions: &[bool],
+) -> bool {
+    if siblings.len() != directions.len() || siblings.len() > MAX_PROOF_DEPTH {
+        return false;
+    }
+    let computed = siblings
+        .iter()
+        .zip(directions)
+        .fold(*leaf, |node, (sibling, &sibling_right)| {
+            if sibling_right {
+                hash_pair(&node, sibling)
+            } else {
+                hash_pair(sibling, &node)
+            }
+        });
+    &computed == root
+}
+
+/// Depth of a `SparseMerkleTree`: one level per bit of a SHA-256 key path
+pub const SMT_DEPTH: usize = 256;
+
+/// Root of an empty subtree at every height: `[0]` is the empty leaf (32
+/// zero bytes) and `[h + 1]` is `hash_pair([h], [h])`, up to the empty
+/// tree's root at `[SMT_DEPTH]`.
+pub fn smt_default_hashes() -> Vec<[u8; 32]> {
+    let mut defaults = vec![[0u8; 32]];
+    for height in 0..SMT_DEPTH {
+        let below = defaults[height];
+        defaults.push(hash_pair(&below, &below));
+    }
+    defaults
+}
+
+/// Bit `depth` of a 256-bit path, most significant bit first.
+fn path_bit(path: &[u8; 32], depth: usize) -> bool {
+    path[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+/// A sparse Merkle tree over the full 256-bit key space.
+///
+/// A key lives at the leaf addressed by `compute_sha256(key)`, read from the
+/// top bit down (0 = left). A present leaf hashes as
+/// `hash_pair(path, value_hash)`; every absent leaf is the zero hash, so
+/// empty subtrees take their value from `smt_default_hashes`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseMerkleTree {
+    leaves: BTreeMap<[u8; 32], [u8; 32]>,
+    defaults: Vec<[u8; 32]>,
+}
+
+/// A sparse Merkle proof with default-node compression.
+///
+/// Bit `d` of `bitmap` (most significant first) is set when the sibling at
+/// depth `d + 1` is not an empty subtree; only those siblings are listed,
+/// leaf level first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SmtProof {
+    pub bitmap: [u8; 32],
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl Default for SparseMerkleTree {
+    fn default() -> Self {
+        SparseMerkleTree::new()
+    }
+}
+
+impl SparseMerkleTree {
+    pub fn new() -> Self {
+        SparseMerkleTree { leaves: BTreeMap::new(), defaults: smt_default_hashes() }
+    }
+
+    /// Set `key` to `value_hash`, returning its previous value hash.
+    pub fn insert(&mut self, key: &[u8], value_hash: [u8; 32]) -> Option<[u8; 32]> {
+        self.leaves.insert(compute_sha256(key), value_hash)
+    }
+
+    /// The value hash stored under `key`.
+    pub fn get(&self, key: &[u8]) -> Option<[u8; 32]> {
+        self.leaves.get(&compute_sha256(key)).copied()
+    }
+
+    /// Number of keys present.
+    pub fn leaf_count(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    /// The root hash; `smt_default_hashes()[SMT_DEPTH]` when empty.
+    pub fn root(&self) -> [u8; 32] {
+        self.subtree(&self.entries(), 0)
+    }
+
+    /// Proof for `key`, showing its value hash when present and its absence
+    /// otherwise.
+    pub fn prove(&self, key: &[u8]) -> SmtProof {
+        let path = compute_sha256(key);
+        let entries = self.entries();
+        let mut range = entries.as_slice();
+        let mut proof = SmtProof { bitmap: [0u8; 32], siblings: Vec::new() };
+        for depth in 0..SMT_DEPTH {
+            let split = range.partition_point(|(p, _)| !path_bit(p, depth));
+            let (left, right) = range.split_at(split);
+            let (own, other) = if path_bit(&path, depth) { (right, left) } else { (left, right) };
+            if !other.is_empty() {
+                proof.bitmap[depth / 8] |= 0x80 >> (depth % 8);
+                proof.siblings.push(self.subtree(other, depth + 1));
+            }
+            range = own;
+        }
+        proof.siblings.reverse();
+        proof
+    }
+
+    fn entries(&self) -> Vec<([u8; 32], [u8; 32])> {
+        self.leaves.iter().map(|(path, value)| (*path, *value)).collect()
+    }
+
+    /// Hash of the subtree at `depth` holding `entries`, which are sorted by
+    /// path and share its first `depth` bits.
+    fn subtree(&self, entries: &[([u8; 32], [u8; 32])], depth: usize) -> [u8; 32] {
+        match entries {
+            [] => self.defaults[SMT_DEPTH - depth],
+            [(path, value), ..] if depth == SMT_DEPTH => hash_pair(path, value),
+            _ => {
+                let split = entries.partition_point(|(p, _)| !path_bit(p, depth));
+                hash_pair(&self.subtree(&entries[..split], depth + 1), &self.subtree(&entries[split..], depth + 1))
+            }
+        }
+    }
+}
+
+/// Verify a sparse Merkle proof for `key` under `root`: that it holds
+/// `value_hash`, or with `None`, that it is absent. Proofs whose sibling
+/// count disagrees with their bitmap are rejected.
+pub fn verify_smt_proof(root: &[u8; 32], key: &[u8], value_hash: Option<[u8; 32]>, proof: &SmtProof) -> bool {
+    let listed: u32 = proof.bitmap.iter().map(|byte| byte.count_ones()).sum();
+    if proof.siblings.len() != listed as usize {
+        return false;
+    }
+    let defaults = smt_default_hashes();
+    let path = compute_sha256(key);
+    let mut siblings = proof.siblings.iter();
+    let mut node = value_hash.map_or(defaults[0], |value| hash_pair(&path, &value));
+    for depth in (0..SMT_DEPTH).rev() {
+        let sibling = match path_bit(&proof.bitmap, depth) {
+            true => *siblings.next().expect("counted above"),
+            false => defaults[SMT_DEPTH - depth - 1],
+        };
+        node = if path_bit(&path, depth) { hash_pair(&sibling, &node) } else { hash_pair(&node, &sibling) };
+    }
+    &node == root
+}
+
+/// Ways a chained Merkle history can fail validation.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ChainError {
+    #[error("Payload {index} does not match its payload hash")]
+    InvalidPayload { index: usize },
+    #[error("Payload {index} links to {found:?}, expected previous root {expected}")]
+    BrokenLink {
+        index: usize,
+        expected: String,
+        found: Option<String>,
+    },
+    #[error("Payload 0 links to {found:?}, expected genesis {expected:?}")]
+    GenesisMismatch {
+        expected: Option<String>,
+        found: Option<String>,
+    },
+    #[error("Payload {index} has {current} leaves, fewer than the previous {previous}")]
+    LeafCountRegression {
+        index: usize,
+        previous: u64,
+        current: u64,
+    },
+}
+
+/// Validate a chain of Merkle root payloads, oldest first.
+///
+/// Every payload must verify, every `previous_root` after the first must be
+/// the prior payload's `root_hash`, and leaf counts must never decrease. The
+/// first payload's own `previous_root` is not checked, so a chain may start
+/// mid-history.
+pub fn validate_chain(chain: &[MerkleRootPayload]) -> Result<(), ChainError> {
+    for (index, payload) in chain.iter().enumerate() {
+        if !payload.verify() {
+            return Err(ChainError::InvalidPayload { index });
+        }
+        let Some(prior) = index.checked_sub(1).map(|i| &chain[i]) else {
+            continue;
+        };
+        if payload.previous_root.as_deref() != Some(prior.root_hash.as_str()) {
+            return Err(ChainError::BrokenLink {
+                index,
+                expected: prior.root_hash.clone(),
+                found: payload.previous_root.clone(),
+            });
+        }
+        if payload.leaf_count < prior.leaf_count {
+            return Err(ChainError::LeafCountRegression {
+                index,
+                previous: prior.leaf_count,
+                current: payload.leaf_count,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Validate a complete chain that starts at genesis: `validate_chain`, plus
+/// the first payload must have no `previous_root`.
+pub fn verify_chain(payloads: &[MerkleRootPayload]) -> Result<(), ChainError> {
+    verify_chain_from(payloads, None)
+}
+
+/// Validate a chain whose first payload must link to `genesis` (`None` for
+/// a chain with no predecessor). An empty chain is valid.
+pub fn verify_chain_from(payloads: &[MerkleRootPayload], genesis: Option<&str>) -> Result<(), ChainError> {
+    if let Some(first) = payloads.first() {
+        let found = first.previous_root.as_deref().filter(|root| !root.is_empty());
+        if first.verify() && found != genesis {
+            return Err(ChainError::GenesisMismatch {
+                expected: genesis.map(String::from),
+                found: found.map(String::from),
+            });
+        }
+    }
+    validate_chain(payloads)
+}
+
 /// Format a Merkle root for on-chain anchoring.
 pub fn format_merkle_anchor(root_hash: &str, leaf_count: u64) -> Vec<u8> {
     let decoded = hex::decode(root_hash).unwrap_or_default();
@@ -132,4 +1172,727 @@ mod tests {
         let result = format_merkle_anchor(&hash_hex, 100);
         assert!(!result.is_empty());
     }
+
+    #[test]
+    fn test_canonical_text_matches_legacy_format() {
+        let payload = MerkleRootPayload::with_version(MERKLE_PAYLOAD_V1, "b".repeat(64), 50, None, Some("c".repeat(64)));
+        let legacy = format!("merkle_root:{}:50:{}", "b".repeat(64), "c".repeat(64));
+        assert_eq!(payload.canonical_bytes(CanonicalMode::Text), legacy.as_bytes());
+        let genesis = MerkleRootPayload::with_version(MERKLE_PAYLOAD_V1, "b".repeat(64), 50, None, None);
+        assert_eq!(genesis.to_canonical_string(), format!("merkle_root:{}:50:", "b".repeat(64)));
+    }
+
+    #[test]
+    fn test_canonical_modes_agree_on_previous_root_presence() {
+        let none = MerkleRootPayload::new("a".repeat(64), 1, None, None);
+        let empty = MerkleRootPayload::new("a".repeat(64), 1, None, Some(String::new()));
+        let some = MerkleRootPayload::new("a".repeat(64), 1, None, Some("c".repeat(64)));
+        for mode in [CanonicalMode::Text, CanonicalMode::Binary] {
+            assert_eq!(none.canonical_bytes(mode), empty.canonical_bytes(mode));
+            assert_ne!(none.canonical_bytes(mode), some.canonical_bytes(mode));
+        }
+    }
+
+    #[test]
+    fn test_canonical_binary_previous_root_round_trip() {
+        use crate::canonical::decode_optional_hash;
+        for prev in [None, Some("c".repeat(64))] {
+            let payload = MerkleRootPayload::with_version(MERKLE_PAYLOAD_V1, "a".repeat(64), 7, None, prev.clone());
+            let bytes = payload.canonical_bytes(CanonicalMode::Binary);
+            // tag (12) + length prefix (4) + root (64) + leaf count (8)
+            let (decoded, used) = decode_optional_hash(&bytes[88..], CanonicalMode::Binary).unwrap();
+            assert_eq!(decoded, prev);
+            assert_eq!(88 + used, bytes.len());
+        }
+    }
+
+    #[test]
+    fn test_verify_inclusion_four_leaves() {
+        let leaves: Vec<[u8; 32]> = (0u8..4).map(|i| compute_sha256(&[i])).collect();
+        let n01 = hash_pair(&leaves[0], &leaves[1]);
+        let n23 = hash_pair(&leaves[2], &leaves[3]);
+        let root = hash_pair(&n01, &n23);
+
+        assert!(verify_inclusion(&root, &leaves[0], &[leaves[1], n23], &[true, true]));
+        assert!(verify_inclusion(&root, &leaves[3], &[leaves[2], n01], &[false, false]));
+        assert!(!verify_inclusion(&root, &leaves[3], &[leaves[2], n01], &[true, false]));
+        assert!(!verify_inclusion(&root, &leaves[0], &[leaves[1]], &[true, true]));
+    }
+
+    #[test]
+    fn test_verify_inclusion_single_leaf_and_depth_cap() {
+        let leaf = compute_sha256(b"only");
+        assert!(verify_inclusion(&leaf, &leaf, &[], &[]));
+        let deep = [[0u8; 32]; MAX_PROOF_DEPTH + 1];
+        assert!(!verify_inclusion(&leaf, &leaf, &deep, &[true; MAX_PROOF_DEPTH + 1]));
+    }
+
+    #[test]
+    fn test_merkle_canonical_round_trip() {
+        for previous in [None, Some("c".repeat(64))] {
+            let payload = MerkleRootPayload::new("a".repeat(64), 1024, None, previous);
+            let parsed = MerkleRootPayload::from_canonical_string(&payload.to_canonical_string()).unwrap();
+            assert_eq!(parsed.payload_hash, payload.payload_hash);
+            assert_eq!(parsed, payload);
+        }
+        let bad = format!("merkle_root:{}:01:", "a".repeat(64));
+        assert!(MerkleRootPayload::from_canonical_string(&bad).is_err());
+    }
+
+    fn chain(leaf_counts: &[u64]) -> Vec<MerkleRootPayload> {
+        let mut chain: Vec<MerkleRootPayload> = Vec::new();
+        for (i, &leaves) in leaf_counts.iter().enumerate() {
+            let previous = chain.last().map(|p| p.root_hash.clone());
+            chain.push(MerkleRootPayload::new(format!("{:064x}", i + 1), leaves, None, previous));
+        }
+        chain
+    }
+
+    #[test]
+    fn test_validate_chain_accepts_valid_history() {
+        assert_eq!(validate_chain(&chain(&[10, 10, 25])), Ok(()));
+        assert_eq!(validate_chain(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_chain_broken_link() {
+        let mut history = chain(&[10, 20, 30]);
+        history[2] = MerkleRootPayload::new(history[2].root_hash.clone(), 30, None, Some("f".repeat(64)));
+        assert_eq!(
+            validate_chain(&history),
+            Err(ChainError::BrokenLink {
+                index: 2,
+                expected: history[1].root_hash.clone(),
+                found: Some("f".repeat(64)),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_chain_invalid_payload() {
+        let mut history = chain(&[10, 20]);
+        history[1].leaf_count = 21;
+        assert_eq!(validate_chain(&history), Err(ChainError::InvalidPayload { index: 1 }));
+    }
+
+    #[test]
+    fn test_validate_chain_leaf_count_regression() {
+        assert_eq!(
+            validate_chain(&chain(&[10, 20, 15])),
+            Err(ChainError::LeafCountRegression { index: 2, previous: 20, current: 15 })
+        );
+    }
+
+    #[test]
+    fn test_verify_chain_from_genesis() {
+        assert_eq!(verify_chain(&[]), Ok(()));
+        assert_eq!(verify_chain_from(&[], Some(&"f".repeat(64))), Ok(()));
+        assert_eq!(verify_chain(&chain(&[10])), Ok(()));
+        assert_eq!(verify_chain(&chain(&[10, 20, 30])), Ok(()));
+
+        // A mid-history slice passes validate_chain but not verify_chain
+        let history = chain(&[10, 20, 30]);
+        assert_eq!(validate_chain(&history[1..]), Ok(()));
+        assert_eq!(
+            verify_chain(&history[1..]),
+            Err(ChainError::GenesisMismatch { expected: None, found: Some(history[0].root_hash.clone()) })
+        );
+        assert_eq!(verify_chain_from(&history[1..], Some(&history[0].root_hash)), Ok(()));
+        assert_eq!(
+            verify_chain_from(&history, Some(&history[0].root_hash)),
+            Err(ChainError::GenesisMismatch { expected: Some(history[0].root_hash.clone()), found: None })
+        );
+    }
+
+    #[test]
+    fn test_verify_chain_pinpoints_failure() {
+        let mut single = chain(&[10]);
+        single[0].leaf_count = 11;
+        assert_eq!(verify_chain(&single), Err(ChainError::InvalidPayload { index: 0 }));
+
+        let mut broken = chain(&[10, 20, 30, 40]);
+        broken[2] = MerkleRootPayload::new(broken[2].root_hash.clone(), 30, None, Some("f".repeat(64)));
+        assert_eq!(
+            verify_chain(&broken),
+            Err(ChainError::BrokenLink { index: 2, expected: broken[1].root_hash.clone(), found: Some("f".repeat(64)) })
+        );
+
+        let mut tampered = chain(&[10, 20, 30, 40]);
+        tampered[2].root_hash = "e".repeat(64);
+        assert_eq!(verify_chain(&tampered), Err(ChainError::InvalidPayload { index: 2 }));
+    }
+
+    #[test]
+    fn test_merkle_refresh_hash() {
+        let mut payload = MerkleRootPayload::new("a".repeat(64), 10, None, None);
+        assert!(!payload.refresh_hash());
+        payload.previous_root = Some("b".repeat(64));
+        assert!(payload.refresh_hash());
+        assert!(payload.verify());
+    }
+
+    fn numbered_leaves(n: u8) -> Vec<[u8; 32]> {
+        (0..n).map(|i| compute_sha256(&[i])).collect()
+    }
+
+    #[test]
+    fn test_merkle_tree_known_answers() {
+        let vectors = [
+            (1, "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"),
+            (2, "30e1867424e66e8b6d159246db94e3486778136f7e386ff5f001859d6b8484ab"),
+            (3, "773a93ac37ea78b3f14ac31872c83886b0a0f1fec562c4e848e023c889c2ce9f"),
+            (7, "7269be49c490af17ec87be84f3dc791c5f9923b4c557fefd83204f0c0f40b5ae"),
+        ];
+        for (n, root) in vectors {
+            let tree = MerkleTree::from_leaves(&numbered_leaves(n));
+            assert_eq!(hex::encode(tree.root()), root, "{} leaves", n);
+            assert_eq!(tree.leaf_count(), n as u64);
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_promotes_odd_node() {
+        let leaves = numbered_leaves(3);
+        let tree = MerkleTree::from_leaves(&leaves);
+        let n01 = hash_pair(&leaves[0], &leaves[1]);
+        assert_eq!(tree.root(), hash_pair(&n01, &leaves[2]));
+        assert_ne!(tree.root(), MerkleTree::from_leaves(&[leaves[0], leaves[1], leaves[2], leaves[2]]).root());
+        // The promoted leaf has no sibling at its own level
+        assert!(verify_inclusion(&tree.root(), &leaves[2], &[n01], &[false]));
+        assert_eq!(MerkleTree::from_leaves(&[]).root(), compute_sha256(b""));
+    }
+
+    #[test]
+    fn test_payload_from_tree() {
+        let tree = MerkleTree::from_leaves(&numbered_leaves(7));
+        let payload = MerkleRootPayload::from_tree(&tree, Some("c".repeat(64)));
+        assert_eq!(payload.root_bytes(), Some(tree.root()));
+        assert_eq!(payload.leaf_count, 7);
+        assert!(payload.verify());
+    }
+
+    #[test]
+    fn test_prove_and_verify_every_leaf() {
+        for n in [1u8, 2, 3, 7] {
+            let leaves = numbered_leaves(n);
+            let tree = MerkleTree::from_leaves(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.prove(i).unwrap();
+                assert_eq!((proof.leaf, proof.leaf_index), (*leaf, i as u64));
+                assert!(verify_proof(&tree.root(), &proof), "leaf {} of {}", i, n);
+            }
+            assert!(tree.prove(n as usize).is_none());
+        }
+    }
+
+    #[test]
+    fn test_proof_shapes() {
+        let leaves = numbered_leaves(7);
+        let tree = MerkleTree::from_leaves(&leaves);
+        let first = tree.prove(0).unwrap();
+        assert_eq!(first.siblings.len(), 3);
+        assert_eq!(first.siblings[0], ProofNode { hash: leaves[1], side: Some(Side::Right) });
+        // The last leaf is promoted at the leaf level, so its path is shorter
+        let last = tree.prove(6).unwrap();
+        assert_eq!(last.siblings.len(), 2);
+        assert!(last.siblings.iter().all(|node| node.side == Some(Side::Left)));
+
+        let single = MerkleTree::from_leaves(&leaves[..1]).prove(0).unwrap();
+        assert!(single.siblings.is_empty());
+        assert!(verify_proof(&leaves[0], &single));
+    }
+
+    #[test]
+    fn test_tampered_proof_fails() {
+        let tree = MerkleTree::from_leaves(&numbered_leaves(7));
+        let proof = tree.prove(3).unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.siblings[1].hash[0] ^= 1;
+        assert!(!verify_proof(&tree.root(), &tampered));
+        let mut flipped = proof.clone();
+        flipped.siblings[0].side = Some(Side::Right);
+        assert!(!verify_proof(&tree.root(), &flipped));
+        let mut other_leaf = proof;
+        other_leaf.leaf = compute_sha256(b"not a leaf");
+        assert!(!verify_proof(&tree.root(), &other_leaf));
+    }
+
+    #[test]
+    fn test_proof_serialization_round_trips() {
+        let tree = MerkleTree::from_leaves(&numbered_leaves(7));
+        let proof = tree.prove(5).unwrap();
+        let hex_form = proof.to_hex();
+        assert_eq!(hex_form.len(), 2 * (40 + 33 * proof.siblings.len()));
+        assert!(hex_form.starts_with("0000000000000005"));
+        assert_eq!(MerkleProof::from_hex(&hex_form), Some(proof.clone()));
+
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<MerkleProof>(&json).unwrap(), proof);
+        assert_eq!(serde_json::to_string(&tree.prove(5).unwrap()).unwrap(), json);
+
+        assert!(MerkleProof::from_hex(&hex_form[..hex_form.len() - 2]).is_none());
+        let bad_side = format!("{}03{}", &hex_form[..80], &hex_form[82..]);
+        assert!(MerkleProof::from_hex(&bad_side).is_none());
+        assert!(MerkleProof::from_hex("zz").is_none());
+    }
+
+    #[test]
+    fn test_sorted_pairing_mode() {
+        let leaves = numbered_leaves(7);
+        let ordered = MerkleTree::from_leaves(&leaves);
+        let sorted = MerkleTree::from_leaves_with_mode(&leaves, PairingMode::Sorted);
+        assert_eq!(ordered.mode(), PairingMode::default());
+        assert_ne!(ordered.root(), sorted.root());
+        // Swapping a pair's children leaves the sorted parent unchanged
+        let (a, b) = (leaves[0], leaves[1]);
+        assert_eq!(PairingMode::Sorted.hash_pair(&a, &b), PairingMode::Sorted.hash_pair(&b, &a));
+        assert_ne!(PairingMode::Ordered.hash_pair(&a, &b), PairingMode::Ordered.hash_pair(&b, &a));
+
+        for index in [0, 3, 6] {
+            let proof = sorted.prove(index).unwrap();
+            assert!(proof.siblings.iter().all(|node| node.side.is_none()));
+            assert!(verify_proof_with_mode(&sorted.root(), &proof, PairingMode::Sorted));
+            assert_eq!(MerkleProof::from_hex(&proof.to_hex()), Some(proof.clone()));
+            assert!(!serde_json::to_string(&proof).unwrap().contains("side"));
+        }
+    }
+
+    #[test]
+    fn test_proofs_fail_across_pairing_modes() {
+        let leaves = numbered_leaves(7);
+        let ordered = MerkleTree::from_leaves(&leaves);
+        let sorted = MerkleTree::from_leaves_with_mode(&leaves, PairingMode::Sorted);
+        for index in 0..leaves.len() {
+            let ordered_proof = ordered.prove(index).unwrap();
+            let sorted_proof = sorted.prove(index).unwrap();
+            assert!(!verify_proof_with_mode(&sorted.root(), &ordered_proof, PairingMode::Sorted));
+            assert!(!verify_proof_with_mode(&ordered.root(), &sorted_proof, PairingMode::Ordered));
+            assert!(!verify_proof(&sorted.root(), &ordered_proof));
+            // Same root, wrong mode for the proof's shape
+            assert!(!verify_proof_with_mode(&ordered.root(), &ordered_proof, PairingMode::Sorted));
+            assert!(!verify_proof_with_mode(&sorted.root(), &sorted_proof, PairingMode::Ordered));
+        }
+    }
+
+    #[test]
+    fn test_incremental_matches_full_tree() {
+        let leaves: Vec<[u8; 32]> = (0u32..3000).map(|i| compute_sha256(&i.to_be_bytes())).collect();
+        // Every length up to 64, then pseudo-random lengths up to 3000
+        let mut lengths: Vec<usize> = (0..=64).collect();
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        for _ in 0..40 {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            lengths.push((state >> 33) as usize % leaves.len() + 1);
+        }
+        lengths.sort_unstable();
+
+        for mode in [PairingMode::Ordered, PairingMode::Sorted] {
+            let mut incremental = IncrementalMerkleTree::with_mode(mode);
+            let mut appended = 0;
+            for &n in &lengths {
+                for leaf in &leaves[appended..n] {
+                    incremental.append(*leaf);
+                }
+                appended = n;
+                let full = MerkleTree::from_leaves_with_mode(&leaves[..n], mode);
+                assert_eq!(incremental.root(), full.root(), "{:?} with {} leaves", mode, n);
+                assert_eq!(incremental.leaf_count(), n as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_checkpoint_round_trip() {
+        let leaves = numbered_leaves(11);
+        let mut uninterrupted = IncrementalMerkleTree::new();
+        for leaf in &leaves {
+            uninterrupted.append(*leaf);
+        }
+
+        let mut first = IncrementalMerkleTree::new();
+        for leaf in &leaves[..6] {
+            first.append(*leaf);
+        }
+        let checkpoint = serde_json::to_string(&first).unwrap();
+        let mut resumed: IncrementalMerkleTree = serde_json::from_str(&checkpoint).unwrap();
+        assert_eq!(resumed, first);
+        for leaf in &leaves[6..] {
+            resumed.append(*leaf);
+        }
+        assert_eq!(resumed.root(), uninterrupted.root());
+
+        let payload = MerkleRootPayload::from_incremental(&resumed, None);
+        assert_eq!(payload, MerkleRootPayload::from_tree(&MerkleTree::from_leaves(&leaves), None));
+
+        // 6 leaves = 0b110: the frontier must hold heights 1 and 2 only
+        let mut bad: FrontierCheckpoint = serde_json::from_str(&checkpoint).unwrap();
+        bad.leaf_count = 7;
+        assert!(serde_json::from_str::<IncrementalMerkleTree>(&serde_json::to_string(&bad).unwrap()).is_err());
+        assert_eq!(IncrementalMerkleTree::new().root(), MerkleTree::from_leaves(&[]).root());
+    }
+
+    fn tables(names: &[&str]) -> Vec<TableHash> {
+        names
+            .iter()
+            .map(|name| TableHash { table_name: name.to_string(), hash_hex: hex::encode(compute_sha256(name.as_bytes())) })
+            .collect()
+    }
+
+    #[test]
+    fn test_table_hashes_are_sorted_and_hashed() {
+        let payload = MerkleRootPayload::new("a".repeat(64), 10, Some(tables(&["sources", "claims"])), None);
+        assert_eq!(payload.version, MERKLE_PAYLOAD_VERSION);
+        assert_eq!(payload.table_hashes, Some(tables(&["claims", "sources"])));
+        assert!(payload.verify());
+        let [claims, sources] = [&tables(&["claims"])[0].hash_hex, &tables(&["sources"])[0].hash_hex];
+        assert!(payload.to_canonical_string().ends_with(&format!(":claims={},sources={}", claims, sources)));
+
+        let without = MerkleRootPayload::new("a".repeat(64), 10, None, None);
+        assert_ne!(payload.payload_hash, without.payload_hash);
+        assert_eq!(MerkleRootPayload::new("a".repeat(64), 10, Some(vec![]), None), without);
+    }
+
+    #[test]
+    fn test_table_hash_tampering_fails_verification() {
+        let payload = MerkleRootPayload::new("a".repeat(64), 10, Some(tables(&["claims", "equations"])), None);
+
+        let mut swapped = payload.clone();
+        swapped.table_hashes.as_mut().unwrap()[1].hash_hex = "f".repeat(64);
+        assert!(!swapped.verify());
+
+        let mut renamed = payload.clone();
+        renamed.table_hashes.as_mut().unwrap()[0].table_name = "claims_v2".into();
+        assert!(!renamed.verify());
+
+        let mut dropped = payload.clone();
+        dropped.table_hashes.as_mut().unwrap().pop();
+        assert!(!dropped.verify());
+
+        let mut stripped = payload.clone();
+        stripped.table_hashes = None;
+        assert!(!stripped.verify());
+
+        // Re-signing with an ambiguous name or out-of-order entries still fails
+        let mut ambiguous = payload.clone();
+        ambiguous.table_hashes.as_mut().unwrap()[0].table_name = "claims,x".into();
+        ambiguous.refresh_hash();
+        assert!(!ambiguous.verify());
+        let mut unsorted = payload;
+        unsorted.table_hashes.as_mut().unwrap().reverse();
+        unsorted.refresh_hash();
+        assert!(!unsorted.verify());
+    }
+
+    #[test]
+    fn test_v1_payloads_still_verify() {
+        // Stored before versioning, with the old free-form table blob
+        let v1 = MerkleRootPayload::with_version(MERKLE_PAYLOAD_V1, "a".repeat(64), 10, None, Some("b".repeat(64)));
+        assert_eq!(v1.to_canonical_string(), format!("merkle_root:{}:10:{}", "a".repeat(64), "b".repeat(64)));
+        let stored = format!(
+            r#"{{"root_hash":"{}","leaf_count":10,"table_hashes":"{{\"claims\":\"00\"}}","previous_root":"{}","payload_hash":"{}"}}"#,
+            "a".repeat(64),
+            "b".repeat(64),
+            v1.payload_hash
+        );
+        let loaded: MerkleRootPayload = cosmwasm_std::from_json(stored.as_bytes()).unwrap();
+        assert_eq!(loaded, v1);
+        assert!(loaded.verify());
+
+        // v1 rules ignore tables entirely; unknown versions never verify
+        let mut with_tables = v1.clone();
+        with_tables.table_hashes = Some(tables(&["claims"]));
+        assert!(with_tables.verify());
+        let mut future = v1;
+        future.version = MERKLE_PAYLOAD_VERSION + 1;
+        future.refresh_hash();
+        assert!(!future.verify());
+    }
+
+    #[test]
+    fn test_v2_canonical_round_trip_with_tables() {
+        let payload = MerkleRootPayload::new("a".repeat(64), 10, Some(tables(&["claims", "sources"])), Some("c".repeat(64)));
+        let parsed = MerkleRootPayload::from_canonical_string(&payload.to_canonical_string()).unwrap();
+        assert_eq!(parsed, payload);
+
+        let bare = MerkleRootPayload::new("a".repeat(64), 10, None, None);
+        assert_eq!(MerkleRootPayload::from_canonical_string(&bare.to_canonical_string()).unwrap(), bare);
+
+        let bad = format!("merkle_root_v2:{}:10::claims=zz", "a".repeat(64));
+        assert!(MerkleRootPayload::from_canonical_string(&bad).is_err());
+        assert_ne!(payload.canonical_bytes(CanonicalMode::Binary), bare.canonical_bytes(CanonicalMode::Binary));
+    }
+
+    #[test]
+    fn test_smt_default_hashes() {
+        let defaults = smt_default_hashes();
+        assert_eq!(defaults.len(), SMT_DEPTH + 1);
+        let vectors = [
+            (0, "0000000000000000000000000000000000000000000000000000000000000000"),
+            (1, "f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b"),
+            (2, "db56114e00fdd4c1f85c892bf35ac9a89289aaecb1ebd0a96cde606a748b5d71"),
+            (3, "c78009fdf07fc56a11f122370658a353aaa542ed63e44c4bc15ff4cd105ab33c"),
+            (SMT_DEPTH, "b178c245c947ea7e21ecede07728941a6ab1b706143c06873baff8ebd6de6308"),
+        ];
+        for (height, expected) in vectors {
+            assert_eq!(hex::encode(defaults[height]), expected, "height {}", height);
+        }
+        assert_eq!(SparseMerkleTree::new().root(), defaults[SMT_DEPTH]);
+    }
+
+    fn snapshot() -> SparseMerkleTree {
+        let mut tree = SparseMerkleTree::new();
+        for (key, value) in [(b"row-1", b"a"), (b"row-2", b"b"), (b"row-3", b"c")] {
+            tree.insert(key, compute_sha256(value));
+        }
+        tree
+    }
+
+    #[test]
+    fn test_smt_root_is_order_independent() {
+        let tree = snapshot();
+        assert_eq!(hex::encode(tree.root()), "e28e33b9d3bcfc53931528370a80db9735cff7613300bb690d01e655176179e6");
+
+        let mut reversed = SparseMerkleTree::new();
+        for (key, value) in [(b"row-3", b"c"), (b"row-2", b"b"), (b"row-1", b"a")] {
+            reversed.insert(key, compute_sha256(value));
+        }
+        assert_eq!(reversed.root(), tree.root());
+
+        let mut updated = tree.clone();
+        assert_eq!(updated.insert(b"row-2", compute_sha256(b"b2")), Some(compute_sha256(b"b")));
+        assert_ne!(updated.root(), tree.root());
+        assert_eq!(updated.leaf_count(), 3);
+    }
+
+    #[test]
+    fn test_smt_membership_proofs() {
+        let tree = snapshot();
+        let root = tree.root();
+        for key in [&b"row-1"[..], b"row-2", b"row-3"] {
+            let value = tree.get(key).unwrap();
+            let proof = tree.prove(key);
+            // Three leaves share few branches, so most siblings are defaults
+            assert!(proof.siblings.len() <= 3, "{} siblings", proof.siblings.len());
+            assert!(verify_smt_proof(&root, key, Some(value), &proof));
+            assert!(!verify_smt_proof(&root, key, Some(compute_sha256(b"other")), &proof));
+            assert!(!verify_smt_proof(&root, key, None, &proof));
+        }
+        assert!(!verify_smt_proof(&root, b"row-2", tree.get(b"row-1"), &tree.prove(b"row-1")));
+    }
+
+    #[test]
+    fn test_smt_non_membership_proofs() {
+        let tree = snapshot();
+        let root = tree.root();
+        let proof = tree.prove(b"row-4");
+        assert!(verify_smt_proof(&root, b"row-4", None, &proof));
+        assert!(!verify_smt_proof(&root, b"row-4", Some(compute_sha256(b"d")), &proof));
+        // An absence proof does not survive the key being added
+        let mut grown = tree.clone();
+        grown.insert(b"row-4", compute_sha256(b"d"));
+        assert!(!verify_smt_proof(&grown.root(), b"row-4", None, &proof));
+        assert!(verify_smt_proof(&SparseMerkleTree::new().root(), b"row-4", None, &SparseMerkleTree::new().prove(b"row-4")));
+    }
+
+    #[test]
+    fn test_smt_rejects_malformed_proofs() {
+        let tree = snapshot();
+        let root = tree.root();
+        let proof = tree.prove(b"row-1");
+        let value = tree.get(b"row-1");
+
+        let mut extra = proof.clone();
+        extra.siblings.push([0u8; 32]);
+        assert!(!verify_smt_proof(&root, b"row-1", value, &extra));
+
+        let mut flipped = proof.clone();
+        flipped.bitmap[31] ^= 1;
+        assert!(!verify_smt_proof(&root, b"row-1", value, &flipped));
+
+        let mut tampered = proof;
+        tampered.siblings[0][0] ^= 1;
+        assert!(!verify_smt_proof(&root, b"row-1", value, &tampered));
+    }
+
+    #[test]
+    fn test_multi_proof_every_subset() {
+        // Seven leaves exercise promoted odd nodes on two levels
+        let leaves = numbered_leaves(7);
+        let tree = MerkleTree::from_leaves(&leaves);
+        for mask in 1u32..(1 << leaves.len()) {
+            let indices: Vec<usize> = (0..leaves.len()).filter(|i| mask & (1 << i) != 0).collect();
+            let claimed: Vec<(usize, [u8; 32])> = indices.iter().map(|&i| (i, leaves[i])).collect();
+            let proof = tree.prove_multi(&indices).unwrap();
+            assert!(verify_multi_proof(&tree.root(), &claimed, &proof), "subset {:07b}", mask);
+        }
+
+        let sorted = MerkleTree::from_leaves_with_mode(&leaves, PairingMode::Sorted);
+        let proof = sorted.prove_multi(&[1, 4, 6]).unwrap();
+        let claimed = [(1, leaves[1]), (4, leaves[4]), (6, leaves[6])];
+        assert!(verify_multi_proof_with_mode(&sorted.root(), &claimed, &proof, PairingMode::Sorted));
+
+        assert_eq!(tree.prove_multi(&[]), None);
+        assert_eq!(tree.prove_multi(&[2, 7]), None);
+        assert_eq!(tree.prove_multi(&[5, 2, 5]), tree.prove_multi(&[2, 5]));
+    }
+
+    #[test]
+    fn test_multi_proof_smaller_than_single_proofs() {
+        let leaves: Vec<[u8; 32]> = (0u32..1024).map(|i| compute_sha256(&i.to_be_bytes())).collect();
+        let tree = MerkleTree::from_leaves(&leaves);
+        let mut state = 7u64;
+        let mut indices = Vec::new();
+        while indices.len() < 200 {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let index = (state >> 33) as usize % leaves.len();
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+
+        let proof = tree.prove_multi(&indices).unwrap();
+        let claimed: Vec<(usize, [u8; 32])> = indices.iter().map(|&i| (i, leaves[i])).collect();
+        assert!(verify_multi_proof(&tree.root(), &claimed, &proof));
+
+        let single: usize = indices.iter().map(|&i| tree.prove(i).unwrap().to_hex().len()).sum();
+        let multi = proof.to_hex().len();
+        assert!(multi * 3 < single, "multiproof {} vs single proofs {}", multi, single);
+    }
+
+    #[test]
+    fn test_multi_proof_rejects_wrong_claims() {
+        let leaves = numbered_leaves(16);
+        let tree = MerkleTree::from_leaves(&leaves);
+        let root = tree.root();
+        let proof = tree.prove_multi(&[2, 3, 9]).unwrap();
+        let claimed = [(2, leaves[2]), (3, leaves[3]), (9, leaves[9])];
+        assert!(verify_multi_proof(&root, &claimed, &proof));
+
+        assert!(!verify_multi_proof(&root, &[(2, leaves[2]), (3, leaves[3]), (9, leaves[8])], &proof));
+        assert!(!verify_multi_proof(&root, &[(2, leaves[2]), (3, leaves[3]), (8, leaves[9])], &proof));
+        assert!(!verify_multi_proof(&root, &[(3, leaves[2]), (2, leaves[3]), (9, leaves[9])], &proof));
+        assert!(!verify_multi_proof(&root, &[(2, leaves[2]), (3, leaves[3])], &proof));
+        assert!(!verify_multi_proof(&root, &[(2, leaves[2]), (2, leaves[3]), (9, leaves[9])], &proof));
+        assert!(!verify_multi_proof(&root, &[], &proof));
+        assert!(!verify_multi_proof(&root, &[(16, leaves[0])], &proof));
+
+        let mut extra = proof.clone();
+        extra.hashes.push([0u8; 32]);
+        assert!(!verify_multi_proof(&root, &claimed, &extra));
+        let mut short = proof.clone();
+        short.hashes.pop();
+        assert!(!verify_multi_proof(&root, &claimed, &short));
+        let mut wider = proof.clone();
+        wider.leaf_count = 17;
+        assert!(!verify_multi_proof(&root, &claimed, &wider));
+    }
+
+    #[test]
+    fn test_multi_proof_serialization() {
+        let tree = MerkleTree::from_leaves(&numbered_leaves(10));
+        let proof = tree.prove_multi(&[9, 0, 4]).unwrap();
+        assert_eq!(MultiProof::from_hex(&proof.to_hex()), Some(proof.clone()));
+        assert_eq!(serde_json::to_string(&proof).unwrap(), serde_json::to_string(&tree.prove_multi(&[0, 4, 9]).unwrap()).unwrap());
+        assert_eq!(&proof.to_hex()[..16], "000000000000000a");
+        assert_eq!(MultiProof::from_hex(&proof.to_hex()[..proof.to_hex().len() - 2]), None);
+    }
+
+    #[test]
+    fn test_hash_algo_is_covered_from_v3() {
+        let payload = MerkleRootPayload::new("a".repeat(64), 10, None, None).with_hash_algo(HashAlgo::Keccak256);
+        assert!(payload.verify());
+        assert!(payload.to_canonical_string().starts_with("merkle_root_v3:keccak256:"));
+        assert_eq!(MerkleRootPayload::from_canonical_string(&payload.to_canonical_string()).unwrap(), payload);
+
+        let mut swapped = payload.clone();
+        swapped.hash_algo = HashAlgo::Sha256;
+        assert!(!swapped.verify());
+
+        // v2 never committed to the algorithm, so only SHA-256 is accepted
+        let mut v2 = MerkleRootPayload::with_version(MERKLE_PAYLOAD_V2, "a".repeat(64), 10, None, None);
+        assert!(v2.verify());
+        v2.hash_algo = HashAlgo::Keccak256;
+        v2.refresh_hash();
+        assert!(!v2.verify());
+
+        let bad = format!("merkle_root_v3:blake3:{}:10::", "a".repeat(64));
+        assert_eq!(
+            MerkleRootPayload::from_canonical_string(&bad),
+            Err(ParseError::InvalidField { field: "hash_algo", value: "blake3".into() })
+        );
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_keccak256_solidity_vectors() {
+        use crate::anchor_registry::compute_keccak256;
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let vectors: [(&[u8], &str); 4] = [
+            (b"", "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+            (b"abc", "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"),
+            // keccak256("transfer(address,uint256)"), the ERC-20 selector preimage
+            (b"transfer(address,uint256)", "a9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b"),
+            // keccak256(abi.encode(uint256(1)))
+            (&one, "b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(hex::encode(compute_keccak256(input)), expected);
+        }
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_keccak_tree_matches_solidity_zero_hashes() {
+        // keccak256(abi.encodePacked(z, z)) ladder used by Solidity
+        // incremental trees, starting from bytes32(0)
+        let zeros = [[0u8; 32]; 4];
+        let two = MerkleTree::<Keccak256Hasher>::from_leaves_with_hasher(&zeros[..2], PairingMode::Ordered);
+        assert_eq!(hex::encode(two.root()), "ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5");
+        let four = MerkleTree::<Keccak256Hasher>::from_leaves_with_hasher(&zeros, PairingMode::Ordered);
+        assert_eq!(hex::encode(four.root()), "b4c11951957c6f8f642c4af61cd6b24640fec6dc7fc607ee8206a99e92410d30");
+        assert_ne!(four.root(), MerkleTree::from_leaves(&zeros).root());
+
+        let payload = MerkleRootPayload::from_tree(&four, None);
+        assert_eq!(payload.hash_algo, HashAlgo::Keccak256);
+        assert!(payload.verify());
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_keccak_proofs_and_incremental_tree() {
+        use crate::anchor_registry::compute_keccak256;
+        let leaves: Vec<[u8; 32]> = (0u8..7).map(|i| compute_keccak256(&[i])).collect();
+        for mode in [PairingMode::Ordered, PairingMode::Sorted] {
+            let tree = MerkleTree::<Keccak256Hasher>::from_leaves_with_hasher(&leaves, mode);
+            for index in 0..leaves.len() {
+                let proof = tree.prove(index).unwrap();
+                assert!(verify_proof_with_hasher::<Keccak256Hasher>(&tree.root(), &proof, mode));
+                assert!(!verify_proof_with_hasher::<Sha256Hasher>(&tree.root(), &proof, mode));
+            }
+            let claimed = [(1, leaves[1]), (5, leaves[5])];
+            let multi = tree.prove_multi(&[1, 5]).unwrap();
+            assert!(verify_multi_proof_with_hasher::<Keccak256Hasher>(&tree.root(), &claimed, &multi, mode));
+            assert!(!verify_multi_proof_with_mode(&tree.root(), &claimed, &multi, mode));
+
+            let mut incremental = IncrementalMerkleTree::<Keccak256Hasher>::with_hasher(mode);
+            for leaf in &leaves {
+                incremental.append(*leaf);
+            }
+            assert_eq!(incremental.root(), tree.root());
+        }
+
+        // OpenZeppelin's sorted pair: keccak256(abi.encodePacked(min, max))
+        let sorted = MerkleTree::<Keccak256Hasher>::from_leaves_with_hasher(&leaves[..2], PairingMode::Sorted);
+        let (lo, hi) = if leaves[0] < leaves[1] { (leaves[0], leaves[1]) } else { (leaves[1], leaves[0]) };
+        assert_eq!(sorted.root(), compute_keccak256(&[lo, hi].concat()));
+
+        // A checkpoint only resumes under the hash function that wrote it
+        let mut sha = IncrementalMerkleTree::new();
+        sha.append(leaves[0]);
+        let checkpoint = serde_json::to_string(&sha).unwrap();
+        assert!(serde_json::from_str::<IncrementalMerkleTree<Keccak256Hasher>>(&checkpoint).is_err());
+        assert!(serde_json::from_str::<IncrementalMerkleTree>(&checkpoint).is_ok());
+    }
 }
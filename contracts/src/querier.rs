@@ -0,0 +1,53 @@
+//! Registry Querier – Typed access to a deployed registry from other contracts.
+//!
+//! Consuming contracts that gate actions on an anchor (for example "only
+//! settle once this root is anchored") query the registry through
+//! `AnchorRegistryQuerier` instead of building `WasmQuery::Smart` requests by
+//! hand. Every `QueryMsg` it sends is constructed here, so a change to the
+//! message shape breaks consumers at compile time rather than on-chain.
+
+use cosmwasm_std::{Addr, Binary, QuerierWrapper, StdResult};
+use serde::de::DeserializeOwned;
+
+use crate::anchor_registry::{ConfigResponse, QueryMsg, VerifyResponse};
+
+/// Read-only handle on a registry contract at `registry`.
+pub struct AnchorRegistryQuerier<'a> {
+    querier: &'a QuerierWrapper<'a>,
+    registry: Addr,
+}
+
+impl<'a> AnchorRegistryQuerier<'a> {
+    pub fn new(querier: &'a QuerierWrapper<'a>, registry: Addr) -> Self {
+        AnchorRegistryQuerier { querier, registry }
+    }
+
+    /// Address of the registry being queried.
+    pub fn registry(&self) -> &Addr {
+        &self.registry
+    }
+
+    /// Whether the Merkle root `hash` is anchored.
+    pub fn verify_root(&self, hash: &[u8; 32]) -> StdResult<VerifyResponse> {
+        self.query(&QueryMsg::VerifyRoot { hash: Some(Binary::from(hash.as_slice())), hash_hex: None })
+    }
+
+    /// Whether the claim score `hash` is anchored.
+    pub fn verify_claim_score(&self, hash: &[u8; 32]) -> StdResult<VerifyResponse> {
+        self.query(&QueryMsg::VerifyClaimScore { hash: Some(Binary::from(hash.as_slice())), hash_hex: None })
+    }
+
+    /// Whether the equation proof `hash` is anchored.
+    pub fn verify_equation_proof(&self, hash: &[u8; 32]) -> StdResult<VerifyResponse> {
+        self.query(&QueryMsg::VerifyEquationProof { hash: Some(Binary::from(hash.as_slice())), hash_hex: None })
+    }
+
+    /// The registry's current configuration.
+    pub fn get_config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::GetConfig {})
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier.query_wasm_smart(&self.registry, msg)
+    }
+}
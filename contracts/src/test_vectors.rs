@@ -0,0 +1,458 @@
+//! Test Vectors – Deterministic cross-implementation fixtures.
+//!
+//! Produces, for every payload type and canonical format version, a set of
+//! inputs together with the exact canonical bytes (hex) and resulting
+//! payload hash, plus Merkle trees (leaves, root, and a proof) for each hash
+//! function and pairing mode. Python, TypeScript, and Solidity ports check
+//! themselves against the committed `testdata/test_vectors.json`;
+//! `verify_vectors` re-derives every vector so an accidental canonical-format
+//! change in this crate fails loudly. Keccak-256 trees need the `keccak` feature, which
+//! `test-vectors` enables; without it they are skipped.
+//!
+//! Regenerate with:
+//!   cargo run --features test-vectors --bin gen_test_vectors
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::anchor_registry::{compute_sha256, PAYLOAD_FORMAT_VERSION};
+use crate::claim_score_anchor::{score_vector_canonical, ClaimScorePayload};
+use crate::equation_proof_anchor::EquationProofPayload;
+#[cfg(feature = "keccak")]
+use crate::merkle_anchor::Keccak256Hasher;
+use crate::merkle_anchor::{
+    HashAlgo, Hasher, MerkleRootPayload, MerkleTree, PairingMode, Sha256Hasher, TableHash, MERKLE_PAYLOAD_V1,
+    MERKLE_PAYLOAD_V2, MERKLE_PAYLOAD_VERSION,
+};
+
+/// Location of the committed vector file, relative to the crate root.
+pub const VECTORS_PATH: &str = "testdata/test_vectors.json";
+
+/// Inputs for a single payload vector, tagged by payload type.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "payload_type", rename_all = "snake_case")]
+pub enum PayloadInputs {
+    ClaimScore {
+        claim_id: u64,
+        composite_score: f64,
+        shannon_entropy: f64,
+        citation_density: f64,
+        support_count: u64,
+        contradict_count: u64,
+        stability_class: String,
+    },
+    EquationProof {
+        equation_name: String,
+        equation_hash: String,
+        proof_tree_hash: String,
+        stability_class: String,
+        solvability_index: f64,
+        compression_ratio: f64,
+        dimensional_valid: bool,
+    },
+    MerkleRoot {
+        /// Merkle payload format; vectors predating it are v1
+        #[serde(default = "merkle_v1")]
+        version: u16,
+        root_hash: String,
+        leaf_count: u64,
+        previous_root: Option<String>,
+        #[serde(default)]
+        table_hashes: Option<Vec<TableHash>>,
+        #[serde(default)]
+        hash_algo: HashAlgo,
+    },
+    ScoreVector { scores: Vec<f64> },
+}
+
+/// One payload vector: inputs, canonical bytes, and resulting hash.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PayloadVector {
+    pub format_version: u16,
+    pub inputs: PayloadInputs,
+    pub canonical_hex: String,
+    pub payload_hash: String,
+}
+
+/// One Merkle tree vector: leaves, the resulting root, and the proof for
+/// the last leaf, which is the one promoted when a level is odd.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TreeVector {
+    pub hash_algo: HashAlgo,
+    pub pairing: PairingMode,
+    pub leaves: Vec<String>,
+    pub root: String,
+    pub proof_index: u64,
+    /// `MerkleProof::to_hex` form
+    pub proof_hex: String,
+}
+
+/// The complete committed vector file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TestVectors {
+    pub payloads: Vec<PayloadVector>,
+    #[serde(default)]
+    pub merkle_trees: Vec<TreeVector>,
+}
+
+/// Errors raised while verifying a vector file.
+#[derive(Error, Debug)]
+pub enum VectorError {
+    #[error("Failed to read vector file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Malformed vector file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported format version {version} in payload vector {index}")]
+    UnsupportedVersion { index: usize, version: u16 },
+    #[error("Payload vector {index}: {field} mismatch (expected {expected}, got {actual})")]
+    Mismatch {
+        index: usize,
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+    #[error("Merkle tree vector {index}: malformed leaf {leaf}")]
+    MalformedLeaf { index: usize, leaf: usize },
+    #[error("Merkle tree vector {index}: {field} mismatch (expected {expected}, got {actual})")]
+    TreeMismatch {
+        index: usize,
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+    #[error("Vector set differs from the generated set ({expected} generated, {actual} committed)")]
+    Incomplete { expected: usize, actual: usize },
+}
+
+impl PayloadInputs {
+    /// Compute (canonical_bytes, payload_hash_hex) for these inputs.
+    pub fn derive(&self) -> (Vec<u8>, String) {
+        match self {
+            PayloadInputs::ClaimScore {
+                claim_id,
+                composite_score,
+                shannon_entropy,
+                citation_density,
+                support_count,
+                contradict_count,
+                stability_class,
+            } => {
+                let payload = ClaimScorePayload::new(
+                    *claim_id, *composite_score, *shannon_entropy, *citation_density,
+                    *support_count, *contradict_count, stability_class.clone(),
+                );
+                (payload.to_canonical_string().into_bytes(), payload.payload_hash)
+            }
+            PayloadInputs::EquationProof {
+                equation_name,
+                equation_hash,
+                proof_tree_hash,
+                stability_class,
+                solvability_index,
+                compression_ratio,
+                dimensional_valid,
+            } => {
+                let payload = EquationProofPayload::new(
+                    equation_name.clone(), equation_hash.clone(), proof_tree_hash.clone(),
+                    stability_class.clone(), *solvability_index, *compression_ratio,
+                    *dimensional_valid,
+                );
+                (payload.to_canonical_string().into_bytes(), payload.payload_hash)
+            }
+            PayloadInputs::MerkleRoot { version, root_hash, leaf_count, previous_root, table_hashes, hash_algo } => {
+                let payload = MerkleRootPayload::with_version(
+                    *version, root_hash.clone(), *leaf_count, table_hashes.clone(), previous_root.clone(),
+                )
+                .with_hash_algo(*hash_algo);
+                (payload.to_canonical_string().into_bytes(), payload.payload_hash)
+            }
+            PayloadInputs::ScoreVector { scores } => {
+                let canonical = score_vector_canonical(scores).expect("vector inputs are finite");
+                let hash = compute_sha256(&canonical);
+                (canonical, hex::encode(hash))
+            }
+        }
+    }
+}
+
+/// Whether this build can derive trees hashed with `hash_algo`.
+pub fn tree_algo_supported(hash_algo: HashAlgo) -> bool {
+    hash_algo == HashAlgo::Sha256 || cfg!(feature = "keccak")
+}
+
+/// Compute (root_hex, proof_hex) for the leaf at `proof_index`, or `None`
+/// when `hash_algo` is unsupported in this build or the index is out of
+/// range.
+pub fn derive_tree(hash_algo: HashAlgo, pairing: PairingMode, leaves: &[[u8; 32]], proof_index: u64) -> Option<(String, String)> {
+    fn with<H: Hasher>(pairing: PairingMode, leaves: &[[u8; 32]], proof_index: u64) -> Option<(String, String)> {
+        let tree = MerkleTree::<H>::from_leaves_with_hasher(leaves, pairing);
+        let proof = tree.prove(usize::try_from(proof_index).ok()?)?;
+        Some((hex::encode(tree.root()), proof.to_hex()))
+    }
+    match hash_algo {
+        HashAlgo::Sha256 => with::<Sha256Hasher>(pairing, leaves, proof_index),
+        #[cfg(feature = "keccak")]
+        HashAlgo::Keccak256 => with::<Keccak256Hasher>(pairing, leaves, proof_index),
+        #[cfg(not(feature = "keccak"))]
+        HashAlgo::Keccak256 => None,
+    }
+}
+
+fn merkle_v1() -> u16 {
+    MERKLE_PAYLOAD_V1
+}
+
+fn vector(inputs: PayloadInputs) -> PayloadVector {
+    let (canonical, payload_hash) = inputs.derive();
+    PayloadVector {
+        format_version: PAYLOAD_FORMAT_VERSION,
+        inputs,
+        canonical_hex: hex::encode(canonical),
+        payload_hash,
+    }
+}
+
+/// Inputs covered by the committed vectors.
+fn vector_inputs() -> Vec<PayloadInputs> {
+    let claim = |claim_id, composite, entropy, density, support, contradict, class: &str| {
+        PayloadInputs::ClaimScore {
+            claim_id,
+            composite_score: composite,
+            shannon_entropy: entropy,
+            citation_density: density,
+            support_count: support,
+            contradict_count: contradict,
+            stability_class: class.to_string(),
+        }
+    };
+    let proof = |name: &str, stability: &str, si, cr, dim| PayloadInputs::EquationProof {
+        equation_name: name.to_string(),
+        equation_hash: hex::encode(compute_sha256(name.as_bytes())),
+        proof_tree_hash: hex::encode(compute_sha256(stability.as_bytes())),
+        stability_class: stability.to_string(),
+        solvability_index: si,
+        compression_ratio: cr,
+        dimensional_valid: dim,
+    };
+    let merkle_v = |version, hash_algo, root: u8, leaf_count, previous: Option<u8>, tables: &[&str]| PayloadInputs::MerkleRoot {
+        version,
+        hash_algo,
+        root_hash: hex::encode([root; 32]),
+        leaf_count,
+        previous_root: previous.map(|p| hex::encode([p; 32])),
+        table_hashes: (!tables.is_empty()).then(|| {
+            tables
+                .iter()
+                .map(|name| TableHash { table_name: name.to_string(), hash_hex: hex::encode(compute_sha256(name.as_bytes())) })
+                .collect()
+        }),
+    };
+    let merkle = |root, leaf_count, previous| merkle_v(MERKLE_PAYLOAD_V1, HashAlgo::Sha256, root, leaf_count, previous, &[]);
+
+    vec![
+        claim(1, 0.85, 1.234, 0.75, 5, 2, "stable"),
+        claim(42, 0.92, 0.5, 0.88, 10, 1, "converging"),
+        claim(0, 0.0, 0.0, 0.0, 0, 0, "unknown"),
+        claim(9_007_199_254_740_991, 1.0, 2.75, 0.123456789, 1, 999, "volatile"),
+        proof("newton_gravity", "stable", 0.95, 0.45, true),
+        proof("einstein_energy", "marginal", 1.0, 0.5, false),
+        proof("maxwell_gauss", "unstable", 0.0, 0.0, true),
+        merkle(0x42, 10, None),
+        merkle(0xab, 100, Some(0x42)),
+        merkle(0x00, 1, None),
+        merkle_v(MERKLE_PAYLOAD_V2, HashAlgo::Sha256, 0x42, 10, None, &[]),
+        merkle_v(MERKLE_PAYLOAD_V2, HashAlgo::Sha256, 0xab, 100, Some(0x42), &["claims", "equations", "sources"]),
+        merkle_v(MERKLE_PAYLOAD_VERSION, HashAlgo::Sha256, 0x42, 10, None, &[]),
+        merkle_v(MERKLE_PAYLOAD_VERSION, HashAlgo::Keccak256, 0xab, 100, Some(0x42), &["claims", "sources"]),
+        PayloadInputs::ScoreVector { scores: vec![] },
+        PayloadInputs::ScoreVector { scores: vec![0.1, 0.2, 0.3] },
+        PayloadInputs::ScoreVector { scores: vec![0.3, 0.2, 0.1] },
+    ]
+}
+
+/// Tree vectors for every supported hash function and pairing mode, over
+/// leaf counts that include odd levels and the single-leaf tree.
+fn tree_vectors() -> Vec<TreeVector> {
+    let mut trees = Vec::new();
+    for hash_algo in [HashAlgo::Sha256, HashAlgo::Keccak256].into_iter().filter(|algo| tree_algo_supported(*algo)) {
+        for pairing in [PairingMode::Ordered, PairingMode::Sorted] {
+            for leaf_count in [1u64, 2, 3, 5, 7] {
+                let leaves: Vec<[u8; 32]> = (0..leaf_count).map(|i| compute_sha256(format!("leaf-{}", i).as_bytes())).collect();
+                let proof_index = leaf_count - 1;
+                let (root, proof_hex) = derive_tree(hash_algo, pairing, &leaves, proof_index).expect("supported algo, index in range");
+                trees.push(TreeVector {
+                    hash_algo,
+                    pairing,
+                    leaves: leaves.iter().map(hex::encode).collect(),
+                    root,
+                    proof_index,
+                    proof_hex,
+                });
+            }
+        }
+    }
+    trees
+}
+
+/// Generate the full vector set from the current implementation.
+pub fn generate() -> TestVectors {
+    TestVectors {
+        payloads: vector_inputs().into_iter().map(vector).collect(),
+        merkle_trees: tree_vectors(),
+    }
+}
+
+/// Serialize vectors in the committed (pretty-printed) layout.
+pub fn to_json(vectors: &TestVectors) -> String {
+    let mut json = serde_json::to_string_pretty(vectors).expect("vectors serialize");
+    json.push('\n');
+    json
+}
+
+/// Re-derive every vector in a committed file and require an exact match.
+pub fn verify_vectors(path: impl AsRef<Path>) -> Result<(), VectorError> {
+    let committed: TestVectors = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+    for (index, v) in committed.payloads.iter().enumerate() {
+        if v.format_version != PAYLOAD_FORMAT_VERSION {
+            return Err(VectorError::UnsupportedVersion { index, version: v.format_version });
+        }
+        let (canonical, payload_hash) = v.inputs.derive();
+        let canonical_hex = hex::encode(canonical);
+        if canonical_hex != v.canonical_hex {
+            return Err(VectorError::Mismatch {
+                index,
+                field: "canonical_hex",
+                expected: v.canonical_hex.clone(),
+                actual: canonical_hex,
+            });
+        }
+        if payload_hash != v.payload_hash {
+            return Err(VectorError::Mismatch {
+                index,
+                field: "payload_hash",
+                expected: v.payload_hash.clone(),
+                actual: payload_hash,
+            });
+        }
+    }
+
+    for (index, t) in committed.merkle_trees.iter().enumerate() {
+        let leaves = t
+            .leaves
+            .iter()
+            .enumerate()
+            .map(|(leaf, h)| {
+                hex::decode(h).ok().and_then(|bytes| <[u8; 32]>::try_from(bytes).ok()).ok_or(VectorError::MalformedLeaf { index, leaf })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !tree_algo_supported(t.hash_algo) {
+            continue;
+        }
+        let (root, proof_hex) = derive_tree(t.hash_algo, t.pairing, &leaves, t.proof_index).ok_or(VectorError::TreeMismatch {
+            index,
+            field: "proof_index",
+            expected: format!("< {}", leaves.len()),
+            actual: t.proof_index.to_string(),
+        })?;
+        if root != t.root {
+            return Err(VectorError::TreeMismatch { index, field: "root", expected: t.root.clone(), actual: root });
+        }
+        if proof_hex != t.proof_hex {
+            return Err(VectorError::TreeMismatch { index, field: "proof_hex", expected: t.proof_hex.clone(), actual: proof_hex });
+        }
+    }
+
+    let expected = generate();
+    let committed_trees = committed.merkle_trees.iter().filter(|t| tree_algo_supported(t.hash_algo)).count();
+    if expected.payloads.len() != committed.payloads.len() || expected.merkle_trees.len() != committed_trees {
+        return Err(VectorError::Incomplete {
+            expected: expected.payloads.len() + expected.merkle_trees.len(),
+            actual: committed.payloads.len() + committed_trees,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle_anchor::{verify_proof_with_hasher, MerkleProof};
+
+    fn committed_path() -> String {
+        format!("{}/{}", env!("CARGO_MANIFEST_DIR"), VECTORS_PATH)
+    }
+
+    #[test]
+    fn test_committed_vectors_verify() {
+        verify_vectors(committed_path()).unwrap();
+    }
+
+    #[test]
+    fn test_committed_vectors_match_generator() {
+        let committed = std::fs::read_to_string(committed_path()).unwrap();
+        if cfg!(feature = "keccak") {
+            assert_eq!(committed, to_json(&generate()));
+        } else {
+            let mut committed: TestVectors = serde_json::from_str(&committed).unwrap();
+            committed.merkle_trees.retain(|t| tree_algo_supported(t.hash_algo));
+            assert_eq!(committed, generate());
+        }
+    }
+
+    #[test]
+    fn test_tree_vectors_cover_both_modes_and_odd_counts() {
+        let trees = generate().merkle_trees;
+        for pairing in [PairingMode::Ordered, PairingMode::Sorted] {
+            assert!(trees.iter().any(|t| t.pairing == pairing && t.leaves.len() % 2 == 1 && t.leaves.len() > 1));
+        }
+        assert_eq!(trees.iter().any(|t| t.hash_algo == HashAlgo::Keccak256), cfg!(feature = "keccak"));
+    }
+
+    #[test]
+    fn test_tree_vector_proofs_verify() {
+        for t in generate().merkle_trees {
+            let root: [u8; 32] = hex::decode(&t.root).unwrap().try_into().unwrap();
+            let proof = MerkleProof::from_hex(&t.proof_hex).unwrap();
+            assert_eq!(hex::encode(proof.leaf), t.leaves[t.proof_index as usize]);
+            let verified = match t.hash_algo {
+                HashAlgo::Sha256 => verify_proof_with_hasher::<Sha256Hasher>(&root, &proof, t.pairing),
+                #[cfg(feature = "keccak")]
+                HashAlgo::Keccak256 => verify_proof_with_hasher::<Keccak256Hasher>(&root, &proof, t.pairing),
+                #[cfg(not(feature = "keccak"))]
+                HashAlgo::Keccak256 => unreachable!("keccak trees need the keccak feature"),
+            };
+            assert!(verified);
+        }
+    }
+
+    #[test]
+    fn test_tampered_tree_vector_fails() {
+        let mut vectors = generate();
+        vectors.merkle_trees[2].root = "00".repeat(32);
+        let path = std::env::temp_dir().join("gravity_tampered_tree_vectors.json");
+        std::fs::write(&path, to_json(&vectors)).unwrap();
+        let err = verify_vectors(&path).unwrap_err();
+        assert!(matches!(err, VectorError::TreeMismatch { index: 2, field: "root", .. }));
+    }
+
+    #[test]
+    fn test_tampered_vector_fails() {
+        let mut vectors = generate();
+        vectors.payloads[0].payload_hash = "00".repeat(32);
+        let path = std::env::temp_dir().join("gravity_tampered_vectors.json");
+        std::fs::write(&path, to_json(&vectors)).unwrap();
+        let err = verify_vectors(&path).unwrap_err();
+        assert!(matches!(err, VectorError::Mismatch { index: 0, field: "payload_hash", .. }));
+    }
+
+    #[test]
+    fn test_canonical_bytes_hash_to_payload_hash() {
+        for v in generate().payloads {
+            let canonical = hex::decode(&v.canonical_hex).unwrap();
+            assert_eq!(hex::encode(compute_sha256(&canonical)), v.payload_hash);
+        }
+    }
+}
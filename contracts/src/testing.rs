@@ -0,0 +1,87 @@
+//! Testing – cw-multi-test harness for integration tests against the registry.
+//!
+//! Downstream crates enable the `test-utils` feature in their
+//! dev-dependencies and drive the real contract logic through an `App`:
+//!
+//! ```ignore
+//! let mut app = App::default();
+//! let registry = setup_registry(&mut app, "admin");
+//! register_root(&mut app, &registry, "bot", [1u8; 32]).unwrap();
+//! assert!(verify_root(&app, &registry, [1u8; 32]).unwrap().exists);
+//! ```
+
+use cosmwasm_std::{Addr, Binary, Empty, StdResult};
+use cw_multi_test::error::AnyResult;
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+
+use crate::anchor_registry::{
+    execute, instantiate, migrate, query, reply, sudo, ExecuteMsg, InstantiateMsg, VerifyResponse,
+};
+use crate::querier::AnchorRegistryQuerier;
+
+/// The registry with every entry point wired up.
+pub fn registry_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(execute, instantiate, query)
+            .with_sudo(sudo)
+            .with_reply(reply)
+            .with_migrate(migrate),
+    )
+}
+
+/// An open, unrestricted registry named "registry" with default limits.
+pub fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        admin: None,
+        name: Some("registry".into()),
+        limits: None,
+        restricted: false,
+        public_registration: None,
+        initial_anchors: None,
+        max_batch_size: None,
+        accepted_hash_lengths: None,
+    }
+}
+
+/// Store and instantiate the registry with `admin` as both the registry
+/// owner and the wasm admin, returning its address.
+pub fn setup_registry(app: &mut App, admin: &str) -> Addr {
+    let code_id = app.store_code(registry_contract());
+    app.instantiate_contract(code_id, Addr::unchecked(admin), &instantiate_msg(), &[], "registry", Some(admin.to_string()))
+        .unwrap()
+}
+
+/// Register `hash` as a Merkle root on behalf of `sender`.
+pub fn register_root(app: &mut App, registry: &Addr, sender: &str, hash: [u8; 32]) -> AnyResult<AppResponse> {
+    execute_as(app, registry, sender, ExecuteMsg::register_root(Binary::from(hash)))
+}
+
+/// Register `hash` as a claim score on behalf of `sender`.
+pub fn register_claim_score(app: &mut App, registry: &Addr, sender: &str, hash: [u8; 32]) -> AnyResult<AppResponse> {
+    execute_as(app, registry, sender, ExecuteMsg::register_claim_score(Binary::from(hash)))
+}
+
+/// Register `hash` as an equation proof on behalf of `sender`.
+pub fn register_equation_proof(app: &mut App, registry: &Addr, sender: &str, hash: [u8; 32]) -> AnyResult<AppResponse> {
+    execute_as(app, registry, sender, ExecuteMsg::register_equation_proof(Binary::from(hash)))
+}
+
+/// Send any registry message on behalf of `sender`.
+pub fn execute_as(app: &mut App, registry: &Addr, sender: &str, msg: ExecuteMsg) -> AnyResult<AppResponse> {
+    app.execute_contract(Addr::unchecked(sender), registry.clone(), &msg, &[])
+}
+
+/// Verify a Merkle root.
+pub fn verify_root(app: &App, registry: &Addr, hash: [u8; 32]) -> StdResult<VerifyResponse> {
+    AnchorRegistryQuerier::new(&app.wrap(), registry.clone()).verify_root(&hash)
+}
+
+/// Verify a claim score.
+pub fn verify_claim_score(app: &App, registry: &Addr, hash: [u8; 32]) -> StdResult<VerifyResponse> {
+    AnchorRegistryQuerier::new(&app.wrap(), registry.clone()).verify_claim_score(&hash)
+}
+
+/// Verify an equation proof.
+pub fn verify_equation_proof(app: &App, registry: &Addr, hash: [u8; 32]) -> StdResult<VerifyResponse> {
+    AnchorRegistryQuerier::new(&app.wrap(), registry.clone()).verify_equation_proof(&hash)
+}
@@ -0,0 +1,611 @@
+//! Multi-test coverage for the registry's migrate, sudo and reply entry
+//! points, latest-root tracking, per-block rate limiting, subscriber
+//! notifications, consumer queries, on-chain leaf proofs and IBC relay sends. Deployment and the
+//! common register/verify calls go through the public `testing` harness.
+
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+};
+use cw_multi_test::{next_block, App, Contract, ContractWrapper, Executor};
+
+use gravity_anchor_contracts::anchor_registry::{
+    execute, instantiate, migrate, query, reply, sudo, AnchorExistsResponse, AnchorKey, AnchorListResponse,
+    AnchorNotification,
+    ConfigResponse, ExecuteMsg, LeafVerifyResponse, ProofStep, FailedDispatchesResponse, InstantiateMsg, LatestRoot, LatestRootResponse, MigrateMsg, QueryMsg, SudoMsg,
+    VerifyResponse, CONTRACT_NAME, CONTRACT_VERSION, GENESIS_ROOT, HARD_MAX_SUBSCRIBER_GAS_LIMIT, ROOTS,
+};
+use gravity_anchor_contracts::error::ContractError;
+use gravity_anchor_contracts::merkle_anchor::{MerkleTree, Side};
+use gravity_anchor_contracts::testing::{self, registry_contract, setup_registry};
+use gravity_anchor_contracts::AnchorRegistryQuerier;
+
+type InstantiateFn = fn(DepsMut, Env, MessageInfo, InstantiateMsg) -> Result<Response, ContractError>;
+
+/// Instantiate, then record `name`/`version` as the deployed cw2 info.
+fn instantiate_recording(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+    name: &str,
+    version: &str,
+) -> Result<Response, ContractError> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    cw2::set_contract_version(deps.storage, name, version)?;
+    Ok(res)
+}
+
+/// The storage a v1 deployment left behind: its version, a config holding
+/// the admin and counters, and roots [7; 32] by "bot" and [5; 32] by
+/// "legacy" in the per-type map, stored before `registered_at_time` existed.
+fn instantiate_v1(deps: DepsMut, _env: Env, _info: MessageInfo, _msg: InstantiateMsg) -> Result<Response, ContractError> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, "0.0.1")?;
+    deps.storage.set(
+        b"config",
+        br#"{"admin":"owner","total_anchors":2,"total_roots":2,"total_claim_scores":0,"total_equation_proofs":0}"#,
+    );
+    for (hash, height, registrant) in [([7u8; 32], 40, "bot"), ([5u8; 32], 42, "legacy")] {
+        let v1_entry = format!(
+            r#"{{"hash_hex":"{}","anchor_type":"root","registered_at":{},"registrant":"{}"}}"#,
+            hex::encode(hash),
+            height,
+            registrant,
+        );
+        deps.storage.set(&ROOTS.key(&hash), v1_entry.as_bytes());
+    }
+    Ok(Response::new())
+}
+
+/// Deploy a registry as v1 code left it, with `owner` as wasm admin.
+fn setup_v1(app: &mut App) -> Addr {
+    let owner = Addr::unchecked("owner");
+    let code_id = app.store_code(registry(instantiate_v1));
+    app.instantiate_contract(code_id, owner.clone(), &testing::instantiate_msg(), &[], "registry", Some(owner.to_string()))
+        .unwrap()
+}
+
+fn instantiate_future(deps: DepsMut, env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    instantiate_recording(deps, env, info, msg, CONTRACT_NAME, "99.0.0")
+}
+
+fn instantiate_foreign(deps: DepsMut, env: Env, info: MessageInfo, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    instantiate_recording(deps, env, info, msg, "crates.io:other-contract", CONTRACT_VERSION)
+}
+
+fn registry(instantiate_fn: InstantiateFn) -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(execute, instantiate_fn, query)
+            .with_sudo(sudo)
+            .with_reply(reply)
+            .with_migrate(migrate),
+    )
+}
+
+/// Deploy a registry through `instantiate_fn` with `owner` as wasm admin and
+/// register one root.
+fn setup(app: &mut App, instantiate_fn: InstantiateFn) -> Addr {
+    let owner = Addr::unchecked("owner");
+    let code_id = app.store_code(registry(instantiate_fn));
+    let contract = app
+        .instantiate_contract(code_id, owner.clone(), &testing::instantiate_msg(), &[], "registry", Some(owner.to_string()))
+        .unwrap();
+    testing::register_root(app, &contract, "bot", [7u8; 32]).unwrap();
+    contract
+}
+
+/// Deploy the current registry with the harness and register one root.
+fn deploy(app: &mut App) -> Addr {
+    let contract = setup_registry(app, "owner");
+    testing::register_root(app, &contract, "bot", [7u8; 32]).unwrap();
+    contract
+}
+
+/// Migrate to the current code, returning the root cause of any failure.
+fn migrate_to_current(app: &mut App, contract: &Addr) -> Result<(), String> {
+    let code_id = app.store_code(registry_contract());
+    app.migrate_contract(Addr::unchecked("owner"), contract.clone(), &MigrateMsg::V1 {}, code_id)
+        .map(|_| ())
+        .map_err(|e| e.root_cause().to_string())
+}
+
+#[test]
+fn migrate_keeps_roots_readable() {
+    let mut app = App::default();
+    let contract = setup_v1(&mut app);
+    migrate_to_current(&mut app, &contract).unwrap();
+
+    let res = testing::verify_root(&app, &contract, [7u8; 32]).unwrap();
+    assert!(res.exists);
+    assert_eq!(res.entry.unwrap().registrant, "bot");
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract, &QueryMsg::GetConfig {}).unwrap();
+    assert_eq!((config.admin.as_str(), config.total_anchors, config.total_roots), ("owner", 2, 2));
+
+    // The v1 registrant keeps its root
+    let err = testing::register_root(&mut app, &contract, "mallory", [7u8; 32]).unwrap_err();
+    assert!(err.root_cause().to_string().contains("already registered"));
+
+    let info = cw2::query_contract_info(&app.wrap(), &contract).unwrap();
+    assert_eq!(info.version, CONTRACT_VERSION);
+}
+
+#[test]
+fn migrate_reads_v1_entries_without_time() {
+    let mut app = App::default();
+    let contract = setup_v1(&mut app);
+    migrate_to_current(&mut app, &contract).unwrap();
+
+    let res = testing::verify_root(&app, &contract, [5u8; 32]).unwrap();
+    assert!(res.exists);
+    let entry = res.entry.unwrap();
+    assert_eq!(entry.registered_at, 42);
+    assert_eq!(entry.registered_at_time, 0);
+
+    // Roots registered after the upgrade carry the block time
+    testing::register_root(&mut app, &contract, "bot", [8u8; 32]).unwrap();
+    let res = testing::verify_root(&app, &contract, [8u8; 32]).unwrap();
+    assert_eq!(res.entry.unwrap().registered_at_time, app.block_info().time.seconds());
+
+    // Moving the v1 entry into the unified store leaves it unchanged
+    let before = testing::verify_root(&app, &contract, [5u8; 32]).unwrap();
+    let code_id = app.store_code(registry_contract());
+    let msg = MigrateMsg::MoveAnchors { limit: None };
+    let res = app.migrate_contract(Addr::unchecked("owner"), contract.clone(), &msg, code_id).unwrap();
+    let moved = res.events.iter().flat_map(|e| &e.attributes).find(|a| a.key == "moved").unwrap();
+    assert_eq!(moved.value, "2");
+    let after = testing::verify_root(&app, &contract, [5u8; 32]).unwrap();
+    assert_eq!(after, before);
+}
+
+#[test]
+fn migrate_rejects_downgrade() {
+    let mut app = App::default();
+    let contract = setup(&mut app, instantiate_future);
+    let err = migrate_to_current(&mut app, &contract).unwrap_err();
+    assert!(err.contains("older version"));
+}
+
+#[test]
+fn migrate_rejects_foreign_contract() {
+    let mut app = App::default();
+    let contract = setup(&mut app, instantiate_foreign);
+    let err = migrate_to_current(&mut app, &contract).unwrap_err();
+    assert!(err.contains("other-contract"));
+}
+
+#[test]
+fn harness_registers_and_verifies() {
+    let mut app = App::default();
+    let registry = setup_registry(&mut app, "admin");
+    testing::register_root(&mut app, &registry, "bot", [1u8; 32]).unwrap();
+    testing::register_claim_score(&mut app, &registry, "bot", [2u8; 32]).unwrap();
+    testing::register_equation_proof(&mut app, &registry, "bot", [3u8; 32]).unwrap();
+
+    assert!(testing::verify_root(&app, &registry, [1u8; 32]).unwrap().exists);
+    assert!(testing::verify_claim_score(&app, &registry, [2u8; 32]).unwrap().exists);
+    assert!(testing::verify_equation_proof(&app, &registry, [3u8; 32]).unwrap().exists);
+    assert!(!testing::verify_root(&app, &registry, [2u8; 32]).unwrap().exists);
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&registry, &QueryMsg::GetConfig {}).unwrap();
+    assert_eq!(config.admin, "admin");
+}
+
+fn latest_root(app: &App, contract: &Addr) -> Option<LatestRoot> {
+    let res: LatestRootResponse = app.wrap().query_wasm_smart(contract, &QueryMsg::GetLatestRoot {}).unwrap();
+    res.latest
+}
+
+/// Register a root in a new block, optionally chained onto `previous_root`.
+fn register_next_root(app: &mut App, contract: &Addr, hash: [u8; 32], previous_root: Option<[u8; 32]>) {
+    app.update_block(next_block);
+    let msg = match previous_root {
+        Some(previous_root) => ExecuteMsg::RegisterRootChained {
+            hash: Binary::from(hash),
+            previous_root: Binary::from(previous_root),
+            tags: None,
+            memo: None,
+        },
+        None => ExecuteMsg::register_root(Binary::from(hash)),
+    };
+    testing::execute_as(app, contract, "bot", msg).unwrap();
+}
+
+#[test]
+fn latest_root_follows_sequential_registrations() {
+    let mut app = App::default();
+    let contract = setup_registry(&mut app, "owner");
+    assert_eq!(latest_root(&app, &contract), None);
+
+    for byte in 1..=3u8 {
+        register_next_root(&mut app, &contract, [byte; 32], None);
+        let latest = latest_root(&app, &contract).unwrap();
+        assert_eq!(latest.hash_hex, hex::encode([byte; 32]));
+        assert_eq!(latest.height, app.block_info().height);
+        assert_eq!(latest.registrant, "bot");
+    }
+
+    // Chained roots advance the head only when they link to it
+    register_next_root(&mut app, &contract, [4u8; 32], Some([3u8; 32]));
+    assert_eq!(latest_root(&app, &contract).unwrap().hash_hex, hex::encode([4u8; 32]));
+    register_next_root(&mut app, &contract, [5u8; 32], Some([2u8; 32]));
+    assert_eq!(latest_root(&app, &contract).unwrap().hash_hex, hex::encode([4u8; 32]));
+    register_next_root(&mut app, &contract, [6u8; 32], Some(GENESIS_ROOT));
+    assert_eq!(latest_root(&app, &contract).unwrap().hash_hex, hex::encode([4u8; 32]));
+
+    // Other anchor types leave it alone
+    app.update_block(next_block);
+    testing::register_claim_score(&mut app, &contract, "bot", [7u8; 32]).unwrap();
+    assert_eq!(latest_root(&app, &contract).unwrap().hash_hex, hex::encode([4u8; 32]));
+}
+
+fn anchors_at_height(app: &App, contract: &Addr, height: u64, start_after: Option<AnchorKey>) -> Vec<Binary> {
+    let msg = QueryMsg::GetAnchorsAtHeight { height, start_after, limit: None, order: None };
+    let res: AnchorListResponse = app.wrap().query_wasm_smart(contract, &msg).unwrap();
+    res.anchors.into_iter().map(|entry| Binary::from(hex::decode(entry.hash_hex).unwrap())).collect()
+}
+
+#[test]
+fn anchors_at_height_partitions_by_block() {
+    let mut app = App::default();
+    // `deploy` registers [7; 32] in the instantiation block
+    let contract = deploy(&mut app);
+    let first = app.block_info().height;
+
+    app.update_block(next_block);
+    for hash in [[9u8; 32], [8u8; 32]] {
+        testing::register_root(&mut app, &contract, "bot", hash).unwrap();
+    }
+    let second = app.block_info().height;
+
+    assert_eq!(anchors_at_height(&app, &contract, first, None), vec![Binary::from([7u8; 32])]);
+    assert_eq!(
+        anchors_at_height(&app, &contract, second, None),
+        vec![Binary::from([8u8; 32]), Binary::from([9u8; 32])]
+    );
+    let cursor = AnchorKey { anchor_type: "root".into(), hash: Binary::from([8u8; 32]) };
+    assert_eq!(anchors_at_height(&app, &contract, second, Some(cursor)), vec![Binary::from([9u8; 32])]);
+    assert!(anchors_at_height(&app, &contract, second + 1, None).is_empty());
+}
+
+#[test]
+fn anchor_exists_agrees_with_verify() {
+    let mut app = App::default();
+    let contract = deploy(&mut app);
+    let revoke = ExecuteMsg::RevokeAnchor { hash: Binary::from([7u8; 32]), anchor_type: "root".into(), reason: "stale".into() };
+    register_next_root(&mut app, &contract, [8u8; 32], None);
+    app.execute_contract(Addr::unchecked("bot"), contract.clone(), &revoke, &[]).unwrap();
+
+    for (anchor_type, byte) in [("root", 7u8), ("root", 8), ("root", 9), ("claim_score", 8), ("equation_proof", 9)] {
+        let hash = Binary::from([byte; 32]);
+        let exists = QueryMsg::AnchorExists { anchor_type: anchor_type.into(), hash: hash.clone() };
+        let exists: AnchorExistsResponse = app.wrap().query_wasm_smart(&contract, &exists).unwrap();
+        let verify = match anchor_type {
+            "root" => QueryMsg::VerifyRoot { hash: Some(hash), hash_hex: None },
+            "claim_score" => QueryMsg::VerifyClaimScore { hash: Some(hash), hash_hex: None },
+            _ => QueryMsg::VerifyEquationProof { hash: Some(hash), hash_hex: None },
+        };
+        let verify: VerifyResponse = app.wrap().query_wasm_smart(&contract, &verify).unwrap();
+        assert_eq!(exists.exists, verify.exists, "{} {}", anchor_type, byte);
+        assert_eq!(exists.exists, byte != 9 && anchor_type == "root");
+    }
+}
+
+#[test]
+fn sudo_recovers_admin_and_unpauses() {
+    let mut app = App::default();
+    let contract = deploy(&mut app);
+    let owner = Addr::unchecked("owner");
+    app.execute_contract(owner.clone(), contract.clone(), &ExecuteMsg::Pause {}, &[]).unwrap();
+
+    // The owner's key is lost; governance appoints a new admin
+    app.wasm_sudo(contract.clone(), &SudoMsg::SetAdmin { address: "council".into() }).unwrap();
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract, &QueryMsg::GetConfig {}).unwrap();
+    assert_eq!(config.admin, "council");
+    assert!(app.execute_contract(owner, contract.clone(), &ExecuteMsg::Unpause {}, &[]).is_err());
+    assert!(app.wasm_sudo(contract.clone(), &SudoMsg::SetAdmin { address: "".into() }).is_err());
+
+    assert!(testing::register_root(&mut app, &contract, "bot", [8u8; 32]).is_err());
+    app.wasm_sudo(contract.clone(), &SudoMsg::Unpause {}).unwrap();
+    testing::register_root(&mut app, &contract, "bot", [8u8; 32]).unwrap();
+
+    app.execute_contract(Addr::unchecked("council"), contract.clone(), &ExecuteMsg::Pause {}, &[]).unwrap();
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract, &QueryMsg::GetConfig {}).unwrap();
+    assert!(config.paused);
+}
+
+#[test]
+fn rate_limit_resets_each_block() {
+    let mut app = App::default();
+    let contract = deploy(&mut app);
+    let owner = Addr::unchecked("owner");
+    let limit = ExecuteMsg::SetRateLimit { max_anchors_per_block_per_registrant: Some(2) };
+    app.execute_contract(owner.clone(), contract.clone(), &limit, &[]).unwrap();
+    assert!(app.execute_contract(Addr::unchecked("bot"), contract.clone(), &limit, &[]).is_err());
+
+    let register = |app: &mut App, sender: &str, byte: u8| {
+        testing::register_root(app, &contract, sender, [byte; 32]).map_err(|e| e.root_cause().to_string())
+    };
+
+    app.update_block(next_block);
+    register(&mut app, "bot", 1).unwrap();
+    register(&mut app, "bot", 2).unwrap();
+    let err = register(&mut app, "bot", 3).unwrap_err();
+    assert_eq!(err, ContractError::RateLimited { max: 2 }.to_string());
+    // The limit is per registrant
+    register(&mut app, "alice", 9).unwrap();
+
+    app.update_block(next_block);
+    register(&mut app, "bot", 3).unwrap();
+    register(&mut app, "bot", 4).unwrap();
+    assert!(register(&mut app, "bot", 5).is_err());
+
+    let unlimited = ExecuteMsg::SetRateLimit { max_anchors_per_block_per_registrant: None };
+    app.execute_contract(owner, contract.clone(), &unlimited, &[]).unwrap();
+    register(&mut app, "bot", 5).unwrap();
+    register(&mut app, "bot", 6).unwrap();
+}
+
+/// Execute message of the mock subscriber, which rejects everything when
+/// instantiated with `true`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SubscriberMsg {
+    OnAnchor(AnchorNotification),
+}
+
+const RECEIVED: cw_storage_plus::Item<Vec<AnchorNotification>> = cw_storage_plus::Item::new("received");
+
+fn subscriber_instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, fail: bool) -> StdResult<Response> {
+    cw_storage_plus::Item::<bool>::new("fail").save(deps.storage, &fail)?;
+    RECEIVED.save(deps.storage, &vec![])?;
+    Ok(Response::new())
+}
+
+fn subscriber_execute(deps: DepsMut, _env: Env, _info: MessageInfo, msg: SubscriberMsg) -> StdResult<Response> {
+    if cw_storage_plus::Item::<bool>::new("fail").load(deps.storage)? {
+        return Err(StdError::generic_err("subscriber down"));
+    }
+    let SubscriberMsg::OnAnchor(notification) = msg;
+    RECEIVED.update(deps.storage, |mut received| -> StdResult<_> {
+        received.push(notification);
+        Ok(received)
+    })?;
+    Ok(Response::new())
+}
+
+fn subscriber_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_json_binary(&RECEIVED.load(deps.storage)?)
+}
+
+#[test]
+fn subscribers_are_notified_and_cannot_block_registration() {
+    let mut app = App::default();
+    let contract = deploy(&mut app);
+    let owner = Addr::unchecked("owner");
+    let code_id = app.store_code(Box::new(ContractWrapper::new(subscriber_execute, subscriber_instantiate, subscriber_query)));
+    let listener = app.instantiate_contract(code_id, owner.clone(), &false, &[], "listener", None).unwrap();
+    let broken = app.instantiate_contract(code_id, owner.clone(), &true, &[], "broken", None).unwrap();
+    for subscriber in [&listener, &broken] {
+        let add = ExecuteMsg::AddSubscriber { contract: subscriber.to_string(), msg_template: "on_anchor".into() };
+        assert!(app.execute_contract(Addr::unchecked("bot"), contract.clone(), &add, &[]).is_err());
+        app.execute_contract(owner.clone(), contract.clone(), &add, &[]).unwrap();
+    }
+
+    app.update_block(next_block);
+    let res = testing::register_root(&mut app, &contract, "bot", [8u8; 32]).unwrap();
+    assert!(res.events.iter().any(|e| e.attributes.iter().any(|a| a.value == "subscriber_failed")));
+
+    let received: Vec<AnchorNotification> = app.wrap().query_wasm_smart(&listener, &Empty {}).unwrap();
+    let expected = AnchorNotification {
+        anchor_type: "root".into(),
+        hash_hex: hex::encode([8u8; 32]),
+        height: app.block_info().height,
+        registrant: "bot".into(),
+    };
+    assert_eq!(received, vec![expected]);
+    assert!(testing::verify_root(&app, &contract, [8u8; 32]).unwrap().exists);
+
+    // Only the broken subscriber's notification is recorded as failed
+    let failed = QueryMsg::ListFailedDispatches { start_after: None, limit: None };
+    let res: FailedDispatchesResponse = app.wrap().query_wasm_smart(&contract, &failed).unwrap();
+    assert_eq!(res.dispatches.len(), 1);
+    let dispatch = &res.dispatches[0];
+    assert_eq!((dispatch.contract.as_str(), dispatch.hash_hex.as_str()), (broken.as_str(), hex::encode([8u8; 32]).as_str()));
+    assert!(dispatch.error.contains("subscriber down"), "{}", dispatch.error);
+
+    let remove = ExecuteMsg::RemoveSubscriber { contract: listener.to_string() };
+    app.execute_contract(owner, contract.clone(), &remove, &[]).unwrap();
+    testing::register_claim_score(&mut app, &contract, "bot", [9u8; 32]).unwrap();
+    let received: Vec<AnchorNotification> = app.wrap().query_wasm_smart(&listener, &Empty {}).unwrap();
+    assert_eq!(received.len(), 1);
+}
+
+/// A subscriber that burns through its gas on every notification. cw-multi-test
+/// does not meter gas, so it fails the way the chain reports a submessage that
+/// exhausts its `gas_limit`: as an ordinary error result.
+fn gas_hungry_execute(deps: DepsMut, _env: Env, _info: MessageInfo, _msg: SubscriberMsg) -> StdResult<Response> {
+    for i in 0u32..1_000 {
+        deps.storage.set(&i.to_be_bytes(), &[0u8; 64]);
+    }
+    Err(StdError::generic_err("out of gas in location: WriteFlat; gasWanted: 500000"))
+}
+
+#[test]
+fn gas_hungry_subscriber_cannot_abort_registration() {
+    let mut app = App::default();
+    let contract = deploy(&mut app);
+    let owner = Addr::unchecked("owner");
+    let code_id = app.store_code(Box::new(ContractWrapper::new(gas_hungry_execute, subscriber_instantiate, subscriber_query)));
+    let hungry = app.instantiate_contract(code_id, owner.clone(), &false, &[], "hungry", None).unwrap();
+    let add = ExecuteMsg::AddSubscriber { contract: hungry.to_string(), msg_template: "on_anchor".into() };
+    app.execute_contract(owner.clone(), contract.clone(), &add, &[]).unwrap();
+
+    for gas_limit in [0, HARD_MAX_SUBSCRIBER_GAS_LIMIT + 1] {
+        let out_of_range = ExecuteMsg::SetSubscriberGasLimit { gas_limit };
+        assert!(app.execute_contract(owner.clone(), contract.clone(), &out_of_range, &[]).is_err());
+    }
+    let limit = ExecuteMsg::SetSubscriberGasLimit { gas_limit: 200_000 };
+    assert!(app.execute_contract(Addr::unchecked("bot"), contract.clone(), &limit, &[]).is_err());
+    app.execute_contract(owner, contract.clone(), &limit, &[]).unwrap();
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract, &QueryMsg::GetConfig {}).unwrap();
+    assert_eq!(config.subscriber_gas_limit, 200_000);
+
+    app.update_block(next_block);
+    testing::register_root(&mut app, &contract, "bot", [8u8; 32]).unwrap();
+    assert!(testing::verify_root(&app, &contract, [8u8; 32]).unwrap().exists);
+
+    // The subscriber's writes were rolled back with its failure
+    assert!(app.wrap().query_wasm_raw(&hungry, 0u32.to_be_bytes().to_vec()).unwrap().is_none());
+    let failed = QueryMsg::ListFailedDispatches { start_after: None, limit: None };
+    let res: FailedDispatchesResponse = app.wrap().query_wasm_smart(&contract, &failed).unwrap();
+    assert_eq!(res.dispatches.len(), 1);
+    assert!(res.dispatches[0].error.contains("out of gas"), "{}", res.dispatches[0].error);
+}
+
+/// A toy consumer that only settles roots the registry has anchored, and
+/// answers queries with the registry's config.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ConsumerMsg {
+    Settle { root: Binary },
+}
+
+const REGISTRY: cw_storage_plus::Item<Addr> = cw_storage_plus::Item::new("registry");
+
+fn consumer_instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, registry: Addr) -> StdResult<Response> {
+    REGISTRY.save(deps.storage, &registry)?;
+    Ok(Response::new())
+}
+
+fn consumer_execute(deps: DepsMut, _env: Env, _info: MessageInfo, msg: ConsumerMsg) -> StdResult<Response> {
+    let ConsumerMsg::Settle { root } = msg;
+    let hash: [u8; 32] = root.as_slice().try_into().map_err(|_| StdError::generic_err("root must be 32 bytes"))?;
+    let registry = AnchorRegistryQuerier::new(&deps.querier, REGISTRY.load(deps.storage)?);
+    if !registry.verify_root(&hash)?.exists {
+        return Err(StdError::generic_err("root not anchored"));
+    }
+    Ok(Response::new().add_attribute("settled", hex::encode(hash)))
+}
+
+fn consumer_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    let registry = AnchorRegistryQuerier::new(&deps.querier, REGISTRY.load(deps.storage)?);
+    to_json_binary(&registry.get_config()?)
+}
+
+#[test]
+fn consumer_checks_anchors_through_querier() {
+    let mut app = App::default();
+    let contract = deploy(&mut app);
+    let owner = Addr::unchecked("owner");
+    let code_id = app.store_code(Box::new(ContractWrapper::new(consumer_execute, consumer_instantiate, consumer_query)));
+    let consumer = app.instantiate_contract(code_id, owner.clone(), &contract, &[], "consumer", None).unwrap();
+
+    let settle = |app: &mut App, root: [u8; 32]| {
+        let msg = ConsumerMsg::Settle { root: Binary::from(root) };
+        app.execute_contract(owner.clone(), consumer.clone(), &msg, &[]).map_err(|e| e.root_cause().to_string())
+    };
+    assert_eq!(settle(&mut app, [4u8; 32]).unwrap_err(), "Generic error: root not anchored");
+    let res = settle(&mut app, [7u8; 32]).unwrap();
+    assert!(res.events.iter().any(|e| e.attributes.iter().any(|a| a.key == "settled")));
+
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&consumer, &Empty {}).unwrap();
+    assert_eq!(config.admin, owner.to_string());
+
+    // The same helper works for the other anchor types
+    testing::register_equation_proof(&mut app, &contract, "bot", [5u8; 32]).unwrap();
+    let wrapper = app.wrap();
+    let registry = AnchorRegistryQuerier::new(&wrapper, contract.clone());
+    assert!(registry.verify_equation_proof(&[5u8; 32]).unwrap().exists);
+    assert!(!registry.verify_claim_score(&[5u8; 32]).unwrap().exists);
+    assert!(!registry.verify_root(&[5u8; 32]).unwrap().exists);
+}
+
+#[test]
+fn verify_leaf_against_registered_tree() {
+    let mut app = App::default();
+    let contract = deploy(&mut app);
+    let leaves: Vec<[u8; 32]> = (0u8..5).map(|i| [i + 1; 32]).collect();
+    let tree = MerkleTree::from_leaves(&leaves);
+    testing::register_root(&mut app, &contract, "bot", tree.root()).unwrap();
+
+    let verify = |root: [u8; 32], leaf: [u8; 32], proof: Vec<ProofStep>| {
+        let msg = QueryMsg::VerifyLeaf { root: Binary::from(root), leaf: Binary::from(leaf), proof };
+        app.wrap().query_wasm_smart::<LeafVerifyResponse>(&contract, &msg)
+    };
+    let steps = |index: usize| -> Vec<ProofStep> {
+        let proof = tree.prove(index).unwrap();
+        let side = |side: Option<Side>| if side == Some(Side::Left) { "left" } else { "right" };
+        proof.siblings.iter().map(|node| ProofStep { hash: Binary::from(node.hash), side: side(node.side).into() }).collect()
+    };
+
+    for (index, leaf) in leaves.iter().enumerate() {
+        let res = verify(tree.root(), *leaf, steps(index)).unwrap();
+        assert_eq!(res, LeafVerifyResponse { root_registered: true, proof_valid: true }, "leaf {}", index);
+    }
+    let res = verify(tree.root(), [9u8; 32], steps(0)).unwrap();
+    assert_eq!(res, LeafVerifyResponse { root_registered: true, proof_valid: false });
+
+    // A valid proof against a root nobody registered
+    let unregistered = MerkleTree::from_leaves(&leaves[..2]);
+    let res = verify(unregistered.root(), leaves[0], vec![ProofStep { hash: Binary::from(leaves[1]), side: "right".into() }]).unwrap();
+    assert_eq!(res, LeafVerifyResponse { root_registered: false, proof_valid: true });
+
+    let mut bad = steps(0);
+    bad[0].hash = Binary::from([1u8; 31]);
+    assert!(verify(tree.root(), leaves[0], bad).is_err());
+    let mut bad = steps(0);
+    bad[0].side = "up".into();
+    assert!(verify(tree.root(), leaves[0], bad).is_err());
+    let deep = vec![ProofStep { hash: Binary::from([1u8; 32]), side: "left".into() }; 65];
+    assert!(verify(tree.root(), leaves[0], deep).is_err());
+
+    // A revoked root no longer vouches for its leaves
+    let revoke = ExecuteMsg::RevokeAnchor { hash: Binary::from(tree.root()), anchor_type: "root".into(), reason: "bad batch".into() };
+    app.execute_contract(Addr::unchecked("bot"), contract.clone(), &revoke, &[]).unwrap();
+    let msg = QueryMsg::VerifyLeaf { root: Binary::from(tree.root()), leaf: Binary::from(leaves[0]), proof: steps(0) };
+    let res: LeafVerifyResponse = app.wrap().query_wasm_smart(&contract, &msg).unwrap();
+    assert_eq!(res, LeafVerifyResponse { root_registered: false, proof_valid: true });
+}
+
+/// A registry with `channel-0` already connected. cw-multi-test cannot drive
+/// an IBC handshake, so the channel is written directly.
+#[cfg(feature = "cosmwasm-ibc")]
+fn instantiate_connected(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    use cosmwasm_std::testing::mock_ibc_channel;
+    use gravity_anchor_contracts::ibc::{ALLOWED_COUNTERPARTIES, CHANNELS, IBC_VERSION};
+
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    let channel = mock_ibc_channel("channel-0", cosmwasm_std::IbcOrder::Unordered, IBC_VERSION);
+    CHANNELS.save(deps.storage, "channel-0", &channel)?;
+    ALLOWED_COUNTERPARTIES.save(deps.storage, (&channel.connection_id, &channel.counterparty_endpoint.port_id), &())?;
+    Ok(res)
+}
+
+#[cfg(feature = "cosmwasm-ibc")]
+#[test]
+fn relay_sends_through_ibc_module_atomically() {
+    use cw_multi_test::{AppBuilder, IbcAcceptingModule};
+
+    let relay = ExecuteMsg::RelayAnchor {
+        channel_id: "channel-0".into(),
+        anchor_type: "root".into(),
+        hash: Binary::from([7u8; 32]),
+    };
+
+    let mut app = AppBuilder::new().with_ibc(IbcAcceptingModule::default()).build(|_, _, _| {});
+    let code_id = app.store_code(registry(instantiate_connected));
+    let msg = testing::instantiate_msg();
+    let contract = app.instantiate_contract(code_id, Addr::unchecked("owner"), &msg, &[], "registry", None).unwrap();
+    let register = ExecuteMsg::RegisterRoot { hash: Binary::from([7u8; 32]), tags: None, memo: None, ttl_blocks: None, payload_version: None };
+    app.execute_contract(Addr::unchecked("bot"), contract.clone(), &register, &[]).unwrap();
+    let res = app.execute_contract(Addr::unchecked("anyone"), contract, &relay, &[]).unwrap();
+    assert!(res.has_event(&cosmwasm_std::Event::new("wasm").add_attribute("action", "relay_anchor")));
+
+    // Without IBC support the packet cannot be sent and the relay fails whole
+    let mut app = App::default();
+    let contract = setup(&mut app, instantiate_connected);
+    assert!(app.execute_contract(Addr::unchecked("anyone"), contract, &relay, &[]).is_err());
+}